@@ -1,3 +1,5 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
 use serde_json::Value;
 
 use std::env;
@@ -11,8 +13,16 @@ pub struct EntityData<'a> {
 }
 
 fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=resources/htmlmathml.json");
+    println!("cargo:rerun-if-changed=resources/unicode.xml");
     let out_dir = env::var("OUT_DIR").unwrap();
-    let dest_path = Path::new(&out_dir).join("entities.rs");
+    write_entities(Path::new(&out_dir));
+    write_operator_dictionary(Path::new(&out_dir));
+}
+
+fn write_entities(out_dir: &Path) {
+    let dest_path = out_dir.join("entities.rs");
 
     let json = std::str::from_utf8(include_bytes!("resources/htmlmathml.json")).unwrap();
     let data: Value = serde_json::from_str(json).unwrap();
@@ -40,3 +50,164 @@ fn main() {
     }
     write!(f, "];").unwrap();
 }
+
+/// The names of the MathML spaces that unicode.xml may use for `lspace` and `rspace`, in 18ths of
+/// an em.
+const NAMED_SPACES: [(&str, u8); 8] = [
+    ("0", 0),
+    ("veryverythinmathspace", 1),
+    ("verythinmathspace", 2),
+    ("thinmathspace", 3),
+    ("mediummathspace", 4),
+    ("thickmathspace", 5),
+    ("verythickmathspace", 6),
+    ("veryverythickmathspace", 7),
+];
+
+struct OperatorEntry {
+    character: u32,
+    form: &'static str,
+    lspace: u8,
+    rspace: u8,
+    priority: u16,
+    linebreak_style: &'static str,
+    flags: Vec<&'static str>,
+}
+
+/// Generates the operator dictionary table from the `operator-dictionary` elements of
+/// `resources/unicode.xml`.
+fn write_operator_dictionary(out_dir: &Path) {
+    let dest_path = out_dir.join("operator_dictionary.rs");
+    let source = include_str!("resources/unicode.xml");
+
+    let mut entries = Vec::new();
+    let mut reader = Reader::from_str(source);
+    // the code point of the enclosing `character` element, `None` for sequences of characters
+    let mut character = None;
+    loop {
+        let position = reader.buffer_position();
+        let error = |message: &str| -> ! { panic!("unicode.xml:{}: {}", position, message) };
+        match reader.read_event() {
+            Ok(Event::Start(ref element)) if element.name().as_ref() == b"character" => {
+                let id = attribute(element, "id").unwrap_or_else(|| error("missing id"));
+                character = id
+                    .strip_prefix('U')
+                    .filter(|code| !code.contains('-'))
+                    .map(|code| {
+                        u32::from_str_radix(code, 16)
+                            .ok()
+                            .filter(|&code| std::char::from_u32(code).is_some())
+                            .unwrap_or_else(|| error("invalid code point"))
+                    });
+            }
+            Ok(Event::End(ref element)) if element.name().as_ref() == b"character" => {
+                character = None;
+            }
+            Ok(Event::Empty(ref element)) if element.name().as_ref() == b"operator-dictionary" => {
+                let character = match character {
+                    Some(character) => character,
+                    None => continue,
+                };
+                let form = match attribute(element, "form").as_deref() {
+                    Some("prefix") => "Prefix",
+                    Some("infix") => "Infix",
+                    Some("postfix") => "Postfix",
+                    _ => error("invalid form"),
+                };
+                let space = |name: &str| {
+                    let value = attribute(element, name).unwrap_or_else(|| "5".into());
+                    NAMED_SPACES
+                        .iter()
+                        .find(|&&(space_name, _)| space_name == value)
+                        .map(|&(_, space)| space)
+                        .or_else(|| value.parse().ok())
+                        .unwrap_or_else(|| error("invalid space"))
+                };
+                let priority = attribute(element, "priority")
+                    .unwrap_or_else(|| error("missing priority"))
+                    .parse()
+                    .unwrap_or_else(|_| error("invalid priority"));
+                let linebreak_style = match attribute(element, "linebreakstyle").as_deref() {
+                    None | Some("before") => "Before",
+                    Some("after") => "After",
+                    Some("duplicate") => "Duplicate",
+                    _ => error("invalid linebreakstyle"),
+                };
+                let properties = [
+                    ("symmetric", "SYMMETRIC"),
+                    ("fence", "FENCE"),
+                    ("stretchy", "STRETCHY"),
+                    ("separator", "SEPARATOR"),
+                    ("accent", "ACCENT"),
+                    ("largeop", "LARGEOP"),
+                    ("movablelimits", "MOVABLE_LIMITS"),
+                ];
+                let flags = properties
+                    .iter()
+                    .filter(|&&(name, _)| attribute(element, name).as_deref() == Some("true"))
+                    .map(|&(_, flag)| flag)
+                    .collect();
+                entries.push(OperatorEntry {
+                    character,
+                    form,
+                    lspace: space("lspace"),
+                    rspace: space("rspace"),
+                    priority,
+                    linebreak_style,
+                    flags,
+                });
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(err) => error(&err.to_string()),
+        }
+    }
+    // `find_entry` expects the forms of an operator to be next to each other in the order infix,
+    // prefix, postfix
+    let form_order = |form| {
+        ["Infix", "Prefix", "Postfix"]
+            .iter()
+            .position(|&f| f == form)
+    };
+    entries.sort_by_key(|entry| (entry.character, form_order(entry.form)));
+    entries.dedup_by_key(|entry| (entry.character, entry.form));
+
+    let mut f = File::create(&dest_path).unwrap();
+    writeln!(
+        f,
+        "pub static DICTIONARY: [_Entry<u8>; {}] = [",
+        entries.len()
+    )
+    .unwrap();
+    for entry in entries {
+        let flags = if entry.flags.is_empty() {
+            "0".to_string()
+        } else {
+            entry.flags.join(" | ")
+        };
+        writeln!(
+            f,
+            "    _Entry {{ character: '\\u{{{:X}}}', form: Form::{}, lspace: {}, rspace: {}, \
+             priority: {}, linebreak_style: LinebreakStyle::{}, flags: {} }},",
+            entry.character,
+            entry.form,
+            entry.lspace,
+            entry.rspace,
+            entry.priority,
+            entry.linebreak_style,
+            flags
+        )
+        .unwrap();
+    }
+    write!(f, "];").unwrap();
+}
+
+/// Returns the unescaped value of the attribute `name` of `element`.
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    element
+        .try_get_attribute(name)
+        .ok()
+        .flatten()
+        .and_then(|attribute| attribute.unescape_value().ok())
+        .map(|value| value.into_owned())
+}
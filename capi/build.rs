@@ -0,0 +1,14 @@
+use std::env;
+use std::path::Path;
+
+// Generates the C header `math_render.h` from the exported functions and types. It is written to
+// `OUT_DIR` so that building never modifies the source tree.
+fn main() {
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = env::var("OUT_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    cbindgen::generate(&crate_dir)
+        .expect("could not generate the C header")
+        .write_to_file(Path::new(&out_dir).join("math_render.h"));
+}
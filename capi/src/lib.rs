@@ -0,0 +1,394 @@
+//! A C interface to the layout engine.
+//!
+//! Fonts, expressions and layouts are opaque objects that are created and freed by the functions
+//! of this crate. Functions that fail return null and store a description of the error that
+//! `math_render_last_error` returns. The build script generates the header `math_render.h` in
+//! its `OUT_DIR`. To get a copy next to the sources run
+//! `cbindgen --config cbindgen.toml --output include/math_render.h` in the directory of this
+//! crate.
+//!
+//! A formula is rendered by parsing it with `math_render_parse_mathml`, laying it out with
+//! `math_render_layout` and drawing the glyphs and rules of the layout.
+
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+use math_render::font;
+use math_render::math_box::{DeviceScale, MathBoxMetrics};
+use math_render::mathmlparser;
+use math_render::shaper::{MathShaper, OwnedShaper};
+use math_render::MathExpression;
+
+/// A math font.
+pub struct MathRenderFont(OwnedShaper);
+
+/// A parsed formula.
+pub struct MathRenderExpression(MathExpression);
+
+/// A glyph placed at an absolute position.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct MathRenderGlyph {
+    pub glyph_code: u32,
+    /// The position of the glyph origin on the baseline in device units (y increases
+    /// downwards).
+    pub x: f32,
+    pub y: f32,
+    /// The font size in device units at which the glyph has to be drawn. Glyphs in scripts are
+    /// drawn smaller than the font size of the layout.
+    pub font_size: f32,
+    /// The font size in the vertical direction. It differs from `font_size` only for delimiters
+    /// that the font can't stretch itself, whose glyphs have to be scaled around their baseline.
+    pub vertical_font_size: f32,
+}
+
+/// A filled rectangle like a fraction bar.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct MathRenderRule {
+    /// The top left corner in device units.
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// A straight line like the diagonal strike of a cancelled expression. It has to be stroked
+/// centered on the segment between its end points with butt caps.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct MathRenderSegment {
+    /// The end points in device units.
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+    pub thickness: f32,
+}
+
+/// The size of a laid out formula in device units. The baseline is at y = 0.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MathRenderMetrics {
+    pub width: f32,
+    pub ascent: f32,
+    pub descent: f32,
+}
+
+/// A laid out formula.
+pub struct MathRenderLayout {
+    metrics: MathRenderMetrics,
+    glyphs: Vec<MathRenderGlyph>,
+    rules: Vec<MathRenderRule>,
+    segments: Vec<MathRenderSegment>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: impl ToString) {
+    let message = CString::new(message.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|error| *error.borrow_mut() = Some(message));
+}
+
+/// Returns the description of the last error on the calling thread or null if no error occurred.
+/// The string stays valid until the next error on the same thread.
+#[no_mangle]
+pub extern "C" fn math_render_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| {
+        error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |error| error.as_ptr())
+    })
+}
+
+/// Loads the face with the given index of a math font from `len` bytes at `data`. The data is
+/// copied, so it may be freed afterwards. Returns null if the face has no MATH table.
+///
+/// # Safety
+/// `data` has to point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn math_render_font_new(
+    data: *const u8,
+    len: usize,
+    face_index: u32,
+) -> *mut MathRenderFont {
+    if data.is_null() {
+        set_last_error("the font data is null");
+        return ptr::null_mut();
+    }
+    let bytes = slice::from_raw_parts(data, len);
+    match panic::catch_unwind(|| font::load_math_font_face(bytes, face_index)) {
+        Ok(Ok(shaper)) => Box::into_raw(Box::new(MathRenderFont(shaper))),
+        Ok(Err(error)) => {
+            set_last_error(error);
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_last_error("loading the font panicked");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Frees a font. Null is ignored.
+///
+/// # Safety
+/// `font` has to be returned by `math_render_font_new` and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn math_render_font_free(font: *mut MathRenderFont) {
+    if !font.is_null() {
+        drop(Box::from_raw(font));
+    }
+}
+
+/// Parses `len` bytes of UTF-8 encoded MathML at `mathml`. Returns null if the MathML is
+/// invalid.
+///
+/// # Safety
+/// `mathml` has to point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn math_render_parse_mathml(
+    mathml: *const c_char,
+    len: usize,
+) -> *mut MathRenderExpression {
+    if mathml.is_null() {
+        set_last_error("the MathML is null");
+        return ptr::null_mut();
+    }
+    let bytes = slice::from_raw_parts(mathml as *const u8, len);
+    match panic::catch_unwind(|| mathmlparser::parse(bytes)) {
+        Ok(Ok(expression)) => Box::into_raw(Box::new(MathRenderExpression(expression))),
+        Ok(Err(error)) => {
+            match error.position {
+                Some(position) => set_last_error(format!("at byte {}: {}", position, error)),
+                None => set_last_error(error),
+            }
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_last_error("the parser panicked");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Frees an expression. Null is ignored.
+///
+/// # Safety
+/// `expression` has to be returned by `math_render_parse_mathml` and must not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn math_render_expression_free(expression: *mut MathRenderExpression) {
+    if !expression.is_null() {
+        drop(Box::from_raw(expression));
+    }
+}
+
+/// Lays out an expression with a font. `font_size` is the size of an em in device units (e.g.
+/// pixels) and all positions of the layout are given in the same units.
+///
+/// # Safety
+/// `expression` and `font` have to be valid pointers returned by this library.
+#[no_mangle]
+pub unsafe extern "C" fn math_render_layout(
+    expression: *const MathRenderExpression,
+    font: *const MathRenderFont,
+    font_size: f32,
+) -> *mut MathRenderLayout {
+    let (expression, font) = match (expression.as_ref(), font.as_ref()) {
+        (Some(expression), Some(font)) => (&expression.0, &font.0),
+        _ => {
+            set_last_error("the expression or the font is null");
+            return ptr::null_mut();
+        }
+    };
+    match panic::catch_unwind(AssertUnwindSafe(|| layout(expression, font, font_size))) {
+        Ok(layout) => Box::into_raw(Box::new(layout)),
+        Err(_) => {
+            set_last_error("the layout panicked");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Lays out an expression and flattens it into glyphs, rules and segments in device units.
+fn layout(expression: &MathExpression, font: &OwnedShaper, font_size: f32) -> MathRenderLayout {
+    let math_box = math_render::layout(expression, font);
+    let scale = DeviceScale::new(font.em_size(), font_size);
+    let extents = math_box.extents();
+    let metrics = MathRenderMetrics {
+        width: scale.to_device(math_box.advance_width()),
+        ascent: scale.to_device(extents.ascent),
+        descent: scale.to_device(extents.descent),
+    };
+    let display_list = math_box.flatten(&scale);
+    let glyphs = display_list
+        .glyph_runs
+        .iter()
+        .flat_map(|run| {
+            run.glyphs.iter().map(move |glyph| MathRenderGlyph {
+                glyph_code: glyph.glyph_code,
+                x: glyph.position.x,
+                y: glyph.position.y,
+                font_size: run.font_size,
+                vertical_font_size: run.vertical_font_size,
+            })
+        })
+        .collect();
+    let rules = display_list
+        .rules
+        .iter()
+        .map(|rule| MathRenderRule {
+            x: rule.origin.x,
+            y: rule.origin.y,
+            width: rule.width,
+            height: rule.height,
+        })
+        .collect();
+    let segments = display_list
+        .segments
+        .iter()
+        .map(|segment| MathRenderSegment {
+            x1: segment.from.x,
+            y1: segment.from.y,
+            x2: segment.to.x,
+            y2: segment.to.y,
+            thickness: segment.thickness,
+        })
+        .collect();
+    MathRenderLayout {
+        metrics,
+        glyphs,
+        rules,
+        segments,
+    }
+}
+
+/// Frees a layout. Null is ignored.
+///
+/// # Safety
+/// `layout` has to be returned by `math_render_layout` and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn math_render_layout_free(layout: *mut MathRenderLayout) {
+    if !layout.is_null() {
+        drop(Box::from_raw(layout));
+    }
+}
+
+/// Returns the size of a layout.
+///
+/// # Safety
+/// `layout` has to be a valid pointer returned by `math_render_layout`.
+#[no_mangle]
+pub unsafe extern "C" fn math_render_layout_metrics(
+    layout: *const MathRenderLayout,
+) -> MathRenderMetrics {
+    layout
+        .as_ref()
+        .map_or_else(MathRenderMetrics::default, |layout| layout.metrics)
+}
+
+/// Returns the glyphs of a layout and stores their number in `count`. The glyphs stay valid
+/// until the layout is freed.
+///
+/// # Safety
+/// `layout` has to be a valid pointer returned by `math_render_layout` and `count` has to be
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn math_render_layout_glyphs(
+    layout: *const MathRenderLayout,
+    count: *mut usize,
+) -> *const MathRenderGlyph {
+    let glyphs = layout.as_ref().map_or(&[][..], |layout| &layout.glyphs[..]);
+    *count = glyphs.len();
+    glyphs.as_ptr()
+}
+
+/// Returns the rules of a layout and stores their number in `count`. The rules stay valid until
+/// the layout is freed.
+///
+/// # Safety
+/// `layout` has to be a valid pointer returned by `math_render_layout` and `count` has to be
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn math_render_layout_rules(
+    layout: *const MathRenderLayout,
+    count: *mut usize,
+) -> *const MathRenderRule {
+    let rules = layout.as_ref().map_or(&[][..], |layout| &layout.rules[..]);
+    *count = rules.len();
+    rules.as_ptr()
+}
+
+/// Returns the line segments of a layout and stores their number in `count`. The segments stay
+/// valid until the layout is freed.
+///
+/// # Safety
+/// `layout` has to be a valid pointer returned by `math_render_layout` and `count` has to be
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn math_render_layout_segments(
+    layout: *const MathRenderLayout,
+    count: *mut usize,
+) -> *const MathRenderSegment {
+    let segments = layout
+        .as_ref()
+        .map_or(&[][..], |layout| &layout.segments[..]);
+    *count = segments.len();
+    segments.as_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_layout_fraction() {
+        let font_data = include_bytes!("../../tests/testfiles/latinmodern-math.otf");
+        let mathml = "<math><mfrac><mn>1</mn><mn>2</mn></mfrac></math>";
+        unsafe {
+            let font = math_render_font_new(font_data.as_ptr(), font_data.len(), 0);
+            assert!(!font.is_null());
+            let expression =
+                math_render_parse_mathml(mathml.as_ptr() as *const c_char, mathml.len());
+            assert!(!expression.is_null());
+
+            let layout = math_render_layout(expression, font, 20.0);
+            assert!(!layout.is_null());
+            let metrics = math_render_layout_metrics(layout);
+            assert!(metrics.width > 0.0 && metrics.ascent > 0.0 && metrics.descent > 0.0);
+            let mut count = 0;
+            let glyphs =
+                slice::from_raw_parts(math_render_layout_glyphs(layout, &mut count), count);
+            assert_eq!(glyphs.len(), 2);
+            // the numerator is above the denominator
+            assert!(glyphs[0].y < glyphs[1].y);
+            math_render_layout_rules(layout, &mut count);
+            assert_eq!(count, 1);
+
+            math_render_layout_free(layout);
+            math_render_expression_free(expression);
+            math_render_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let mathml = "<math><mfrac><mn>1</mn></mfrac></math>";
+        unsafe {
+            let expression =
+                math_render_parse_mathml(mathml.as_ptr() as *const c_char, mathml.len());
+            assert!(expression.is_null());
+            let error = CStr::from_ptr(math_render_last_error());
+            assert!(error.to_str().unwrap().contains("mfrac"));
+        }
+    }
+}
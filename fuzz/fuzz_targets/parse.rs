@@ -0,0 +1,7 @@
+//! Feeds arbitrary bytes to the MathML parser. Run with `cargo fuzz run parse`.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = math_render::mathmlparser::parse_lossy(data);
+});
@@ -1,238 +1,936 @@
-use freetype;
-
 use math_render;
 
+mod raster_renderer;
 mod svg_renderer;
 
-use std::borrow::Cow;
-use std::fs::File;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
 use std::io;
-use std::io::BufReader;
+use std::io::{BufRead, Cursor, Read};
 use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use math_render::font::{self, FontError, FontInfo};
+use math_render::math_box::{DeviceScale, Rounding};
+use math_render::mathmlparser::{self, ParsingError};
+use math_render::shaper::{HarfbuzzShaper, MathShaper};
+use math_render::MathExpression;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
-use freetype::Face as FT_Face;
+use notify::{DebouncedEvent, RecursiveMode, Watcher};
 
-use harfbuzz_rs::{hb, Face, Font as HbFont, HarfbuzzObject};
+use tiny_http::{Header, Method, Request, Response, Server};
 
-use math_render::mathmlparser;
-use math_render::shaper::HarfbuzzShaper;
+const EXIT_STATUS: &str = "\
+Exit status:
+    0  Success.
+    1  Some files of a batch could not be converted.
+    2  Invalid command line arguments.
+    3  No usable math font.
+    4  An input could not be read.
+    5  An input is not valid MathML.
+    6  An output could not be written.
+    7  The input could not be watched for changes.
+    8  The server could not be started.";
+
+/// Renders MathML formulas to SVG and PNG images.
+#[derive(Debug, Parser)]
+#[command(name = "mathimg", version, after_help = EXIT_STATUS)]
+struct Cli {
+    /// Don't print any diagnostics, only set the exit status.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Show additional information like the converted files.
+    #[arg(long, global = true)]
+    verbose: bool,
+    #[command(subcommand)]
+    command: Command,
+}
 
-use fontconfig::{list_fonts, Pattern};
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Renders a single file or the MathML given with --expr.
+    #[command(allow_missing_positional = true)]
+    Render(RenderArgs),
+    /// Converts every .xml and .mml file in a directory.
+    Batch(BatchArgs),
+    /// Starts an HTTP server that renders the MathML sent in the body of POST requests.
+    ///
+    /// The format of the response is chosen with the format query parameter, e.g.
+    /// POST /?format=png, and defaults to --output-format.
+    Serve(ServeArgs),
+    /// Lists all available math fonts on the system.
+    ListFonts,
+}
 
-use memmap::{Mmap, Protection};
+#[derive(Debug, Args)]
+struct RenderArgs {
+    #[command(flatten)]
+    font_args: FontArgs,
+    #[command(flatten)]
+    options: RenderOptions,
+    /// Render the given MathML string instead of an input file.
+    #[arg(short, long, value_name = "MATHML", conflicts_with = "input")]
+    expr: Option<String>,
+    /// Convert the input again whenever it changes.
+    #[arg(short, long, conflicts_with = "expr")]
+    watch: bool,
+    /// The MathML file to render or - to read it from stdin.
+    #[arg(required_unless_present = "expr")]
+    input: Option<String>,
+    /// The image file to write. If it is a directory the image is named after the input.
+    output: PathBuf,
+}
 
-use docopt::Docopt;
+#[derive(Debug, Args)]
+struct BatchArgs {
+    #[command(flatten)]
+    font_args: FontArgs,
+    #[command(flatten)]
+    options: RenderOptions,
+    /// The number of files converted in parallel.
+    #[arg(short, long, value_name = "N", default_value_t = 1)]
+    jobs: usize,
+    /// The directory with the files to convert. If it is - the paths of the files are read from
+    /// stdin, one per line.
+    input_dir: String,
+    /// The directory the images are written to.
+    output_dir: PathBuf,
+}
 
-const USAGE: &'static str = "
-Usage: mathimg [options] <input> <output>
-       mathimg list-fonts [--verbose]
+#[derive(Debug, Args)]
+struct ServeArgs {
+    #[command(flatten)]
+    font_args: FontArgs,
+    #[command(flatten)]
+    options: RenderOptions,
+    /// The address the server listens on.
+    #[arg(long, default_value = "127.0.0.1")]
+    address: String,
+    /// The port the server listens on.
+    #[arg(long, default_value_t = 8080)]
+    port: u16,
+    /// The number of requests handled in parallel.
+    #[arg(short, long, value_name = "N", default_value_t = 4)]
+    jobs: usize,
+}
 
-Subcommands:
-    list-fonts  Lists all available math fonts on the system.
+// The font that formulas are rendered with.
+#[derive(Debug, Args)]
+struct FontArgs {
+    /// Path or name of the font to use. Names are matched against the names shown by list-fonts,
+    /// ignoring case, spaces and punctuation. Partial names like 'latin modern' work as long as
+    /// they match only one font.
+    #[arg(short, long)]
+    font: Option<String>,
+    /// The face to use from a font collection. By default the face found by list-fonts or the
+    /// first face of a file.
+    #[arg(long, value_name = "N")]
+    face_index: Option<u32>,
+    /// Selects an instance of a variable font by the value of a variation axis, e.g. opsz=8 or
+    /// wght=700. Can be given once for every axis.
+    #[arg(long = "variation", value_name = "AXIS=VALUE", value_parser = parse_variation)]
+    variations: Vec<([u8; 4], f32)>,
+}
 
-Options:
-    -o FORMAT --output-format=FORMAT  The output format to use. [default: svg]
-    -f FONT --font=FONT               Name of the font to use.
-    --show-ink-bounds                 Render the ink boxes around every glyph.
-    --show-logical-bounds             Render the logical boxes around every glyph.
-    --show-top-accent-attachment      Render a line displaying top accent attachment.
-    --verbose                         Show additional information
-    ";
+// Parses the value of a variation axis given as `AXIS=VALUE`.
+fn parse_variation(arg: &str) -> Result<([u8; 4], f32), String> {
+    let (axis, value) = arg.split_once('=').ok_or("expected AXIS=VALUE")?;
+    let tag = <[u8; 4]>::try_from(axis.as_bytes())
+        .map_err(|_| format!("'{}' is not a four character axis tag", axis))?;
+    let value = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))?;
+    Ok((tag, value))
+}
 
-#[derive(Debug, RustcDecodable)]
-struct Args {
-    arg_input: String,
-    arg_output: String,
-    flag_output_format: Option<Format>,
-    cmd_list_fonts: bool,
-    flag_font: String,
-    flag_verbose: bool,
-    flag_show_ink_bounds: bool,
-    flag_show_logical_bounds: bool,
-    flag_show_top_accent_attachment: bool,
+// How formulas are rendered.
+#[derive(Debug, Args)]
+struct RenderOptions {
+    /// The output format to use.
+    #[arg(short, long, value_enum, default_value_t = Format::Svg)]
+    output_format: Format,
+    /// Render the ink boxes around every glyph (SVG only).
+    #[arg(long)]
+    show_ink_bounds: bool,
+    /// Render the logical boxes around every glyph (SVG only).
+    #[arg(long)]
+    show_logical_bounds: bool,
+    /// Render a line displaying top accent attachment (SVG only).
+    #[arg(long)]
+    show_top_accent_attachment: bool,
+    /// The font size in pixels at 96 dpi. By default SVG output uses font units and PNG output
+    /// a font size of 32.
+    #[arg(long, value_name = "PX")]
+    font_size: Option<f32>,
+    /// The resolution of PNG output.
+    #[arg(long, value_name = "N", default_value_t = 96.0)]
+    dpi: f32,
+    /// Rounding of coordinates.
+    #[arg(long, value_enum, default_value_t = RoundingMode::Exact)]
+    rounding: RoundingMode,
+    /// Fit SVG output to the ink of the formula.
+    #[arg(long)]
+    tight: bool,
+    /// The space around the formula in font units.
+    #[arg(long, value_name = "UNITS", default_value_t = 10)]
+    padding: i32,
+    /// The minimum thickness of rules in pixels if a font size is given.
+    #[arg(long, value_name = "PX", default_value_t = 1.0)]
+    min_rule_thickness: f32,
 }
 
-#[derive(RustcDecodable, Debug, Copy, Clone)]
+#[derive(ValueEnum, Debug, Copy, Clone)]
 enum Format {
     Svg,
+    Png,
+}
+
+#[derive(ValueEnum, Debug, Copy, Clone)]
+enum RoundingMode {
+    /// No rounding.
+    Exact,
+    /// Round to 1/64 pixels.
+    Fixed,
+    /// Round to whole pixels.
+    Pixel,
+}
+
+impl From<RoundingMode> for Rounding {
+    fn from(mode: RoundingMode) -> Rounding {
+        match mode {
+            RoundingMode::Exact => Rounding::Exact,
+            RoundingMode::Fixed => Rounding::FixedPoint,
+            RoundingMode::Pixel => Rounding::Pixel,
+        }
+    }
 }
 
 impl Format {
     fn extension(self) -> &'static str {
         match self {
             Format::Svg => ".svg",
+            Format::Png => ".png",
         }
     }
+
+    fn media_type(self) -> &'static str {
+        match self {
+            Format::Svg => "image/svg+xml",
+            Format::Png => "image/png",
+        }
+    }
+}
+
+/// The font size of PNG output if none is given on the command line.
+const DEFAULT_RASTER_FONT_SIZE: f32 = 32.0;
+
+fn find_math_fonts() -> Vec<FontInfo> {
+    font::find_math_fonts()
+        .into_iter()
+        .filter(|font| font.has_math_table)
+        .collect()
 }
 
+/// Everything that can go wrong when running mathimg. Every kind of error exits with its own
+/// status so that scripts can react to it (see `EXIT_STATUS`).
 #[derive(Debug)]
-struct Font {
-    name: String,
-    path: PathBuf,
-    face_index: u32,
+enum Error {
+    /// The command line arguments don't fit together.
+    Usage(String),
+    /// No math font was found or the font couldn't be loaded.
+    Font(String),
+    /// An input couldn't be read.
+    Input { path: PathBuf, error: io::Error },
+    /// An input isn't valid MathML. The location is the line and column of the error.
+    Parse {
+        input: String,
+        location: Option<(usize, usize)>,
+        error: ParsingError,
+    },
+    /// An output couldn't be written.
+    Output { path: PathBuf, message: String },
+    /// The given number of files of a batch couldn't be converted.
+    Batch(usize),
+    /// Changes of the input can't be watched.
+    Watch(String),
+    /// The server can't be started or a response couldn't be sent.
+    Serve(String),
 }
 
-struct Shaper<'a> {
-    hb_shaper: HarfbuzzShaper<'a>,
-    ft_face: FT_Face<'a>,
+impl Error {
+    fn exit_code(&self) -> i32 {
+        match *self {
+            Error::Batch(_) => 1,
+            Error::Usage(_) => 2,
+            Error::Font(_) => 3,
+            Error::Input { .. } => 4,
+            Error::Parse { .. } => 5,
+            Error::Output { .. } => 6,
+            Error::Watch(_) => 7,
+            Error::Serve(_) => 8,
+        }
+    }
 }
 
-fn find_math_fonts() -> Vec<Font> {
-    let pat = Pattern::new();
-    let fontset = list_fonts(&pat);
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Usage(ref message) | Error::Font(ref message) => write!(f, "{}", message),
+            Error::Watch(ref message) => write!(f, "could not watch the input: {}", message),
+            Error::Serve(ref message) => write!(f, "server error: {}", message),
+            Error::Input {
+                ref path,
+                ref error,
+            } => write!(f, "could not read {}: {}", path.display(), error),
+            Error::Parse {
+                ref input,
+                location: Some((line, column)),
+                ref error,
+            } => write!(f, "{}:{}:{}: {}", input, line, column, error),
+            Error::Parse {
+                ref input,
+                location: None,
+                ref error,
+            } => write!(f, "{}: {}", input, error),
+            Error::Output {
+                ref path,
+                ref message,
+            } => write!(f, "could not write {}: {}", path.display(), message),
+            Error::Batch(failures) => write!(f, "{} file(s) could not be converted", failures),
+        }
+    }
+}
 
-    (&fontset)
-        .iter()
-        .filter_map(|pattern| {
-            pattern.get_string("capability").and_then(|cap| {
-                if cap.contains("otlayout:math") {
-                    Some(Font {
-                        name: pattern.name().unwrap().into(),
-                        path: pattern.filename().unwrap().into(),
-                        face_index: pattern.face_index().unwrap() as u32,
-                    })
-                } else {
-                    None
-                }
-            })
-        })
-        .filter(has_math_data)
-        .collect()
+fn main() {
+    // invalid arguments exit with the status of `Error::Usage`
+    let cli = Cli::parse();
+
+    if let Err(error) = run(&cli) {
+        report(&cli, &error);
+        process::exit(error.exit_code());
+    }
 }
 
-/// checks if a math table exists in the font
-fn has_math_data(font: &Font) -> bool {
-    let mapped_file = Mmap::open_path(&font.path, Protection::Read).unwrap();
-    let buffer = unsafe { mapped_file.as_slice() };
-    let face = Face::new(buffer, font.face_index);
-    let result = unsafe { hb::hb_ot_math_has_data(face.as_raw()) };
-    result != 0
-}
-
-fn create_shaper<'a>(font_bytes: &'a [u8]) -> Shaper<'a> {
-    // let mut font_funcs = FontFuncsBuilder::new();
-    // font_funcs.set_glyph_extents_func(|_, ft_face, glyph| {
-    //     let result = FT_Face::load_glyph(ft_face, glyph, face::NO_SCALE);
-    //     if result.is_err() {
-    //         return None;
-    //     }
-    //     let metrics = ft_face.glyph().metrics();
-    //     Some(GlyphExtents {
-    //         width: metrics.width as i32,
-    //         height: -metrics.height as i32,
-    //         x_bearing: metrics.horiBearingX as i32,
-    //         y_bearing: metrics.horiBearingY as i32,
-    //     })
-    // });
-    // let font_funcs = font_funcs.finish();
-    let library = freetype::Library::init().unwrap();
-    let face = library.new_memory_face(font_bytes, 0).unwrap();
-    let hb_face = Face::new(font_bytes, 0);
-    let font = HbFont::new(hb_face);
-    // font.set_font_funcs(&font_funcs, face.clone());
-    let hb_shaper = HarfbuzzShaper::new(font.into());
-    Shaper {
-        hb_shaper: hb_shaper,
-        ft_face: face,
+/// Prints an error unless `--quiet` is given.
+fn report(cli: &Cli, error: &Error) {
+    if !cli.quiet {
+        eprintln!("mathimg: {}", error);
     }
 }
 
-fn main() {
-    let args: Args = Docopt::new(USAGE)
-        .and_then(|d| d.decode())
-        .unwrap_or_else(|e| e.exit());
+fn run(cli: &Cli) -> Result<(), Error> {
+    let font_args = match cli.command {
+        Command::Render(ref args) => &args.font_args,
+        Command::Batch(ref args) => &args.font_args,
+        Command::Serve(ref args) => &args.font_args,
+        Command::ListFonts => return list_fonts(cli),
+    };
 
-    let (list, output_name) = if args.arg_input == "-" {
-        let stdin = io::stdin();
-        let handle = stdin.lock();
-        (Some(mathmlparser::parse(handle).unwrap()), "output".into())
-    } else if args.arg_input != "" {
-        let path = match PathBuf::from(args.arg_input.clone()).canonicalize() {
-            Ok(path) => path,
-            Err(err) => {
-                println!("Error opening {:?}", args.arg_input);
-                panic!("{}", err);
-            }
-        };
-        let file = File::open(&path).unwrap();
-        let name = path
-            .file_stem()
-            .or_else(|| path.file_name())
-            .expect("input file has no name");
-        (
-            Some(mathmlparser::parse(BufReader::new(file)).expect("could not parse file")),
-            Cow::from(name.to_string_lossy().into_owned()),
-        )
-    } else {
-        (None, "".into())
+    let (font_path, face_index) = find_font(font_args)?;
+    let shaper = font::load_math_font_face(font_path.as_path(), face_index).map_err(|error| {
+        Error::Font(match error {
+            FontError::Io(error) => format!("could not load {}: {}", font_path.display(), error),
+            FontError::NoMathTable => format!(
+                "face {} of {} has no MATH table",
+                face_index,
+                font_path.display()
+            ),
+        })
+    })?;
+    let shaper = shaper.with_variations(&font_args.variations);
+
+    match cli.command {
+        Command::Render(ref args) if args.watch => watch(cli, args, &shaper),
+        Command::Render(ref args) => convert_input(cli, args, &shaper),
+        Command::Batch(ref args) => batch(cli, args, &shaper),
+        Command::Serve(ref args) => serve(cli, args, &shaper),
+        Command::ListFonts => unreachable!("listing the fonts doesn't load one"),
+    }
+}
+
+/// Converts the input of the render subcommand.
+fn convert_input(cli: &Cli, args: &RenderArgs, shaper: &HarfbuzzShaper<'_>) -> Result<(), Error> {
+    let (expr, output_name) = read_expression(args)?;
+    let mut out_path = args.output.clone();
+    if out_path.is_dir() {
+        out_path.push(output_name + args.options.output_format.extension());
+    }
+
+    write_output(&expr, shaper, &args.options, &out_path)?;
+    if cli.verbose {
+        println!("{} -> {}", input_name(args), out_path.display());
+    }
+    Ok(())
+}
+
+/// Converts the input every time it changes until mathimg is stopped. Errors are reported but
+/// don't stop watching, so mistakes in the input can be fixed while mathimg keeps running.
+fn watch(cli: &Cli, args: &RenderArgs, shaper: &HarfbuzzShaper<'_>) -> Result<(), Error> {
+    let input = match args.input {
+        Some(ref input) if input != "-" => Path::new(input),
+        _ => return Err(Error::Usage("--watch needs an input file".into())),
     };
+    let input = input.canonicalize().map_err(|error| Error::Input {
+        path: input.to_owned(),
+        error,
+    })?;
 
-    if args.cmd_list_fonts {
-        let vec = find_math_fonts();
-        if vec.len() == 0 {
-            panic!("Found no math fonts.");
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::watcher(sender, Duration::from_millis(100))
+        .map_err(|error| Error::Watch(error.to_string()))?;
+    // many editors save a file by replacing it, so the directory is watched instead of the file
+    let directory = input.parent().unwrap_or(&input);
+    watcher
+        .watch(directory, RecursiveMode::NonRecursive)
+        .map_err(|error| Error::Watch(error.to_string()))?;
+
+    let convert = || {
+        if let Err(error) = convert_input(cli, args, shaper) {
+            report(cli, &error);
         }
+    };
+    convert();
+    for event in receiver {
+        let changed = match event {
+            DebouncedEvent::Create(ref path)
+            | DebouncedEvent::Write(ref path)
+            | DebouncedEvent::Rename(_, ref path) => *path == input,
+            DebouncedEvent::Error(error, _) => return Err(Error::Watch(error.to_string())),
+            _ => false,
+        };
+        if changed {
+            convert();
+        }
+    }
+    Ok(())
+}
+
+fn list_fonts(cli: &Cli) -> Result<(), Error> {
+    let fonts = find_math_fonts();
+    if fonts.is_empty() {
+        return Err(Error::Font("found no math fonts".into()));
+    }
 
-        for font in &vec {
-            print!("{}", font.name);
-            if args.flag_verbose {
-                print!(": {:?}", font.path);
+    for font in &fonts {
+        print!("{}", font.name);
+        if cli.verbose {
+            print!(": {:?}", font.path);
+            if font.face_index != 0 {
+                print!(" (face {})", font.face_index);
             }
-            print!("\n");
         }
-        return;
+        println!();
     }
+    Ok(())
+}
 
-    let font_path = if args.flag_font.is_empty() {
-        PathBuf::from(
-            find_math_fonts()
-                .get(0)
-                .expect("Could not find suitable math font on system.")
-                .path
-                .clone(),
-        )
-    } else {
-        match PathBuf::from(args.flag_font.clone()).canonicalize() {
-            Ok(path) => path,
-            Err(err) => {
-                println!("Error opening {:?}", args.flag_font);
-                panic!("{}", err);
+/// Lowercases a font name and removes everything but letters and digits.
+fn normalize_font_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Returns the math fonts whose name matches `query` best. A name matches exactly if it is equal
+/// after normalization. Otherwise the fonts whose name starts with the query are preferred over
+/// those that only contain it.
+fn match_font_name(fonts: Vec<FontInfo>, query: &str) -> Vec<FontInfo> {
+    let query = normalize_font_name(query);
+    let score = |font: &FontInfo| {
+        let name = normalize_font_name(&font.name);
+        if name == query {
+            3
+        } else if name.starts_with(&query) {
+            2
+        } else if name.contains(&query) {
+            1
+        } else {
+            0
+        }
+    };
+    let best = fonts.iter().map(score).max().unwrap_or(0);
+    if best == 0 {
+        return Vec::new();
+    }
+    fonts
+        .into_iter()
+        .filter(|font| score(font) == best)
+        .collect()
+}
+
+/// Returns the path and face index of the font given on the command line, which is either a
+/// file or the name of a math font installed on the system. Without `--font` the first math
+/// font is used.
+fn find_font(args: &FontArgs) -> Result<(PathBuf, u32), Error> {
+    let font = match args.font {
+        None => find_math_fonts().into_iter().next().ok_or_else(|| {
+            Error::Font("could not find a suitable math font on the system".into())
+        })?,
+        Some(ref font) if Path::new(font).exists() => {
+            let path = PathBuf::from(font)
+                .canonicalize()
+                .map_err(|error| Error::Font(format!("could not open {}: {}", font, error)))?;
+            return Ok((path, args.face_index.unwrap_or(0)));
+        }
+        Some(ref font) => {
+            let mut matches = match_font_name(find_math_fonts(), font);
+            let mut names = matches
+                .iter()
+                .map(|font| font.name.as_str())
+                .collect::<Vec<_>>();
+            names.dedup();
+            match names.len() {
+                0 => {
+                    return Err(Error::Font(format!(
+                        "{} is neither a file nor the name of a math font (see list-fonts)",
+                        font
+                    )))
+                }
+                1 => matches.remove(0),
+                _ => {
+                    return Err(Error::Font(format!(
+                        "font name {} is ambiguous, it matches {}",
+                        font,
+                        names.join(", ")
+                    )))
+                }
             }
         }
     };
+    Ok((font.path, args.face_index.unwrap_or(font.face_index)))
+}
 
-    let mut out_path = Cow::from(Path::new(&args.arg_output));
-    if out_path.is_dir() {
-        let extension = args
-            .flag_output_format
-            .map(|format| format.extension())
-            .unwrap_or("");
-        out_path.to_mut().push(output_name.into_owned() + extension);
+/// The name of the input in diagnostics.
+fn input_name(args: &RenderArgs) -> &str {
+    match (&args.expr, &args.input) {
+        (Some(_), _) => "<expr>",
+        (None, Some(input)) if input != "-" => input,
+        (None, _) => "<stdin>",
     }
+}
+
+/// Returns the line and column (both starting at 1) of a byte offset into `text`.
+fn line_and_column(text: &[u8], offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line_start = before
+        .iter()
+        .rposition(|&byte| byte == b'\n')
+        .map_or(0, |index| index + 1);
+    let line = before.iter().filter(|&&byte| byte == b'\n').count() + 1;
+    let column = String::from_utf8_lossy(&before[line_start..]);
+    (line, column.chars().count() + 1)
+}
+
+fn parse(text: &[u8], input: &str) -> Result<MathExpression, Error> {
+    mathmlparser::parse(text).map_err(|error| Error::Parse {
+        input: input.to_string(),
+        location: error.position.map(|offset| line_and_column(text, offset)),
+        error,
+    })
+}
 
-    let mapped_file =
-        Mmap::open_path(font_path, Protection::Read).expect("could not mmap font file");
-    let font_bytes = unsafe { mapped_file.as_slice() };
+/// Reads the expression to render if a single file is converted and returns it together with
+/// the name of the output file.
+fn read_expression(args: &RenderArgs) -> Result<(MathExpression, String), Error> {
+    match (&args.expr, &args.input) {
+        (Some(expr), _) => Ok((parse(expr.as_bytes(), input_name(args))?, "output".into())),
+        (None, Some(input)) if input != "-" => {
+            let path = Path::new(input);
+            let text = fs::read(path).map_err(|error| Error::Input {
+                path: path.to_owned(),
+                error,
+            })?;
+            let name = path.file_stem().or_else(|| path.file_name()).map_or_else(
+                || "output".into(),
+                |name| name.to_string_lossy().into_owned(),
+            );
+            Ok((parse(&text, input_name(args))?, name))
+        }
+        (None, _) => {
+            let mut text = Vec::new();
+            io::stdin()
+                .lock()
+                .read_to_end(&mut text)
+                .map_err(|error| Error::Input {
+                    path: PathBuf::from(input_name(args)),
+                    error,
+                })?;
+            Ok((parse(&text, input_name(args))?, "output".into()))
+        }
+    }
+}
 
-    let shaper = create_shaper(font_bytes);
+/// Lays out and renders an expression and returns the encoded image. With `safe_urls` links and
+/// images in SVG output are dropped unless their URLs are relative or use a safe scheme.
+fn render(
+    expr: &MathExpression,
+    shaper: &HarfbuzzShaper<'_>,
+    options: &RenderOptions,
+    format: Format,
+    safe_urls: bool,
+) -> Result<Vec<u8>, String> {
+    // the font size in device pixels
+    let font_size = match format {
+        Format::Png => {
+            Some(options.font_size.unwrap_or(DEFAULT_RASTER_FONT_SIZE) * options.dpi / 96.0)
+        }
+        Format::Svg => options.font_size,
+    };
 
-    let typeset = math_render::layout(list.as_ref().unwrap(), &shaper.hb_shaper);
-    match args.flag_output_format {
-        Some(Format::Svg) => {
+    let mut typeset = math_render::layout(expr, shaper);
+    if let Some(font_size) = font_size {
+        let scale = DeviceScale::new(shaper.em_size(), font_size);
+        typeset.enforce_min_rule_thickness(&scale, options.min_rule_thickness);
+    }
+    match format {
+        Format::Svg => {
             let flags = svg_renderer::Flags {
-                show_ink_bounds: args.flag_show_ink_bounds,
-                show_logical_bounds: args.flag_show_logical_bounds,
-                show_top_accent_attachment: args.flag_show_top_accent_attachment,
+                show_ink_bounds: options.show_ink_bounds,
+                show_logical_bounds: options.show_logical_bounds,
+                show_top_accent_attachment: options.show_top_accent_attachment,
+                font_size,
+                rounding: options.rounding.into(),
+                tight: options.tight,
+                padding: options.padding,
+                safe_urls,
+            };
+
+            Ok(svg_renderer::render(typeset, shaper, flags).into_bytes())
+        }
+        Format::Png => {
+            let flags = raster_renderer::Flags {
+                font_size: font_size.unwrap_or(DEFAULT_RASTER_FONT_SIZE),
+                rounding: options.rounding.into(),
+                padding: options.padding,
             };
 
-            svg_renderer::render(
-                typeset,
-                &shaper.hb_shaper,
-                &shaper.ft_face,
-                flags,
-                &out_path,
-            )
+            raster_renderer::render(typeset, shaper, flags).map_err(|error| error.to_string())
+        }
+    }
+}
+
+/// Renders an expression in the output format given on the command line to a file.
+fn write_output(
+    expr: &MathExpression,
+    shaper: &HarfbuzzShaper<'_>,
+    options: &RenderOptions,
+    out_path: &Path,
+) -> Result<(), Error> {
+    let output_error = |message: String| Error::Output {
+        path: out_path.to_owned(),
+        message,
+    };
+    let image =
+        render(expr, shaper, options, options.output_format, false).map_err(output_error)?;
+    fs::write(out_path, image).map_err(|error| output_error(error.to_string()))
+}
+
+/// Returns the format requested with the `format` query parameter of a URL or `None` if the
+/// format is unknown.
+fn requested_format(url: &str, default: Format) -> Option<Format> {
+    let query = url.split_once('?').map_or("", |(_, query)| query);
+    let format = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("format="));
+    match format {
+        None => Some(default),
+        Some("svg") => Some(Format::Svg),
+        Some("png") => Some(Format::Png),
+        Some(_) => None,
+    }
+}
+
+/// The media types of TeX documents.
+const TEX_MEDIA_TYPES: [&str; 2] = ["application/x-tex", "text/x-tex"];
+
+/// Returns true if the request says that its body is TeX.
+fn is_tex(request: &Request) -> bool {
+    request.headers().iter().any(|header| {
+        header.field.equiv("Content-Type") && is_tex_media_type(header.value.as_str())
+    })
+}
+
+/// Returns true if the value of a `Content-Type` header is the media type of TeX. Parameters
+/// like the charset are ignored.
+fn is_tex_media_type(content_type: &str) -> bool {
+    let media_type = content_type.split(';').next().unwrap_or_default();
+    let media_type = media_type.trim().to_ascii_lowercase();
+    TEX_MEDIA_TYPES.contains(&media_type.as_str())
+}
+
+/// The largest request body the server accepts.
+const MAX_BODY_SIZE: u64 = 1 << 20;
+
+/// How long the server waits for the body of a request.
+const BODY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The most request bodies that are read at the same time, counting the bodies of requests that
+/// already timed out. Further requests are rejected until a body is read, so slow clients can't
+/// pile up threads.
+const MAX_BODY_READERS: usize = 64;
+
+type HttpResponse = Response<Cursor<Vec<u8>>>;
+
+/// Returns a response with the given status that explains it in the body.
+fn error_response(status: u16, message: &str) -> HttpResponse {
+    Response::from_string(format!("{}\n", message)).with_status_code(status)
+}
+
+/// Checks the method and headers of a request and returns the image format it asks for.
+fn check_request(request: &Request, options: &RenderOptions) -> Result<Format, HttpResponse> {
+    if *request.method() != Method::Post {
+        return Err(error_response(
+            405,
+            "send the MathML to render in a POST request",
+        ));
+    }
+    let format = requested_format(request.url(), options.output_format)
+        .ok_or_else(|| error_response(400, "unknown format, use svg or png"))?;
+    if is_tex(request) {
+        return Err(error_response(
+            415,
+            "TeX input is not supported, send MathML instead",
+        ));
+    }
+    if matches!(request.body_length(), Some(length) if length as u64 > MAX_BODY_SIZE) {
+        return Err(error_response(413, "the request body is too large"));
+    }
+    Ok(format)
+}
+
+/// Reads the body of a request on another thread, so a client that sends it slowly can't stall
+/// the server. Returns `None` if the body doesn't arrive within `BODY_TIMEOUT`. The request is then
+/// answered by the reading thread.
+///
+/// `readers` counts the threads that are reading bodies. The request is rejected if there are
+/// already `MAX_BODY_READERS` of them.
+fn read_body(
+    mut request: Request,
+    readers: &Arc<AtomicUsize>,
+) -> Option<(Request, Result<Vec<u8>, HttpResponse>)> {
+    if readers.fetch_add(1, Ordering::SeqCst) >= MAX_BODY_READERS {
+        readers.fetch_sub(1, Ordering::SeqCst);
+        let response = error_response(503, "the server is busy, try again later");
+        return Some((request, Err(response)));
+    }
+    let readers = Arc::clone(readers);
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut body = Vec::new();
+        let result = match request
+            .as_reader()
+            .take(MAX_BODY_SIZE + 1)
+            .read_to_end(&mut body)
+        {
+            Ok(_) if body.len() as u64 > MAX_BODY_SIZE => {
+                Err(error_response(413, "the request body is too large"))
+            }
+            Ok(_) => Ok(body),
+            Err(io_error) => Err(error_response(400, &io_error.to_string())),
+        };
+        readers.fetch_sub(1, Ordering::SeqCst);
+        if let Err(mpsc::SendError((request, _))) = sender.send((request, result)) {
+            let _ = request.respond(error_response(408, "the request body took too long"));
+        }
+    });
+    receiver.recv_timeout(BODY_TIMEOUT).ok()
+}
+
+/// Renders the body of a request and returns the response to it.
+fn handle_request(
+    body: &[u8],
+    format: Format,
+    shaper: &HarfbuzzShaper<'_>,
+    options: &RenderOptions,
+) -> HttpResponse {
+    let expr = match parse(body, "request") {
+        Ok(expr) => expr,
+        Err(parse_error) => return error_response(400, &parse_error.to_string()),
+    };
+    // the image goes to an arbitrary client, so it must not contain script links
+    match render(&expr, shaper, options, format, true) {
+        Ok(image) => {
+            let content_type = Header::from_bytes("Content-Type", format.media_type())
+                .expect("the content type is a valid header");
+            Response::from_data(image).with_header(content_type)
+        }
+        Err(message) => error_response(500, &message),
+    }
+}
+
+/// Answers a single request to render MathML.
+fn answer(
+    cli: &Cli,
+    request: Request,
+    shaper: &HarfbuzzShaper<'_>,
+    options: &RenderOptions,
+    readers: &Arc<AtomicUsize>,
+) {
+    if cli.verbose {
+        println!("{} {}", request.method(), request.url());
+    }
+    let (request, response) = match check_request(&request, options) {
+        Ok(format) => match read_body(request, readers) {
+            Some((request, Ok(body))) => {
+                let response = handle_request(&body, format, shaper, options);
+                (request, response)
+            }
+            Some((request, Err(response))) => (request, response),
+            None => {
+                report(
+                    cli,
+                    &Error::Serve("timed out reading a request".to_string()),
+                );
+                return;
+            }
+        },
+        Err(response) => (request, response),
+    };
+    if let Err(error) = request.respond(response) {
+        report(cli, &Error::Serve(error.to_string()));
+    }
+}
+
+/// Answers requests to render MathML until mathimg is stopped. The font stays loaded, so every
+/// request only needs to be laid out and rendered. Requests are handled by `jobs` threads, so a
+/// slow request doesn't hold up the others.
+fn serve(cli: &Cli, args: &ServeArgs, shaper: &HarfbuzzShaper<'_>) -> Result<(), Error> {
+    let server = Server::http((args.address.as_str(), args.port))
+        .map_err(|error| Error::Serve(error.to_string()))?;
+    if cli.verbose {
+        println!("listening on {}:{}", args.address, args.port);
+    }
+    let readers = Arc::new(AtomicUsize::new(0));
+    thread::scope(|scope| {
+        for _ in 0..args.jobs.max(1) {
+            scope.spawn(|| {
+                for request in server.incoming_requests() {
+                    answer(cli, request, shaper, &args.options, &readers);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+/// Collects the files to convert in batch mode.
+fn batch_inputs(input: &str) -> io::Result<Vec<PathBuf>> {
+    if input == "-" {
+        let stdin = io::stdin();
+        let lines = stdin.lock().lines().collect::<io::Result<Vec<_>>>()?;
+        Ok(lines
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(PathBuf::from)
+            .collect())
+    } else {
+        let mut paths = fs::read_dir(input)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        paths.retain(|path| {
+            path.extension()
+                .map_or(false, |ext| ext == "xml" || ext == "mml")
+        });
+        paths.sort();
+        Ok(paths)
+    }
+}
+
+/// Returns the path of the image of every input of a batch. Images are named after their input,
+/// so inputs with the same name in different directories (or with different extensions) are
+/// rejected instead of overwriting each other's image.
+fn batch_outputs(
+    inputs: &[PathBuf],
+    out_dir: &Path,
+    extension: &str,
+) -> Result<Vec<PathBuf>, Error> {
+    let mut sources = HashMap::new();
+    let mut outputs = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let mut file_name = input.file_stem().unwrap_or_default().to_os_string();
+        file_name.push(extension);
+        let out_path = out_dir.join(file_name);
+        if let Some(other) = sources.insert(out_path.clone(), input) {
+            return Err(Error::Output {
+                message: format!(
+                    "both {} and {} would be written to it",
+                    other.display(),
+                    input.display()
+                ),
+                path: out_path,
+            });
+        }
+        outputs.push(out_path);
+    }
+    Ok(outputs)
+}
+
+/// Converts a single file of a batch.
+fn convert(
+    input: &Path,
+    out_path: &Path,
+    shaper: &HarfbuzzShaper<'_>,
+    options: &RenderOptions,
+) -> Result<(), Error> {
+    let text = fs::read(input).map_err(|error| Error::Input {
+        path: input.to_owned(),
+        error,
+    })?;
+    let expr = parse(&text, &input.display().to_string())?;
+    write_output(&expr, shaper, options, out_path)
+}
+
+/// Converts all input files of a batch with the same shaper. Files that fail are reported and
+/// counted but don't stop the batch.
+fn batch(cli: &Cli, args: &BatchArgs, shaper: &HarfbuzzShaper<'_>) -> Result<(), Error> {
+    let inputs = batch_inputs(&args.input_dir).map_err(|error| Error::Input {
+        path: PathBuf::from(&args.input_dir),
+        error,
+    })?;
+    let out_dir = &args.output_dir;
+    let extension = args.options.output_format.extension();
+    let outputs = batch_outputs(&inputs, out_dir, extension)?;
+    let conversions = inputs.iter().zip(&outputs).collect::<Vec<_>>();
+    fs::create_dir_all(out_dir).map_err(|error| Error::Output {
+        path: out_dir.to_owned(),
+        message: error.to_string(),
+    })?;
+
+    let next_input = AtomicUsize::new(0);
+    let failures = AtomicUsize::new(0);
+    let jobs = args.jobs.max(1).min(inputs.len().max(1));
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                while let Some(&(input, out_path)) =
+                    conversions.get(next_input.fetch_add(1, Ordering::Relaxed))
+                {
+                    match convert(input, out_path, shaper, &args.options) {
+                        Ok(()) if cli.verbose => {
+                            println!("{} -> {}", input.display(), out_path.display())
+                        }
+                        Ok(()) => {}
+                        Err(error) => {
+                            report(cli, &error);
+                            failures.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                }
+            });
         }
-        _ => panic!(),
+    });
+    match failures.into_inner() {
+        0 => Ok(()),
+        failures => Err(Error::Batch(failures)),
     }
 }
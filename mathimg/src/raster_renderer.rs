@@ -0,0 +1,312 @@
+use math_render;
+
+use math_render::math_box::*;
+use math_render::outline::{GlyphOutlines, PathSegment};
+use math_render::shaper::*;
+
+pub struct Flags {
+    /// The font size in device pixels.
+    pub font_size: f32,
+    pub rounding: Rounding,
+    /// The space around the formula in font units.
+    pub padding: i32,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Point {
+    x: f32,
+    y: f32,
+}
+
+impl Point {
+    fn new(x: f32, y: f32) -> Point {
+        Point { x, y }
+    }
+
+    fn lerp(self, other: Point, t: f32) -> Point {
+        Point::new(
+            self.x + t * (other.x - self.x),
+            self.y + t * (other.y - self.y),
+        )
+    }
+}
+
+/// An anti-aliased coverage mask.
+///
+/// Every edge of a shape adds its signed area to an accumulation buffer. A running sum over the
+/// buffer then yields the coverage of every pixel (using the non-zero winding rule for shapes
+/// that don't overlap themselves).
+struct Canvas {
+    width: usize,
+    height: usize,
+    accumulation: Vec<f32>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            // edges ending at the right border write one element past the end of their row
+            accumulation: vec![0.0; width * height + 4],
+        }
+    }
+
+    fn draw_line(&mut self, p0: Point, p1: Point) {
+        if p0.y == p1.y {
+            return;
+        }
+        let (direction, p0, p1) = if p0.y < p1.y {
+            (1.0, p0, p1)
+        } else {
+            (-1.0, p1, p0)
+        };
+        let width = self.width as f32;
+        let dxdy = (p1.x - p0.x) / (p1.y - p0.y);
+        let mut x = p0.x;
+        if p0.y < 0.0 {
+            x -= p0.y * dxdy;
+        }
+        let y_start = p0.y.max(0.0) as usize;
+        let y_end = (p1.y.ceil().max(0.0) as usize).min(self.height);
+        for y in y_start..y_end {
+            let row = y * self.width;
+            let dy = p1.y.min((y + 1) as f32) - p0.y.max(y as f32);
+            let x_next = x + dxdy * dy;
+            let d = dy * direction;
+            // clip against the left and right border, the area still ends up in the right row
+            let (x0, x1) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let (x0, x1) = (x0.max(0.0).min(width), x1.max(0.0).min(width));
+            let x0_floor = x0.floor();
+            let x0_index = x0_floor as usize;
+            let x1_ceil = x1.ceil();
+            let x1_index = x1_ceil as usize;
+            if x1_index <= x0_index + 1 {
+                let x_mid = 0.5 * (x0 + x1) - x0_floor;
+                self.accumulation[row + x0_index] += d - d * x_mid;
+                self.accumulation[row + x0_index + 1] += d * x_mid;
+            } else {
+                let s = (x1 - x0).recip();
+                let x0_fract = x0 - x0_floor;
+                let a0 = 0.5 * s * (1.0 - x0_fract) * (1.0 - x0_fract);
+                let x1_fract = x1 - x1_ceil + 1.0;
+                let a_max = 0.5 * s * x1_fract * x1_fract;
+                self.accumulation[row + x0_index] += d * a0;
+                if x1_index == x0_index + 2 {
+                    self.accumulation[row + x0_index + 1] += d * (1.0 - a0 - a_max);
+                } else {
+                    let a1 = s * (1.5 - x0_fract);
+                    self.accumulation[row + x0_index + 1] += d * (a1 - a0);
+                    for index in x0_index + 2..x1_index - 1 {
+                        self.accumulation[row + index] += d * s;
+                    }
+                    let a2 = a1 + (x1_index - x0_index - 3) as f32 * s;
+                    self.accumulation[row + x1_index - 1] += d * (1.0 - a2 - a_max);
+                }
+                self.accumulation[row + x1_index] += d * a_max;
+            }
+            x = x_next;
+        }
+    }
+
+    fn draw_quad(&mut self, p0: Point, p1: Point, p2: Point) {
+        let dev_x = p0.x - 2.0 * p1.x + p2.x;
+        let dev_y = p0.y - 2.0 * p1.y + p2.y;
+        let segments = segment_count(dev_x * dev_x + dev_y * dev_y);
+        let mut last = p0;
+        for i in 1..segments {
+            let t = i as f32 / segments as f32;
+            let next = p0.lerp(p1, t).lerp(p1.lerp(p2, t), t);
+            self.draw_line(last, next);
+            last = next;
+        }
+        self.draw_line(last, p2);
+    }
+
+    fn draw_cubic(&mut self, p0: Point, p1: Point, p2: Point, p3: Point) {
+        let dev = |a: Point, b: Point, c: Point| {
+            let x = a.x - 2.0 * b.x + c.x;
+            let y = a.y - 2.0 * b.y + c.y;
+            x * x + y * y
+        };
+        let segments = segment_count(dev(p0, p1, p2).max(dev(p1, p2, p3)));
+        let mut last = p0;
+        for i in 1..segments {
+            let t = i as f32 / segments as f32;
+            let (a, b, c) = (p0.lerp(p1, t), p1.lerp(p2, t), p2.lerp(p3, t));
+            let next = a.lerp(b, t).lerp(b.lerp(c, t), t);
+            self.draw_line(last, next);
+            last = next;
+        }
+        self.draw_line(last, p3);
+    }
+
+    fn fill_polygon(&mut self, points: &[Point]) {
+        for (index, &point) in points.iter().enumerate() {
+            self.draw_line(point, points[(index + 1) % points.len()]);
+        }
+    }
+
+    /// Converts the coverage to 8 bit gray values (black ink on a white background).
+    fn to_gray(&self) -> Vec<u8> {
+        let mut coverage = 0.0f32;
+        self.accumulation[..self.width * self.height]
+            .iter()
+            .map(|value| {
+                coverage += value;
+                255 - (coverage.abs().min(1.0) * 255.0).round() as u8
+            })
+            .collect()
+    }
+}
+
+/// The number of line segments needed to approximate a curve with the given (squared) second
+/// difference of its control points to a fraction of a pixel.
+fn segment_count(deviation_squared: f32) -> usize {
+    if deviation_squared < 0.333 {
+        1
+    } else {
+        1 + (3.0 * deviation_squared).sqrt().sqrt().floor() as usize
+    }
+}
+
+/// Returns the PNG image of a laid out formula.
+pub fn render(
+    math_box: MathBox,
+    shaper: &HarfbuzzShaper<'_>,
+    flags: Flags,
+) -> image::ImageResult<Vec<u8>> {
+    let logical_extents = math_box.extents();
+    let scale = DeviceScale::new(shaper.em_size(), flags.font_size).with_rounding(flags.rounding);
+    let margin = scale.to_device(flags.padding);
+
+    let left = scale.to_device(math_box.origin.x) - margin;
+    let top = scale.to_device(math_box.origin.y - logical_extents.ascent) - margin;
+    let width = scale.to_device(math_box.advance_width()) + 2.0 * margin;
+    let height =
+        scale.to_device(logical_extents.descent + logical_extents.ascent) + 2.0 * margin;
+    let width = width.ceil().max(1.0) as usize;
+    let height = height.ceil().max(1.0) as usize;
+
+    let mut canvas = Canvas::new(width, height);
+    let offset = Point::new(-left, -top);
+    draw_box(&mut canvas, &math_box, Vector::default(), offset, &scale, shaper);
+
+    let mut png = Vec::new();
+    image::png::PngEncoder::new(&mut png).encode(
+        &canvas.to_gray(),
+        width as u32,
+        height as u32,
+        image::ColorType::L8,
+    )?;
+    Ok(png)
+}
+
+// Like in the SVG renderer only absolute coordinates (in font units) are converted to device
+// units. `offset` moves device coordinates into the canvas.
+fn draw_box(
+    canvas: &mut Canvas,
+    math_box: &MathBox,
+    parent_origin: Vector<i32>,
+    offset: Point,
+    scale: &DeviceScale,
+    shaper: &impl GlyphOutlines,
+) {
+    let origin = parent_origin + math_box.origin;
+    match *math_box.content() {
+        MathBoxContent::Boxes(ref list) => {
+            for item in list.as_slice() {
+                draw_box(canvas, item, origin, offset, scale, shaper);
+            }
+        }
+        MathBoxContent::Drawable(Drawable::Glyphs {
+            ref glyphs,
+            scale: glyph_scale,
+        }) => {
+            let outline_scale = glyph_scale.horizontal.as_scale_mult() * scale.factor();
+            let vertical_outline_scale = glyph_scale.vertical.as_scale_mult() * scale.factor();
+            let mut advance = 0;
+            for glyph in glyphs {
+                let x = scale.to_device(origin.x + advance * glyph_scale.horizontal) + offset.x;
+                let y = scale.to_device(origin.y) + offset.y;
+                advance += glyph.advance_width();
+
+                let outline = shaper.glyph_outline(glyph.glyph_code).unwrap_or_default();
+                let point = |px: f32, py: f32| {
+                    Point::new(x + px * outline_scale, y - py * vertical_outline_scale)
+                };
+                let mut start = Point::new(x, y);
+                let mut current = start;
+                for segment in outline {
+                    match segment {
+                        PathSegment::MoveTo { x, y } => {
+                            canvas.draw_line(current, start);
+                            start = point(x, y);
+                            current = start;
+                        }
+                        PathSegment::LineTo { x, y } => {
+                            let next = point(x, y);
+                            canvas.draw_line(current, next);
+                            current = next;
+                        }
+                        PathSegment::QuadTo { x1, y1, x, y } => {
+                            let next = point(x, y);
+                            canvas.draw_quad(current, point(x1, y1), next);
+                            current = next;
+                        }
+                        PathSegment::CubicTo {
+                            x1,
+                            y1,
+                            x2,
+                            y2,
+                            x,
+                            y,
+                        } => {
+                            let next = point(x, y);
+                            canvas.draw_cubic(current, point(x1, y1), point(x2, y2), next);
+                            current = next;
+                        }
+                        PathSegment::Close => {
+                            canvas.draw_line(current, start);
+                            current = start;
+                        }
+                    }
+                }
+                // contours are closed implicitly
+                canvas.draw_line(current, start);
+            }
+        }
+        MathBoxContent::Drawable(Drawable::Line { vector, thickness }) => {
+            let p0 = Point::new(scale.to_device(origin.x), scale.to_device(origin.y));
+            let p1 = Point::new(
+                scale.to_device(origin.x + vector.x),
+                scale.to_device(origin.y + vector.y),
+            );
+            let length = ((p1.x - p0.x).powi(2) + (p1.y - p0.y).powi(2)).sqrt();
+            if length == 0.0 {
+                return;
+            }
+            // the rule is stroked centered on the line like in the SVG output
+            let half_thickness = 0.5 * scale.thickness_to_device(thickness as i32);
+            let normal_x = -(p1.y - p0.y) / length * half_thickness;
+            let normal_y = (p1.x - p0.x) / length * half_thickness;
+            let corner = |p: Point, sign: f32| {
+                Point::new(
+                    p.x + offset.x + sign * normal_x,
+                    p.y + offset.y + sign * normal_y,
+                )
+            };
+            canvas.fill_polygon(&[
+                corner(p0, 1.0),
+                corner(p1, 1.0),
+                corner(p1, -1.0),
+                corner(p0, -1.0),
+            ]);
+        }
+        // images referenced by `mglyph` are only supported in SVG output and the grayscale canvas
+        // has no colors to draw the backgrounds of decorated expressions
+        MathBoxContent::Empty(_)
+        | MathBoxContent::Foreign { .. }
+        | MathBoxContent::Drawable(Drawable::Rectangle { .. }) => {}
+    }
+}
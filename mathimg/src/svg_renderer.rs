@@ -1,145 +1,256 @@
-use freetype;
-
 use math_render;
 use svg;
 
-use std::path;
-
 use math_render::math_box::*;
+use math_render::outline::{GlyphOutlines, PathSegment};
 use math_render::shaper::*;
+use math_render::{Hyperlink, ImageReference};
 
 use self::svg::node::element::path::Data;
-use self::svg::node::element::{Group, Line, Path, Rectangle};
+use self::svg::node::element::{Anchor, Group, Image, Line, Path, Rectangle};
 use self::svg::node::Node;
 use self::svg::Document;
 
-use freetype::face::Face as FT_Face;
-use freetype::outline::Curve;
-use freetype::{face, Vector};
-
 pub struct Flags {
     pub show_ink_bounds: bool,
     pub show_logical_bounds: bool,
     pub show_top_accent_attachment: bool,
+    /// The font size in pixels. If `None` the document uses font units.
+    pub font_size: Option<f32>,
+    pub rounding: Rounding,
+    /// Fit the document to the ink of the formula instead of its logical box.
+    pub tight: bool,
+    /// The space around the formula in font units.
+    pub padding: i32,
+    /// Drop links and images unless their URL is relative or uses one of the `SAFE_URL_SCHEMES`.
+    pub safe_urls: bool,
 }
 
-pub fn render<'a, T: AsRef<path::Path>>(
-    math_box: MathBox,
-    _: &HarfbuzzShaper<'_>,
-    font: &'a FT_Face<'_>,
-    flags: Flags,
-    out_path: T,
-) {
+/// The URL schemes that links and images may use if `Flags::safe_urls` is set.
+const SAFE_URL_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+/// Returns the SVG document of a laid out formula.
+pub fn render(math_box: MathBox, shaper: &HarfbuzzShaper<'_>, flags: Flags) -> String {
     let logical_extents = math_box.extents();
+    let em_size = shaper.em_size();
+    let scale = DeviceScale::new(em_size, flags.font_size.unwrap_or(em_size as f32))
+        .with_rounding(flags.rounding);
+    let margin = scale.to_device(flags.padding);
+
+    let (min_x, min_y, max_x, max_y) = match ink_rect(&math_box, Vector::default()) {
+        Some(rect) if flags.tight => rect,
+        _ => (
+            math_box.origin.x,
+            math_box.origin.y - logical_extents.ascent,
+            math_box.origin.x + math_box.advance_width(),
+            math_box.origin.y + logical_extents.descent,
+        ),
+    };
 
     let mut document = Document::new();
-    // let mut group = Group::new();
+    let left = scale.to_device(min_x) - margin;
+    let top = scale.to_device(min_y) - margin;
+    let width = scale.to_device(max_x) - scale.to_device(min_x) + 2.0 * margin;
+    let height = scale.to_device(max_y) - scale.to_device(min_y) + 2.0 * margin;
+    document.assign("viewBox", (left, top, width, height));
+    if flags.font_size.is_some() {
+        document.assign("width", width);
+        document.assign("height", height);
+    }
+    // Distances from the top edge of the document to the baseline and the math axis. Embedders
+    // use them to align the image with the surrounding text.
+    let axis_height = shaper.math_constant(MathConstant::AxisHeight);
+    document.assign("data-baseline", scale.to_device(math_box.origin.y) - top);
     document.assign(
-        "viewBox",
-        (
-            math_box.origin.x - 10,
-            math_box.origin.y - math_box.extents().ascent - 10,
-            math_box.advance_width() + 20,
-            logical_extents.descent + logical_extents.ascent + 20,
-        ),
+        "data-axis",
+        scale.to_device(math_box.origin.y - axis_height) - top,
     );
 
     let mut italic_cor_group = Group::new()
         .set("stroke", "black")
-        .set("stroke-width", 5)
+        .set("stroke-width", scale.thickness_to_device(5))
         .set("fill", "none")
         .set("stroke-dasharray", "30,20")
         .set("stroke-linecap", "round");
 
     let mut top_accent_attachment_group = Group::new()
         .set("stroke", "green")
-        .set("stroke-width", 13)
+        .set("stroke-width", scale.thickness_to_device(13))
         .set("fill", "none")
         .set("stroke-dasharray", "140,70")
         .set("stroke-linecap", "round");
 
+    let mut background_group = Group::new().set("stroke", "none");
     let mut black_group = Group::new().set("fill", "black").set("stroke", "none");
 
-    generate_svg(&mut italic_cor_group, &math_box, &|group, math_box| {
-        draw_italic_correction(group, math_box)
-    });
+    let origin = Vector::default();
+    // backgrounds and borders of decorated expressions lie behind everything else
+    generate_svg(
+        &mut background_group,
+        &math_box,
+        origin,
+        flags.safe_urls,
+        &|group, math_box, origin| draw_rectangle(group, math_box, origin, &scale),
+    );
+    generate_svg(
+        &mut italic_cor_group,
+        &math_box,
+        origin,
+        flags.safe_urls,
+        &|group, math_box, origin| draw_italic_correction(group, math_box, origin, &scale),
+    );
     generate_svg(
         &mut top_accent_attachment_group,
         &math_box,
-        &|group, math_box| draw_top_accent_attachment(group, math_box),
+        origin,
+        flags.safe_urls,
+        &|group, math_box, origin| draw_top_accent_attachment(group, math_box, origin, &scale),
+    );
+    generate_svg(
+        &mut black_group,
+        &math_box,
+        origin,
+        flags.safe_urls,
+        &|group, math_box, origin| draw_glyph(group, math_box, origin, &scale, shaper),
+    );
+    generate_svg(
+        &mut black_group,
+        &math_box,
+        origin,
+        flags.safe_urls,
+        &|group, math_box, origin| draw_filled(group, math_box, origin, &scale),
+    );
+    generate_svg(
+        &mut black_group,
+        &math_box,
+        origin,
+        flags.safe_urls,
+        &|group, math_box, origin| draw_image(group, math_box, origin, &scale, flags.safe_urls),
     );
-    generate_svg(&mut black_group, &math_box, &|group, math_box| {
-        draw_glyph(group, math_box, font)
-    });
-    generate_svg(&mut black_group, &math_box, &|group, math_box| {
-        draw_filled(group, math_box)
-    });
 
     if flags.show_ink_bounds {
         let mut ink_group = Group::new().set("stroke", "none").set("fill", "#FFE6E6");
-        generate_svg(&mut ink_group, &math_box, &|group, math_box| {
-            draw_ink_rect(group, math_box)
-        });
+        generate_svg(
+            &mut ink_group,
+            &math_box,
+            origin,
+            flags.safe_urls,
+            &|group, math_box, origin| draw_ink_rect(group, math_box, origin, &scale),
+        );
         document.append(ink_group);
     }
 
     if flags.show_logical_bounds {
         let mut logical_group = Group::new()
             .set("stroke", "#FF0000")
-            .set("stroke-width", 5)
+            .set("stroke-width", scale.thickness_to_device(5))
             .set("fill", "none");
-        generate_svg(&mut logical_group, &math_box, &|group, math_box| {
-            draw_logical_bounds(group, math_box)
-        });
+        generate_svg(
+            &mut logical_group,
+            &math_box,
+            origin,
+            flags.safe_urls,
+            &|group, math_box, origin| draw_logical_bounds(group, math_box, origin, &scale),
+        );
         document.append(logical_group);
     }
 
     //    document.append(italic_cor_group);
+    document.append(background_group);
     document.append(black_group);
 
     if flags.show_top_accent_attachment {
         document.append(top_accent_attachment_group);
     }
 
-    svg::save(out_path, &document).unwrap();
+    document.to_string()
+}
+
+/// Returns the rectangle `(left, top, right, bottom)` in font units that contains the ink of all
+/// boxes or `None` if nothing is drawn.
+fn ink_rect(math_box: &MathBox, parent_origin: Vector<i32>) -> Option<(i32, i32, i32, i32)> {
+    let origin = parent_origin + math_box.origin;
+    match *math_box.content() {
+        MathBoxContent::Boxes(ref list) => list
+            .iter()
+            .filter_map(|item| ink_rect(item, origin))
+            .fold(None, |acc, rect| match acc {
+                None => Some(rect),
+                Some((left, top, right, bottom)) => Some((
+                    left.min(rect.0),
+                    top.min(rect.1),
+                    right.max(rect.2),
+                    bottom.max(rect.3),
+                )),
+            }),
+        MathBoxContent::Empty(_) => None,
+        _ => {
+            let extents = math_box.extents();
+            Some((
+                origin.x + extents.left_side_bearing,
+                origin.y - extents.ascent,
+                origin.x + extents.right_edge(),
+                origin.y + extents.descent,
+            ))
+        }
+    }
+}
+
+// All drawing functions receive the absolute origin (in font units) of the box they draw. Only
+// absolute coordinates are converted to device units so rounding errors don't accumulate.
+fn generate_svg<'a, F>(
+    node: &mut Group,
+    math_box: &MathBox,
+    parent_origin: Vector<i32>,
+    safe_urls: bool,
+    func: &F,
+) where
+    F: Fn(&mut Group, &MathBox, Vector<i32>),
+{
+    match math_box.payload_as::<Hyperlink>() {
+        Some(link) if !safe_urls || is_safe_url(&link.href) => {
+            let mut group = Group::new();
+            generate_svg_content(&mut group, math_box, parent_origin, safe_urls, func);
+            node.append(Anchor::new().set("href", link.href.as_str()).add(group));
+        }
+        _ => generate_svg_content(node, math_box, parent_origin, safe_urls, func),
+    }
 }
 
-fn generate_svg<'a, F>(node: &mut Group, math_box: &MathBox, func: &F)
-where
-    F: Fn(&mut Group, &MathBox),
+fn generate_svg_content<'a, F>(
+    node: &mut Group,
+    math_box: &MathBox,
+    parent_origin: Vector<i32>,
+    safe_urls: bool,
+    func: &F,
+) where
+    F: Fn(&mut Group, &MathBox, Vector<i32>),
 {
-    let content = math_box.content();
-    match *content {
+    let origin = parent_origin + math_box.origin;
+    match *math_box.content() {
         MathBoxContent::Boxes(ref list) => {
-            let pt = math_box.origin;
-            if pt.x == 0 && pt.y == 0 {
-                for item in list.as_slice() {
-                    generate_svg(node, item, func);
-                }
-                return;
-            }
-            let mut group =
-                Group::new().set("transform", format!("translate({:?}, {:?})", pt.x, pt.y));
             for item in list.as_slice() {
-                generate_svg(&mut group, item, func);
+                generate_svg(node, item, origin, safe_urls, func);
             }
-            node.append(group);
         }
-        _ => func(node, math_box),
+        _ => func(node, math_box, origin),
     }
 }
 
-fn draw_filled<'a, T: Node>(doc: &mut T, math_box: &MathBox) {
+fn draw_filled<'a, T: Node>(
+    doc: &mut T,
+    math_box: &MathBox,
+    origin: Vector<i32>,
+    scale: &DeviceScale,
+) {
     if let MathBoxContent::Drawable(Drawable::Line { vector, thickness }) = *math_box.content() {
+        // lines start at the origin of their box
         let line = Line::new()
-            .set("x1", math_box.origin.x)
-            .set("y1", math_box.origin.y - math_box.extents().ascent)
-            .set("x2", vector.x + math_box.origin.x)
-            .set(
-                "y2",
-                math_box.origin.y - math_box.extents().ascent + vector.y,
-            )
-            .set("stroke-width", thickness)
+            .set("x1", scale.to_device(origin.x))
+            .set("y1", scale.to_device(origin.y))
+            .set("x2", scale.to_device(origin.x + vector.x))
+            .set("y2", scale.to_device(origin.y + vector.y))
+            .set("stroke-width", scale.thickness_to_device(thickness as i32))
             .set("stroke", "black")
             .set("z-index", 1);
 
@@ -147,10 +258,10 @@ fn draw_filled<'a, T: Node>(doc: &mut T, math_box: &MathBox) {
     }
     if let MathBoxContent::Empty(_) = *math_box.content() {
         let _rect = Rectangle::new()
-            .set("x", math_box.origin.x)
-            .set("y", math_box.origin.y - math_box.extents().ascent)
-            .set("width", math_box.extents().width)
-            .set("height", 100)
+            .set("x", scale.to_device(origin.x))
+            .set("y", scale.to_device(origin.y - math_box.extents().ascent))
+            .set("width", scale.to_device(math_box.extents().width))
+            .set("height", scale.to_device(100))
             .set("stroke", "none")
             .set("fill", "red")
             .set("z-index", 1);
@@ -159,48 +270,134 @@ fn draw_filled<'a, T: Node>(doc: &mut T, math_box: &MathBox) {
     }
 }
 
-fn draw_ink_rect<'a, T: Node>(group: &mut T, math_box: &MathBox) {
+fn draw_rectangle<'a, T: Node>(
+    doc: &mut T,
+    math_box: &MathBox,
+    origin: Vector<i32>,
+    scale: &DeviceScale,
+) {
+    if let MathBoxContent::Drawable(Drawable::Rectangle { ref extents, color }) =
+        *math_box.content()
+    {
+        let left = scale.to_device(origin.x + extents.left_side_bearing);
+        let top = scale.to_device(origin.y - extents.ascent);
+        let rect = Rectangle::new()
+            .set("x", left)
+            .set("y", top)
+            .set("width", scale.to_device(origin.x + extents.right_edge()) - left)
+            .set("height", scale.to_device(origin.y + extents.descent) - top)
+            .set("fill", format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b))
+            .set("fill-opacity", f32::from(color.a) / 255.0);
+        doc.append(rect);
+    }
+}
+
+/// Returns true if `url` is relative or uses one of the `SAFE_URL_SCHEMES`.
+fn is_safe_url(url: &str) -> bool {
+    // browsers ignore these characters in URLs, so they can hide a scheme like `java\tscript:`
+    let url: String = url
+        .chars()
+        .filter(|&c| !matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    let url = url.trim_start_matches(|c: char| c <= ' ');
+    match url.find(|c| matches!(c, ':' | '/' | '?' | '#')) {
+        Some(end) if url[end..].starts_with(':') => {
+            SAFE_URL_SCHEMES.contains(&url[..end].to_ascii_lowercase().as_str())
+        }
+        _ => true,
+    }
+}
+
+fn draw_image<'a, T: Node>(
+    doc: &mut T,
+    math_box: &MathBox,
+    origin: Vector<i32>,
+    scale: &DeviceScale,
+    safe_urls: bool,
+) {
+    if let MathBoxContent::Foreign {
+        ref extents,
+        ref payload,
+    } = *math_box.content()
+    {
+        if let Some(image) = payload
+            .downcast_ref::<ImageReference>()
+            .filter(|image| !safe_urls || is_safe_url(&image.src))
+        {
+            let left = origin.x + extents.left_side_bearing;
+            let top = origin.y - extents.ascent;
+            let image = Image::new()
+                .set("href", image.src.as_str())
+                .set("x", scale.to_device(left))
+                .set("y", scale.to_device(top))
+                .set("width", scale.to_device(left + extents.width) - scale.to_device(left))
+                .set("height", scale.to_device(origin.y + extents.descent) - scale.to_device(top))
+                .set("preserveAspectRatio", "none");
+            doc.append(image);
+        }
+    }
+}
+
+fn draw_ink_rect<'a, T: Node>(
+    group: &mut T,
+    math_box: &MathBox,
+    origin: Vector<i32>,
+    scale: &DeviceScale,
+) {
     if let MathBoxContent::Drawable(Drawable::Glyphs { .. }) = *math_box.content() {
+        let extents = math_box.extents();
         let ink_rect = Rectangle::new()
-            .set(
-                "x",
-                math_box.origin.x + math_box.extents().left_side_bearing,
-            )
-            .set("y", math_box.origin.y - math_box.extents().ascent)
-            .set("width", math_box.extents().width)
-            .set("height", math_box.extents().height());
+            .set("x", scale.to_device(origin.x + extents.left_side_bearing))
+            .set("y", scale.to_device(origin.y - extents.ascent))
+            .set("width", scale.to_device(extents.width))
+            .set("height", scale.to_device(extents.height()));
 
         group.append(ink_rect);
     }
 }
 
-fn draw_logical_bounds<'a, T: Node>(group: &mut T, math_box: &MathBox) {
+fn draw_logical_bounds<'a, T: Node>(
+    group: &mut T,
+    math_box: &MathBox,
+    origin: Vector<i32>,
+    scale: &DeviceScale,
+) {
     if let MathBoxContent::Drawable(Drawable::Glyphs { .. }) = *math_box.content() {
-        let logical_bounds = math_box.bounds().normalize();
+        let mut logical_bounds = math_box.bounds().normalize();
+        logical_bounds.origin = logical_bounds.origin - math_box.origin + origin;
 
         if logical_bounds.extents.ascent != 0 {
             let logical_rect1 = Rectangle::new()
-                .set("x", logical_bounds.origin.x)
-                .set("y", logical_bounds.origin.y - logical_bounds.extents.ascent)
-                .set("width", math_box.advance_width())
-                .set("height", logical_bounds.extents.ascent);
+                .set("x", scale.to_device(logical_bounds.origin.x))
+                .set(
+                    "y",
+                    scale.to_device(logical_bounds.origin.y - logical_bounds.extents.ascent),
+                )
+                .set("width", scale.to_device(math_box.advance_width()))
+                .set("height", scale.to_device(logical_bounds.extents.ascent));
             group.append(logical_rect1);
         }
 
         if logical_bounds.extents.descent != 0 {
             let logical_rect2 = Rectangle::new()
-                .set("x", logical_bounds.origin.x)
-                .set("y", logical_bounds.origin.y)
-                .set("width", math_box.advance_width())
-                .set("height", logical_bounds.extents.descent);
+                .set("x", scale.to_device(logical_bounds.origin.x))
+                .set("y", scale.to_device(logical_bounds.origin.y))
+                .set("width", scale.to_device(math_box.advance_width()))
+                .set("height", scale.to_device(logical_bounds.extents.descent));
             group.append(logical_rect2);
         }
     }
 }
 
-fn draw_italic_correction<'a, T: Node>(doc: &mut T, math_box: &MathBox) {
+fn draw_italic_correction<'a, T: Node>(
+    doc: &mut T,
+    math_box: &MathBox,
+    origin: Vector<i32>,
+    scale: &DeviceScale,
+) {
     if let MathBoxContent::Drawable(Drawable::Glyphs { .. }) = *math_box.content() {
-        let ink_bounds = math_box.bounds().normalize();
+        let mut ink_bounds = math_box.bounds().normalize();
+        ink_bounds.origin = ink_bounds.origin - math_box.origin + origin;
 
         if math_box.italic_correction() == 0 {
             return;
@@ -217,16 +414,19 @@ fn draw_italic_correction<'a, T: Node>(doc: &mut T, math_box: &MathBox) {
         let ink_rect = Rectangle::new()
             .set(
                 "x",
-                ink_bounds.origin.x + ink_bounds.extents.left_side_bearing,
+                scale.to_device(ink_bounds.origin.x + ink_bounds.extents.left_side_bearing),
+            )
+            .set(
+                "y",
+                scale.to_device(ink_bounds.origin.y - ink_bounds.extents.ascent),
             )
-            .set("y", ink_bounds.origin.y - ink_bounds.extents.ascent)
             .set(
                 "width",
-                ink_bounds.extents.width - math_box.italic_correction(),
+                scale.to_device(ink_bounds.extents.width - math_box.italic_correction()),
             )
             .set(
                 "height",
-                ink_bounds.extents.ascent + ink_bounds.extents.descent,
+                scale.to_device(ink_bounds.extents.ascent + ink_bounds.extents.descent),
             );
 
         group.append(ink_rect);
@@ -235,66 +435,70 @@ fn draw_italic_correction<'a, T: Node>(doc: &mut T, math_box: &MathBox) {
     }
 }
 
-fn draw_top_accent_attachment<'a, T: Node>(doc: &mut T, math_box: &MathBox) {
+fn draw_top_accent_attachment<'a, T: Node>(
+    doc: &mut T,
+    math_box: &MathBox,
+    origin: Vector<i32>,
+    scale: &DeviceScale,
+) {
+    let x = scale.to_device(origin.x + math_box.top_accent_attachment());
     let line = Line::new()
-        .set("x1", math_box.top_accent_attachment() + math_box.origin.x)
-        .set("y1", math_box.origin.y + math_box.extents().descent + 200)
-        .set("x2", math_box.top_accent_attachment() + math_box.origin.x)
-        .set("y2", math_box.origin.y - math_box.extents().ascent - 200);
+        .set("x1", x)
+        .set("y1", scale.to_device(origin.y + math_box.extents().descent + 200))
+        .set("x2", x)
+        .set("y2", scale.to_device(origin.y - math_box.extents().ascent - 200));
     doc.append(line);
 }
 
-fn draw_glyph<'a, T: Node>(doc: &mut T, math_box: &MathBox, face: &FT_Face<'_>) {
-    let (glyphs, scale_x, scale_y) =
+fn draw_glyph<'a, T: Node>(
+    doc: &mut T,
+    math_box: &MathBox,
+    origin: Vector<i32>,
+    scale: &DeviceScale,
+    shaper: &impl GlyphOutlines,
+) {
+    let (glyphs, glyph_scale) =
         if let MathBoxContent::Drawable(Drawable::Glyphs { glyphs, scale }) = math_box.content() {
-            (glyphs, scale.as_scale_mult(), scale.as_scale_mult())
+            (glyphs, *scale)
         } else {
             return;
         };
-
-    let mut group = Group::new();
-    {
-        let origin = math_box.origin;
-
-        group.assign(
+    let outline_scale = glyph_scale.horizontal.as_scale_mult() * scale.factor();
+    let vertical_outline_scale = glyph_scale.vertical.as_scale_mult() * scale.factor();
+
+    let mut advance = 0;
+    for glyph in glyphs {
+        // position every glyph at its absolute (rounded) position
+        let x = scale.to_device(origin.x + advance * glyph_scale.horizontal);
+        let y = scale.to_device(origin.y);
+        advance += glyph.advance_width();
+
+        let outline = shaper.glyph_outline(glyph.glyph_code).unwrap_or_default();
+
+        let mut data = Data::new();
+        for segment in outline {
+            data = match segment {
+                PathSegment::MoveTo { x, y } => data.move_to((x, y)),
+                PathSegment::LineTo { x, y } => data.line_to((x, y)),
+                PathSegment::QuadTo { x1, y1, x, y } => data.quadratic_curve_to((x1, y1, x, y)),
+                PathSegment::CubicTo {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    x,
+                    y,
+                } => data.cubic_curve_to((x1, y1, x2, y2, x, y)),
+                PathSegment::Close => data.close(),
+            };
+        }
+        let path = Path::new().set("d", data).set(
             "transform",
             format!(
                 "translate({:?}, {:?}) scale({:?}, {:?})",
-                origin.x, origin.y, scale_x, -scale_y
+                x, y, outline_scale, -vertical_outline_scale
             ),
         );
-
-        let mut advance = 0;
-        for glyph in glyphs {
-            let mut glyph_group =
-                Group::new().set("transform", format!("translate({}, 0)", advance));
-            advance += glyph.advance_width();
-
-            face.load_glyph(glyph.glyph_code, face::NO_SCALE).unwrap();
-            let outline = face.glyph().outline().expect("Glyph has no outline.");
-
-            let mut data = Data::new();
-            for contour in outline.contours_iter() {
-                let Vector { x, y } = *contour.start();
-                data = data.move_to((x, y));
-                for curve in contour {
-                    match curve {
-                        Curve::Line(pt) => data = data.line_to((pt.x, pt.y)),
-                        Curve::Bezier2(pt1, pt2) => {
-                            data = data.quadratic_curve_to((pt1.x, pt1.y, pt2.x, pt2.y))
-                        }
-                        Curve::Bezier3(pt1, pt2, pt3) => {
-                            data = data.cubic_curve_to((pt1.x, pt1.y, pt2.x, pt2.y, pt3.x, pt3.y))
-                        }
-                    }
-                }
-            }
-            data = data.close();
-            let path = Path::new().set("d", data);
-            glyph_group.append(path);
-            group.append(glyph_group);
-        }
+        doc.append(path);
     }
-
-    doc.append(group);
 }
@@ -0,0 +1,174 @@
+//! Python bindings for the layout engine.
+//!
+//! The module is built with maturin and can be used like this:
+//!
+//! ```python
+//! import mlayout
+//!
+//! font = mlayout.Font.from_file("latinmodern-math.otf")
+//! expression = mlayout.parse("<math><msup><mi>x</mi><mn>2</mn></msup></math>")
+//! svg = mlayout.render_svg(expression, font, font_size=32)
+//! ```
+
+use std::path::PathBuf;
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+
+use math_render::font::{self, FontError};
+use math_render::math_box::{DeviceScale, MathBoxMetrics};
+use math_render::mathmlparser;
+use math_render::shaper::{MathShaper, OwnedShaper};
+use math_render::MathExpression;
+
+mod svg;
+
+fn font_error(error: FontError) -> PyErr {
+    match error {
+        FontError::Io(error) => PyIOError::new_err(error.to_string()),
+        error @ FontError::NoMathTable => PyValueError::new_err(error.to_string()),
+    }
+}
+
+/// A font with an OpenType MATH table.
+#[pyclass]
+struct Font {
+    shaper: OwnedShaper,
+}
+
+#[pymethods]
+impl Font {
+    /// Loads a math font from the contents of a font file.
+    #[new]
+    #[pyo3(signature = (data, face_index = 0))]
+    fn new(data: &[u8], face_index: u32) -> PyResult<Self> {
+        let shaper = font::load_math_font_face(data, face_index).map_err(font_error)?;
+        Ok(Font { shaper })
+    }
+
+    /// Loads a math font from a file.
+    #[staticmethod]
+    #[pyo3(signature = (path, face_index = 0))]
+    fn from_file(path: PathBuf, face_index: u32) -> PyResult<Self> {
+        let shaper = font::load_math_font_face(path, face_index).map_err(font_error)?;
+        Ok(Font { shaper })
+    }
+
+    /// The number of font units per em.
+    #[getter]
+    fn units_per_em(&self) -> i32 {
+        self.shaper.em_size()
+    }
+}
+
+/// A parsed formula.
+#[pyclass]
+struct Expression {
+    expression: MathExpression,
+}
+
+#[pymethods]
+impl Expression {
+    /// Returns a description of the expression tree with one expression per line.
+    fn dump(&self) -> String {
+        self.expression.dump()
+    }
+}
+
+/// A laid out formula. All lengths are in pixels, the y axis points downwards and the baseline
+/// is at y = 0.
+#[pyclass(get_all)]
+struct Layout {
+    width: f32,
+    ascent: f32,
+    descent: f32,
+    /// The glyphs as `(glyph_code, x, y, font_size, vertical_font_size)` tuples. Glyphs in
+    /// scripts have a smaller font size than the formula. The vertical font size differs from the
+    /// font size only for delimiters that the font can't stretch itself, whose glyphs have to be
+    /// scaled vertically around their baseline.
+    glyphs: Vec<(u32, f32, f32, f32, f32)>,
+    /// The fraction bars and other rules as `(x, y, width, height)` tuples of their top left
+    /// corner and size.
+    rules: Vec<(f32, f32, f32, f32)>,
+    /// Diagonal lines like cancel strikes as `(x1, y1, x2, y2, thickness)` tuples of their end
+    /// points and stroke width.
+    segments: Vec<(f32, f32, f32, f32, f32)>,
+}
+
+/// Parses a MathML document.
+#[pyfunction]
+fn parse(mathml: &str) -> PyResult<Expression> {
+    match mathmlparser::parse(mathml.as_bytes()) {
+        Ok(expression) => Ok(Expression { expression }),
+        Err(error) => {
+            let message = match error.position {
+                Some(position) => format!("at byte {}: {}", position, error),
+                None => error.to_string(),
+            };
+            Err(PyValueError::new_err(message))
+        }
+    }
+}
+
+/// Lays out an expression at the given font size in pixels.
+#[pyfunction]
+#[pyo3(signature = (expression, font, font_size = 16.0))]
+fn layout(expression: &Expression, font: &Font, font_size: f32) -> Layout {
+    let math_box = math_render::layout(&expression.expression, &font.shaper);
+    let scale = DeviceScale::new(font.shaper.em_size(), font_size);
+    let extents = math_box.extents();
+    let list = math_box.flatten(&scale);
+    Layout {
+        width: scale.to_device(math_box.advance_width()),
+        ascent: scale.to_device(extents.ascent),
+        descent: scale.to_device(extents.descent),
+        glyphs: list
+            .glyph_runs
+            .iter()
+            .flat_map(|run| {
+                run.glyphs.iter().map(move |glyph| {
+                    let position = glyph.position;
+                    (
+                        glyph.glyph_code,
+                        position.x,
+                        position.y,
+                        run.font_size,
+                        run.vertical_font_size,
+                    )
+                })
+            })
+            .collect(),
+        rules: list
+            .rules
+            .iter()
+            .map(|rule| (rule.origin.x, rule.origin.y, rule.width, rule.height))
+            .collect(),
+        segments: list
+            .segments
+            .iter()
+            .map(|segment| {
+                let (from, to) = (segment.from, segment.to);
+                (from.x, from.y, to.x, to.y, segment.thickness)
+            })
+            .collect(),
+    }
+}
+
+/// Lays out an expression and returns an SVG document of it at the given font size in pixels.
+#[pyfunction]
+#[pyo3(signature = (expression, font, font_size = 16.0))]
+fn render_svg(expression: &Expression, font: &Font, font_size: f32) -> String {
+    let math_box = math_render::layout(&expression.expression, &font.shaper);
+    svg::render_svg(&math_box, &font.shaper, font_size)
+}
+
+#[pymodule]
+fn mlayout(_py: Python, module: &PyModule) -> PyResult<()> {
+    module.add_class::<Font>()?;
+    module.add_class::<Expression>()?;
+    module.add_class::<Layout>()?;
+    module.add_function(wrap_pyfunction!(parse, module)?)?;
+    module.add_function(wrap_pyfunction!(layout, module)?)?;
+    module.add_function(wrap_pyfunction!(render_svg, module)?)?;
+    Ok(())
+}
@@ -0,0 +1,104 @@
+//! A small SVG writer for laid out formulas.
+//!
+//! Glyphs are drawn as paths using their outlines, so the document doesn't depend on the font
+//! being installed where it is displayed.
+
+use std::fmt::Write;
+
+use math_render::math_box::{DeviceScale, MathBox, MathBoxMetrics};
+use math_render::outline::{GlyphOutlines, PathSegment};
+use math_render::shaper::MathShaper;
+
+fn path_data(outline: &[PathSegment]) -> String {
+    let mut data = String::new();
+    for segment in outline {
+        let _ = match *segment {
+            PathSegment::MoveTo { x, y } => write!(data, "M{} {}", x, y),
+            PathSegment::LineTo { x, y } => write!(data, "L{} {}", x, y),
+            PathSegment::QuadTo { x1, y1, x, y } => write!(data, "Q{} {} {} {}", x1, y1, x, y),
+            PathSegment::CubicTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => write!(data, "C{} {} {} {} {} {}", x1, y1, x2, y2, x, y),
+            PathSegment::Close => write!(data, "Z"),
+        };
+    }
+    data
+}
+
+/// Returns an SVG document that draws the box at the given font size in pixels.
+pub fn render_svg<S>(math_box: &MathBox, shaper: &S, font_size: f32) -> String
+where
+    S: MathShaper + GlyphOutlines,
+{
+    let em_size = shaper.em_size();
+    let scale = DeviceScale::new(em_size, font_size);
+    let extents = math_box.extents();
+    let left = scale.to_device(math_box.origin.x);
+    let top = scale.to_device(math_box.origin.y - extents.ascent);
+    let width = scale.to_device(math_box.advance_width());
+    let height = scale.to_device(extents.ascent + extents.descent);
+    let list = math_box.flatten(&scale);
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+        width, height, left, top, width, height
+    );
+    // backgrounds lie behind everything else
+    for rectangle in &list.rectangles {
+        let color = rectangle.color;
+        let fill = format!("rgb({}, {}, {})", color.r, color.g, color.b);
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="{}"/>"#,
+            rectangle.origin.x,
+            rectangle.origin.y,
+            rectangle.width,
+            rectangle.height,
+            fill,
+            f32::from(color.a) / 255.0
+        );
+    }
+    for run in &list.glyph_runs {
+        // outlines are in font units with the y axis pointing upwards
+        let outline_scale = run.font_size / em_size as f32;
+        let vertical_outline_scale = run.vertical_font_size / em_size as f32;
+        for glyph in &run.glyphs {
+            let outline = match shaper.glyph_outline(glyph.glyph_code) {
+                Some(outline) => outline,
+                None => continue,
+            };
+            let _ = writeln!(
+                svg,
+                r#"<path d="{}" transform="translate({} {}) scale({} {})"/>"#,
+                path_data(&outline),
+                glyph.position.x,
+                glyph.position.y,
+                outline_scale,
+                -vertical_outline_scale
+            );
+        }
+    }
+    for rule in &list.rules {
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}"/>"#,
+            rule.origin.x, rule.origin.y, rule.width, rule.height
+        );
+    }
+    for segment in &list.segments {
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="black" stroke-width="{}"/>"#,
+            segment.from.x, segment.from.y, segment.to.x, segment.to.y, segment.thickness
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
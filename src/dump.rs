@@ -0,0 +1,279 @@
+//! A compact text representation of expressions and boxes with one node per line.
+//!
+//! Unlike the `Debug` output it stays readable for large formulas, which makes it suitable for
+//! golden-file tests and bug reports.
+
+use std::fmt::Write;
+
+#[cfg(feature = "layout")]
+use crate::typesetting::math_box::{Drawable, MathBox, MathBoxContent, MathBoxMetrics};
+use crate::types::{
+    Field, ForeignObject, Length, LengthUnit, MathExpression, MathItem, TextRunItem,
+};
+
+#[cfg(feature = "layout")]
+fn write_box(out: &mut String, math_box: &MathBox, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let kind = match *math_box.content() {
+        MathBoxContent::Empty(_) => "empty",
+        MathBoxContent::Foreign { .. } => "foreign",
+        MathBoxContent::Drawable(Drawable::Glyphs { .. }) => "glyphs",
+        MathBoxContent::Drawable(Drawable::Line { .. }) => "line",
+        MathBoxContent::Drawable(Drawable::Rectangle { .. }) => "rectangle",
+        MathBoxContent::Boxes(_) => "boxes",
+    };
+    let extents = math_box.extents();
+    let _ = write!(
+        out,
+        "{}{} origin=({}, {}) extents=({}, {}, {}, {}) advance={} user_data={}",
+        indent,
+        kind,
+        math_box.origin.x,
+        math_box.origin.y,
+        extents.left_side_bearing,
+        extents.width,
+        extents.ascent,
+        extents.descent,
+        math_box.advance_width(),
+        math_box.user_data()
+    );
+
+    match *math_box.content() {
+        MathBoxContent::Drawable(Drawable::Glyphs { ref glyphs, scale }) => {
+            if scale.is_uniform() {
+                let _ = writeln!(
+                    out,
+                    " scale={}%",
+                    scale.horizontal.magnification_percentage()
+                );
+            } else {
+                let _ = writeln!(
+                    out,
+                    " scale={}% vertical_scale={}%",
+                    scale.horizontal.magnification_percentage(),
+                    scale.vertical.magnification_percentage()
+                );
+            }
+            for glyph in glyphs {
+                let _ = writeln!(
+                    out,
+                    "{}  glyph {} offset=({}, {}) advance={}",
+                    indent, glyph.glyph_code, glyph.offset.x, glyph.offset.y, glyph.advance_width
+                );
+            }
+        }
+        MathBoxContent::Drawable(Drawable::Line { vector, thickness }) => {
+            let _ = writeln!(
+                out,
+                " vector=({}, {}) thickness={}",
+                vector.x, vector.y, thickness
+            );
+        }
+        MathBoxContent::Drawable(Drawable::Rectangle { color, .. }) => {
+            let _ = writeln!(
+                out,
+                " color=#{:02x}{:02x}{:02x}{:02x}",
+                color.r, color.g, color.b, color.a
+            );
+        }
+        MathBoxContent::Boxes(ref boxes) => {
+            out.push('\n');
+            for child in boxes {
+                write_box(out, child, depth + 1);
+            }
+        }
+        MathBoxContent::Empty(_) | MathBoxContent::Foreign { .. } => out.push('\n'),
+    }
+}
+
+fn format_length(length: Length) -> String {
+    let unit = match length.unit {
+        LengthUnit::Point => "pt",
+        LengthUnit::Em => "em",
+        LengthUnit::DisplayOperatorMinHeight => "display-op-min-height",
+        LengthUnit::OperatorDefaultSize => "x",
+    };
+    format!("{}{}", length.value, unit)
+}
+
+fn write_field(out: &mut String, field: &Field) {
+    match *field {
+        Field::Empty => out.push_str(" empty"),
+        Field::Unicode(ref text) => {
+            let _ = write!(out, " {:?}", text);
+        }
+        Field::Glyph(ref glyph) => {
+            let scale = glyph.scale.magnification_percentage();
+            let _ = write!(out, " glyph={} scale={}%", glyph.glyph_code, scale);
+        }
+    }
+}
+
+fn write_expression(out: &mut String, expr: &MathExpression, role: &str, depth: usize) {
+    let kind = match *expr.item() {
+        MathItem::Field(_) => "field",
+        MathItem::Space(_) => "space",
+        MathItem::Atom(_) => "atom",
+        MathItem::OverUnder(_) => "over_under",
+        MathItem::GeneralizedFraction(_) => "fraction",
+        MathItem::Root(_) => "root",
+        MathItem::Operator(_) => "operator",
+        MathItem::List(_) => "list",
+        MathItem::Foreign(_) => "foreign",
+        MathItem::TextRun(_) => "text_run",
+        MathItem::Other(_) => "other",
+    };
+    let _ = write!(out, "{}{}{}", "  ".repeat(depth), role, kind);
+    match *expr.item() {
+        MathItem::Field(ref field) => write_field(out, field),
+        MathItem::Operator(ref operator) => {
+            write_field(out, &operator.field);
+            let _ = write!(
+                out,
+                " form={:?} lspace={} rspace={}",
+                operator.form,
+                format_length(operator.leading_space),
+                format_length(operator.trailing_space)
+            );
+            if let Some(constraints) = operator.stretch_constraints {
+                out.push_str(" stretchy");
+                if constraints.symmetric {
+                    out.push_str(" symmetric");
+                }
+            }
+            let flags = [
+                (operator.is_large_op, " largeop"),
+                (operator.is_fence, " fence"),
+                (operator.is_separator, " separator"),
+            ];
+            for &(is_set, name) in flags.iter() {
+                if is_set {
+                    out.push_str(name);
+                }
+            }
+        }
+        MathItem::Space(ref space) | MathItem::Foreign(ForeignObject { size: ref space, .. }) => {
+            let _ = write!(
+                out,
+                " width={} ascent={} descent={}",
+                format_length(space.width),
+                format_length(space.ascent),
+                format_length(space.descent)
+            );
+        }
+        MathItem::OverUnder(ref over_under) => {
+            if over_under.over_is_accent {
+                out.push_str(" accent");
+            }
+            if over_under.under_is_accent {
+                out.push_str(" accentunder");
+            }
+            if over_under.is_limits {
+                out.push_str(" limits");
+            }
+        }
+        _ => {}
+    }
+    let _ = write!(out, " user_data={}", expr.get_user_data());
+    if let Some(element) = expr.element() {
+        let _ = write!(out, " element={}", element.name);
+    }
+    out.push('\n');
+
+    let children: Vec<(&str, &Option<MathExpression>)> = match *expr.item() {
+        MathItem::List(ref list) => {
+            for child in list {
+                write_expression(out, child, "", depth + 1);
+            }
+            return;
+        }
+        MathItem::TextRun(ref run) => {
+            for item in &run.items {
+                match *item {
+                    TextRunItem::Text(ref text) => {
+                        let _ = writeln!(out, "{}text {:?}", "  ".repeat(depth + 1), text);
+                    }
+                    TextRunItem::Math(ref expr) => write_expression(out, expr, "", depth + 1),
+                }
+            }
+            return;
+        }
+        MathItem::Atom(ref atom) => vec![
+            ("nucleus: ", &atom.nucleus),
+            ("top_left: ", &atom.top_left),
+            ("top_right: ", &atom.top_right),
+            ("bottom_left: ", &atom.bottom_left),
+            ("bottom_right: ", &atom.bottom_right),
+        ],
+        MathItem::OverUnder(ref over_under) => vec![
+            ("nucleus: ", &over_under.nucleus),
+            ("over: ", &over_under.over),
+            ("under: ", &over_under.under),
+        ],
+        MathItem::GeneralizedFraction(ref frac) => vec![
+            ("numerator: ", &frac.numerator),
+            ("denominator: ", &frac.denominator),
+            ("thickness: ", &frac.thickness),
+        ],
+        MathItem::Root(ref root) => vec![
+            ("radicand: ", &root.radicand),
+            ("degree: ", &root.degree),
+        ],
+        _ => Vec::new(),
+    };
+    for (role, child) in children {
+        if let Some(ref child) = *child {
+            write_expression(out, child, role, depth + 1);
+        }
+    }
+}
+
+#[cfg(feature = "layout")]
+impl MathBox {
+    /// Returns an indented description of the box tree with one box (or glyph) per line
+    /// containing its kind, origin, extents, advance width and user data.
+    pub fn dump(&self) -> String {
+        let mut out = String::new();
+        write_box(&mut out, self, 0);
+        out
+    }
+}
+
+impl MathExpression {
+    /// Returns an indented description of the expression tree with one expression per line
+    /// containing its kind, content, user data and markup element.
+    pub fn dump(&self) -> String {
+        let mut out = String::new();
+        write_expression(&mut out, self, "", 0);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Atom, ElementInfo};
+
+    #[test]
+    fn test_expression_dump() {
+        let field = |text: &str, user_data| {
+            MathExpression::new(MathItem::Field(Field::Unicode(text.to_string())), user_data)
+        };
+        let atom = Atom {
+            nucleus: Some(field("x", 2).with_element(ElementInfo::new("mi"))),
+            top_right: Some(field("2", 3)),
+            ..Default::default()
+        };
+        let expr = MathExpression::new(
+            MathItem::List(vec![MathExpression::new(MathItem::Atom(atom), 1)]),
+            0,
+        );
+        assert_eq!(
+            expr.dump(),
+            "list user_data=0\n\
+             \x20 atom user_data=1\n\
+             \x20   nucleus: field \"x\" user_data=2 element=mi\n\
+             \x20   top_right: field \"2\" user_data=3\n"
+        );
+    }
+}
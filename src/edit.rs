@@ -0,0 +1,619 @@
+//! Structural editing of expression trees.
+//!
+//! Expressions are addressed by their user data, so the values found in the layout (e.g. in a
+//! `CaretPosition` or a `MathBox`) can be used directly. The operations are given the
+//! `ParseContext` of the document: expressions created by an operation get their user data from
+//! its id allocator and the information the parser recorded about the edited elements is kept up
+//! to date, so editors don't need to maintain a model of their own. New expressions also get the
+//! element information of the MathML element they correspond to.
+
+use std::error::Error;
+use std::fmt;
+use std::mem;
+
+use crate::mathmlparser::ParseContext;
+use crate::types::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditError {
+    /// There is no expression with this user data in the tree.
+    NotFound(u64),
+    /// The operation isn't possible for the root of the tree.
+    IsRoot,
+    /// The operation needs a token containing text.
+    NotText(u64),
+    /// The offset is not inside the text or not at a character boundary.
+    InvalidOffset(usize),
+    /// The expressions are not siblings in a list or are in the wrong order.
+    NotSiblings(u64, u64),
+    /// The tokens can't be merged because they are of different kinds.
+    NotMergeable(u64, u64),
+}
+
+pub type Result<T> = ::std::result::Result<T, EditError>;
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EditError::NotFound(user_data) => {
+                write!(f, "No expression with user data {}.", user_data)
+            }
+            EditError::IsRoot => write!(f, "The operation is not possible for the root."),
+            EditError::NotText(user_data) => {
+                write!(f, "Expression {} is not a token with text.", user_data)
+            }
+            EditError::InvalidOffset(offset) => write!(f, "Invalid text offset {}.", offset),
+            EditError::NotSiblings(first, last) => write!(
+                f,
+                "Expressions {} and {} are not siblings in a list.",
+                first, last
+            ),
+            EditError::NotMergeable(first, second) => {
+                write!(f, "Expressions {} and {} can't be merged.", first, second)
+            }
+        }
+    }
+}
+
+impl Error for EditError {}
+
+fn children_of(item: &MathItem) -> Vec<&MathExpression> {
+    match *item {
+        MathItem::List(ref list) => list.iter().collect(),
+        MathItem::Atom(ref atom) => [
+            &atom.nucleus,
+            &atom.top_left,
+            &atom.top_right,
+            &atom.bottom_left,
+            &atom.bottom_right,
+        ]
+        .iter()
+        .filter_map(|expr| expr.as_ref())
+        .collect(),
+        MathItem::OverUnder(ref over_under) => {
+            [&over_under.nucleus, &over_under.over, &over_under.under]
+                .iter()
+                .filter_map(|expr| expr.as_ref())
+                .collect()
+        }
+        MathItem::GeneralizedFraction(ref frac) => {
+            [&frac.numerator, &frac.denominator, &frac.thickness]
+                .iter()
+                .filter_map(|expr| expr.as_ref())
+                .collect()
+        }
+        MathItem::Root(ref root) => [&root.radicand, &root.degree]
+            .iter()
+            .filter_map(|expr| expr.as_ref())
+            .collect(),
+        MathItem::TextRun(ref run) => run.formulas().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the optional subexpressions of an item (everything except list elements).
+fn slots_of(item: &mut MathItem) -> Vec<&mut Option<MathExpression>> {
+    match *item {
+        MathItem::Atom(ref mut atom) => vec![
+            &mut atom.nucleus,
+            &mut atom.top_left,
+            &mut atom.top_right,
+            &mut atom.bottom_left,
+            &mut atom.bottom_right,
+        ],
+        MathItem::OverUnder(ref mut over_under) => vec![
+            &mut over_under.nucleus,
+            &mut over_under.over,
+            &mut over_under.under,
+        ],
+        MathItem::GeneralizedFraction(ref mut frac) => vec![
+            &mut frac.numerator,
+            &mut frac.denominator,
+            &mut frac.thickness,
+        ],
+        MathItem::Root(ref mut root) => vec![&mut root.radicand, &mut root.degree],
+        _ => Vec::new(),
+    }
+}
+
+pub(crate) fn children_of_mut(item: &mut MathItem) -> Vec<&mut MathExpression> {
+    match *item {
+        MathItem::List(ref mut list) => list.iter_mut().collect(),
+        MathItem::TextRun(ref mut run) => run
+            .items
+            .iter_mut()
+            .filter_map(|item| match *item {
+                TextRunItem::Math(ref mut expr) => Some(expr),
+                TextRunItem::Text(_) => None,
+            })
+            .collect(),
+        ref mut item => slots_of(item)
+            .into_iter()
+            .filter_map(|expr| expr.as_mut())
+            .collect(),
+    }
+}
+
+fn unicode_text(expr: &MathExpression) -> Option<&str> {
+    match *expr.item {
+        MathItem::Field(Field::Unicode(ref text)) => Some(text),
+        _ => None,
+    }
+}
+
+fn element_name(expr: &MathExpression) -> Option<&str> {
+    expr.element().map(|element| element.name.as_str())
+}
+
+impl MathExpression {
+    /// Returns the item describing this expression.
+    pub fn item(&self) -> &MathItem {
+        &self.item
+    }
+
+    /// Returns the item describing this expression for modification.
+    pub fn item_mut(&mut self) -> &mut MathItem {
+        &mut self.item
+    }
+
+    /// Returns the direct subexpressions of this expression.
+    pub fn children(&self) -> Vec<&MathExpression> {
+        children_of(&self.item)
+    }
+
+    /// Returns the expression with the given user data in the tree (including this expression).
+    pub fn find(&self, user_data: u64) -> Option<&MathExpression> {
+        if self.get_user_data() == user_data {
+            return Some(self);
+        }
+        self.children()
+            .into_iter()
+            .filter_map(|child| child.find(user_data))
+            .next()
+    }
+
+    /// Returns the expression with the given user data in the tree for modification.
+    pub fn find_mut(&mut self, user_data: u64) -> Option<&mut MathExpression> {
+        if self.get_user_data() == user_data {
+            return Some(self);
+        }
+        children_of_mut(&mut self.item)
+            .into_iter()
+            .filter_map(|child| child.find_mut(user_data))
+            .next()
+    }
+
+    fn find_parent(&self, user_data: u64) -> Option<&MathExpression> {
+        if self
+            .children()
+            .iter()
+            .any(|child| child.get_user_data() == user_data)
+        {
+            return Some(self);
+        }
+        self.children()
+            .into_iter()
+            .filter_map(|child| child.find_parent(user_data))
+            .next()
+    }
+
+    fn find_parent_mut(&mut self, user_data: u64) -> Option<&mut MathExpression> {
+        if self
+            .children()
+            .iter()
+            .any(|child| child.get_user_data() == user_data)
+        {
+            return Some(self);
+        }
+        children_of_mut(&mut self.item)
+            .into_iter()
+            .filter_map(|child| child.find_parent_mut(user_data))
+            .next()
+    }
+
+    /// Checks that `first` and `last` are elements of the same list and `last` doesn't come
+    /// before `first`.
+    fn check_siblings(&self, first: u64, last: u64) -> Result<()> {
+        if self.get_user_data() == first {
+            return Err(EditError::IsRoot);
+        }
+        let parent = self.find_parent(first).ok_or(EditError::NotFound(first))?;
+        let list = match *parent.item {
+            MathItem::List(ref list) => list,
+            _ => return Err(EditError::NotSiblings(first, last)),
+        };
+        let position = |user_data| list.iter().position(|expr| expr.get_user_data() == user_data);
+        match (position(first), position(last)) {
+            (Some(first_index), Some(last_index)) if first_index <= last_index => Ok(()),
+            _ => Err(EditError::NotSiblings(first, last)),
+        }
+    }
+
+    /// Returns the list containing the expression and its index in the list. If the expression
+    /// is not part of a list it is wrapped in a new list first.
+    fn containing_list(
+        &mut self,
+        user_data: u64,
+        context: &mut ParseContext,
+    ) -> Result<(&mut Vec<MathExpression>, usize)> {
+        if self.get_user_data() == user_data {
+            return Err(EditError::IsRoot);
+        }
+        let parent = self
+            .find_parent_mut(user_data)
+            .ok_or(EditError::NotFound(user_data))?;
+        let parent_is_list = match *parent.item {
+            MathItem::List(_) => true,
+            _ => false,
+        };
+        let (list_expr, index) = if parent_is_list {
+            let index = parent
+                .children()
+                .iter()
+                .position(|child| child.get_user_data() == user_data)
+                .unwrap();
+            (parent, index)
+        } else {
+            let expr = children_of_mut(&mut parent.item)
+                .into_iter()
+                .find(|child| child.get_user_data() == user_data)
+                .unwrap();
+            let wrapped = mem::take(expr);
+            let list_user_data = context.allocate_node_id().user_data();
+            *expr = MathExpression::new(MathItem::List(vec![wrapped]), list_user_data)
+                .with_element(ElementInfo::new("mrow"));
+            (expr, 0)
+        };
+        match *list_expr.item {
+            MathItem::List(ref mut list) => Ok((list, index)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Inserts `sibling` before or after the expression with the given user data. The user data
+    /// of `sibling` and its subexpressions should be allocated with `context`.
+    pub fn insert_sibling(
+        &mut self,
+        user_data: u64,
+        sibling: MathExpression,
+        after: bool,
+        context: &mut ParseContext,
+    ) -> Result<()> {
+        let (list, index) = self.containing_list(user_data, context)?;
+        list.insert(if after { index + 1 } else { index }, sibling);
+        Ok(())
+    }
+
+    /// Removes the expression with the given user data from the tree and returns it. The
+    /// information about the removed elements is removed from `context`.
+    pub fn delete(&mut self, user_data: u64, context: &mut ParseContext) -> Result<MathExpression> {
+        if self.get_user_data() == user_data {
+            return Err(EditError::IsRoot);
+        }
+        let parent = self
+            .find_parent_mut(user_data)
+            .ok_or(EditError::NotFound(user_data))?;
+        if let MathItem::List(ref mut list) = *parent.item {
+            let index = list
+                .iter()
+                .position(|child| child.get_user_data() == user_data)
+                .unwrap();
+            let removed = list.remove(index);
+            remove_info(&removed, context);
+            return Ok(removed);
+        }
+        let slot = slots_of(&mut parent.item)
+            .into_iter()
+            .find(|slot| slot.as_ref().map(|expr| expr.get_user_data()) == Some(user_data))
+            .unwrap();
+        let removed = slot.take().unwrap();
+        remove_info(&removed, context);
+        Ok(removed)
+    }
+
+    /// Replaces the siblings from `first` to `last` (or the single expression if both are the
+    /// same) by the expression returned by `wrap` and returns its user data. If there are several
+    /// siblings they are passed to `wrap` as a new list.
+    ///
+    /// `wrap` is called with the user data for the new expression and the context to allocate
+    /// the user data of other new expressions.
+    fn wrap(
+        &mut self,
+        first: u64,
+        last: u64,
+        context: &mut ParseContext,
+        wrap: impl FnOnce(MathExpression, u64, &mut ParseContext) -> MathExpression,
+    ) -> Result<u64> {
+        if first == last {
+            let expr = self.find_mut(first).ok_or(EditError::NotFound(first))?;
+            let wrapper_user_data = context.allocate_node_id().user_data();
+            let wrapped = mem::take(expr);
+            *expr = wrap(wrapped, wrapper_user_data, context);
+            return Ok(wrapper_user_data);
+        }
+        self.check_siblings(first, last)?;
+        let (list, start) = self.containing_list(first, context)?;
+        let end = list[start..]
+            .iter()
+            .position(|expr| expr.get_user_data() == last)
+            .unwrap();
+        let selection = list.drain(start..=start + end).collect();
+        let list_user_data = context.allocate_node_id().user_data();
+        let selection = MathExpression::new(MathItem::List(selection), list_user_data)
+            .with_element(ElementInfo::new("mrow"));
+        let wrapper_user_data = context.allocate_node_id().user_data();
+        list.insert(start, wrap(selection, wrapper_user_data, context));
+        Ok(wrapper_user_data)
+    }
+
+    /// Makes the siblings from `first` to `last` the numerator of a new fraction with an empty
+    /// denominator and returns the user data of the fraction.
+    pub fn wrap_in_fraction(
+        &mut self,
+        first: u64,
+        last: u64,
+        context: &mut ParseContext,
+    ) -> Result<u64> {
+        self.wrap(first, last, context, |numerator, user_data, context| {
+            // like a parsed `mfrac` the fraction is an embellished operator if its numerator is
+            let info = context.embellished_info(Some(&numerator));
+            let denominator_user_data = context.allocate_node_id().user_data();
+            let denominator =
+                MathExpression::new(MathItem::List(Vec::new()), denominator_user_data)
+                    .with_element(ElementInfo::new("mrow"));
+            let fraction = GeneralizedFraction {
+                numerator: Some(numerator),
+                denominator: Some(denominator),
+                thickness: None,
+            };
+            let fraction = MathExpression::new(MathItem::GeneralizedFraction(fraction), user_data)
+                .with_element(ElementInfo::new("mfrac"));
+            context.insert_info(&fraction, info);
+            fraction
+        })
+    }
+
+    /// Puts the siblings from `first` to `last` under a new square root and returns the user
+    /// data of the root.
+    pub fn wrap_in_root(
+        &mut self,
+        first: u64,
+        last: u64,
+        context: &mut ParseContext,
+    ) -> Result<u64> {
+        self.wrap(first, last, context, |radicand, user_data, _| {
+            let root = Root {
+                radicand: Some(radicand),
+                degree: None,
+            };
+            MathExpression::new(MathItem::Root(root), user_data)
+                .with_element(ElementInfo::new("msqrt"))
+        })
+    }
+
+    /// Splits the text of a token at the byte offset `offset`. The text after the offset is
+    /// moved into a new token of the same kind following the original one. Returns the user
+    /// data of the new token, which gets a copy of the information about the original token in
+    /// `context`.
+    pub fn split_token(
+        &mut self,
+        user_data: u64,
+        offset: usize,
+        context: &mut ParseContext,
+    ) -> Result<u64> {
+        let token = self.find(user_data).ok_or(EditError::NotFound(user_data))?;
+        let text = unicode_text(token).ok_or(EditError::NotText(user_data))?;
+        if offset == 0 || offset >= text.len() || !text.is_char_boundary(offset) {
+            return Err(EditError::InvalidOffset(offset));
+        }
+        let info = context.info_for_expr(token).cloned();
+        let (list, index) = self.containing_list(user_data, context)?;
+        let new_user_data = context.allocate_node_id().user_data();
+        let token = &mut list[index];
+        let tail = match *token.item {
+            MathItem::Field(Field::Unicode(ref mut text)) => text.split_off(offset),
+            _ => unreachable!(),
+        };
+        let mut new_token =
+            MathExpression::new(MathItem::Field(Field::Unicode(tail)), new_user_data);
+        if let Some(element) = token.element() {
+            new_token = new_token.with_element(element.clone());
+        }
+        if let Some(payload) = token.payload() {
+            new_token = new_token.with_payload(payload.clone());
+        }
+        if let Some(info) = info {
+            context.insert_info(&new_token, info);
+        }
+        list.insert(index + 1, new_token);
+        Ok(new_user_data)
+    }
+
+    /// Appends the text of the token `second` to the text of the token `first` that directly
+    /// precedes it and removes `second` and the information about it in `context`.
+    pub fn merge_tokens(
+        &mut self,
+        first: u64,
+        second: u64,
+        context: &mut ParseContext,
+    ) -> Result<()> {
+        self.check_siblings(first, second)?;
+        // siblings are always in a list, so no new list is created
+        let (list, index) = self.containing_list(first, context)?;
+        if list.get(index + 1).map(|expr| expr.get_user_data()) != Some(second) {
+            return Err(EditError::NotSiblings(first, second));
+        }
+        let (first_expr, second_expr) = (&list[index], &list[index + 1]);
+        match (unicode_text(first_expr), unicode_text(second_expr)) {
+            (Some(_), Some(_)) if element_name(first_expr) == element_name(second_expr) => {}
+            (Some(_), Some(_)) => return Err(EditError::NotMergeable(first, second)),
+            (None, _) => return Err(EditError::NotText(first)),
+            (_, None) => return Err(EditError::NotText(second)),
+        }
+        let second_expr = list.remove(index + 1);
+        if let (MathItem::Field(Field::Unicode(ref mut text)), Some(tail)) =
+            (&mut *list[index].item, unicode_text(&second_expr))
+        {
+            text.push_str(tail);
+        }
+        context.remove_info(&second_expr);
+        Ok(())
+    }
+}
+
+/// Removes the information about an expression and its subexpressions from `context`.
+fn remove_info(expr: &MathExpression, context: &mut ParseContext) {
+    context.remove_info(expr);
+    for child in expr.children() {
+        remove_info(child, context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mathmlparser::MathmlInfo;
+
+    /// Returns a new `mi` token whose user data is allocated with the context like the tokens of
+    /// a parsed document.
+    fn token(text: &str, context: &mut ParseContext) -> MathExpression {
+        let user_data = context.allocate_node_id().user_data();
+        let token =
+            MathExpression::new(MathItem::Field(Field::Unicode(text.to_string())), user_data)
+                .with_element(ElementInfo::new("mi"));
+        context.insert_info(&token, MathmlInfo::default());
+        token
+    }
+
+    fn row(list: Vec<MathExpression>, context: &mut ParseContext) -> MathExpression {
+        MathExpression::new(MathItem::List(list), context.allocate_node_id().user_data())
+    }
+
+    fn user_data_of(list: &[&MathExpression]) -> Vec<u64> {
+        list.iter().map(|expr| expr.get_user_data()).collect()
+    }
+
+    fn has_info(context: &ParseContext, user_data: u64) -> bool {
+        context.nodes().any(|(id, _)| id.user_data() == user_data)
+    }
+
+    #[test]
+    fn test_insert_and_delete() {
+        let context = &mut ParseContext::default();
+        let (a_token, b_token) = (token("a", context), token("b", context));
+        let (a, b) = (a_token.get_user_data(), b_token.get_user_data());
+        let mut expr = row(vec![a_token, b_token], context);
+        let c = token("c", context);
+        let c_user_data = c.get_user_data();
+        expr.insert_sibling(a, c, true, context).unwrap();
+        assert_eq!(user_data_of(&expr.children()), vec![a, c_user_data, b]);
+        assert_eq!(expr.delete(a, context).unwrap().get_user_data(), a);
+        assert!(!has_info(context, a));
+        assert_eq!(user_data_of(&expr.children()), vec![c_user_data, b]);
+        assert_eq!(expr.delete(a, context).unwrap_err(), EditError::NotFound(a));
+        let root = expr.get_user_data();
+        assert_eq!(expr.delete(root, context).unwrap_err(), EditError::IsRoot);
+
+        // siblings of a numerator are put into a new row
+        let x = token("x", context);
+        let x_user_data = x.get_user_data();
+        let fraction = GeneralizedFraction {
+            numerator: Some(x),
+            ..Default::default()
+        };
+        let fraction_user_data = context.allocate_node_id().user_data();
+        let mut expr =
+            MathExpression::new(MathItem::GeneralizedFraction(fraction), fraction_user_data);
+        let y = token("y", context);
+        let y_user_data = y.get_user_data();
+        expr.insert_sibling(x_user_data, y, false, context).unwrap();
+        let numerator = expr.children()[0];
+        assert!(numerator.get_user_data() > y_user_data);
+        let siblings = vec![y_user_data, x_user_data];
+        assert_eq!(user_data_of(&numerator.children()), siblings);
+    }
+
+    #[test]
+    fn test_wrap() {
+        let context = &mut ParseContext::default();
+        let tokens = vec![
+            token("a", context),
+            token("b", context),
+            token("c", context),
+        ];
+        let user_data = user_data_of(&tokens.iter().collect::<Vec<_>>());
+        let (a, b, c) = (user_data[0], user_data[1], user_data[2]);
+        let mut expr = row(tokens, context);
+        let fraction = expr.wrap_in_fraction(a, b, context).unwrap();
+        assert_eq!(user_data_of(&expr.children()), vec![fraction, c]);
+        assert!(has_info(context, fraction));
+        let fraction = expr.find(fraction).unwrap();
+        assert_eq!(element_name(fraction), Some("mfrac"));
+        let parts = fraction.children();
+        assert_eq!(user_data_of(&parts[0].children()), vec![a, b]);
+        assert!(parts[1].children().is_empty());
+        let mut all = vec![parts[0].get_user_data(), parts[1].get_user_data(), a, b, c];
+        all.push(fraction.get_user_data());
+        all.push(expr.get_user_data());
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 7);
+
+        let root = expr.wrap_in_root(c, c, context).unwrap();
+        let root = expr.find(root).unwrap();
+        assert_eq!(user_data_of(&root.children()), vec![c]);
+        assert_eq!(
+            expr.wrap_in_root(c, a, context).unwrap_err(),
+            EditError::NotSiblings(c, a)
+        );
+    }
+
+    #[test]
+    fn test_split_and_merge() {
+        let context = &mut ParseContext::default();
+        let (sin, x) = (token("sin", context), token("x", context));
+        let (sin_user_data, x_user_data) = (sin.get_user_data(), x.get_user_data());
+        let mut expr = row(vec![sin, x], context);
+        let tail = expr.split_token(sin_user_data, 1, context).unwrap();
+        assert_eq!(
+            user_data_of(&expr.children()),
+            vec![sin_user_data, tail, x_user_data]
+        );
+        assert_eq!(unicode_text(expr.find(tail).unwrap()), Some("in"));
+        assert_eq!(element_name(expr.find(tail).unwrap()), Some("mi"));
+        // the new token gets the information of the token it was split off
+        let tail_info = context.info_for_expr(expr.find(tail)).unwrap();
+        assert!(!tail_info.is_operator() && !tail_info.is_space);
+        assert_eq!(
+            expr.split_token(sin_user_data, 1, context).unwrap_err(),
+            EditError::InvalidOffset(1)
+        );
+
+        assert_eq!(
+            expr.merge_tokens(tail, sin_user_data, context).unwrap_err(),
+            EditError::NotSiblings(tail, sin_user_data)
+        );
+        expr.merge_tokens(sin_user_data, tail, context).unwrap();
+        assert_eq!(unicode_text(expr.find(sin_user_data).unwrap()), Some("sin"));
+        assert_eq!(
+            user_data_of(&expr.children()),
+            vec![sin_user_data, x_user_data]
+        );
+        // only the information about the removed token is gone
+        assert!(has_info(context, sin_user_data));
+        assert!(!has_info(context, tail));
+
+        // the user data of removed tokens isn't handed out again
+        assert!(expr.split_token(sin_user_data, 2, context).unwrap() > tail);
+
+        let two = token("2", context).with_element(ElementInfo::new("mn"));
+        let two_user_data = two.get_user_data();
+        expr.insert_sibling(x_user_data, two, true, context)
+            .unwrap();
+        assert_eq!(
+            expr.merge_tokens(x_user_data, two_user_data, context)
+                .unwrap_err(),
+            EditError::NotMergeable(x_user_data, two_user_data)
+        );
+    }
+}
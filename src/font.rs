@@ -0,0 +1,151 @@
+//! Utilities for finding and loading OpenType math fonts.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use harfbuzz_rs::{hb, Face, HarfbuzzObject};
+
+pub use crate::shaper::OwnedShaper;
+
+/// Describes a font installed on the system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontInfo {
+    /// The family name of the font.
+    pub name: String,
+    /// The path of the font file.
+    pub path: PathBuf,
+    /// The index of the face inside the font file.
+    pub face_index: u32,
+    /// True if the font contains an OpenType MATH table.
+    pub has_math_table: bool,
+}
+
+/// Returns true if the face with the given index in the font data has a MATH table.
+pub fn has_math_table(font_data: &[u8], face_index: u32) -> bool {
+    let face = Face::new(font_data, face_index);
+    unsafe { hb::hb_ot_math_has_data(face.as_raw()) != 0 }
+}
+
+/// Lists the fonts installed on the system that claim support for math layout.
+///
+/// The fonts are looked up using fontconfig. `has_math_table` tells whether the font file
+/// actually contains a MATH table (fonts that can't be read are reported without one).
+#[cfg(feature = "font-discovery")]
+pub fn find_math_fonts() -> Vec<FontInfo> {
+    use fontconfig::{list_fonts, Pattern};
+
+    let pattern = Pattern::new();
+    let fontset = list_fonts(&pattern);
+
+    (&fontset)
+        .iter()
+        .filter(|pattern| {
+            pattern
+                .get_string("capability")
+                .map_or(false, |capability| capability.contains("otlayout:math"))
+        })
+        .filter_map(|pattern| {
+            let path = PathBuf::from(pattern.filename()?);
+            let face_index = pattern.face_index().unwrap_or(0) as u32;
+            let has_math_table = std::fs::read(&path)
+                .map(|data| has_math_table(&data, face_index))
+                .unwrap_or(false);
+            Some(FontInfo {
+                name: pattern.name().unwrap_or_default().into(),
+                path,
+                face_index,
+                has_math_table,
+            })
+        })
+        .collect()
+}
+
+/// The data a math font is loaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontSource {
+    /// A font file on disk.
+    Path(PathBuf),
+    /// The contents of a font file.
+    Bytes(Vec<u8>),
+}
+
+impl From<PathBuf> for FontSource {
+    fn from(path: PathBuf) -> FontSource {
+        FontSource::Path(path)
+    }
+}
+
+impl<'a> From<&'a Path> for FontSource {
+    fn from(path: &'a Path) -> FontSource {
+        FontSource::Path(path.to_owned())
+    }
+}
+
+impl From<Vec<u8>> for FontSource {
+    fn from(bytes: Vec<u8>) -> FontSource {
+        FontSource::Bytes(bytes)
+    }
+}
+
+impl<'a> From<&'a [u8]> for FontSource {
+    fn from(bytes: &'a [u8]) -> FontSource {
+        FontSource::Bytes(bytes.to_vec())
+    }
+}
+
+/// The error returned when a math font can't be loaded.
+#[derive(Debug)]
+pub enum FontError {
+    /// The font file could not be read.
+    Io(io::Error),
+    /// The font has no face with the requested index or the face has no OpenType MATH table.
+    NoMathTable,
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            FontError::Io(ref error) => write!(f, "could not read font: {}", error),
+            FontError::NoMathTable => write!(f, "font has no MATH table"),
+        }
+    }
+}
+
+impl Error for FontError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            FontError::Io(ref error) => Some(error),
+            FontError::NoMathTable => None,
+        }
+    }
+}
+
+impl From<io::Error> for FontError {
+    fn from(error: io::Error) -> FontError {
+        FontError::Io(error)
+    }
+}
+
+/// Loads the first face of a math font from a file or from memory and returns a shaper for it.
+///
+/// Font files are read into memory, so the returned shaper doesn't borrow anything.
+pub fn load_math_font<S: Into<FontSource>>(source: S) -> Result<OwnedShaper, FontError> {
+    load_math_font_face(source, 0)
+}
+
+/// Like `load_math_font` but loads the face with the given index of a font collection.
+pub fn load_math_font_face<S: Into<FontSource>>(
+    source: S,
+    face_index: u32,
+) -> Result<OwnedShaper, FontError> {
+    let bytes = match source.into() {
+        FontSource::Path(path) => std::fs::read(path)?,
+        FontSource::Bytes(bytes) => bytes,
+    };
+    if !has_math_table(&bytes, face_index) {
+        return Err(FontError::NoMathTable);
+    }
+    Ok(OwnedShaper::from_bytes(bytes, face_index))
+}
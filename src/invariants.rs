@@ -0,0 +1,289 @@
+//! Consistency checks for laid out formulas.
+//!
+//! The layout code relies on a few properties of box trees that are easy to break when changing
+//! it, e.g. that the cached metrics of a box match its content. `check` verifies them for a whole
+//! box tree so that fuzzers and property tests can catch such mistakes early.
+
+use std::fmt;
+
+use crate::math_box::{Drawable, Extents, MathBox, MathBoxContent, MathBoxMetrics};
+use crate::types::{MathExpression, MathItem};
+
+/// A property of a box that doesn't hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// The extents of the box don't match the extents of its content.
+    StaleExtents {
+        cached: Extents<i32>,
+        content: Extents<i32>,
+    },
+    /// The box advances further than its content does.
+    AdvanceWidth { cached: i32, content: i32 },
+    /// The child at `index` lies (partly) outside the extents of the box.
+    ChildOutsideParent { index: usize },
+    /// The thickness of a rule was computed from a negative length.
+    NegativeThickness { thickness: u32 },
+    /// The glyph at `index` moves the cursor backwards.
+    NegativeGlyphAdvance { index: usize, advance_width: i32 },
+    /// The item at `index` of a list starts before the preceding item ends.
+    ListItemOverlaps { index: usize, overlap: i32 },
+}
+
+/// A violated invariant together with the path to the box where it occurs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The indices of the children to follow from the root to reach the box.
+    pub path: Vec<usize>,
+    pub kind: ViolationKind,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "/")?;
+        for (i, index) in self.path.iter().enumerate() {
+            if i > 0 {
+                write!(f, "/")?;
+            }
+            write!(f, "{}", index)?;
+        }
+        write!(f, ": ")?;
+        match self.kind {
+            ViolationKind::StaleExtents { cached, content } => write!(
+                f,
+                "extents ({}, {}, {}, {}) don't match content ({}, {}, {}, {})",
+                cached.left_side_bearing,
+                cached.width,
+                cached.ascent,
+                cached.descent,
+                content.left_side_bearing,
+                content.width,
+                content.ascent,
+                content.descent
+            ),
+            ViolationKind::AdvanceWidth { cached, content } => write!(
+                f,
+                "advance width {} exceeds advance width of content {}",
+                cached, content
+            ),
+            ViolationKind::ChildOutsideParent { index } => {
+                write!(f, "child {} is outside of the box", index)
+            }
+            ViolationKind::NegativeThickness { thickness } => {
+                write!(f, "negative rule thickness {}", thickness as i32)
+            }
+            ViolationKind::NegativeGlyphAdvance {
+                index,
+                advance_width,
+            } => write!(f, "glyph {} has advance width {}", index, advance_width),
+            ViolationKind::ListItemOverlaps { index, overlap } => {
+                write!(
+                    f,
+                    "list item {} overlaps its predecessor by {}",
+                    index, overlap
+                )
+            }
+        }
+    }
+}
+
+/// Checks the invariants of every box in the tree. Lengths may deviate by at most `tolerance`
+/// font units before they count as a violation.
+pub fn check(math_box: &MathBox, tolerance: i32) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    check_box(&mut violations, &mut Vec::new(), math_box, tolerance);
+    violations
+}
+
+/// Like `check` but additionally verifies that the items of every list in `expr` are laid out
+/// from left to right. The boxes of the lists are found by their user data, so `expr` must use
+/// distinct user data for its lists.
+pub fn check_layout(expr: &MathExpression, math_box: &MathBox, tolerance: i32) -> Vec<Violation> {
+    let mut violations = check(math_box, tolerance);
+    check_lists(&mut violations, expr, math_box, tolerance);
+    violations
+}
+
+fn is_inside(child: &MathBox, parent: &Extents<i32>, tolerance: i32) -> bool {
+    let extents = child.extents();
+    // empty boxes (e.g. spaces) have no ink that could stick out
+    if extents.width <= 0 && extents.height() <= 0 {
+        return true;
+    }
+    // the left side bearing of a box is the one of its first child, so ink may stick out to the
+    // left (e.g. an accent over an italic letter) and only the right edge is checked
+    child.origin.x + extents.right_edge() <= parent.right_edge() + tolerance
+        && -child.origin.y + extents.ascent <= parent.ascent + tolerance
+        && child.origin.y + extents.descent <= parent.descent + tolerance
+}
+
+fn check_box(
+    violations: &mut Vec<Violation>,
+    path: &mut Vec<usize>,
+    math_box: &MathBox,
+    tolerance: i32,
+) {
+    let close = |a: i32, b: i32| (a - b).abs() <= tolerance;
+    let mut push = |kind| {
+        violations.push(Violation {
+            path: path.clone(),
+            kind,
+        })
+    };
+
+    let (cached, content) = (math_box.extents(), math_box.content().extents());
+    let extents_are_close = close(cached.left_side_bearing, content.left_side_bearing)
+        && close(cached.width, content.width)
+        && close(cached.ascent, content.ascent)
+        && close(cached.descent, content.descent);
+    if !extents_are_close {
+        push(ViolationKind::StaleExtents { cached, content });
+    }
+    // accents don't contribute to the advance width of their nucleus, so the advance width may
+    // be smaller than the one of the content but never larger
+    let content_advance_width = math_box.content().advance_width();
+    if math_box.advance_width() > content_advance_width + tolerance {
+        push(ViolationKind::AdvanceWidth {
+            cached: math_box.advance_width(),
+            content: content_advance_width,
+        });
+    }
+
+    match *math_box.content() {
+        MathBoxContent::Drawable(Drawable::Line { thickness, .. }) => {
+            if (thickness as i32) < 0 {
+                push(ViolationKind::NegativeThickness { thickness });
+            }
+        }
+        MathBoxContent::Drawable(Drawable::Glyphs { ref glyphs, .. }) => {
+            for (index, glyph) in glyphs.iter().enumerate() {
+                if glyph.advance_width < 0 {
+                    push(ViolationKind::NegativeGlyphAdvance {
+                        index,
+                        advance_width: glyph.advance_width,
+                    });
+                }
+            }
+        }
+        MathBoxContent::Boxes(ref boxes) => {
+            for (index, child) in boxes.iter().enumerate() {
+                if !is_inside(child, &cached, tolerance) {
+                    push(ViolationKind::ChildOutsideParent { index });
+                }
+            }
+            for (index, child) in boxes.iter().enumerate() {
+                path.push(index);
+                check_box(violations, path, child, tolerance);
+                path.pop();
+            }
+        }
+        MathBoxContent::Empty(_)
+        | MathBoxContent::Foreign { .. }
+        | MathBoxContent::Drawable(Drawable::Rectangle { .. }) => {}
+    }
+}
+
+/// Returns the outermost box with the given user data and stores the path to it in `path`.
+fn find_box<'a>(
+    math_box: &'a MathBox,
+    user_data: u64,
+    path: &mut Vec<usize>,
+) -> Option<&'a MathBox> {
+    if math_box.user_data() == user_data {
+        return Some(math_box);
+    }
+    if let MathBoxContent::Boxes(ref boxes) = *math_box.content() {
+        for (index, child) in boxes.iter().enumerate() {
+            path.push(index);
+            if let Some(found) = find_box(child, user_data, path) {
+                return Some(found);
+            }
+            path.pop();
+        }
+    }
+    None
+}
+
+fn check_lists(
+    violations: &mut Vec<Violation>,
+    expr: &MathExpression,
+    root: &MathBox,
+    tolerance: i32,
+) {
+    if let MathItem::List(_) = *expr.item() {
+        let mut path = Vec::new();
+        let boxes = match find_box(root, expr.get_user_data(), &mut path).map(MathBox::content) {
+            Some(MathBoxContent::Boxes(boxes)) => boxes.as_slice(),
+            _ => &[],
+        };
+        for (index, pair) in boxes.windows(2).enumerate() {
+            let cursor = pair[0].origin.x + pair[0].advance_width();
+            if pair[1].origin.x + tolerance < cursor {
+                violations.push(Violation {
+                    path: path.clone(),
+                    kind: ViolationKind::ListItemOverlaps {
+                        index: index + 1,
+                        overlap: cursor - pair[1].origin.x,
+                    },
+                });
+            }
+        }
+    }
+    for child in expr.children() {
+        check_lists(violations, child, root, tolerance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math_box::Vector;
+    use crate::types::Field;
+
+    #[test]
+    fn test_check() {
+        let line = |x| {
+            let from = Vector { x, y: 0 };
+            MathBox::with_line(from, from + Vector { x: 500, y: 0 }, 40, 1)
+        };
+        let mut math_box = MathBox::with_vec(vec![line(0), line(500)], 0);
+        assert!(check(&math_box, 0).is_empty());
+
+        math_box.content = MathBoxContent::Boxes(vec![line(0), line(400)]);
+        let kinds = check(&math_box, 0)
+            .into_iter()
+            .map(|violation| violation.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ViolationKind::StaleExtents {
+                    cached: Extents::new(0, 1000, 0, 0),
+                    content: Extents::new(0, 900, 0, 0),
+                },
+                ViolationKind::AdvanceWidth {
+                    cached: 1000,
+                    content: 900,
+                },
+            ]
+        );
+        assert!(check(&math_box, 100).is_empty());
+
+        let field = |user_data| MathExpression::new(MathItem::Field(Field::Empty), user_data);
+        let expr = MathExpression::new(MathItem::List(vec![field(1), field(2)]), 0);
+        let math_box = MathBox::with_vec(vec![line(0), line(400)], 0);
+        assert_eq!(
+            check_layout(&expr, &math_box, 0),
+            vec![Violation {
+                path: vec![],
+                kind: ViolationKind::ListItemOverlaps {
+                    index: 1,
+                    overlap: 100,
+                },
+            }]
+        );
+        assert_eq!(
+            check_layout(&expr, &math_box, 0)[0].to_string(),
+            "/: list item 1 overlaps its predecessor by 100"
+        );
+    }
+}
@@ -4,13 +4,39 @@
 #[macro_use]
 extern crate bitflags;
 
+mod dump;
 mod types;
+#[cfg(feature = "layout")]
 mod typesetting;
 
 #[cfg(feature = "mathml_parser")]
 extern crate quick_xml;
 
+pub mod edit;
+#[cfg(feature = "layout")]
+pub mod font;
+#[cfg(feature = "invariants")]
+pub mod invariants;
 pub mod mathmlparser;
+pub mod prelude;
+#[cfg(feature = "layout")]
+pub mod testing;
+pub mod unicode_math;
 
-pub use crate::typesetting::{math_box, unicode_math, shaper, layout, layout_with_style};
-pub use crate::types::*;
+pub use crate::types::{
+    AccentWidth, Atom, Border, Color, CornerPosition, Decoration, ElementInfo, Field,
+    ForeignObject, Form, GeneralizedFraction, Glyph, GlyphCode, Hyperlink, ImageReference,
+    IndentAlign, Length, LengthUnit, LimitsPlacement, Linebreak, LinebreakAttributes,
+    LinebreakStyle, MathExpression, MathItem, MathSpace, MathStyle, Operator, OverUnder, Payload,
+    PercentScale2D, PercentValue, ReactionArrow, Root, StretchConstraints, Strike, TextDirection,
+    TextRun, TextRunItem,
+};
+#[cfg(feature = "layout")]
+pub use crate::types::{FontFeatures, LayoutStyle};
+#[cfg(feature = "layout")]
+pub use crate::typesetting::{
+    display, display_list, estimate_extents, layout, layout_expression, layout_with_style,
+    layout_with_style_context, math_box, navigation, outline, shaper, try_layout, IntegralLimits,
+    LayoutError, LayoutOptions, MathKerning, MathLayout, RadicalSymbol, SpacingClass, SpacingModel,
+    StyleContext,
+};
@@ -1,9 +1,8 @@
 use std;
 use std::fmt;
-use std::io::prelude::*;
 
 #[cfg(feature = "mathml_parser")]
-use quick_xml::{self, XmlReader};
+use quick_xml::{self, NsReader};
 
 pub type Result<T> = std::result::Result<T, ParsingError>;
 
@@ -14,7 +13,7 @@ pub struct ParsingError {
 }
 impl ParsingError {
     #[cfg(feature = "mathml_parser")]
-    pub fn from_string<B: BufRead, S: ToString>(parser: &XmlReader<B>, string: S) -> ParsingError {
+    pub fn from_string<B, S: ToString>(parser: &NsReader<B>, string: S) -> ParsingError {
         ParsingError {
             position: Some(parser.buffer_position()),
             error_type: ErrorType::OtherError(string.to_string()),
@@ -22,12 +21,17 @@ impl ParsingError {
     }
 
     #[cfg(feature = "mathml_parser")]
-    pub fn of_type<B: BufRead>(parser: &XmlReader<B>, err_type: ErrorType) -> ParsingError {
+    pub fn of_type<B>(parser: &NsReader<B>, err_type: ErrorType) -> ParsingError {
         ParsingError {
             position: Some(parser.buffer_position()),
             error_type: err_type,
         }
     }
+
+    #[cfg(feature = "mathml_parser")]
+    pub fn from_xml_error<B>(parser: &NsReader<B>, error: quick_xml::Error) -> ParsingError {
+        ParsingError::of_type(parser, ErrorType::XmlError(error))
+    }
 }
 
 #[derive(Debug)]
@@ -35,10 +39,20 @@ pub enum ErrorType {
     UnknownElement(String),
     UnexpectedEndOfInput,
     WrongEndElement(String),
+    /// A layout schema like `mfrac` was given the wrong number of child elements.
+    WrongNumberOfArguments {
+        element: String,
+        expected: u8,
+        found: usize,
+    },
+    /// The element is known but not supported by the parser.
+    UnsupportedElement(String),
+    /// The elements are nested deeper than `ParserOptions::max_depth` allows.
+    NestingTooDeep(usize),
     OtherError(String),
     Utf8Error(std::str::Utf8Error),
     #[cfg(feature = "mathml_parser")]
-    XmlError(quick_xml::error::Error),
+    XmlError(quick_xml::Error),
 }
 
 impl fmt::Display for ParsingError {
@@ -52,6 +66,21 @@ impl fmt::Display for ParsingError {
                 "Unexpected end element \"<{}>\" without corresponding start element.",
                 name
             ),
+            ErrorType::WrongNumberOfArguments {
+                ref element,
+                expected,
+                found,
+            } => write!(
+                f,
+                "\"{}\" element requires {} arguments. Found {} arguments.",
+                element, expected, found
+            ),
+            ErrorType::UnsupportedElement(ref name) => {
+                write!(f, "Unsupported Element: \"{}\"", name)
+            }
+            ErrorType::NestingTooDeep(max_depth) => {
+                write!(f, "Elements are nested deeper than {} levels.", max_depth)
+            }
             ErrorType::OtherError(ref string) => write!(f, "Error: {}", string),
             #[cfg(feature = "mathml_parser")]
             ErrorType::XmlError(ref error) => write!(f, "XML error: {}", error),
@@ -67,6 +96,14 @@ impl std::error::Error for ParsingError {
         }
     }
 }
+impl ::std::convert::From<ErrorType> for ParsingError {
+    fn from(error_type: ErrorType) -> ParsingError {
+        ParsingError {
+            position: None,
+            error_type,
+        }
+    }
+}
 impl<'a> ::std::convert::From<&'a str> for ParsingError {
     fn from(string: &str) -> ParsingError {
         ParsingError {
@@ -84,23 +121,14 @@ impl ::std::convert::From<String> for ParsingError {
     }
 }
 #[cfg(feature = "mathml_parser")]
-impl ::std::convert::From<quick_xml::error::Error> for ParsingError {
-    fn from(error: quick_xml::error::Error) -> ParsingError {
+impl ::std::convert::From<quick_xml::Error> for ParsingError {
+    fn from(error: quick_xml::Error) -> ParsingError {
         ParsingError {
             position: None,
             error_type: ErrorType::XmlError(error),
         }
     }
 }
-#[cfg(feature = "mathml_parser")]
-impl ::std::convert::From<(quick_xml::error::Error, usize)> for ParsingError {
-    fn from((error, position): (quick_xml::error::Error, usize)) -> ParsingError {
-        ParsingError {
-            position: Some(position),
-            error_type: ErrorType::XmlError(error),
-        }
-    }
-}
 impl ::std::convert::From<std::str::Utf8Error> for ParsingError {
     fn from(error: std::str::Utf8Error) -> ParsingError {
         ParsingError {
@@ -1,31 +1,43 @@
+// Without the XML reader only the writer and the operator dictionary are used.
+#![cfg_attr(not(feature = "mathml_parser"), allow(dead_code))]
+
+#[cfg(feature = "mathml_parser")]
 mod escape;
 mod operator;
 mod operator_dict;
+mod spacing;
 mod token;
+mod writer;
 
 mod error;
 #[cfg(feature = "mathml_parser")]
 mod xml_reader;
 #[cfg(feature = "mathml_parser")]
-pub use xml_reader::parse;
+pub use xml_reader::{parse, parse_lossy, parse_with_context, parse_with_options};
 
-pub use operator::{Attributes as OperatorAttributes, Flags, Form};
-pub use token::{Attributes as TokenAttributes, StringExtMathml};
+pub use error::{ErrorType, ParsingError};
+
+pub use operator::{
+    has_movable_limits, infer_operator_forms, operator_from_dictionary,
+    Attributes as OperatorAttributes, Flags, Form,
+};
+pub use crate::types::LinebreakStyle;
+pub use token::Attributes as TokenAttributes;
+pub use writer::to_mathml;
 
 use std;
-use std::collections::BTreeMap;
+use std::collections::HashMap;
 
-use crate::{
-    types::{
-        Atom, GeneralizedFraction, Length, LengthUnit, MathExpression, MathItem, OverUnder, Root,
-    },
-    Field,
+use crate::types::{
+    Atom, Decoration, GeneralizedFraction, IndentAlign, Length, LengthUnit, LimitsPlacement,
+    Linebreak, MathExpression, MathItem, MathSpace, OverUnder, Root, Strike,
 };
 
+use self::error::Result;
 use self::operator::{guess_if_operator_with_form};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct MathmlElement {
+pub(crate) struct MathmlElement {
     identifier: &'static str,
     elem_type: ElementType,
 }
@@ -62,12 +74,12 @@ enum ArgumentRequirements {
     Special,
 }
 
-pub trait FromXmlAttribute: Sized {
+pub(crate) trait FromXmlAttribute: Sized {
     type Err;
     fn from_xml_attr(attr: &str) -> std::result::Result<Self, Self::Err>;
 }
 
-pub trait AttributeParse {
+pub(crate) trait AttributeParse {
     fn parse_xml<T: FromXmlAttribute>(&self) -> std::result::Result<T, T::Err>;
 }
 
@@ -78,7 +90,7 @@ impl AttributeParse for str {
 }
 
 // a static list of all mathml elements known to this parser
-static MATHML_ELEMENTS: [MathmlElement; 16] = [
+static MATHML_ELEMENTS: [MathmlElement; 17] = [
     MathmlElement {
         identifier: "mi",
         elem_type: ElementType::TokenElement,
@@ -165,44 +177,178 @@ static MATHML_ELEMENTS: [MathmlElement; 16] = [
             args: ArgumentRequirements::RequiredArguments(2),
         },
     },
+    MathmlElement {
+        identifier: "menclose",
+        elem_type: ElementType::LayoutSchema {
+            args: ArgumentRequirements::ArgumentList,
+        },
+    },
 ];
 
-pub fn match_math_element(identifier: &[u8]) -> Option<MathmlElement> {
+pub(crate) fn match_math_element(identifier: &[u8]) -> Option<MathmlElement> {
     MATHML_ELEMENTS
         .iter()
         .find(|elem| elem.identifier.as_bytes() == identifier)
         .cloned()
 }
 
+/// Options that change how MathML is turned into math expressions.
+#[derive(Debug, Clone, Copy)]
+pub struct ParserOptions {
+    /// Insert thin spaces before differentials in integrals and between function names and their
+    /// arguments like TeX does.
+    pub semantic_spacing: bool,
+    /// The maximum nesting depth of elements. Deeper documents are rejected with
+    /// `ErrorType::NestingTooDeep` instead of overflowing the stack during parsing or layout.
+    pub max_depth: usize,
+    /// How whitespace in the text of token elements is treated.
+    pub whitespace: Whitespace,
+}
+
+impl Default for ParserOptions {
+    fn default() -> ParserOptions {
+        ParserOptions {
+            semantic_spacing: false,
+            max_depth: 100,
+            whitespace: Whitespace::Trim,
+        }
+    }
+}
+
+/// The treatment of whitespace in the text of token elements like `mi` or `mtext`.
+///
+/// Runs of whitespace inside the text always collapse to a single space and whitespace between
+/// elements is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Whitespace {
+    /// Removes the whitespace at the start and the end of every token element as MathML
+    /// specifies, so `<mtext> if </mtext>` is just the word.
+    Trim,
+    /// Keeps a space at the start and the end of `mtext` elements, so that `<mtext> if </mtext>`
+    /// is rendered with spaces around the word. Other token elements are trimmed.
+    PreserveInText,
+}
+
+/// Identifies an element of a parsed document. The parser gives every element its own id and
+/// uses it as the user data of the expression the element is parsed into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(u64);
+
+impl NodeId {
+    /// Returns the user data of the expression with this id.
+    pub fn user_data(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<NodeId> for u64 {
+    fn from(id: NodeId) -> u64 {
+        id.0
+    }
+}
+
+/// What the parser found out about the elements of a document, e.g. the attributes of operators.
+/// It can be retrieved with `parse_with_context`.
 #[derive(Clone, Debug, Default)]
 pub struct ParseContext {
-    pub mathml_info: BTreeMap<u64, MathmlInfo>,
+    mathml_info: HashMap<NodeId, MathmlInfo>,
+    pub(crate) options: ParserOptions,
+    /// The number of elements enclosing the element that is currently parsed.
+    pub(crate) depth: usize,
+    next_node_id: u64,
 }
 
 impl ParseContext {
-    fn info_for_expr<'a, T: Into<Option<&'a MathExpression>>>(
-        &self,
-        expr: T,
-    ) -> Option<&MathmlInfo> {
-        if let Some(index) = expr.into().map(|x| x.get_user_data()) {
-            self.mathml_info.get(&index)
+    /// Returns the options the document was parsed with.
+    pub fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+
+    /// Returns an id that no other element of the document has. Expressions that are added to
+    /// the document after parsing (e.g. with the operations of `edit`) should use it as their
+    /// user data.
+    pub fn allocate_node_id(&mut self) -> NodeId {
+        let id = NodeId(self.next_node_id);
+        self.next_node_id += 1;
+        id
+    }
+
+    /// Returns the id of the element `expr` was parsed from or `None` if its user data wasn't
+    /// handed out by the parser (e.g. because the expression was created by the user).
+    pub fn node_id(&self, expr: &MathExpression) -> Option<NodeId> {
+        let user_data = expr.get_user_data();
+        if user_data < self.next_node_id {
+            Some(NodeId(user_data))
         } else {
             None
         }
     }
 
+    /// Returns the information about the element with the given id.
+    pub fn info(&self, id: NodeId) -> Option<&MathmlInfo> {
+        self.mathml_info.get(&id)
+    }
+
+    /// Returns the ids of the elements the parser has information about together with the
+    /// information (in no particular order).
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &MathmlInfo)> {
+        self.mathml_info.iter().map(|(&id, info)| (id, info))
+    }
+
+    /// Records the information about the element `expr` was parsed from.
+    pub(crate) fn insert_info(&mut self, expr: &MathExpression, info: MathmlInfo) {
+        let id = NodeId(expr.get_user_data());
+        debug_assert!(
+            id.0 < self.next_node_id,
+            "the user data {} was not allocated by the parser",
+            id.0
+        );
+        let previous = self.mathml_info.insert(id, info);
+        debug_assert!(
+            previous.is_none(),
+            "two elements share the user data {}",
+            id.0
+        );
+    }
+
+    /// Removes the information about the element `expr` was parsed from and returns it.
+    pub(crate) fn remove_info(&mut self, expr: &MathExpression) -> Option<MathmlInfo> {
+        let id = self.node_id(expr)?;
+        self.mathml_info.remove(&id)
+    }
+
+    /// Returns the information about a new element whose first argument is `first_argument`. The
+    /// element is an embellished operator if the argument is one.
+    pub(crate) fn embellished_info(&self, first_argument: Option<&MathExpression>) -> MathmlInfo {
+        MathmlInfo {
+            operator_attrs: self
+                .info_for_expr(first_argument)
+                .and_then(|info| info.operator_attrs.clone()),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the information about the element `expr` was created from.
+    pub fn info_for_expr<'a, T: Into<Option<&'a MathExpression>>>(
+        &self,
+        expr: T,
+    ) -> Option<&MathmlInfo> {
+        expr.into()
+            .and_then(|expr| self.node_id(expr))
+            .and_then(|id| self.info(id))
+    }
+
     fn info_for_expr_mut<'a, T: Into<Option<&'a MathExpression>>>(
         &mut self,
         expr: T,
     ) -> Option<&mut MathmlInfo> {
-        if let Some(index) = expr.into().map(|x| x.get_user_data()) {
-            self.mathml_info.get_mut(&index)
-        } else {
-            None
-        }
+        let id = expr.into().and_then(|expr| self.node_id(expr))?;
+        self.mathml_info.get_mut(&id)
     }
 
-    fn operator_attrs<'a, 'b: 'a, T: Into<Option<&'a MathExpression>>>(
+    /// Returns the attributes of the operator `expr` was created from (including the defaults
+    /// from the operator dictionary) or `None` if it is no operator.
+    pub fn operator_attrs<'a, 'b: 'a, T: Into<Option<&'a MathExpression>>>(
         &'b self,
         expr: T,
     ) -> Option<&'b operator::Attributes> {
@@ -218,74 +364,36 @@ pub struct MathmlInfo {
 }
 
 impl MathmlInfo {
-    fn is_operator(&self) -> bool {
+    /// Returns whether the element is an (embellished) operator.
+    pub fn is_operator(&self) -> bool {
         !self.operator_attrs.is_none()
     }
 }
 
-pub enum Child {
-    Field((Field, u64)),
-    Expression(MathExpression),
-}
-
-#[derive(Debug, Default, Clone)]
-pub struct Attributes {
-    pub token: token::Attributes,
-    pub schema: SchemaAttributes,
-}
-
 #[derive(Debug, Default, Clone)]
-pub struct SchemaAttributes {
+pub(crate) struct SchemaAttributes {
     accent: bool,
     accentunder: bool,
-}
-
-pub fn build_element<'a>(
-    elem: MathmlElement,
-    attributes: Attributes,
-    children: impl Iterator<Item = Child>,
-    context: &mut ParseContext,
-    // You have to ensure that this key is unique
-    user_data: u64,
-) -> MathExpression {
-    match elem.elem_type {
-        ElementType::LayoutSchema {
-            args: ArgumentRequirements::RequiredArguments(_),
-        } => {
-            let expressions = children.filter_map(|child| match child {
-                Child::Expression(expr) => Some(expr),
-                _ => None,
-            });
-            parse_fixed_schema(expressions, elem, attributes.schema, context, user_data)
-        }
-        ElementType::LayoutSchema {
-            args: ArgumentRequirements::ArgumentList,
-        }
-        | ElementType::MathmlRoot => {
-            let expressions = children.filter_map(|child| match child {
-                Child::Expression(expr) => Some(expr),
-                _ => None,
-            });
-            let mut list = expressions.collect();
-            operator::process_operators(&mut list, context);
-            parse_list_schema(list, elem, user_data)
-        }
-        ElementType::TokenElement => {
-            let fields = children.filter_map(|child| match child {
-                Child::Field(field) => Some(field),
-                _ => None,
-            });
-            token::build_token(fields, elem, attributes.token, context, user_data).unwrap()
-        }
-        _ => todo!(),
-    }
+    limits: LimitsPlacement,
+    linethickness: Option<Length>,
+    /// The strikes of a `menclose`. Other notations aren't supported.
+    notation: Vec<Strike>,
 }
 
 fn parse_list_schema<'a>(
     mut content: Vec<MathExpression>,
     elem: MathmlElement,
+    attributes: SchemaAttributes,
     user_data: u64,
 ) -> MathExpression {
+    // the strikes cross all of the content, so an `menclose` never collapses to its only child
+    if elem.is("menclose") && !attributes.notation.is_empty() {
+        let decoration = Decoration {
+            strikes: attributes.notation,
+            ..Default::default()
+        };
+        return MathExpression::new(MathItem::List(content), user_data).with_decoration(decoration);
+    }
     // a mrow with a single element is strictly equivalent to the element
     let content = if content.len() == 1 {
         content.remove(0)
@@ -334,6 +442,7 @@ fn construct_under_over<'a>(
         over,
         over_is_accent: attributes.accent,
         under_is_accent: attributes.accentunder,
+        limits_placement: attributes.limits,
         ..Default::default()
     };
 
@@ -346,29 +455,65 @@ fn parse_fixed_schema<'a>(
     attributes: SchemaAttributes,
     context: &mut ParseContext,
     user_data: u64,
-) -> MathExpression {
-    let mut next = || Some(content.next().unwrap());
+) -> Result<MathExpression> {
+    let expected = match elem.elem_type {
+        ElementType::LayoutSchema {
+            args: ArgumentRequirements::RequiredArguments(num_args),
+        } => num_args,
+        _ => return Err(ErrorType::UnsupportedElement(elem.identifier.to_string()).into()),
+    };
+    let mut found = 0;
+    let mut next = || match content.next() {
+        Some(expr) => {
+            found += 1;
+            Ok(expr)
+        }
+        None => Err(ParsingError::from(ErrorType::WrongNumberOfArguments {
+            element: elem.identifier.to_string(),
+            expected,
+            found,
+        })),
+    };
     let result = match elem.identifier {
         "mfrac" => {
+            // the thickness is a node of its own that is recorded like a space
+            let thickness = attributes.linethickness.map(|thickness| {
+                let space = MathSpace {
+                    ascent: thickness,
+                    ..Default::default()
+                };
+                let thickness = MathExpression::new(
+                    MathItem::Space(space),
+                    context.allocate_node_id().user_data(),
+                );
+                context.insert_info(
+                    &thickness,
+                    MathmlInfo {
+                        operator_attrs: None,
+                        is_space: true,
+                    },
+                );
+                thickness
+            });
             let frac = GeneralizedFraction {
-                numerator: next(),
-                denominator: next(),
-                thickness: None,
+                numerator: Some(next()?),
+                denominator: Some(next()?),
+                thickness,
             };
             MathItem::GeneralizedFraction(frac)
         }
         "mroot" => {
             let root = Root {
-                radicand: next(),
-                degree: next(),
+                radicand: Some(next()?),
+                degree: Some(next()?),
             };
             MathItem::Root(root)
         }
         "msub" => {
             let atom = Atom {
-                nucleus: next(),
+                nucleus: Some(next()?),
                 bottom_right: Some(guess_if_operator_with_form(
-                    next().unwrap(),
+                    next()?,
                     Form::Postfix,
                     context,
                 )),
@@ -378,9 +523,9 @@ fn parse_fixed_schema<'a>(
         }
         "msup" => {
             let atom = Atom {
-                nucleus: next(),
+                nucleus: Some(next()?),
                 top_right: Some(guess_if_operator_with_form(
-                    next().unwrap(),
+                    next()?,
                     Form::Postfix,
                     context,
                 )),
@@ -390,14 +535,14 @@ fn parse_fixed_schema<'a>(
         }
         "msubsup" => {
             let atom = Atom {
-                nucleus: next(),
+                nucleus: Some(next()?),
                 bottom_right: Some(guess_if_operator_with_form(
-                    next().unwrap(),
+                    next()?,
                     Form::Postfix,
                     context,
                 )),
                 top_right: Some(guess_if_operator_with_form(
-                    next().unwrap(),
+                    next()?,
                     Form::Postfix,
                     context,
                 )),
@@ -406,41 +551,32 @@ fn parse_fixed_schema<'a>(
             MathItem::Atom(atom)
         }
         "mover" => {
-            let nuc = next();
-            let over = next();
+            let nuc = Some(next()?);
+            let over = Some(next()?);
             construct_under_over(nuc, None, over, attributes, context)
         }
         "munder" => {
-            let nuc = next();
-            let under = next();
+            let nuc = Some(next()?);
+            let under = Some(next()?);
             construct_under_over(nuc, under, None, attributes, context)
         }
         "munderover" => {
-            let nuc = next();
-            let under = next();
-            let over = next();
+            let nuc = Some(next()?);
+            let under = Some(next()?);
+            let over = Some(next()?);
             construct_under_over(nuc, under, over, attributes, context)
         }
-        _ => unreachable!(),
-    };
-    let info = MathmlInfo {
-        operator_attrs: match result {
-            MathItem::Atom(ref atom) => context
-                .info_for_expr(atom.nucleus.as_ref())
-                .and_then(|info| info.operator_attrs.clone()),
-            MathItem::OverUnder(ref ou) => context
-                .info_for_expr(ou.nucleus.as_ref())
-                .and_then(|info| info.operator_attrs.clone()),
-            MathItem::GeneralizedFraction(ref frac) => context
-                .info_for_expr(frac.numerator.as_ref())
-                .and_then(|info| info.operator_attrs.clone()),
-            _ => None,
-        },
-        ..Default::default()
+        _ => return Err(ErrorType::UnsupportedElement(elem.identifier.to_string()).into()),
     };
-    context.mathml_info.insert(user_data, info);
+    let info = context.embellished_info(match result {
+        MathItem::Atom(ref atom) => atom.nucleus.as_ref(),
+        MathItem::OverUnder(ref ou) => ou.nucleus.as_ref(),
+        MathItem::GeneralizedFraction(ref frac) => frac.numerator.as_ref(),
+        _ => None,
+    });
     let expr = MathExpression::new(result, user_data);
-    expr
+    context.insert_info(&expr, info);
+    Ok(expr)
 }
 
 impl FromXmlAttribute for Length {
@@ -480,6 +616,45 @@ impl FromXmlAttribute for bool {
     }
 }
 
+impl FromXmlAttribute for Linebreak {
+    type Err = &'static str;
+    fn from_xml_attr(attr: &str) -> std::result::Result<Self, Self::Err> {
+        match attr.trim() {
+            "auto" => Ok(Linebreak::Auto),
+            "newline" => Ok(Linebreak::Newline),
+            "nobreak" => Ok(Linebreak::NoBreak),
+            "goodbreak" => Ok(Linebreak::GoodBreak),
+            "badbreak" => Ok(Linebreak::BadBreak),
+            _ => Err("unrecognized linebreak value"),
+        }
+    }
+}
+
+impl FromXmlAttribute for LinebreakStyle {
+    type Err = &'static str;
+    fn from_xml_attr(attr: &str) -> std::result::Result<Self, Self::Err> {
+        match attr.trim() {
+            "before" => Ok(LinebreakStyle::Before),
+            "after" => Ok(LinebreakStyle::After),
+            "duplicate" => Ok(LinebreakStyle::Duplicate),
+            _ => Err("unrecognized linebreakstyle value"),
+        }
+    }
+}
+
+impl FromXmlAttribute for IndentAlign {
+    type Err = &'static str;
+    fn from_xml_attr(attr: &str) -> std::result::Result<Self, Self::Err> {
+        match attr.trim() {
+            "auto" => Ok(IndentAlign::Auto),
+            "left" => Ok(IndentAlign::Left),
+            "center" => Ok(IndentAlign::Center),
+            "right" => Ok(IndentAlign::Right),
+            _ => Err("unrecognized indentalign value"),
+        }
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "mathml_parser")]
 mod tests {
@@ -562,4 +737,503 @@ mod tests {
             ref other_item => panic!("Expected MathItem::Operator. Found {:?}.", other_item),
         }
     }
+
+    #[test]
+    fn test_operator_from_dictionary() {
+        let cases = [
+            ("<mo form=\"prefix\">\u{2211}</mo>", '\u{2211}'),
+            ("<mo form=\"prefix\">(</mo>", '('),
+        ];
+        for &(xml, character) in cases.iter() {
+            let expr = parse(xml.as_bytes()).unwrap();
+            match *find_operator(&expr).item {
+                MathItem::Operator(ref operator) => {
+                    assert_eq!(*operator, operator_from_dictionary(character, Form::Prefix))
+                }
+                ref other_item => panic!("Expected MathItem::Operator. Found {:?}.", other_item),
+            }
+        }
+        assert!(operator_from_dictionary('\u{2211}', Form::Prefix).is_large_op);
+        assert!(has_movable_limits('\u{2211}', Form::Prefix));
+        assert!(!has_movable_limits('\u{222b}', Form::Prefix));
+    }
+
+    #[test]
+    fn test_infer_operator_forms() {
+        let operator = |text: &str| {
+            let character = text.chars().next().unwrap();
+            MathExpression::new(
+                MathItem::Operator(operator_from_dictionary(character, Form::Infix)),
+                0,
+            )
+        };
+        let field =
+            |text: &str| MathExpression::new(MathItem::Field(Field::Unicode(text.into())), 0);
+        let custom = MathExpression::new(
+            MathItem::Operator(Operator {
+                field: Field::Unicode("-".into()),
+                leading_space: Length::em(1.0),
+                ..Default::default()
+            }),
+            0,
+        );
+        let inner = MathExpression::new(MathItem::List(vec![operator("-"), field("x")]), 0);
+        let list = vec![custom, field("2"), operator("+"), inner, operator("!")];
+        let mut expr = MathExpression::new(MathItem::List(list), 0);
+        infer_operator_forms(&mut expr);
+
+        let parsed = parse("<mo>-</mo><mi>x</mi>".as_bytes()).unwrap();
+        let operators = |expr: &MathExpression| {
+            expr.children()
+                .into_iter()
+                .filter_map(|child| match *child.item {
+                    MathItem::Operator(ref operator) => Some(operator.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let children = expr.children();
+        assert_eq!(operators(children[3]), operators(&parsed));
+
+        let outer = operators(&expr);
+        assert_eq!(outer[0].form, Form::Prefix);
+        assert_eq!(outer[0].field, Field::Unicode("\u{2212}".into()));
+        assert_eq!(outer[0].leading_space, Length::em(1.0));
+        assert_eq!(outer[1], operator_from_dictionary('+', Form::Infix));
+        assert_eq!(outer[2], operator_from_dictionary('!', Form::Postfix));
+    }
+
+    #[test]
+    fn test_parse_with_context() {
+        let parse = |xml: &str| {
+            xml_reader::parse_with_context(xml.as_bytes(), ParserOptions::default()).unwrap()
+        };
+        let (expr, context) = parse("<mo>+</mo>");
+        let info = context.info_for_expr(&expr).unwrap();
+        assert!(info.is_operator() && !info.is_space);
+        let attributes = context.operator_attrs(&expr).unwrap();
+        assert_eq!(attributes.form, Some(Form::Infix));
+        assert_eq!(attributes.lspace, Some(Length::em(4.0 / 18.0)));
+
+        let (expr, context) = parse("<mspace width=\"1em\"/>");
+        let info = context.info_for_expr(&expr).unwrap();
+        assert!(info.is_space && !info.is_operator());
+
+        // every token gets its own user data, so only the `mo` is an operator
+        let (expr, context) = parse("<mi>x</mi><mo>=</mo><mn>1</mn>");
+        let children = expr.children();
+        let user_data = children
+            .iter()
+            .map(|child| child.get_user_data())
+            .collect::<Vec<_>>();
+        assert!(user_data[0] != user_data[1] && user_data[1] != user_data[2]);
+        let is_operator = children
+            .iter()
+            .map(|&child| context.info_for_expr(child).unwrap().is_operator())
+            .collect::<Vec<_>>();
+        assert_eq!(is_operator, [false, true, false]);
+    }
+
+    #[test]
+    fn test_node_ids() {
+        let xml = "<mrow><mi>x</mi><mo>+</mo><mfrac><mn>1</mn><mn>2</mn></mfrac></mrow>";
+        let (expr, context) =
+            xml_reader::parse_with_context(xml.as_bytes(), ParserOptions::default()).unwrap();
+        let ids = expr
+            .children()
+            .into_iter()
+            .map(|child| context.node_id(child).unwrap())
+            .collect::<Vec<_>>();
+        assert!(ids[0] != ids[1] && ids[1] != ids[2] && ids[0] != ids[2]);
+        assert!(context.info(ids[1]).unwrap().is_operator());
+        let operators = context.nodes().filter(|(_, info)| info.is_operator());
+        assert_eq!(operators.count(), 1);
+
+        // expressions created by the user don't belong to the document
+        let foreign = MathExpression::new(MathItem::List(vec![]), u64::MAX);
+        assert_eq!(context.node_id(&foreign), None);
+        assert!(context.info_for_expr(&foreign).is_none());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "share the user data")]
+    fn test_user_data_collision() {
+        let mut context = ParseContext::default();
+        let id = context.allocate_node_id();
+        let expr = MathExpression::new(MathItem::List(vec![]), id.user_data());
+        context.insert_info(&expr, MathmlInfo::default());
+        context.insert_info(&expr, MathmlInfo::default());
+    }
+
+    #[test]
+    fn test_element_info() {
+        let xml = "<mrow><mi mathvariant=\"normal\">x</mi><mtext>ab</mtext></mrow>";
+        let expr = parse(xml.as_bytes()).unwrap();
+        assert_eq!(expr.element().map(|info| &*info.name), Some("mrow"));
+        let list = match *expr.item {
+            MathItem::List(ref list) => list,
+            ref other_item => panic!("Expected list. Found {:?}.", other_item),
+        };
+        let identifier = list[0].element().unwrap();
+        assert_eq!(identifier.name, "mi");
+        assert_eq!(identifier.attribute("mathvariant"), Some("normal"));
+        assert_eq!(list[1].element(), Some(&ElementInfo::new("mtext")));
+    }
+
+    #[test]
+    fn test_href() {
+        let xml = "<mrow><mi href=\"#x\">x</mi><mrow href=\"#y\"><mi>y</mi></mrow><mi>z</mi></mrow>";
+        let expr = parse(xml.as_bytes()).unwrap();
+        let list = match *expr.item {
+            MathItem::List(ref list) => list,
+            ref other_item => panic!("Expected list. Found {:?}.", other_item),
+        };
+        let href = |expr: &MathExpression| {
+            expr.payload()
+                .and_then(|payload| payload.downcast_ref::<Hyperlink>())
+                .map(|link| link.href.clone())
+        };
+        assert_eq!(href(&list[0]), Some("#x".to_string()));
+        assert_eq!(href(&list[1]), Some("#y".to_string()));
+        assert_eq!(href(&list[2]), None);
+    }
+
+    #[test]
+    fn test_namespaces() {
+        let xml = "<m:math xmlns:m=\"http://www.w3.org/1998/Math/MathML\">\
+                   <m:mi>x</m:mi><m:mo>+</m:mo><m:mn>1</m:mn></m:math>";
+        let expr = parse(xml.as_bytes()).unwrap();
+        assert_eq!(expr.children().len(), 3);
+        // the namespace declaration is no attribute of the element
+        assert_eq!(expr.element(), Some(&ElementInfo::new("math")));
+
+        let xml = "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mi>x</mi></math>";
+        assert!(parse(xml.as_bytes()).is_ok());
+
+        let xml = "<mrow xmlns:svg=\"http://www.w3.org/2000/svg\"><svg:mi>x</svg:mi></mrow>";
+        match parse(xml.as_bytes()) {
+            Err(ParsingError {
+                error_type: ErrorType::UnknownElement(name),
+                ..
+            }) => assert_eq!(name, "svg:mi"),
+            other => panic!("Expected UnknownElement. Found {:?}.", other),
+        }
+    }
+
+    #[test]
+    fn test_mglyph() {
+        let xml = "<mi><mglyph index=\"42\" alt=\"x\"/></mi>";
+        let expr = parse(xml.as_bytes()).unwrap();
+        match *expr.item {
+            MathItem::Field(Field::Glyph(glyph)) => assert_eq!(glyph.glyph_code, 42),
+            ref other_item => panic!("Expected glyph field. Found {:?}.", other_item),
+        }
+
+        let xml = "<mi><mglyph src=\"a.png\" alt=\"a\" width=\"2em\" height=\"1em\" \
+                   valign=\"-0.5em\"/></mi>";
+        let expr = parse(xml.as_bytes()).unwrap();
+        match *expr.item {
+            MathItem::Foreign(ref foreign) => {
+                assert_eq!(foreign.size.width, Length::em(2.0));
+                assert_eq!(foreign.size.ascent, Length::em(0.5));
+                assert_eq!(foreign.size.descent, Length::em(0.5));
+                let image = foreign.payload.downcast_ref::<ImageReference>().unwrap();
+                assert_eq!(image.src, "a.png");
+                assert_eq!(image.alt.as_ref().map(|alt| &**alt), Some("a"));
+            }
+            ref other_item => panic!("Expected image. Found {:?}.", other_item),
+        }
+
+        let xml = "<mi><mglyph alt=\"b\"/></mi>";
+        let expr = parse(xml.as_bytes()).unwrap();
+        match *expr.item {
+            MathItem::Field(Field::Unicode(ref text)) => assert_eq!(text, "b"),
+            ref other_item => panic!("Expected alternative text. Found {:?}.", other_item),
+        }
+
+        let xml = "<mi><mglyph alt=\"&lt;&alpha;\"/></mi>";
+        let expr = parse(xml.as_bytes()).unwrap();
+        match *expr.item {
+            MathItem::Field(Field::Unicode(ref text)) => assert_eq!(text, "<\u{3B1}"),
+            ref other_item => panic!("Expected alternative text. Found {:?}.", other_item),
+        }
+    }
+
+    #[test]
+    fn test_stretch_size_attributes() {
+        let xml = "<mo minsize=\"50%\" maxsize=\"2\">(</mo>";
+        let expr = parse(xml.as_bytes()).unwrap();
+        let operator = find_operator(&expr);
+        match *operator.item {
+            MathItem::Operator(Operator {
+                stretch_constraints: Some(constraints),
+                ..
+            }) => {
+                assert_eq!(
+                    constraints.min_size,
+                    Some(Length::new(0.5, LengthUnit::OperatorDefaultSize))
+                );
+                assert_eq!(
+                    constraints.max_size,
+                    Some(Length::new(2.0, LengthUnit::OperatorDefaultSize))
+                );
+            }
+            ref other_item => panic!("Expected stretchy operator. Found {:?}.", other_item),
+        }
+
+        let xml = "<mo minsize=\"1em\" maxsize=\"infinity\">(</mo>";
+        let expr = parse(xml.as_bytes()).unwrap();
+        let operator = find_operator(&expr);
+        match *operator.item {
+            MathItem::Operator(Operator {
+                stretch_constraints: Some(constraints),
+                ..
+            }) => {
+                assert_eq!(constraints.min_size, Some(Length::em(1.0)));
+                assert_eq!(constraints.max_size, None);
+            }
+            ref other_item => panic!("Expected stretchy operator. Found {:?}.", other_item),
+        }
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        let error = parse_lossy(b"<mfrac><mi>a</mi></mfrac>").unwrap_err();
+        match error.error_type {
+            ErrorType::WrongNumberOfArguments {
+                expected, found, ..
+            } => assert_eq!((expected, found), (2, 1)),
+            other => panic!("Expected WrongNumberOfArguments. Found {:?}.", other),
+        }
+
+        let inputs: &[&[u8]] = &[
+            b"<msub><mi>x</mi></msub>",
+            b"<munderover></munderover>",
+            b"<mi>&#;</mi>",
+            b"<mi>&;</mi>",
+            b"<mo>&#x;</mo>",
+            b"<mover accent=\"maybe\"><mi>x</mi><mo>^</mo></mover>",
+            b"<mrow><mi>x</mi>",
+            b"</mi>",
+            b"<mi>\xff\xfe</mi>",
+        ];
+        for input in inputs {
+            let _ = parse_lossy(input);
+        }
+
+        // errors of the XML reader know where they occurred
+        let error = parse(&b"<mrow><mi>x</mo></mrow>"[..]).unwrap_err();
+        match error.error_type {
+            ErrorType::XmlError(_) => assert!(error.position.is_some()),
+            other => panic!("Expected XmlError. Found {:?}.", other),
+        }
+
+        // the input must not end inside of a token element
+        for input in &["<mi>x", "<mtext>a <mglyph src=\"a.png\"/>"] {
+            match parse(input.as_bytes()).unwrap_err().error_type {
+                ErrorType::UnexpectedEndOfInput => {}
+                other => panic!("Expected UnexpectedEndOfInput. Found {:?}.", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_semantic_spacing() {
+        fn spaces(xml: &str, semantic_spacing: bool) -> Vec<usize> {
+            let options = ParserOptions {
+                semantic_spacing,
+                ..Default::default()
+            };
+            let expr = parse_with_options(xml.as_bytes(), options).unwrap();
+            match *expr.item {
+                MathItem::List(ref list) => list
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, expr)| match *expr.item {
+                        MathItem::Space(_) => true,
+                        _ => false,
+                    })
+                    .map(|(index, _)| index)
+                    .collect(),
+                ref other_item => panic!("Expected list. Found {:?}.", other_item),
+            }
+        }
+
+        let integral = "<mrow><mo>&#x222B;</mo><mi>f</mi><mi>d</mi><mi>x</mi></mrow>";
+        assert_eq!(spaces(integral, true), vec![2]);
+        assert_eq!(spaces(integral, false), vec![]);
+        // a `d` without an integral is just a variable
+        assert_eq!(spaces("<mrow><mi>a</mi><mi>d</mi></mrow>", true), vec![]);
+
+        let function = "<mrow><mi>sin</mi><mo>&#x2061;</mo><mi>x</mi></mrow>";
+        assert_eq!(spaces(function, true), vec![2]);
+        let fenced = "<mrow><mi>sin</mi><mrow><mo>(</mo><mi>x</mi><mo>)</mo></mrow></mrow>";
+        assert_eq!(spaces(fenced, true), vec![1]);
+        let parenthesized = "<mrow><mi>sin</mi><mo>(</mo><mi>x</mi><mo>)</mo></mrow>";
+        assert_eq!(spaces(parenthesized, true), vec![]);
+
+        // the inserted spaces are elements of their own
+        let options = ParserOptions {
+            semantic_spacing: true,
+            ..Default::default()
+        };
+        let xml = "<mrow><mi>sin</mi><mi>x</mi><mo>&#x222B;</mo><mi>d</mi><mi>x</mi></mrow>";
+        let (expr, context) = parse_with_context(xml.as_bytes(), options).unwrap();
+        let list = match *expr.item {
+            MathItem::List(ref list) => list,
+            ref other_item => panic!("Expected list. Found {:?}.", other_item),
+        };
+        let mut user_data = list.iter().map(|expr| expr.get_user_data()).collect::<Vec<_>>();
+        user_data.push(expr.get_user_data());
+        user_data.sort();
+        user_data.dedup();
+        assert_eq!(user_data.len(), list.len() + 1);
+        for space in list.iter().filter(|expr| matches!(*expr.item, MathItem::Space(_))) {
+            assert!(context.info_for_expr(space).unwrap().is_space);
+        }
+    }
+
+    #[test]
+    fn test_limits_attribute() {
+        let placement = |xml: &str| match *parse(xml.as_bytes()).unwrap().item {
+            MathItem::OverUnder(ref over_under) => over_under.limits_placement,
+            ref other_item => panic!("Expected OverUnder. Found {:?}.", other_item),
+        };
+        assert_eq!(
+            placement("<munder><mi>x</mi><mi>y</mi></munder>"),
+            LimitsPlacement::Auto
+        );
+        assert_eq!(
+            placement("<mover limits=\"true\"><mi>x</mi><mi>y</mi></mover>"),
+            LimitsPlacement::Limits
+        );
+        assert_eq!(
+            placement("<munderover limits=\"false\"><mi>x</mi><mi>y</mi><mi>z</mi></munderover>"),
+            LimitsPlacement::Scripts
+        );
+    }
+
+    #[test]
+    fn test_linethickness_attribute() {
+        let thickness = |xml: &str| match *parse(xml.as_bytes()).unwrap().item {
+            MathItem::GeneralizedFraction(ref frac) => frac.rule_thickness(),
+            ref other_item => panic!("Expected fraction. Found {:?}.", other_item),
+        };
+        assert_eq!(thickness("<mfrac><mi>x</mi><mi>y</mi></mfrac>"), None);
+        assert_eq!(
+            thickness("<mfrac linethickness=\"0\"><mi>n</mi><mi>k</mi></mfrac>"),
+            Some(Length::new(0.0, LengthUnit::Point))
+        );
+        assert_eq!(
+            thickness("<mfrac linethickness=\"0.2em\"><mi>x</mi><mi>y</mi></mfrac>"),
+            Some(Length::em(0.2))
+        );
+
+        // the thickness has an id of its own
+        let xml = "<mfrac linethickness=\"0\"><mi>n</mi><mi>k</mi></mfrac>";
+        let (expr, context) =
+            xml_reader::parse_with_context(xml.as_bytes(), ParserOptions::default()).unwrap();
+        let thickness = match *expr.item {
+            MathItem::GeneralizedFraction(ref frac) => frac.thickness.as_ref().unwrap(),
+            _ => unreachable!(),
+        };
+        let id = context.node_id(thickness).unwrap();
+        assert_ne!(Some(id), context.node_id(&expr));
+        assert!(context.info(id).unwrap().is_space);
+    }
+
+    #[test]
+    fn test_linebreak_attributes() {
+        let xml = "<mrow><mi>x</mi><mo linebreak=\"newline\" indentalign=\"right\" \
+                   indentshift=\"2em\">=</mo><mi>y</mi><mo linebreakstyle=\"after\">+</mo>\
+                   <mspace linebreak=\"goodbreak\"/><mi>z</mi><mo>-</mo><mi>w</mi></mrow>";
+        let expr = parse(xml.as_bytes()).unwrap();
+        let children = expr.children();
+        assert_eq!(
+            children[1].linebreak(),
+            Some(&LinebreakAttributes {
+                linebreak: Linebreak::Newline,
+                indent_align: IndentAlign::Right,
+                indent_shift: Some(Length::em(2.0)),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            children[3].linebreak().map(|linebreak| linebreak.style),
+            Some(LinebreakStyle::After)
+        );
+        assert_eq!(
+            children[4].linebreak().map(|linebreak| linebreak.linebreak),
+            Some(Linebreak::GoodBreak)
+        );
+        assert_eq!(children[6].linebreak(), None);
+    }
+
+    #[test]
+    fn test_menclose_notation() {
+        let xml = "<menclose notation=\"updiagonalstrike longdiv horizontalstrike\">\
+                   <mn>1</mn></menclose>";
+        let expr = parse(xml.as_bytes()).unwrap();
+        let strikes = expr.decoration().map(|decoration| &*decoration.strikes);
+        assert_eq!(strikes, Some(&[Strike::UpDiagonal, Strike::Horizontal][..]));
+        assert_eq!(expr.element().map(|info| &*info.name), Some("menclose"));
+        assert_eq!(
+            to_mathml(&expr),
+            "<menclose notation=\"updiagonalstrike horizontalstrike\"><mn>1</mn></menclose>"
+        );
+
+        // without a supported notation `menclose` behaves like `mrow`
+        let expr = parse("<menclose notation=\"box\"><mi>x</mi></menclose>".as_bytes()).unwrap();
+        assert!(expr.decoration().is_none());
+    }
+
+    #[test]
+    fn test_nesting_depth() {
+        let nested = |depth: usize| {
+            "<mrow>".repeat(depth) + "<mi>x</mi>" + &"</mrow>".repeat(depth)
+        };
+        let max_depth = ParserOptions::default().max_depth;
+        // the `mi` is the innermost element
+        assert!(parse(nested(max_depth - 1).as_bytes()).is_ok());
+        assert!(parse(nested(max_depth).as_bytes()).is_err());
+        match parse(nested(100_000).as_bytes()) {
+            Err(ParsingError {
+                error_type: ErrorType::NestingTooDeep(depth),
+                ..
+            }) => assert_eq!(depth, max_depth),
+            other => panic!("Expected NestingTooDeep. Found {:?}.", other),
+        }
+    }
+
+    #[test]
+    fn test_whitespace() {
+        fn text(xml: &str, whitespace: Whitespace) -> String {
+            let options = ParserOptions {
+                whitespace,
+                ..Default::default()
+            };
+            let expr = parse_with_options(xml.as_bytes(), options).unwrap();
+            match *expr.item {
+                MathItem::Field(Field::Unicode(ref text)) => text.clone(),
+                ref other_item => panic!("Expected text. Found {:?}.", other_item),
+            }
+        }
+
+        let xml = "<mtext> if \n\t x </mtext>";
+        assert_eq!(text(xml, Whitespace::Trim), "if x");
+        assert_eq!(text(xml, Whitespace::PreserveInText), " if x ");
+        assert_eq!(text("<mtext> &amp; </mtext>", Whitespace::PreserveInText), " & ");
+        // other tokens are always trimmed, so single letters are still italic
+        assert_eq!(text("<mi> x </mi>", Whitespace::PreserveInText), "\u{1D465}");
+
+        let xml = "<mtext> </mtext>";
+        assert_eq!(text(xml, Whitespace::PreserveInText), " ");
+        let expr = parse(xml.as_bytes()).unwrap();
+        assert!(expr.children().is_empty());
+
+        // whitespace between elements is ignored
+        let xml = "<mrow>\n  <mi>x</mi>\n  <mo>+</mo>\n</mrow>";
+        assert_eq!(parse(xml.as_bytes()).unwrap().children().len(), 2);
+    }
 }
@@ -1,8 +1,11 @@
+pub use crate::types::Form;
 use crate::types::{
-    Atom, GeneralizedFraction, Length, MathExpression, MathItem, Operator, OverUnder,
-    StretchConstraints,
+    Atom, Field, GeneralizedFraction, Length, LinebreakAttributes, LinebreakStyle, MathExpression,
+    MathItem, Operator, OverUnder, StretchConstraints,
 };
 
+use crate::edit::children_of_mut;
+
 use super::operator_dict;
 use super::{FromXmlAttribute, ParseContext};
 
@@ -24,23 +27,11 @@ impl Default for Flags {
     }
 }
 
+#[allow(dead_code)]
 pub struct FormParsingError {
     pub unknown_str: String,
 }
 
-#[derive(PartialEq, Eq, Copy, Clone, Debug, Ord, PartialOrd)]
-pub enum Form {
-    Prefix,
-    Infix,
-    Postfix,
-}
-
-impl Default for Form {
-    fn default() -> Form {
-        Form::Infix
-    }
-}
-
 impl FromXmlAttribute for Form {
     type Err = FormParsingError;
     fn from_xml_attr(s: &str) -> Result<Form, FormParsingError> {
@@ -61,8 +52,16 @@ pub struct Attributes {
     pub form: Option<Form>,
     pub lspace: Option<Length>,
     pub rspace: Option<Length>,
+    /// The minimum size of the operator when stretched.
+    pub minsize: Option<Length>,
+    /// The maximum size of the operator when stretched. `None` means the size is unbounded.
+    pub maxsize: Option<Length>,
     pub flags: Flags,
     pub user_overrides: Flags,
+    /// The `linebreak`, `indentalign` and `indentshift` attributes.
+    pub linebreak: LinebreakAttributes,
+    /// The `linebreakstyle` attribute or the style of the operator dictionary.
+    pub linebreak_style: Option<LinebreakStyle>,
 }
 
 impl Attributes {
@@ -118,6 +117,121 @@ pub fn process_operators(list: &mut Vec<MathExpression>, context: &mut ParseCont
     }
 }
 
+/// Returns the operator the parser creates for `character` in the given form, i.e. with the
+/// spacing and flags of the operator dictionary. Characters missing from the dictionary get the
+/// default spacing and no flags.
+///
+/// This allows building expressions programmatically that lay out like parsed MathML.
+pub fn operator_from_dictionary(character: char, form: Form) -> Operator {
+    let entry = operator_dict::find_entry(character, form).unwrap_or_default();
+    let flags = entry.flags;
+    Operator {
+        stretch_constraints: if flags.contains(Flags::STRETCHY) {
+            Some(StretchConstraints {
+                symmetric: flags.contains(Flags::SYMMETRIC),
+                ..Default::default()
+            })
+        } else {
+            None
+        },
+        is_large_op: flags.contains(Flags::LARGEOP),
+        is_fence: flags.contains(Flags::FENCE),
+        form,
+        is_separator: flags.contains(Flags::SEPARATOR),
+        leading_space: Length::em(entry.lspace as f32 / 18.0f32),
+        trailing_space: Length::em(entry.rspace as f32 / 18.0f32),
+        field: Field::Unicode(character.to_string()),
+    }
+}
+
+/// Infers the forms of the operators in a programmatically built expression the way the parser
+/// does for `mo` elements: an operator that is the first of several items of a list (ignoring
+/// spaces) is a prefix operator, the last one is a postfix operator and all others are infix
+/// operators. Embellished operators and all nested lists are processed as well.
+///
+/// Operators that have the attributes `operator_from_dictionary` gives them for their previous
+/// form get the attributes of the inferred form, so e.g. the minus sign of `-x` loses the space
+/// of a binary operator. Other operators only change their form. Like in `mo` elements a
+/// hyphen-minus is replaced by a minus sign.
+pub fn infer_operator_forms(expr: &mut MathExpression) {
+    for child in children_of_mut(&mut expr.item) {
+        infer_operator_forms(child);
+    }
+    let list = match *expr.item {
+        MathItem::List(ref mut list) => list,
+        _ => return,
+    };
+    let mut items = list
+        .iter_mut()
+        .filter(|expr| !matches!(*expr.item, MathItem::Space(_)))
+        .collect::<Vec<_>>();
+    let len = items.len();
+    for (i, expr) in items.iter_mut().enumerate() {
+        let form = if len > 1 && i == 0 {
+            Form::Prefix
+        } else if len > 1 && i == len - 1 {
+            Form::Postfix
+        } else {
+            Form::Infix
+        };
+        if let Some(operator) = core_operator_mut(expr) {
+            set_operator_form(operator, form);
+        }
+    }
+}
+
+/// Returns the core of an embellished operator built without the parser.
+fn core_operator_mut(expr: &mut MathExpression) -> Option<&mut Operator> {
+    match *expr.item {
+        MathItem::Operator(ref mut operator) => Some(operator),
+        MathItem::Atom(Atom {
+            nucleus: Some(ref mut nucleus),
+            ..
+        })
+        | MathItem::OverUnder(OverUnder {
+            nucleus: Some(ref mut nucleus),
+            ..
+        })
+        | MathItem::GeneralizedFraction(GeneralizedFraction {
+            numerator: Some(ref mut nucleus),
+            ..
+        }) => core_operator_mut(nucleus),
+        _ => None,
+    }
+}
+
+fn set_operator_form(operator: &mut Operator, form: Form) {
+    let character = match operator.field {
+        Field::Unicode(ref text) if text.chars().count() == 1 => text.chars().next().unwrap(),
+        _ => {
+            operator.form = form;
+            return;
+        }
+    };
+    let has_dictionary_attributes = *operator == operator_from_dictionary(character, operator.form);
+    let character = if character == '-' {
+        '\u{2212}' // Minus Sign
+    } else {
+        character
+    };
+    if has_dictionary_attributes {
+        *operator = operator_from_dictionary(character, form);
+    } else {
+        operator.field = Field::Unicode(character.to_string());
+        operator.form = form;
+    }
+}
+
+/// Returns whether the operator dictionary gives `character` movable limits in the given form.
+///
+/// Limits of such operators (e.g. the bounds of a sum) are drawn as scripts in inline style. The
+/// parser sets `OverUnder::is_limits` for them.
+pub fn has_movable_limits(character: char, form: Form) -> bool {
+    operator_dict::find_entry(character, form)
+        .map(|entry| entry.flags.contains(Flags::MOVABLE_LIMITS))
+        .unwrap_or(false)
+}
+
 /// Guess the default attributes of a math operator.
 ///
 /// This function will create a `MathExpression` representing an operator with the correct default
@@ -156,7 +270,7 @@ fn guess_operator_attributes(expr: &MathExpression, context: &mut ParseContext)
         None => return,
     };
 
-    let form = operator_attrs.form.expect("operator has no form");
+    let form = operator_attrs.form.unwrap_or_default();
     let entry = operator_attrs
         .character
         .and_then(|chr| operator_dict::find_entry(chr, form))
@@ -169,6 +283,10 @@ fn guess_operator_attributes(expr: &MathExpression, context: &mut ParseContext)
         operator_attrs.rspace = Some(Length::em(entry.rspace as f32 / 18.0f32));
     }
 
+    operator_attrs.linebreak_style = operator_attrs
+        .linebreak_style
+        .or(Some(entry.linebreak_style));
+
     // apply user overrides
     operator_attrs.flags = (operator_attrs.user_overrides & operator_attrs.flags)
         | (!operator_attrs.user_overrides & entry.flags);
@@ -243,23 +361,36 @@ fn make_operator(expr: &mut MathExpression, context: &mut ParseContext) {
         let stretch_constraints = if flags.contains(Flags::STRETCHY) {
             Some(StretchConstraints {
                 symmetric: flags.contains(Flags::SYMMETRIC),
-                ..Default::default()
+                min_size: operator_attrs.minsize,
+                max_size: operator_attrs.maxsize,
+                asymmetry_tolerance: None,
             })
         } else {
             None
         };
         let field = match *core_expr.item {
             MathItem::Field(ref field) => field.clone(),
-            _ => unreachable!(),
+            _ => return,
         };
         let new_elem = Operator {
             stretch_constraints,
             field,
             is_large_op: flags.contains(Flags::LARGEOP),
-            leading_space: operator_attrs.lspace.expect("operator has no lspace"),
-            trailing_space: operator_attrs.rspace.expect("operator has no rspace"),
+            is_fence: flags.contains(Flags::FENCE),
+            form: operator_attrs.form.unwrap_or_default(),
+            is_separator: flags.contains(Flags::SEPARATOR),
+            leading_space: operator_attrs.lspace.unwrap_or_default(),
+            trailing_space: operator_attrs.rspace.unwrap_or_default(),
             ..Default::default()
         };
         core_expr.item = Box::new(MathItem::Operator(new_elem));
+
+        let linebreak = LinebreakAttributes {
+            style: operator_attrs.linebreak_style.unwrap_or_default(),
+            ..operator_attrs.linebreak
+        };
+        if linebreak != LinebreakAttributes::default() {
+            *core_expr = std::mem::take(core_expr).with_linebreak(linebreak);
+        }
     }
 }
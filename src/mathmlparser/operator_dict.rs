@@ -1,5 +1,6 @@
 use std;
 use super::operator::{Form, Flags};
+use crate::types::LinebreakStyle;
 
 
 pub type Entry = _Entry<Flags>;
@@ -10,6 +11,10 @@ pub struct _Entry<T> {
     pub form: Form,
     pub lspace: u8,
     pub rspace: u8,
+    /// Orders the operators of an expression by how tightly they bind (higher values bind
+    /// tighter).
+    pub priority: u16,
+    pub linebreak_style: LinebreakStyle,
     pub flags: T,
 }
 
@@ -20,6 +25,8 @@ impl<T: Default> std::default::Default for _Entry<T> {
             form: Default::default(),
             lspace: 5,
             rspace: 5,
+            priority: 0,
+            linebreak_style: Default::default(),
             flags: Default::default(),
         }
     }
@@ -51,6 +58,8 @@ impl std::convert::From<_Entry<u8>> for Entry {
             form: entry.form,
             lspace: entry.lspace,
             rspace: entry.rspace,
+            priority: entry.priority,
+            linebreak_style: entry.linebreak_style,
             flags: Flags::from_bits(entry.flags).unwrap(),
         }
     }
@@ -64,1052 +73,8 @@ const ACCENT: u8 = 0b00010000;
 const LARGEOP: u8 = 0b00100000;
 const MOVABLE_LIMITS: u8 = 0b01000000;
 
-#[cfg_attr(rustfmt, rustfmt_skip)]
-pub static DICTIONARY: [_Entry<u8>; 1043] = [
-    _Entry { character: '\u{21}', form: Form::Postfix, lspace: 1, rspace: 0, flags: 0 },
-    _Entry { character: '\u{25}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{26}', form: Form::Postfix, lspace: 0, rspace: 0, flags: 0 },
-    _Entry { character: '\u{27}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT },
-    _Entry { character: '\u{28}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{29}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2A}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{2B}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2B}', form: Form::Prefix, lspace: 0, rspace: 1, flags: 0 },
-    _Entry { character: '\u{2C}', form: Form::Infix, lspace: 0, rspace: 3, flags: SEPARATOR },
-    _Entry { character: '\u{2D}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2D}', form: Form::Prefix, lspace: 0, rspace: 1, flags: 0 },
-    _Entry { character: '\u{2E}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{2F}', form: Form::Infix, lspace: 1, rspace: 1, flags: 0 },
-    _Entry { character: '\u{3A}', form: Form::Infix, lspace: 1, rspace: 2, flags: 0 },
-    _Entry { character: '\u{3B}', form: Form::Infix, lspace: 0, rspace: 3, flags: SEPARATOR },
-    _Entry { character: '\u{3C}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{3D}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{3E}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{3F}', form: Form::Infix, lspace: 1, rspace: 1, flags: 0 },
-    _Entry { character: '\u{40}', form: Form::Infix, lspace: 1, rspace: 1, flags: 0 },
-    _Entry { character: '\u{5B}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{5C}', form: Form::Infix, lspace: 0, rspace: 0, flags: 0 },
-    _Entry { character: '\u{5D}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{5E}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{5E}', form: Form::Infix, lspace: 1, rspace: 1, flags: 0 },
-    _Entry { character: '\u{5F}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{5F}', form: Form::Infix, lspace: 1, rspace: 1, flags: 0 },
-    _Entry { character: '\u{60}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT },
-    _Entry { character: '\u{7B}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{7C}', form: Form::Infix, lspace: 2, rspace: 2, flags: STRETCHY | SYMMETRIC | FENCE },
-    _Entry { character: '\u{7C}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{7C}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{7D}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{7E}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{A8}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT },
-    _Entry { character: '\u{AC}', form: Form::Prefix, lspace: 2, rspace: 1, flags: 0 },
-    _Entry { character: '\u{AF}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{B0}', form: Form::Postfix, lspace: 0, rspace: 0, flags: 0 },
-    _Entry { character: '\u{B1}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{B1}', form: Form::Prefix, lspace: 0, rspace: 1, flags: 0 },
-    _Entry { character: '\u{B4}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT },
-    _Entry { character: '\u{B7}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{B8}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT },
-    _Entry { character: '\u{D7}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{F7}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2C6}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{2C7}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{2C9}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{2CA}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT },
-    _Entry { character: '\u{2CB}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT },
-    _Entry { character: '\u{2CD}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{2D8}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT },
-    _Entry { character: '\u{2D9}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT },
-    _Entry { character: '\u{2DA}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT },
-    _Entry { character: '\u{2DC}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{2DD}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT },
-    _Entry { character: '\u{2F7}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{302}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{311}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT },
-    _Entry { character: '\u{3F6}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2016}', form: Form::Prefix, lspace: 0, rspace: 0, flags: FENCE | STRETCHY },
-    _Entry { character: '\u{2016}', form: Form::Postfix, lspace: 0, rspace: 0, flags: FENCE | STRETCHY },
-    _Entry { character: '\u{2018}', form: Form::Prefix, lspace: 0, rspace: 0, flags: FENCE },
-    _Entry { character: '\u{2019}', form: Form::Postfix, lspace: 0, rspace: 0, flags: FENCE },
-    _Entry { character: '\u{201C}', form: Form::Prefix, lspace: 0, rspace: 0, flags: FENCE },
-    _Entry { character: '\u{201D}', form: Form::Postfix, lspace: 0, rspace: 0, flags: FENCE },
-    _Entry { character: '\u{2022}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2026}', form: Form::Infix, lspace: 0, rspace: 0, flags: 0 },
-    _Entry { character: '\u{2032}', form: Form::Postfix, lspace: 0, rspace: 2, flags: 0 },
-    _Entry { character: '\u{203E}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{2044}', form: Form::Infix, lspace: 4, rspace: 4, flags: STRETCHY },
-    _Entry { character: '\u{2061}', form: Form::Infix, lspace: 0, rspace: 0, flags: 0 },
-    _Entry { character: '\u{2062}', form: Form::Infix, lspace: 0, rspace: 0, flags: 0 },
-    _Entry { character: '\u{2063}', form: Form::Infix, lspace: 0, rspace: 0, flags: SEPARATOR },
-    _Entry { character: '\u{2064}', form: Form::Infix, lspace: 0, rspace: 0, flags: 0 },
-    _Entry { character: '\u{20DB}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT },
-    _Entry { character: '\u{20DC}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT },
-    _Entry { character: '\u{2145}', form: Form::Prefix, lspace: 2, rspace: 1, flags: 0 },
-    _Entry { character: '\u{2146}', form: Form::Prefix, lspace: 2, rspace: 0, flags: 0 },
-    _Entry { character: '\u{2190}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{2191}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2192}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{2193}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2194}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{2195}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2196}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2197}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2198}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2199}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{219A}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{219B}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{219C}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{219D}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{219E}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{219F}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21A0}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21A1}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21A2}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21A3}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21A4}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21A5}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21A6}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21A7}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21A8}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21A9}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21AA}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21AB}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21AC}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21AD}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21AE}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{21AF}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21B0}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21B1}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21B2}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21B3}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21B4}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21B5}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21B6}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{21B7}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{21B8}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{21B9}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21BA}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{21BB}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{21BC}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21BD}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21BE}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21BF}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21C0}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21C1}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21C2}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21C3}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21C4}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21C5}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21C6}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21C7}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21C8}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21C9}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21CA}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21CB}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21CC}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21CD}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{21CE}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{21CF}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{21D0}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21D1}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21D2}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21D3}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21D4}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21D5}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21D6}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21D7}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21D8}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21D9}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21DA}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21DB}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21DC}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21DD}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21DE}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{21DF}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{21E0}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21E1}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21E2}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21E3}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21E4}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21E5}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21E6}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21E7}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21E8}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21E9}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21EA}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21EB}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21EC}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21ED}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21EE}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21EF}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21F0}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21F1}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{21F2}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{21F3}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21F4}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{21F5}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{21F6}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21F7}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{21F8}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{21F9}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{21FA}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{21FB}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{21FC}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{21FD}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21FE}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{21FF}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{2200}', form: Form::Prefix, lspace: 2, rspace: 1, flags: 0 },
-    _Entry { character: '\u{2201}', form: Form::Infix, lspace: 1, rspace: 2, flags: 0 },
-    _Entry { character: '\u{2202}', form: Form::Prefix, lspace: 2, rspace: 1, flags: 0 },
-    _Entry { character: '\u{2203}', form: Form::Prefix, lspace: 2, rspace: 1, flags: 0 },
-    _Entry { character: '\u{2204}', form: Form::Prefix, lspace: 2, rspace: 1, flags: 0 },
-    _Entry { character: '\u{2206}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{2207}', form: Form::Prefix, lspace: 2, rspace: 1, flags: 0 },
-    _Entry { character: '\u{2208}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2209}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{220A}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{220B}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{220C}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{220D}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{220E}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{220F}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2210}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2211}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2212}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2212}', form: Form::Prefix, lspace: 0, rspace: 1, flags: 0 },
-    _Entry { character: '\u{2213}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2213}', form: Form::Prefix, lspace: 0, rspace: 1, flags: 0 },
-    _Entry { character: '\u{2214}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2215}', form: Form::Infix, lspace: 4, rspace: 4, flags: STRETCHY },
-    _Entry { character: '\u{2216}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2217}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2218}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2219}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{221A}', form: Form::Prefix, lspace: 1, rspace: 1, flags: STRETCHY },
-    _Entry { character: '\u{221B}', form: Form::Prefix, lspace: 1, rspace: 1, flags: 0 },
-    _Entry { character: '\u{221C}', form: Form::Prefix, lspace: 1, rspace: 1, flags: 0 },
-    _Entry { character: '\u{221D}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{221F}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2220}', form: Form::Prefix, lspace: 0, rspace: 0, flags: 0 },
-    _Entry { character: '\u{2221}', form: Form::Prefix, lspace: 0, rspace: 0, flags: 0 },
-    _Entry { character: '\u{2222}', form: Form::Prefix, lspace: 0, rspace: 0, flags: 0 },
-    _Entry { character: '\u{2223}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2224}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2225}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2225}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2225}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2226}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2227}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2228}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2229}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{222A}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{222B}', form: Form::Prefix, lspace: 0, rspace: 1, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{222C}', form: Form::Prefix, lspace: 0, rspace: 1, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{222D}', form: Form::Prefix, lspace: 0, rspace: 1, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{222E}', form: Form::Prefix, lspace: 0, rspace: 1, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{222F}', form: Form::Prefix, lspace: 0, rspace: 1, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2230}', form: Form::Prefix, lspace: 0, rspace: 1, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2231}', form: Form::Prefix, lspace: 0, rspace: 1, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2232}', form: Form::Prefix, lspace: 0, rspace: 1, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2233}', form: Form::Prefix, lspace: 0, rspace: 1, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2234}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2235}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2236}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2237}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2238}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2239}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{223A}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{223B}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{223C}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{223D}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{223E}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{223F}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{2240}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2241}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2242}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2243}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2244}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2245}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2246}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2247}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2248}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2249}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{224A}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{224B}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{224C}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{224D}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{224E}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{224F}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2250}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2251}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2252}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2253}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2254}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2255}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2256}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2257}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2258}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2259}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{225A}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{225C}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{225D}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{225E}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{225F}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2260}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2261}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2262}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2263}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2264}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2265}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2266}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2267}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2268}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2269}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{226A}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{226B}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{226C}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{226D}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{226E}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{226F}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2270}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2271}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2272}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2273}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2274}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2275}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2276}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2277}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2278}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2279}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{227A}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{227B}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{227C}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{227D}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{227E}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{227F}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2280}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2281}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2282}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2283}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2284}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2285}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2286}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2287}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2288}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2289}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{228A}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{228B}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{228C}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{228D}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{228E}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{228F}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2290}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2291}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2292}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2293}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2294}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2295}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2296}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2297}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2298}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2299}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{229A}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{229B}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{229C}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{229D}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{229E}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{229F}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22A0}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22A1}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22A2}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22A3}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22A4}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22A5}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22A6}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22A7}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22A8}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22A9}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22AA}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22AB}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22AC}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22AD}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22AE}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22AF}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22B0}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22B1}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22B2}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22B3}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22B4}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22B5}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22B6}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22B7}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22B8}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22B9}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22BA}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22BB}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22BC}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22BD}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22BE}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{22BF}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{22C0}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{22C1}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{22C2}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{22C3}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{22C4}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22C5}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22C6}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22C7}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22C8}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22C9}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22CA}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22CB}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22CC}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22CD}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22CE}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22CF}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22D0}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22D1}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22D2}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22D3}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{22D4}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22D5}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22D6}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22D7}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22D8}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22D9}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22DA}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22DB}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22DC}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22DD}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22DE}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22DF}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22E0}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22E1}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22E2}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22E3}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22E4}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22E5}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22E6}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22E7}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22E8}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22E9}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22EA}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22EB}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22EC}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22ED}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22EE}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22EF}', form: Form::Infix, lspace: 0, rspace: 0, flags: 0 },
-    _Entry { character: '\u{22F0}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22F1}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22F2}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22F3}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22F4}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22F5}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22F6}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22F7}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22F8}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22F9}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22FA}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22FB}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22FC}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22FD}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22FE}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{22FF}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2308}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2309}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{230A}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{230B}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{23B4}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{23B5}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{23DC}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{23DD}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{23DE}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{23DF}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{23E0}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{23E1}', form: Form::Postfix, lspace: 0, rspace: 0, flags: ACCENT | STRETCHY },
-    _Entry { character: '\u{25A0}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{25A1}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{25AA}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{25AB}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{25AD}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{25AE}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{25AF}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{25B0}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{25B1}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{25B2}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25B3}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25B4}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25B5}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25B6}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25B7}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25B8}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25B9}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25BC}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25BD}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25BE}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25BF}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25C0}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25C1}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25C2}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25C3}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25C4}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25C5}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25C6}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25C7}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25C8}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25C9}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25CC}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25CD}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25CE}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25CF}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25D6}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25D7}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{25E6}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{266D}', form: Form::Postfix, lspace: 0, rspace: 2, flags: 0 },
-    _Entry { character: '\u{266E}', form: Form::Postfix, lspace: 0, rspace: 2, flags: 0 },
-    _Entry { character: '\u{266F}', form: Form::Postfix, lspace: 0, rspace: 2, flags: 0 },
-    _Entry { character: '\u{2758}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2772}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2773}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{27E6}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{27E7}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{27E8}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{27E9}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{27EA}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{27EB}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{27EC}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{27ED}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{27EE}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{27EF}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{27F0}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{27F1}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{27F5}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{27F6}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{27F7}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{27F8}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{27F9}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{27FA}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{27FB}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{27FC}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{27FD}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{27FE}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{27FF}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{2900}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2901}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2902}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2903}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2904}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2905}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2906}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2907}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2908}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2909}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{290A}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{290B}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{290C}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{290D}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{290E}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{290F}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{2910}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{2911}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2912}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2913}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2914}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2915}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2916}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2917}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2918}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2919}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{291A}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{291B}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{291C}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{291D}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{291E}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{291F}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2920}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2921}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2922}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2923}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2924}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2925}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2926}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2927}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2928}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2929}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{292A}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{292B}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{292C}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{292D}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{292E}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{292F}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2930}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2931}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2932}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2933}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2934}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2935}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2936}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2937}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2938}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2939}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{293A}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{293B}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{293C}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{293D}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{293E}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{293F}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2940}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2941}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2942}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2943}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2944}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2945}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2946}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2947}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2948}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2949}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{294A}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{294B}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{294C}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{294D}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{294E}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{294F}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2950}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{2951}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2952}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{2953}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{2954}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2955}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2956}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2957}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2958}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2959}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{295A}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{295B}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{295C}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{295D}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{295E}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{295F}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY | ACCENT },
-    _Entry { character: '\u{2960}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2961}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2962}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2963}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2964}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2965}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2966}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2967}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2968}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2969}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{296A}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{296B}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{296C}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{296D}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{296E}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{296F}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2970}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2971}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2972}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2973}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2974}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2975}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2976}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2977}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2978}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{2979}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{297A}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{297B}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{297C}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{297D}', form: Form::Infix, lspace: 5, rspace: 5, flags: ACCENT },
-    _Entry { character: '\u{297E}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{297F}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2980}', form: Form::Prefix, lspace: 0, rspace: 0, flags: FENCE | STRETCHY },
-    _Entry { character: '\u{2980}', form: Form::Postfix, lspace: 0, rspace: 0, flags: FENCE | STRETCHY },
-    _Entry { character: '\u{2981}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{2982}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{2983}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2984}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2985}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2986}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2987}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2988}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2989}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{298A}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{298B}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{298C}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{298D}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{298E}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{298F}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2990}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2991}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2992}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2993}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2994}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2995}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2996}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2997}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2998}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{2999}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{299A}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{299B}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{299C}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{299D}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{299E}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{299F}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29A0}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29A1}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29A2}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29A3}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29A4}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29A5}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29A6}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29A7}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29A8}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29A9}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29AA}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29AB}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29AC}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29AD}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29AE}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29AF}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29B0}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29B1}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29B2}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29B3}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29B4}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29B5}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29B6}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29B7}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29B8}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29B9}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29BA}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29BB}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29BC}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29BD}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29BE}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29BF}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29C0}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29C1}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29C2}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29C3}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29C4}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29C5}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29C6}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29C7}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29C8}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29C9}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29CA}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29CB}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29CC}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29CD}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29CE}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29CF}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29D0}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29D1}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29D2}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29D3}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29D4}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29D5}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29D6}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29D7}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29D8}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29D9}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29DB}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29DC}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29DD}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29DE}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29DF}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29E0}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29E1}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29E2}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29E3}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29E4}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29E5}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29E6}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29E7}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29E8}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29E9}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29EA}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29EB}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29EC}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29ED}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29EE}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29EF}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29F0}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29F1}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29F2}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29F3}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29F4}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{29F5}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29F6}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29F7}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29F8}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29F9}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29FA}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29FB}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{29FC}', form: Form::Prefix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{29FD}', form: Form::Postfix, lspace: 0, rspace: 0, flags: SYMMETRIC | FENCE | STRETCHY },
-    _Entry { character: '\u{29FE}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{29FF}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A00}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A01}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A02}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A03}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A04}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A05}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A06}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A07}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A08}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A09}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A0A}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A0B}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2A0C}', form: Form::Prefix, lspace: 0, rspace: 1, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2A0D}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2A0E}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2A0F}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2A10}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A11}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A12}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A13}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A14}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2A15}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2A16}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2A17}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2A18}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2A19}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2A1A}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2A1B}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2A1C}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP },
-    _Entry { character: '\u{2A1D}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{2A1E}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{2A1F}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{2A20}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{2A21}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{2A22}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A23}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A24}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A25}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A26}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A27}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A28}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A29}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A2A}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A2B}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A2C}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A2D}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A2E}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A2F}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A30}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A31}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A32}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A33}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A34}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A35}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A36}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A37}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A38}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A39}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A3A}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A3B}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A3C}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A3D}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A3E}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A3F}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A40}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A41}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A42}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A43}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A44}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A45}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A46}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A47}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A48}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A49}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A4A}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A4B}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A4C}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A4D}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A4E}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A4F}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A50}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A51}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A52}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A53}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A54}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A55}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A56}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A57}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A58}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A59}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A5A}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A5B}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A5C}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A5D}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A5E}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A5F}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A60}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A61}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A62}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A63}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A64}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A65}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A66}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A67}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A68}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A69}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A6A}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A6B}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A6C}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A6D}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A6E}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A6F}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A70}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A71}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A72}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2A73}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A74}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A75}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A76}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A77}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A78}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A79}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A7A}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A7B}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A7C}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A7D}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A7E}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A7F}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A80}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A81}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A82}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A83}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A84}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A85}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A86}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A87}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A88}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A89}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A8A}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A8B}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A8C}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A8D}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A8E}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A8F}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A90}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A91}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A92}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A93}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A94}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A95}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A96}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A97}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A98}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A99}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A9A}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A9B}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A9C}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A9D}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A9E}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2A9F}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AA0}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AA1}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AA2}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AA3}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AA4}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AA5}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AA6}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AA7}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AA8}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AA9}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AAA}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AAB}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AAC}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AAD}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AAE}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AAF}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AB0}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AB1}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AB2}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AB3}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AB4}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AB5}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AB6}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AB7}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AB8}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AB9}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ABA}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ABB}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ABC}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ABD}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ABE}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ABF}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AC0}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AC1}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AC2}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AC3}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AC4}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AC5}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AC6}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AC7}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AC8}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AC9}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ACA}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ACB}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ACC}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ACD}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ACE}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ACF}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AD0}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AD1}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AD2}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AD3}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AD4}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AD5}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AD6}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AD7}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AD8}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AD9}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ADA}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ADB}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ADD}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ADE}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2ADF}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AE0}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AE1}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AE2}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AE3}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AE4}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AE5}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AE6}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AE7}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AE8}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AE9}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AEA}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AEB}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AEC}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AED}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AEE}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AEF}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AF0}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AF1}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AF2}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AF3}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AF4}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2AF5}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2AF6}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2AF7}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AF8}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AF9}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AFA}', form: Form::Infix, lspace: 5, rspace: 5, flags: 0 },
-    _Entry { character: '\u{2AFB}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2AFC}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2AFD}', form: Form::Infix, lspace: 4, rspace: 4, flags: 0 },
-    _Entry { character: '\u{2AFE}', form: Form::Infix, lspace: 3, rspace: 3, flags: 0 },
-    _Entry { character: '\u{2AFF}', form: Form::Prefix, lspace: 1, rspace: 2, flags: SYMMETRIC | LARGEOP | MOVABLE_LIMITS },
-    _Entry { character: '\u{2B45}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{2B46}', form: Form::Infix, lspace: 5, rspace: 5, flags: STRETCHY },
-    _Entry { character: '\u{1EEF0}', form: Form::Prefix, lspace: 0, rspace: 0, flags: STRETCHY },
-    _Entry { character: '\u{1EEF1}', form: Form::Prefix, lspace: 0, rspace: 0, flags: STRETCHY },
-];
+// Generated by build.rs from `resources/unicode.xml`.
+include!(concat!(env!("OUT_DIR"), "/operator_dictionary.rs"));
 
 fn try_entry_at_offset(index: usize, offset: isize, requested_form: Form) -> Option<Entry> {
     if (offset >= 0 && index < (DICTIONARY.len() - offset as usize)) ||
@@ -1186,5 +151,9 @@ mod tests {
                     .unwrap()
                     .flags
                     .contains(Flags::from_bits(LARGEOP).unwrap()));
+        assert_eq!(find_entry(',', Form::Infix).unwrap().linebreak_style,
+                   LinebreakStyle::After);
+        assert!(find_entry('+', Form::Infix).unwrap().priority >
+                find_entry('=', Form::Infix).unwrap().priority);
     }
 }
@@ -0,0 +1,127 @@
+// Semantic spacing inserts the thin spaces that TeX would add around differentials and function
+// applications but that MathML authors (and converters) usually leave out.
+
+use super::{MathmlInfo, ParseContext};
+use crate::types::{
+    Atom, Field, Length, MathExpression, MathItem, MathSpace, Operator, OverUnder,
+};
+
+/// A thin space (3/18 em) like TeX's `\,`. It gets its own user data and is recorded as a space
+/// like the spaces in the document.
+fn thin_space(context: &mut ParseContext) -> MathExpression {
+    let space = MathExpression::new(
+        MathItem::Space(MathSpace::horizontal_space(Length::em(3.0 / 18.0))),
+        context.allocate_node_id().user_data(),
+    );
+    context.insert_info(
+        &space,
+        MathmlInfo {
+            operator_attrs: None,
+            is_space: true,
+        },
+    );
+    space
+}
+
+fn element_name(expr: &MathExpression) -> Option<&str> {
+    expr.element().map(|element| element.name.as_str())
+}
+
+fn token_text(expr: &MathExpression) -> Option<&str> {
+    match *expr.item {
+        MathItem::Field(Field::Unicode(ref text))
+        | MathItem::Operator(Operator {
+            field: Field::Unicode(ref text),
+            ..
+        }) => Some(text),
+        _ => None,
+    }
+}
+
+/// Returns true for integral signs and for scripted integral signs.
+fn is_integral(expr: &MathExpression) -> bool {
+    match *expr.item {
+        MathItem::Atom(Atom {
+            nucleus: Some(ref nucleus),
+            ..
+        })
+        | MathItem::OverUnder(OverUnder {
+            nucleus: Some(ref nucleus),
+            ..
+        }) => is_integral(nucleus),
+        _ => {
+            is_operator(expr)
+                && token_text(expr).map_or(false, |text| {
+                    text.chars().any(|chr| match chr {
+                        '\u{222B}'..='\u{2233}' | '\u{2A0B}'..='\u{2A1C}' => true,
+                        _ => false,
+                    })
+                })
+        }
+    }
+}
+
+/// Returns true for the `d` of a differential like `dx`.
+fn is_differential(expr: &MathExpression) -> bool {
+    match token_text(expr) {
+        Some("d") | Some("\u{1D451}") | Some("\u{2146}") => true,
+        _ => false,
+    }
+}
+
+/// Function names like `sin` are identifiers of more than one character.
+fn is_function_name(expr: &MathExpression) -> bool {
+    element_name(expr) == Some("mi")
+        && token_text(expr).map_or(false, |text| text.chars().nth(1).is_some())
+}
+
+fn is_function_application(expr: &MathExpression) -> bool {
+    is_operator(expr) && token_text(expr) == Some("\u{2061}")
+}
+
+fn is_operator(expr: &MathExpression) -> bool {
+    match *expr.item {
+        MathItem::Operator(_) => true,
+        _ => element_name(expr) == Some("mo"),
+    }
+}
+
+/// Returns true if the argument of a function is a parenthesized group that is not part of the
+/// surrounding list (TeX's `\left( ... \right)`).
+fn is_fenced_group(expr: &MathExpression) -> bool {
+    match *expr.item {
+        MathItem::List(ref list) => list.first().map_or(false, is_operator),
+        _ => false,
+    }
+}
+
+/// Inserts thin spaces into a list of expressions
+///
+/// - before the `d` of a differential that follows an integral in the same list,
+/// - between a function name and its argument unless the argument starts with an operator
+///   (like an opening parenthesis) that belongs to the same list. Just like in TeX `\sin x` and
+///   `\sin\left(x\right)` get a space but `\sin(x)` doesn't.
+pub fn insert_semantic_spaces(list: &mut Vec<MathExpression>, context: &mut ParseContext) {
+    let mut result = Vec::with_capacity(list.len());
+    let mut after_integral = false;
+    let mut after_function_name = false;
+    for expr in list.drain(..) {
+        if is_function_application(&expr) {
+            // the invisible function application operator belongs to the function name
+            result.push(expr);
+            continue;
+        }
+        if after_function_name && (!is_operator(&expr) || is_fenced_group(&expr)) {
+            result.push(thin_space(context));
+        } else if after_integral && is_differential(&expr) && !result.is_empty() {
+            let previous_is_integral = result.last().map_or(false, is_integral);
+            if !previous_is_integral {
+                result.push(thin_space(context));
+            }
+        }
+        after_integral = after_integral || is_integral(&expr);
+        after_function_name = is_function_name(&expr);
+        result.push(expr);
+    }
+    *list = result;
+}
@@ -8,14 +8,10 @@ use super::{
 };
 
 
-use crate::types::{Field, Length, MathExpression, MathItem, MathSpace};
-use crate::unicode_math::{convert_character_to_family, Family};
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub enum TextDirection {
-    Ltr,
-    Rtl,
-}
+use crate::types::{
+    Field, Length, LinebreakAttributes, MathExpression, MathItem, MathSpace, TextDirection,
+};
+use crate::unicode_math::{convert_character_to_family, convert_str_to_family, Family};
 
 impl FromXmlAttribute for TextDirection {
     type Err = ();
@@ -28,12 +24,6 @@ impl FromXmlAttribute for TextDirection {
     }
 }
 
-impl std::default::Default for TextDirection {
-    fn default() -> TextDirection {
-        TextDirection::Ltr
-    }
-}
-
 impl FromXmlAttribute for Family {
     type Err = ();
     fn from_xml_attr(bytes: &str) -> std::result::Result<Self, Self::Err> {
@@ -63,31 +53,28 @@ pub struct TokenStyle {
     // a single glyph or multiple glyphs. A single glyph is laid out in italic style. Multiple
     // glyphs would be layed out in normal style.
     pub math_variant: Option<Family>,
-    // TODO: missing math_size
+    // `mathsize` stays in the element info and is applied during layout like `dir`.
     pub direction: TextDirection,
 }
 
-pub trait StringExtMathml {
+pub(crate) trait StringExtMathml {
     fn adapt_to_family(&self, family: Option<Family>) -> Cow<str>;
     fn replace_anomalous_characters(&self, elem: MathmlElement) -> String;
 }
 
 impl StringExtMathml for str {
     fn adapt_to_family(&self, family: Option<Family>) -> Cow<str> {
-        if family.is_none() {
-            if self.chars().count() == 1 {
-                let conv =
-                    convert_character_to_family(self.chars().next().unwrap(), Family::Italics);
-                conv.to_string().into()
-            } else {
-                self.into()
+        match family {
+            Some(family) => convert_str_to_family(self, family).into(),
+            None => {
+                let mut chars = self.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(chr), None) => convert_character_to_family(chr, Family::Italics)
+                        .to_string()
+                        .into(),
+                    _ => self.into(),
+                }
             }
-        } else {
-            let family = family.unwrap();
-            self.chars()
-                .map(|chr| convert_character_to_family(chr, family))
-                .collect::<String>()
-                .into()
         }
     }
 
@@ -125,25 +112,30 @@ pub struct Attributes {
     pub operator_attributes: operator::Attributes,
     pub token_style: TokenStyle,
     pub horizontal_space: Option<Length>,
+    /// The line break attributes of an `mspace`. Those of an `mo` are operator attributes.
+    pub linebreak: LinebreakAttributes,
 }
 
 pub fn build_token<'a>(
-    fields: impl Iterator<Item = (Field, u64)>,
+    fields: impl Iterator<Item = MathExpression>,
     elem: MathmlElement,
     mut attributes: Attributes,
     context: &mut ParseContext,
     user_data: u64,
 ) -> Result<MathExpression, ParsingError> {
     if let Some(width) = attributes.horizontal_space {
-        let item = MathExpression::new(
+        let mut item = MathExpression::new(
             MathItem::Space(MathSpace::horizontal_space(width)),
             user_data,
         );
-        context.mathml_info.insert(
-            user_data,
+        if attributes.linebreak != LinebreakAttributes::default() {
+            item = item.with_linebreak(attributes.linebreak);
+        }
+        context.insert_info(
+            &item,
             MathmlInfo {
                 operator_attrs: None,
-                ..Default::default()
+                is_space: true,
             },
         );
         return Ok(item);
@@ -151,12 +143,12 @@ pub fn build_token<'a>(
 
     let mut list = vec![];
     let mut first_field_char = None;
-    for (field_num, field) in fields.enumerate() {
-        let (field, field_user_data) = field;
+    for (field_num, expr) in fields.enumerate() {
         if field_num == 0 {
-            first_field_char = try_extract_char(&field);
+            if let MathItem::Field(ref field) = *expr.item {
+                first_field_char = try_extract_char(field);
+            }
         }
-        let expr = MathExpression::new(MathItem::Field(field), field_user_data);
         list.push(expr);
     }
 
@@ -169,8 +161,8 @@ pub fn build_token<'a>(
         MathExpression::new(MathItem::List(list), user_data)
     };
 
-    context.mathml_info.insert(
-        expr.get_user_data(),
+    context.insert_info(
+        &expr,
         MathmlInfo {
             operator_attrs: if elem.is("mo") {
                 Some(attributes.operator_attributes)
@@ -190,11 +182,11 @@ mod tests {
     use super::*;
     use crate::mathmlparser::{match_math_element, xml_reader::parse_token_contents};
 
-    use quick_xml::{Event, XmlReader};
+    use quick_xml::{events::Event, NsReader};
 
     // fn test_operator_flag_parse(attr_name: &str, flag: operator::Flags) {
     //     let xml = format!("<mo {}=\"true\">a</mo>", attr_name);
-    //     let mut parser = XmlReader::from(&xml as &str).trim_text(true);
+    //     let mut parser = NsReader::from_str(&xml);
 
     //     let elem = match parser.next().unwrap().unwrap() {
     //         Event::Start(elem) => elem,
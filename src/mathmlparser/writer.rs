@@ -0,0 +1,571 @@
+// Serializes expressions back to presentation MathML.
+//
+// Operators only get the attributes that differ from what the parser would derive from the
+// operator dictionary, so the output of parsing and writing a document stays close to the input.
+
+use std::fmt::Write;
+
+use crate::types::{
+    Atom, Field, Form, Hyperlink, ImageReference, IndentAlign, Length, LengthUnit, Linebreak,
+    LinebreakAttributes, LinebreakStyle, MathExpression, MathItem, MathSpace, Operator, OverUnder,
+    Root, Strike, TextRunItem,
+};
+
+use super::operator::Flags;
+use super::operator_dict;
+
+/// Returns the presentation MathML markup for the expression.
+///
+/// Lists become `mrow` elements (or a `math` element if they were parsed from one). The element
+/// names of tokens are taken from the element the expression was parsed from if possible.
+pub fn to_mathml(expr: &MathExpression) -> String {
+    let mut output = String::new();
+    write_expression(&mut output, expr, Form::Infix);
+    output
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for chr in text.chars() {
+        match chr {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(chr),
+        }
+    }
+    escaped
+}
+
+fn format_length(length: Length) -> Option<String> {
+    match length.unit {
+        LengthUnit::Em => Some(format!("{}em", length.value)),
+        LengthUnit::Point => Some(format!("{}pt", length.value)),
+        LengthUnit::OperatorDefaultSize => Some(format!("{}%", length.value * 100.0)),
+        LengthUnit::DisplayOperatorMinHeight => None,
+    }
+}
+
+fn element_name(expr: &MathExpression) -> Option<&str> {
+    expr.element().map(|element| element.name.as_str())
+}
+
+fn start_tag(
+    output: &mut String,
+    name: &str,
+    expr: &MathExpression,
+    attributes: &[(&str, String)],
+) {
+    output.push('<');
+    output.push_str(name);
+    let href = expr
+        .payload()
+        .and_then(|payload| payload.downcast_ref::<Hyperlink>())
+        .map(|link| ("href", link.href.clone()));
+    for &(name, ref value) in attributes.iter().chain(href.as_ref()) {
+        let _ = write!(output, " {}=\"{}\"", name, escape(value));
+    }
+    output.push('>');
+}
+
+fn end_tag(output: &mut String, name: &str) {
+    let _ = write!(output, "</{}>", name);
+}
+
+fn write_element(
+    output: &mut String,
+    name: &str,
+    expr: &MathExpression,
+    attributes: &[(&str, String)],
+    children: &[(Option<&MathExpression>, Form)],
+) {
+    start_tag(output, name, expr, attributes);
+    for &(child, form) in children {
+        match child {
+            Some(child) => write_expression(output, child, form),
+            None => output.push_str("<mrow></mrow>"),
+        }
+    }
+    end_tag(output, name);
+}
+
+fn write_field(output: &mut String, field: &Field) {
+    match *field {
+        Field::Empty => {}
+        Field::Unicode(ref text) => output.push_str(&escape(text)),
+        Field::Glyph(ref glyph) => {
+            let _ = write!(output, "<mglyph index=\"{}\"/>", glyph.glyph_code);
+        }
+    }
+}
+
+fn space_attributes(size: &MathSpace) -> Vec<(&'static str, String)> {
+    [
+        ("width", size.width),
+        ("height", size.ascent),
+        ("depth", size.descent),
+    ]
+    .iter()
+    .filter(|&&(_, length)| !length.is_null())
+    .filter_map(|&(name, length)| format_length(length).map(|value| (name, value)))
+    .collect()
+}
+
+/// Returns the value of the `notation` attribute of an `menclose` with the given strikes.
+fn strike_notation(strikes: &[Strike]) -> String {
+    strikes
+        .iter()
+        .map(|strike| match *strike {
+            Strike::Horizontal => "horizontalstrike",
+            Strike::Vertical => "verticalstrike",
+            Strike::UpDiagonal => "updiagonalstrike",
+            Strike::DownDiagonal => "downdiagonalstrike",
+            Strike::UpDiagonalArrow => "updiagonalarrow",
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The form the parser assumes for the element at `index` of a list with `len` elements.
+fn form_in_list(index: usize, len: usize) -> Form {
+    if len > 1 && index == 0 {
+        Form::Prefix
+    } else if len > 1 && index == len - 1 {
+        Form::Postfix
+    } else {
+        Form::Infix
+    }
+}
+
+/// Writes an expression. `form` is the form the parser would assume for an operator at the
+/// position of the expression.
+fn write_expression(output: &mut String, expr: &MathExpression, form: Form) {
+    match *expr.item() {
+        MathItem::Field(Field::Empty) => write_element(output, "mrow", expr, &[], &[]),
+        MathItem::Field(ref field) => {
+            let name = match element_name(expr) {
+                Some(name) if ["mi", "mn", "mtext", "ms"].contains(&name) => name,
+                _ => match *field {
+                    Field::Unicode(ref text) if text.chars().all(|chr| chr.is_ascii_digit()) => {
+                        "mn"
+                    }
+                    _ => "mi",
+                },
+            };
+            start_tag(output, name, expr, &[]);
+            write_field(output, field);
+            end_tag(output, name);
+        }
+        MathItem::Space(ref space) => {
+            let mut attributes = space_attributes(space);
+            if let Some(linebreak) = expr.linebreak() {
+                attributes.extend(linebreak_attributes(linebreak, linebreak.style));
+            }
+            write_element(output, "mspace", expr, &attributes, &[])
+        }
+        MathItem::Operator(ref operator) => {
+            let attributes = operator_attributes(operator, form, expr.linebreak());
+            start_tag(output, "mo", expr, &attributes);
+            write_field(output, &operator.field);
+            end_tag(output, "mo");
+        }
+        MathItem::List(ref list) => {
+            let notation = expr
+                .decoration()
+                .filter(|decoration| !decoration.strikes.is_empty())
+                .map(|decoration| strike_notation(&decoration.strikes));
+            let name = match element_name(expr) {
+                _ if notation.is_some() => "menclose",
+                Some("math") => "math",
+                _ => "mrow",
+            };
+            // spaces are ignored when the parser determines the form of operators
+            let len = list.iter().filter(|expr| !is_space(expr)).count();
+            let mut index = 0;
+            let children = list
+                .iter()
+                .map(|child| {
+                    if is_space(child) {
+                        return (Some(child), Form::Infix);
+                    }
+                    index += 1;
+                    (Some(child), form_in_list(index - 1, len))
+                })
+                .collect::<Vec<_>>();
+            let attributes = notation
+                .map(|notation| vec![("notation", notation)])
+                .unwrap_or_default();
+            write_element(output, name, expr, &attributes, &children)
+        }
+        MathItem::Atom(ref atom) => write_atom(output, expr, atom, form),
+        MathItem::OverUnder(ref over_under) => write_over_under(output, expr, over_under, form),
+        MathItem::GeneralizedFraction(ref frac) => write_element(
+            output,
+            "mfrac",
+            expr,
+            &frac
+                .rule_thickness()
+                .and_then(format_length)
+                .map(|value| vec![("linethickness", value)])
+                .unwrap_or_default(),
+            &[
+                (frac.numerator.as_ref(), form),
+                (frac.denominator.as_ref(), Form::Infix),
+            ],
+        ),
+        MathItem::Root(Root {
+            ref radicand,
+            degree: None,
+        }) => write_element(output, "msqrt", expr, &[], &[(radicand.as_ref(), Form::Infix)]),
+        MathItem::Root(Root {
+            ref radicand,
+            ref degree,
+        }) => write_element(
+            output,
+            "mroot",
+            expr,
+            &[],
+            &[
+                (radicand.as_ref(), Form::Infix),
+                (degree.as_ref(), Form::Infix),
+            ],
+        ),
+        MathItem::Foreign(ref foreign) => {
+            let image = foreign.payload.downcast_ref::<ImageReference>();
+            match image {
+                Some(image) => {
+                    let mut attributes = vec![("src", image.src.clone())];
+                    if let Some(ref alt) = image.alt {
+                        attributes.push(("alt", alt.clone()));
+                    }
+                    // the depth of the image is lost as there is no attribute for it
+                    let size = [("width", foreign.size.width), ("height", foreign.size.ascent)];
+                    for &(name, length) in size.iter() {
+                        attributes.extend(format_length(length).map(|value| (name, value)));
+                    }
+                    start_tag(output, "mi", expr, &[]);
+                    output.push_str("<mglyph");
+                    for (name, value) in attributes {
+                        let _ = write!(output, " {}=\"{}\"", name, escape(&value));
+                    }
+                    output.push_str("/>");
+                    end_tag(output, "mi");
+                }
+                // other objects can't be represented in MathML, so only their space is kept
+                None => {
+                    let attributes = space_attributes(&foreign.size);
+                    write_element(output, "mspace", expr, &attributes, &[])
+                }
+            }
+        }
+        MathItem::TextRun(ref run) => {
+            start_tag(output, "mrow", expr, &[]);
+            for item in &run.items {
+                match *item {
+                    TextRunItem::Text(ref text) => {
+                        let _ = write!(output, "<mtext>{}</mtext>", escape(text));
+                    }
+                    TextRunItem::Math(ref expr) => write_expression(output, expr, Form::Infix),
+                }
+            }
+            end_tag(output, "mrow");
+        }
+        MathItem::Other(_) => write_element(output, "mrow", expr, &[], &[]),
+    }
+}
+
+fn is_space(expr: &MathExpression) -> bool {
+    match *expr.item() {
+        MathItem::Space(_) => true,
+        _ => false,
+    }
+}
+
+fn write_atom(output: &mut String, expr: &MathExpression, atom: &Atom, form: Form) {
+    let nucleus = (atom.nucleus.as_ref(), form);
+    let (sub, sup) = (atom.bottom_right.as_ref(), atom.top_right.as_ref());
+    if atom.top_left.is_some() || atom.bottom_left.is_some() {
+        fn write_optional(output: &mut String, expr: Option<&MathExpression>) {
+            match expr {
+                Some(expr) => write_expression(output, expr, Form::Infix),
+                None => output.push_str("<none/>"),
+            }
+        }
+        start_tag(output, "mmultiscripts", expr, &[]);
+        write_optional(output, atom.nucleus.as_ref());
+        if sub.is_some() || sup.is_some() {
+            write_optional(output, sub);
+            write_optional(output, sup);
+        }
+        output.push_str("<mprescripts/>");
+        write_optional(output, atom.bottom_left.as_ref());
+        write_optional(output, atom.top_left.as_ref());
+        end_tag(output, "mmultiscripts");
+        return;
+    }
+    match (sub, sup) {
+        (Some(sub), Some(sup)) => write_element(
+            output,
+            "msubsup",
+            expr,
+            &[],
+            &[nucleus, (Some(sub), Form::Infix), (Some(sup), Form::Infix)],
+        ),
+        (Some(sub), None) => {
+            write_element(output, "msub", expr, &[], &[nucleus, (Some(sub), Form::Infix)])
+        }
+        (None, Some(sup)) => {
+            write_element(output, "msup", expr, &[], &[nucleus, (Some(sup), Form::Infix)])
+        }
+        (None, None) => match atom.nucleus {
+            Some(ref nucleus) => write_expression(output, nucleus, form),
+            None => write_element(output, "mrow", expr, &[], &[]),
+        },
+    }
+}
+
+fn write_over_under(
+    output: &mut String,
+    expr: &MathExpression,
+    over_under: &OverUnder,
+    form: Form,
+) {
+    use crate::types::LimitsPlacement;
+
+    let mut attributes = Vec::new();
+    if over_under.over.is_some() && over_under.over_is_accent {
+        attributes.push(("accent", "true".to_string()));
+    }
+    if over_under.under.is_some() && over_under.under_is_accent {
+        attributes.push(("accentunder", "true".to_string()));
+    }
+    match over_under.limits_placement {
+        LimitsPlacement::Auto => {}
+        LimitsPlacement::Limits => attributes.push(("limits", "true".to_string())),
+        LimitsPlacement::Scripts => attributes.push(("limits", "false".to_string())),
+    }
+    let nucleus = (over_under.nucleus.as_ref(), form);
+    match (over_under.under.as_ref(), over_under.over.as_ref()) {
+        (Some(under), Some(over)) => write_element(
+            output,
+            "munderover",
+            expr,
+            &attributes,
+            &[nucleus, (Some(under), Form::Infix), (Some(over), Form::Infix)],
+        ),
+        (Some(under), None) => write_element(
+            output,
+            "munder",
+            expr,
+            &attributes,
+            &[nucleus, (Some(under), Form::Infix)],
+        ),
+        (None, Some(over)) => write_element(
+            output,
+            "mover",
+            expr,
+            &attributes,
+            &[nucleus, (Some(over), Form::Infix)],
+        ),
+        (None, None) => match over_under.nucleus {
+            Some(ref nucleus) => write_expression(output, nucleus, form),
+            None => write_element(output, "mrow", expr, &[], &[]),
+        },
+    }
+}
+
+/// Returns the line break attributes that differ from the defaults. `default_style` is the
+/// `linebreakstyle` the parser would assume.
+fn linebreak_attributes(
+    linebreak: &LinebreakAttributes,
+    default_style: LinebreakStyle,
+) -> Vec<(&'static str, String)> {
+    let mut attributes = Vec::new();
+    let value = match linebreak.linebreak {
+        Linebreak::Auto => None,
+        Linebreak::Newline => Some("newline"),
+        Linebreak::NoBreak => Some("nobreak"),
+        Linebreak::GoodBreak => Some("goodbreak"),
+        Linebreak::BadBreak => Some("badbreak"),
+    };
+    attributes.extend(value.map(|value| ("linebreak", value.to_string())));
+    if linebreak.style != default_style {
+        let style = match linebreak.style {
+            LinebreakStyle::Before => "before",
+            LinebreakStyle::After => "after",
+            LinebreakStyle::Duplicate => "duplicate",
+        };
+        attributes.push(("linebreakstyle", style.to_string()));
+    }
+    let indent_align = match linebreak.indent_align {
+        IndentAlign::Auto => None,
+        IndentAlign::Left => Some("left"),
+        IndentAlign::Center => Some("center"),
+        IndentAlign::Right => Some("right"),
+    };
+    attributes.extend(indent_align.map(|value| ("indentalign", value.to_string())));
+    attributes.extend(
+        linebreak
+            .indent_shift
+            .and_then(format_length)
+            .map(|value| ("indentshift", value)),
+    );
+    attributes
+}
+
+/// Returns the attributes of an operator that differ from the defaults the parser would use for
+/// an operator of the given form.
+fn operator_attributes(
+    operator: &Operator,
+    form: Form,
+    linebreak: Option<&LinebreakAttributes>,
+) -> Vec<(&'static str, String)> {
+    let character = match operator.field {
+        Field::Unicode(ref text) => text.chars().next(),
+        _ => None,
+    };
+    let mut attributes = Vec::new();
+    if operator.form != form {
+        let form = match operator.form {
+            Form::Prefix => "prefix",
+            Form::Infix => "infix",
+            Form::Postfix => "postfix",
+        };
+        attributes.push(("form", form.to_string()));
+    }
+    let entry = character
+        .and_then(|chr| operator_dict::find_entry(chr, operator.form))
+        .unwrap_or_default();
+
+    let mut push_flag = |name, flag, value: bool| {
+        if entry.flags.contains(flag) != value {
+            attributes.push((name, value.to_string()));
+        }
+    };
+    push_flag("stretchy", Flags::STRETCHY, operator.stretch_constraints.is_some());
+    if let Some(constraints) = operator.stretch_constraints {
+        push_flag("symmetric", Flags::SYMMETRIC, constraints.symmetric);
+    }
+    push_flag("largeop", Flags::LARGEOP, operator.is_large_op);
+    push_flag("fence", Flags::FENCE, operator.is_fence);
+    push_flag("separator", Flags::SEPARATOR, operator.is_separator);
+
+    let spaces = [
+        ("lspace", operator.leading_space, entry.lspace),
+        ("rspace", operator.trailing_space, entry.rspace),
+    ];
+    for &(name, space, default) in spaces.iter() {
+        if space != Length::em(default as f32 / 18.0f32) {
+            attributes.extend(format_length(space).map(|value| (name, value)));
+        }
+    }
+    if let Some(constraints) = operator.stretch_constraints {
+        let sizes = [("minsize", constraints.min_size), ("maxsize", constraints.max_size)];
+        for &(name, size) in sizes.iter() {
+            attributes.extend(size.and_then(format_length).map(|value| (name, value)));
+        }
+    }
+    if let Some(linebreak) = linebreak {
+        attributes.extend(linebreak_attributes(linebreak, entry.linebreak_style));
+    }
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mathmlparser::operator_from_dictionary;
+    use crate::types::{ElementInfo, GeneralizedFraction};
+
+    fn token(name: &str, text: &str, user_data: u64) -> MathExpression {
+        MathExpression::new(MathItem::Field(Field::Unicode(text.to_string())), user_data)
+            .with_element(ElementInfo::new(name))
+    }
+
+    fn operator(text: &str, form: Form) -> Operator {
+        operator_from_dictionary(text.chars().next().unwrap(), form)
+    }
+
+    #[test]
+    fn test_write_schemata() {
+        let fraction = GeneralizedFraction {
+            numerator: Some(token("mi", "a", 2)),
+            denominator: Some(token("mn", "2", 3)),
+            thickness: None,
+        };
+        let atom = Atom {
+            nucleus: Some(token("mi", "x", 5)),
+            bottom_right: Some(token("mn", "1", 6)),
+            ..Default::default()
+        };
+        let root = Root {
+            radicand: Some(token("mi", "y", 8)),
+            degree: None,
+        };
+        let list = vec![
+            MathExpression::new(MathItem::GeneralizedFraction(fraction), 1),
+            MathExpression::new(MathItem::Atom(atom), 4),
+            MathExpression::new(MathItem::Root(root), 7),
+            token("mtext", "a<b", 9),
+        ];
+        let expr = MathExpression::new(MathItem::List(list), 0);
+        assert_eq!(
+            to_mathml(&expr),
+            "<mrow><mfrac><mi>a</mi><mn>2</mn></mfrac><msub><mi>x</mi><mn>1</mn></msub>\
+             <msqrt><mi>y</mi></msqrt><mtext>a&lt;b</mtext></mrow>"
+        );
+
+        let (top, bottom) = (token("mi", "n", 1), token("mi", "k", 2));
+        let stack = GeneralizedFraction::with_thickness(top, bottom, Length::default(), 3);
+        let expr = MathExpression::new(MathItem::GeneralizedFraction(stack), 0);
+        assert_eq!(
+            to_mathml(&expr),
+            "<mfrac linethickness=\"0pt\"><mi>n</mi><mi>k</mi></mfrac>"
+        );
+    }
+
+    #[test]
+    fn test_write_operator_attributes() {
+        let list = vec![
+            MathExpression::new(MathItem::Operator(operator("(", Form::Prefix)), 1),
+            token("mi", "x", 2),
+            MathExpression::new(MathItem::Operator(operator("+", Form::Infix)), 3),
+            MathExpression::new(
+                MathItem::Operator(Operator {
+                    stretch_constraints: None,
+                    ..operator(")", Form::Postfix)
+                }),
+                4,
+            ),
+        ];
+        let expr = MathExpression::new(MathItem::List(list), 0);
+        assert_eq!(
+            to_mathml(&expr),
+            "<mrow><mo>(</mo><mi>x</mi><mo>+</mo><mo stretchy=\"false\">)</mo></mrow>"
+        );
+
+        let plus = Operator {
+            leading_space: Length::em(1.0),
+            ..operator("+", Form::Infix)
+        };
+        let expr = MathExpression::new(MathItem::Operator(plus), 0);
+        assert_eq!(to_mathml(&expr), "<mo lspace=\"1em\">+</mo>");
+
+        let prefix_plus = operator("+", Form::Prefix);
+        let expr = MathExpression::new(MathItem::Operator(prefix_plus), 0);
+        assert_eq!(to_mathml(&expr), "<mo form=\"prefix\">+</mo>");
+    }
+
+    #[test]
+    #[cfg(feature = "mathml_parser")]
+    fn test_round_trip() {
+        use super::super::parse;
+
+        let xml = "<munderover limits=\"false\"><mi>a</mi><mn>0</mn><mi>b</mi></munderover>";
+        let expr = parse(xml.as_bytes()).unwrap();
+        let written = to_mathml(&expr);
+        assert_eq!(to_mathml(&parse(written.as_bytes()).unwrap()), written);
+        assert!(written.starts_with("<munderover limits=\"false\">"));
+    }
+}
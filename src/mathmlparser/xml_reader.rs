@@ -1,45 +1,98 @@
 use super::error::{ErrorType, ParsingError, Result};
 use super::{
-    escape::StringExtUnescape, match_math_element, operator, parse_fixed_schema, parse_list_schema,
-    token, ArgumentRequirements, AttributeParse, ElementType, MathmlElement, ParseContext,
-    SchemaAttributes, StringExtMathml,
+    escape, match_math_element, operator, parse_fixed_schema, parse_list_schema, spacing, token,
+    token::StringExtMathml, ArgumentRequirements, AttributeParse, ElementType, MathmlElement,
+    ParseContext, ParserOptions, SchemaAttributes, Whitespace,
 };
 
-use crate::{unicode_math::Family, Field, Length, MathExpression};
-pub use quick_xml::error::ResultPos;
-pub use quick_xml::{Element, Event, XmlReader};
+use crate::{
+    unicode_math::Family, ElementInfo, Field, ForeignObject, Glyph, GlyphCode, Hyperlink, ImageReference,
+    Length, LengthUnit, LimitsPlacement, LinebreakAttributes, MathExpression, MathItem, MathSpace,
+    Strike,
+};
+use quick_xml::events::attributes::{AttrError, Attribute};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::{QName, ResolveResult};
+use quick_xml::NsReader;
 use std::io::BufRead;
+use std::sync::Arc;
+
+const MATHML_NAMESPACE: &[u8] = b"http://www.w3.org/1998/Math/MathML";
 
 pub fn parse<R: BufRead>(file: R) -> Result<MathExpression> {
-    let mut parser = XmlReader::from_reader(file).trim_text(true);
+    parse_with_options(file, ParserOptions::default())
+}
+
+/// Parses a MathML document like `parse` with the given options.
+pub fn parse_with_options<R: BufRead>(file: R, options: ParserOptions) -> Result<MathExpression> {
+    parse_with_context(file, options).map(|(expr, _)| expr)
+}
+
+/// Parses a MathML document like `parse_with_options` and additionally returns what the parser
+/// found out about the elements, e.g. which elements are operators or spaces. The information
+/// for an expression is looked up by its user data (see `ParseContext::info_for_expr`).
+pub fn parse_with_context<R: BufRead>(
+    file: R,
+    options: ParserOptions,
+) -> Result<(MathExpression, ParseContext)> {
+    // whitespace is handled by the token elements (see `Whitespace`)
+    let mut parser = NsReader::from_reader(file);
+    parser.expand_empty_elements(true);
     let root_elem = MathmlElement {
         identifier: "ROOT_ELEMENT", // this identifier is arbitrary and should not be used elsewhere
         elem_type: ElementType::MathmlRoot,
     };
-    let mut context = ParseContext::default();
+    let mut context = ParseContext {
+        options,
+        ..Default::default()
+    };
 
-    parse_element(&mut parser, root_elem, std::iter::empty(), &mut context)
+    let expr = parse_element(&mut parser, root_elem, std::iter::empty(), &mut context)?;
+    Ok((expr, context))
+}
+
+/// Parses a MathML document from arbitrary bytes.
+///
+/// Unlike `parse` this accepts input that is not valid UTF-8 (invalid sequences are replaced by
+/// U+FFFD) and it is meant to be fed untrusted input: malformed documents are reported as a
+/// `ParsingError` and never cause a panic.
+pub fn parse_lossy(bytes: &[u8]) -> Result<MathExpression> {
+    let text = String::from_utf8_lossy(bytes);
+    parse(text.as_bytes())
 }
 
 pub fn parse_element<'a, R: BufRead, A>(
-    parser: &mut XmlReader<R>,
+    parser: &mut NsReader<R>,
     elem: MathmlElement,
     attributes: A,
     context: &mut ParseContext,
 ) -> Result<MathExpression>
 where
-    A: Iterator<Item = ResultPos<(&'a [u8], &'a [u8])>>,
+    A: Iterator<Item = std::result::Result<Attribute<'a>, AttrError>>,
 {
-    let attrs = attributes.filter_map(|res| {
-        res.ok()
-            .and_then(|(a, b)| Some((std::str::from_utf8(a).ok()?, std::str::from_utf8(b).ok()?)))
-    });
-    let user_data = context.mathml_info.len() as u64;
-    match elem.elem_type {
+    let element_info = ElementInfo {
+        name: elem.identifier.to_string(),
+        attributes: attributes
+            .filter_map(|attr| attr.ok())
+            // namespace declarations are not attributes of the element
+            .filter(|attr| attr.key.as_namespace_binding().is_none())
+            .filter_map(|attr| {
+                let name = std::str::from_utf8(attr.key.as_ref()).ok()?;
+                Some((name.to_string(), attribute_value(&attr).ok()?))
+            })
+            .collect(),
+    };
+    let attrs = element_info
+        .attributes
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()));
+    let user_data = context.allocate_node_id().user_data();
+    let expr: Result<MathExpression> = match elem.elem_type {
         ElementType::TokenElement => {
             let mut token_style = token::TokenStyle::default();
             let mut op_attrs = operator::Attributes::default();
             let mut space = None;
+            let mut linebreak = LinebreakAttributes::default();
             attrs
                 .filter(|attr| !parse_token_attribute(&mut token_style, elem.identifier, &attr))
                 .filter(|attr| {
@@ -50,14 +103,21 @@ where
                     }
                 })
                 .filter(|attr| !parse_mspace_attribute(&mut space, elem.identifier, &attr))
+                .filter(|attr| !parse_linebreak_attribute(&mut linebreak, elem.identifier, &attr))
                 .fold((), |_, _| {});
+            op_attrs.linebreak = linebreak;
+            // an `mspace` that only marks a break point has no width
+            if elem.is("mspace") && linebreak != LinebreakAttributes::default() {
+                space = space.or_else(|| Some(Length::default()));
+            }
 
-            let fields = parse_token_contents(parser, elem, token_style)?;
+            let fields = parse_token_contents(parser, elem, token_style, context)?;
 
             let attributes = token::Attributes {
                 operator_attributes: op_attrs,
                 token_style,
                 horizontal_space: space,
+                linebreak,
             };
 
             Ok(token::build_token(
@@ -68,9 +128,17 @@ where
             args: ArgumentRequirements::ArgumentList,
         }
         | ElementType::MathmlRoot => {
+            let mut attributes = SchemaAttributes::default();
+            for attr in attrs {
+                parse_schema_attribute(&mut attributes, &attr);
+            }
+
             let mut list = parse_element_list(parser, elem, context)?;
             operator::process_operators(&mut list, context);
-            Ok(parse_list_schema(list, elem, user_data))
+            if context.options.semantic_spacing {
+                spacing::insert_semantic_spaces(&mut list, context);
+            }
+            Ok(parse_list_schema(list, elem, attributes, user_data))
         }
         ElementType::LayoutSchema {
             args: ArgumentRequirements::RequiredArguments(_),
@@ -81,69 +149,126 @@ where
             }
 
             let arguments = parse_fixed_arguments(parser, elem, context)?;
-            Ok(parse_fixed_schema(
-                arguments.into_iter(),
-                elem,
-                attributes,
-                context,
-                user_data,
-            ))
+            parse_fixed_schema(arguments.into_iter(), elem, attributes, context, user_data)
         }
-        _ => unimplemented!(),
+        ElementType::LayoutSchema {
+            args: ArgumentRequirements::Special,
+        } => Err(ParsingError::of_type(
+            parser,
+            ErrorType::UnsupportedElement(elem.identifier.to_string()),
+        )),
+    };
+    let expr = expr?;
+
+    if elem.elem_type == ElementType::MathmlRoot {
+        return Ok(expr);
+    }
+
+    // Elements that collapse to their only child (like an `mrow` with one argument) keep the
+    // information of the child element.
+    let expr = match element_info.attribute("href") {
+        Some(href) if expr.payload().is_none() => expr.with_payload(Arc::new(Hyperlink {
+            href: href.to_string(),
+        })),
+        _ => expr,
+    };
+    // The direction of the collapsed element applies to the child unless it has its own.
+    let child_with_direction = match (element_info.attribute("dir"), expr.element()) {
+        (Some(dir), Some(child)) if child.attribute("dir").is_none() => {
+            let mut child = child.clone();
+            child.attributes.push(("dir".to_string(), dir.to_string()));
+            Some(child)
+        }
+        _ => None,
+    };
+    let expr = match child_with_direction {
+        Some(child) => expr.with_element(child),
+        None => expr,
+    };
+    if expr.element().is_some() {
+        Ok(expr)
+    } else {
+        Ok(expr.with_element(element_info))
+    }
+}
+
+// Returns the local name of an element in the MathML namespace. Elements without a namespace are
+// MathML elements as well, because most documents don't declare it.
+fn mathml_name<'n, R>(parser: &NsReader<R>, name: QName<'n>) -> Option<&'n [u8]> {
+    match parser.resolve_element(name) {
+        (ResolveResult::Unbound, local_name) => Some(local_name.into_inner()),
+        (ResolveResult::Bound(namespace), local_name) if namespace.as_ref() == MATHML_NAMESPACE => {
+            Some(local_name.into_inner())
+        }
+        _ => None,
     }
 }
 
 fn parse_sub_element<R: BufRead>(
-    parser: &mut XmlReader<R>,
-    elem: &Element,
+    parser: &mut NsReader<R>,
+    elem: &BytesStart,
     context: &mut ParseContext,
 ) -> Result<MathExpression> {
-    let sub_elem = match_math_element(elem.name());
+    let sub_elem = mathml_name(parser, elem.name()).and_then(match_math_element);
     match sub_elem {
-        Some(sub_elem) => parse_element(parser, sub_elem, elem.attributes(), context),
+        Some(_) if context.depth >= context.options.max_depth => Err(ParsingError::of_type(
+            parser,
+            ErrorType::NestingTooDeep(context.options.max_depth),
+        )),
+        Some(sub_elem) => {
+            context.depth += 1;
+            let result = parse_element(parser, sub_elem, elem.attributes(), context);
+            context.depth -= 1;
+            result
+        }
         None => {
-            let name = String::from_utf8_lossy(elem.name()).into_owned();
-            let result: Result<_> = parser.read_to_end(elem.name()).map_err(|err| err.into());
-            result.and(Err(ParsingError::of_type(
+            let name = String::from_utf8_lossy(elem.name().as_ref()).into_owned();
+            let mut buf = Vec::new();
+            parser
+                .read_to_end_into(elem.name(), &mut buf)
+                .map_err(|error| ParsingError::from_xml_error(parser, error))?;
+            Err(ParsingError::of_type(
                 parser,
                 ErrorType::UnknownElement(name),
-            )))
+            ))
         }
     }
 }
 
 fn parse_element_list<R: BufRead>(
-    parser: &mut XmlReader<R>,
+    parser: &mut NsReader<R>,
     elem: MathmlElement,
     context: &mut ParseContext,
 ) -> Result<Vec<MathExpression>> {
     let mut list = Vec::new();
+    let mut buf = Vec::new();
     loop {
-        let next_event = parser.next();
+        let next_event = parser
+            .read_event_into(&mut buf)
+            .map_err(|error| ParsingError::from_xml_error(parser, error))?;
         match next_event {
-            Some(Ok(Event::Start(ref start_elem))) => {
+            Event::Start(ref start_elem) => {
                 list.push(parse_sub_element(parser, start_elem, context)?)
             }
-            Some(Ok(Event::End(ref end_elem))) => {
+            Event::End(ref end_elem) => {
                 if elem.elem_type == ElementType::MathmlRoot {
-                    let name = std::str::from_utf8(end_elem.name())?.to_string();
+                    let name = std::str::from_utf8(end_elem.name().as_ref())?.to_string();
                     return Err(ParsingError::of_type(
                         parser,
                         ErrorType::WrongEndElement(name),
                     ));
                 }
-                if end_elem.name() == elem.identifier.as_bytes() {
+                if end_elem.local_name().as_ref() == elem.identifier.as_bytes() {
                     break;
                 } else {
-                    let name = std::str::from_utf8(end_elem.name())?.to_string();
+                    let name = std::str::from_utf8(end_elem.name().as_ref())?.to_string();
                     return Err(ParsingError::of_type(
                         parser,
                         ErrorType::WrongEndElement(name),
                     ));
                 }
             }
-            Some(Err(error)) => Err(error)?,
-            None => {
+            Event::Eof => {
                 if elem.elem_type == ElementType::MathmlRoot {
                     break;
                 } else {
@@ -155,12 +280,13 @@ fn parse_element_list<R: BufRead>(
             }
             _ => {}
         }
+        buf.clear();
     }
     Ok(list)
 }
 
 fn parse_fixed_arguments<'a, R: BufRead>(
-    parser: &mut XmlReader<R>,
+    parser: &mut NsReader<R>,
     elem: MathmlElement,
     context: &mut ParseContext,
 ) -> Result<Vec<MathExpression>> {
@@ -172,19 +298,20 @@ fn parse_fixed_arguments<'a, R: BufRead>(
         if args.len() == num_args as usize {
             Ok(args)
         } else {
-            Err(ParsingError::from_string(
+            Err(ParsingError::of_type(
                 parser,
-                format!(
-                    "\"{:?}\" element requires {:?} arguments. \
-                     Found {:?} arguments.",
-                    elem.identifier,
-                    num_args,
-                    args.len()
-                ),
+                ErrorType::WrongNumberOfArguments {
+                    element: elem.identifier.to_string(),
+                    expected: num_args,
+                    found: args.len(),
+                },
             ))
         }
     } else {
-        unreachable!();
+        Err(ParsingError::of_type(
+            parser,
+            ErrorType::UnsupportedElement(elem.identifier.to_string()),
+        ))
     }
 }
 
@@ -192,46 +319,176 @@ fn parse_fixed_arguments<'a, R: BufRead>(
 // the cursor is moved behind the end element of the token expression
 // the result (if ok) is guaranteed to not be empty
 pub fn parse_token_contents<R: BufRead>(
-    parser: &mut XmlReader<R>,
+    parser: &mut NsReader<R>,
     elem: MathmlElement,
     token_style: token::TokenStyle,
-) -> Result<impl ExactSizeIterator<Item = (Field, u64)>> {
-    let mut fields: Vec<(Field, u64)> = Vec::new();
-
-    while let Some(event) = parser.next() {
-        match event? {
-            Event::Text(text) => {
-                let text = std::str::from_utf8(text.content())?;
-
-                let text = text.unescape().map(|text| {
-                    text.adapt_to_family(token_style.math_variant)
-                        .replace_anomalous_characters(elem)
-                })?;
+    context: &mut ParseContext,
+) -> Result<impl ExactSizeIterator<Item = MathExpression>> {
+    let preserve_whitespace =
+        context.options.whitespace == Whitespace::PreserveInText && elem.is("mtext");
+    let mut fields: Vec<MathExpression> = Vec::new();
+    // the text since the last child element
+    let mut text = String::new();
+    let mut buf = Vec::new();
 
-                fields.push((Field::Unicode(text), 0));
+    loop {
+        buf.clear();
+        let event = parser
+            .read_event_into(&mut buf)
+            .map_err(|error| ParsingError::from_xml_error(parser, error))?;
+        // the child element that ends the text or `None` at the end of the token
+        let child = match event {
+            Event::Text(ref raw_text) => {
+                let unescaped = raw_text
+                    .unescape_with(escape::resolve_entity)
+                    .map_err(|error| ParsingError::from_xml_error(parser, error))?;
+                text.push_str(&unescaped);
+                continue;
             }
-            Event::Start(elem) => match elem.name() {
-                b"mglyph" | b"malignmark" => Err(ParsingError::from_string(
+            Event::Start(ref child) => match mathml_name(parser, child.name()) {
+                Some(b"mglyph") => Some(child),
+                Some(b"malignmark") => Err(ParsingError::from_string(
                     parser,
-                    format!(
-                        "{:?} element is currently not \
-                         implemented.",
-                        elem.name()
-                    ),
+                    "\"malignmark\" element is currently not implemented.",
                 ))?,
                 _ => Err(ParsingError::from_string(parser, "Unexpected new element."))?,
             },
-            Event::End(ref end_elem) => {
-                if elem.identifier.as_bytes() == end_elem.name() {
-                    break;
-                }
+            Event::End(ref end_elem)
+                if elem.identifier.as_bytes() == end_elem.local_name().as_ref() =>
+            {
+                None
             }
-            _ => {}
+            Event::Eof => {
+                return Err(ParsingError::of_type(
+                    parser,
+                    ErrorType::UnexpectedEndOfInput,
+                ))
+            }
+            _ => continue,
+        };
+
+        let trim = !preserve_whitespace;
+        let collapsed =
+            collapse_whitespace(&text, trim && fields.is_empty(), trim && child.is_none());
+        text.clear();
+        if !collapsed.is_empty() {
+            let collapsed = collapsed
+                .adapt_to_family(token_style.math_variant)
+                .replace_anomalous_characters(elem);
+            let user_data = context.allocate_node_id().user_data();
+            fields.push(MathExpression::new(
+                MathItem::Field(Field::Unicode(collapsed)),
+                user_data,
+            ));
+        }
+
+        match child {
+            Some(child) => {
+                let user_data = context.allocate_node_id().user_data();
+                fields.push(parse_mglyph(child, user_data)?)
+            }
+            None => break,
         }
     }
     Ok(fields.into_iter())
 }
 
+// Replaces every run of whitespace in the text of a token element by a single space and removes
+// the whitespace at the start and the end if requested.
+fn collapse_whitespace(text: &str, trim_start: bool, trim_end: bool) -> String {
+    let is_whitespace = |chr: char| matches!(chr, ' ' | '\t' | '\n' | '\r');
+    let mut collapsed = String::with_capacity(text.len());
+    let mut space = !trim_start && text.starts_with(is_whitespace);
+    for word in text.split(is_whitespace).filter(|word| !word.is_empty()) {
+        if space {
+            collapsed.push(' ');
+        }
+        collapsed.push_str(word);
+        space = true;
+    }
+    if space && !trim_end && text.ends_with(is_whitespace) {
+        collapsed.push(' ');
+    }
+    collapsed
+}
+
+// Resolves an `mglyph` element to a glyph of the current font (when the legacy `index` attribute
+// is given), a foreign object for the image referenced by `src` or its alternative text.
+fn parse_mglyph(elem: &BytesStart, user_data: u64) -> Result<MathExpression> {
+    let mut index = None;
+    let mut src = None;
+    let mut alt = None;
+    let mut width = None;
+    let mut height = None;
+    let mut valign = None;
+    for attr in elem.attributes() {
+        let attr = attr.map_err(quick_xml::Error::from)?;
+        let value = attribute_value(&attr)?;
+        match attr.key.as_ref() {
+            b"index" => index = value.trim().parse::<GlyphCode>().ok(),
+            b"src" => src = Some(value),
+            b"alt" => alt = Some(value),
+            b"width" => width = value.parse_xml::<Length>().ok(),
+            b"height" => height = value.parse_xml::<Length>().ok(),
+            b"valign" => valign = value.parse_xml::<Length>().ok(),
+            _ => {}
+        }
+    }
+
+    if let Some(glyph_code) = index {
+        let glyph = Glyph {
+            glyph_code,
+            ..Default::default()
+        };
+        return Ok(MathExpression::new(
+            MathItem::Field(Field::Glyph(glyph)),
+            user_data,
+        ));
+    }
+
+    match (src, alt) {
+        (Some(src), alt) => {
+            let height = height.unwrap_or(Length::em(1.0));
+            // `valign` moves the baseline of the image. Negative values make it descend below
+            // the baseline of the surrounding text.
+            let (ascent, descent) = match valign {
+                Some(valign) if valign.unit == height.unit => (
+                    Length::new(height.value + valign.value, height.unit),
+                    Length::new(-valign.value, valign.unit),
+                ),
+                Some(valign) => (height, Length::new(-valign.value, valign.unit)),
+                None => (height, Length::default()),
+            };
+            let image = ForeignObject {
+                size: MathSpace {
+                    width: width.unwrap_or(Length::em(1.0)),
+                    ascent,
+                    descent,
+                },
+                payload: Arc::new(ImageReference { src, alt }),
+            };
+            Ok(MathExpression::new(MathItem::Foreign(image), user_data))
+        }
+        (None, Some(alt)) => Ok(MathExpression::new(
+            MathItem::Field(Field::Unicode(alt)),
+            user_data,
+        )),
+        (None, None) => Ok(MathExpression::new(
+            MathItem::Field(Field::Empty),
+            user_data,
+        )),
+    }
+}
+
+// Returns the value of an attribute with its entities replaced. Values with stray ampersands like
+// unescaped URLs are taken literally.
+fn attribute_value(attr: &Attribute) -> Result<String> {
+    match attr.unescape_value_with(escape::resolve_entity) {
+        Ok(value) => Ok(value.into_owned()),
+        Err(_) => Ok(std::str::from_utf8(&attr.value)?.to_string()),
+    }
+}
+
 #[allow(match_same_arms)]
 fn parse_token_attribute<'a>(
     style: &mut token::TokenStyle,
@@ -240,7 +497,11 @@ fn parse_token_attribute<'a>(
 ) -> bool {
     match *new_attribute {
         ("mathvariant", variant) => style.math_variant = variant.parse_xml().ok(),
-        ("dir", dir) => style.direction = dir.parse_xml().unwrap(),
+        ("dir", dir) => {
+            if let Ok(direction) = dir.parse_xml() {
+                style.direction = direction;
+            }
+        }
         _ => return false,
     }
     match (element_identifier, style.math_variant) {
@@ -260,6 +521,16 @@ fn parse_operator_attribute(op_attrs: &mut operator::Attributes, new_attr: &(&st
         ("rspace", rspace) => {
             op_attrs.rspace = rspace.parse_xml().ok();
         }
+        ("minsize", minsize) => {
+            op_attrs.minsize = parse_stretch_size(minsize);
+        }
+        ("maxsize", maxsize) => {
+            op_attrs.maxsize = if maxsize.trim() == "infinity" {
+                None
+            } else {
+                parse_stretch_size(maxsize)
+            };
+        }
         ("fence", is_fence) => {
             if let Ok(is_fence) = is_fence.parse_xml() {
                 op_attrs.set_user_override(operator::Flags::FENCE, is_fence);
@@ -290,11 +561,26 @@ fn parse_operator_attribute(op_attrs: &mut operator::Attributes, new_attr: &(&st
                 op_attrs.set_user_override(operator::Flags::ACCENT, is_accent);
             }
         }
+        ("linebreakstyle", style) => op_attrs.linebreak_style = style.parse_xml().ok(),
         _ => return false,
     }
     true
 }
 
+// Parses the value of a `minsize` or `maxsize` attribute. Unitless numbers and percentages are
+// relative to the default size of the operator.
+fn parse_stretch_size(value: &str) -> Option<Length> {
+    let value = value.trim();
+    if let Some(percentage) = value.strip_suffix('%') {
+        let percentage: f32 = percentage.trim().parse().ok()?;
+        Some(Length::new(percentage / 100.0, LengthUnit::OperatorDefaultSize))
+    } else if let Ok(multiple) = value.parse::<f32>() {
+        Some(Length::new(multiple, LengthUnit::OperatorDefaultSize))
+    } else {
+        value.parse_xml().ok()
+    }
+}
+
 fn parse_mspace_attribute(
     horiz_space: &mut Option<Length>,
     identifier: &str,
@@ -314,10 +600,59 @@ fn parse_mspace_attribute(
     }
 }
 
+// Parses the attributes of `mo` and `mspace` that control line breaks at them.
+fn parse_linebreak_attribute(
+    linebreak: &mut LinebreakAttributes,
+    identifier: &str,
+    new_attr: &(&str, &str),
+) -> bool {
+    if identifier != "mo" && identifier != "mspace" {
+        return false;
+    }
+    match *new_attr {
+        ("linebreak", value) => linebreak.linebreak = value.parse_xml().unwrap_or_default(),
+        ("indentalign", value) => linebreak.indent_align = value.parse_xml().unwrap_or_default(),
+        ("indentshift", value) => linebreak.indent_shift = value.parse_xml().ok(),
+        _ => return false,
+    }
+    true
+}
+
 fn parse_schema_attribute(attributes: &mut SchemaAttributes, new_attr: &(&str, &str)) {
     match *new_attr {
-        ("accent", is_accent) => attributes.accent = is_accent.parse().unwrap(),
-        ("accentunder", is_accent) => attributes.accentunder = is_accent.parse().unwrap(),
+        ("accent", is_accent) => {
+            attributes.accent = is_accent.parse().unwrap_or(attributes.accent)
+        }
+        ("accentunder", is_accent) => {
+            attributes.accentunder = is_accent.parse().unwrap_or(attributes.accentunder)
+        }
+        // Not part of MathML: `limits="true"` and `limits="false"` force the attachments of
+        // `munder`, `mover` and `munderover` above/below the base or into script positions.
+        ("limits", limits) => {
+            attributes.limits = match limits.parse() {
+                Ok(true) => LimitsPlacement::Limits,
+                Ok(false) => LimitsPlacement::Scripts,
+                Err(_) => attributes.limits,
+            }
+        }
+        ("linethickness", thickness) => {
+            if let Ok(thickness) = thickness.parse_xml() {
+                attributes.linethickness = Some(thickness);
+            }
+        }
+        ("notation", notation) => {
+            attributes.notation = notation
+                .split_whitespace()
+                .filter_map(|notation| match notation {
+                    "horizontalstrike" => Some(Strike::Horizontal),
+                    "verticalstrike" => Some(Strike::Vertical),
+                    "updiagonalstrike" => Some(Strike::UpDiagonal),
+                    "downdiagonalstrike" => Some(Strike::DownDiagonal),
+                    "updiagonalarrow" => Some(Strike::UpDiagonalArrow),
+                    _ => None,
+                })
+                .collect()
+        }
         _ => {}
     }
 }
@@ -0,0 +1,21 @@
+//! The items needed to lay out and draw a formula.
+//!
+//! ```
+//! use math_render::prelude::*;
+//! ```
+//!
+//! The prelude only grows in a backwards compatible way, so glob importing it is safe.
+
+#[cfg(feature = "layout")]
+pub use crate::math_box::{
+    DeviceScale, Drawable, Extents, MathBox, MathBoxContent, MathBoxMetrics,
+};
+#[cfg(feature = "mathml_parser")]
+pub use crate::mathmlparser::{parse, ParsingError};
+#[cfg(feature = "layout")]
+pub use crate::shaper::{HarfbuzzShaper, MathShaper, OwnedShaper};
+#[cfg(feature = "layout")]
+pub use crate::types::LayoutStyle;
+pub use crate::types::{Field, MathExpression, MathItem, PercentValue};
+#[cfg(feature = "layout")]
+pub use crate::typesetting::{layout, try_layout, LayoutError, LayoutOptions};
@@ -0,0 +1,400 @@
+//! Helpers for testing layout changes.
+//!
+//! `diff` compares two box trees and reports every difference together with the path to the box
+//! where it occurs, so regression tests can print what changed instead of failing on an opaque
+//! `assert!`.
+
+use std::fmt;
+
+use crate::math_box::{Drawable, Extents, MathBox, MathBoxContent, MathBoxMetrics, Vector};
+use crate::types::{Color, PercentScale2D};
+
+/// A difference between the expected and the actual value of a property of a box.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DifferenceKind {
+    Origin {
+        expected: Vector<i32>,
+        actual: Vector<i32>,
+    },
+    Extents {
+        expected: Extents<i32>,
+        actual: Extents<i32>,
+    },
+    AdvanceWidth {
+        expected: i32,
+        actual: i32,
+    },
+    UserData {
+        expected: u64,
+        actual: u64,
+    },
+    /// The boxes have different kinds of content (e.g. glyphs and a line).
+    Content {
+        expected: &'static str,
+        actual: &'static str,
+    },
+    /// The boxes contain a different number of children or glyphs.
+    Count {
+        expected: usize,
+        actual: usize,
+    },
+    /// The glyph at `index` is a different glyph.
+    Glyph {
+        index: usize,
+        expected: u32,
+        actual: u32,
+    },
+    /// The glyph at `index` is placed differently.
+    GlyphPosition {
+        index: usize,
+        expected: Vector<i32>,
+        actual: Vector<i32>,
+    },
+    Scale {
+        expected: PercentScale2D,
+        actual: PercentScale2D,
+    },
+    Line {
+        expected: (Vector<i32>, u32),
+        actual: (Vector<i32>, u32),
+    },
+    /// The rectangles are filled with different colors.
+    Color {
+        expected: Color,
+        actual: Color,
+    },
+}
+
+/// A difference between two box trees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// The indices of the children to follow from the root to reach the differing box.
+    pub path: Vec<usize>,
+    pub kind: DifferenceKind,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "/")?;
+        for (i, index) in self.path.iter().enumerate() {
+            if i > 0 {
+                write!(f, "/")?;
+            }
+            write!(f, "{}", index)?;
+        }
+        write!(f, ": ")?;
+        match self.kind {
+            DifferenceKind::Origin { expected, actual } => write!(
+                f,
+                "origin ({}, {}) != ({}, {})",
+                expected.x, expected.y, actual.x, actual.y
+            ),
+            DifferenceKind::Extents { expected, actual } => write!(
+                f,
+                "extents ({}, {}, {}, {}) != ({}, {}, {}, {})",
+                expected.left_side_bearing,
+                expected.width,
+                expected.ascent,
+                expected.descent,
+                actual.left_side_bearing,
+                actual.width,
+                actual.ascent,
+                actual.descent
+            ),
+            DifferenceKind::AdvanceWidth { expected, actual } => {
+                write!(f, "advance width {} != {}", expected, actual)
+            }
+            DifferenceKind::UserData { expected, actual } => {
+                write!(f, "user data {} != {}", expected, actual)
+            }
+            DifferenceKind::Content { expected, actual } => {
+                write!(f, "content {} != {}", expected, actual)
+            }
+            DifferenceKind::Count { expected, actual } => {
+                write!(f, "{} items != {} items", expected, actual)
+            }
+            DifferenceKind::Glyph {
+                index,
+                expected,
+                actual,
+            } => write!(f, "glyph {}: {} != {}", index, expected, actual),
+            DifferenceKind::GlyphPosition {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "glyph {} position ({}, {}) != ({}, {})",
+                index, expected.x, expected.y, actual.x, actual.y
+            ),
+            DifferenceKind::Scale { expected, actual } => write!(
+                f,
+                "scale {}%x{}% != {}%x{}%",
+                expected.horizontal.magnification_percentage(),
+                expected.vertical.magnification_percentage(),
+                actual.horizontal.magnification_percentage(),
+                actual.vertical.magnification_percentage()
+            ),
+            DifferenceKind::Line { expected, actual } => write!(
+                f,
+                "line ({}, {}) thickness {} != ({}, {}) thickness {}",
+                expected.0.x, expected.0.y, expected.1, actual.0.x, actual.0.y, actual.1
+            ),
+            DifferenceKind::Color { expected, actual } => {
+                write!(f, "color {:?} != {:?}", expected, actual)
+            }
+        }
+    }
+}
+
+/// Returns all differences between the expected and the actual box tree.
+pub fn diff(expected: &MathBox, actual: &MathBox) -> Vec<Difference> {
+    diff_with_tolerance(expected, actual, 0)
+}
+
+/// Returns all differences between the expected and the actual box tree ignoring deviations of
+/// lengths of at most `tolerance` font units.
+pub fn diff_with_tolerance(
+    expected: &MathBox,
+    actual: &MathBox,
+    tolerance: i32,
+) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    diff_boxes(&mut differences, &mut Vec::new(), expected, actual, tolerance);
+    differences
+}
+
+fn kind_of(math_box: &MathBox) -> &'static str {
+    match *math_box.content() {
+        MathBoxContent::Empty(_) => "empty",
+        MathBoxContent::Foreign { .. } => "foreign",
+        MathBoxContent::Drawable(Drawable::Glyphs { .. }) => "glyphs",
+        MathBoxContent::Drawable(Drawable::Line { .. }) => "line",
+        MathBoxContent::Drawable(Drawable::Rectangle { .. }) => "rectangle",
+        MathBoxContent::Boxes(_) => "boxes",
+    }
+}
+
+fn diff_boxes(
+    differences: &mut Vec<Difference>,
+    path: &mut Vec<usize>,
+    expected: &MathBox,
+    actual: &MathBox,
+    tolerance: i32,
+) {
+    let close = |a: i32, b: i32| (a - b).abs() <= tolerance;
+    let close_vectors = |a: Vector<i32>, b: Vector<i32>| close(a.x, b.x) && close(a.y, b.y);
+    let mut push = |kind| {
+        differences.push(Difference {
+            path: path.clone(),
+            kind,
+        })
+    };
+
+    if !close_vectors(expected.origin, actual.origin) {
+        push(DifferenceKind::Origin {
+            expected: expected.origin,
+            actual: actual.origin,
+        });
+    }
+    let (expected_extents, actual_extents) = (expected.extents(), actual.extents());
+    let extents_are_close = close(
+        expected_extents.left_side_bearing,
+        actual_extents.left_side_bearing,
+    ) && close(expected_extents.width, actual_extents.width)
+        && close(expected_extents.ascent, actual_extents.ascent)
+        && close(expected_extents.descent, actual_extents.descent);
+    if !extents_are_close {
+        push(DifferenceKind::Extents {
+            expected: expected_extents,
+            actual: actual_extents,
+        });
+    }
+    if !close(expected.advance_width(), actual.advance_width()) {
+        push(DifferenceKind::AdvanceWidth {
+            expected: expected.advance_width(),
+            actual: actual.advance_width(),
+        });
+    }
+    if expected.user_data() != actual.user_data() {
+        push(DifferenceKind::UserData {
+            expected: expected.user_data(),
+            actual: actual.user_data(),
+        });
+    }
+
+    match (expected.content(), actual.content()) {
+        (
+            &MathBoxContent::Drawable(Drawable::Glyphs {
+                glyphs: ref expected_glyphs,
+                scale: expected_scale,
+            }),
+            &MathBoxContent::Drawable(Drawable::Glyphs {
+                glyphs: ref actual_glyphs,
+                scale: actual_scale,
+            }),
+        ) => {
+            if expected_scale != actual_scale {
+                push(DifferenceKind::Scale {
+                    expected: expected_scale,
+                    actual: actual_scale,
+                });
+            }
+            if expected_glyphs.len() != actual_glyphs.len() {
+                push(DifferenceKind::Count {
+                    expected: expected_glyphs.len(),
+                    actual: actual_glyphs.len(),
+                });
+            }
+            let (mut expected_x, mut actual_x) = (0, 0);
+            let glyphs = expected_glyphs.iter().zip(actual_glyphs);
+            for (index, (expected_glyph, actual_glyph)) in glyphs.enumerate() {
+                if expected_glyph.glyph_code != actual_glyph.glyph_code {
+                    push(DifferenceKind::Glyph {
+                        index,
+                        expected: expected_glyph.glyph_code,
+                        actual: actual_glyph.glyph_code,
+                    });
+                }
+                let expected_position = expected_glyph.offset + Vector { x: expected_x, y: 0 };
+                let actual_position = actual_glyph.offset + Vector { x: actual_x, y: 0 };
+                if !close_vectors(expected_position, actual_position) {
+                    push(DifferenceKind::GlyphPosition {
+                        index,
+                        expected: expected_position,
+                        actual: actual_position,
+                    });
+                }
+                expected_x += expected_glyph.advance_width;
+                actual_x += actual_glyph.advance_width;
+            }
+        }
+        (
+            &MathBoxContent::Drawable(Drawable::Line {
+                vector: expected_vector,
+                thickness: expected_thickness,
+            }),
+            &MathBoxContent::Drawable(Drawable::Line {
+                vector: actual_vector,
+                thickness: actual_thickness,
+            }),
+        ) => {
+            let thickness_is_close = close(expected_thickness as i32, actual_thickness as i32);
+            if !close_vectors(expected_vector, actual_vector) || !thickness_is_close {
+                push(DifferenceKind::Line {
+                    expected: (expected_vector, expected_thickness),
+                    actual: (actual_vector, actual_thickness),
+                });
+            }
+        }
+        (
+            &MathBoxContent::Drawable(Drawable::Rectangle {
+                color: expected_color,
+                ..
+            }),
+            &MathBoxContent::Drawable(Drawable::Rectangle {
+                color: actual_color,
+                ..
+            }),
+        ) if expected_color != actual_color => push(DifferenceKind::Color {
+            expected: expected_color,
+            actual: actual_color,
+        }),
+        (&MathBoxContent::Boxes(ref expected_boxes), &MathBoxContent::Boxes(ref actual_boxes)) => {
+            if expected_boxes.len() != actual_boxes.len() {
+                push(DifferenceKind::Count {
+                    expected: expected_boxes.len(),
+                    actual: actual_boxes.len(),
+                });
+            }
+            let children = expected_boxes.iter().zip(actual_boxes);
+            for (index, (expected_child, actual_child)) in children.enumerate() {
+                path.push(index);
+                diff_boxes(differences, path, expected_child, actual_child, tolerance);
+                path.pop();
+            }
+        }
+        _ if kind_of(expected) != kind_of(actual) => push(DifferenceKind::Content {
+            expected: kind_of(expected),
+            actual: kind_of(actual),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shaper::MathGlyph;
+    use crate::PercentValue;
+
+    fn glyph_box(glyph_code: u32, x: i32) -> MathBox {
+        let glyph = MathGlyph {
+            glyph_code,
+            advance_width: 500,
+            extents: Extents::new(0, 500, 700, 0),
+            ..Default::default()
+        };
+        let mut math_box = MathBox::with_glyphs(vec![glyph], PercentValue::new(100), 0);
+        math_box.origin.x = x;
+        math_box
+    }
+
+    #[test]
+    fn test_diff() {
+        let expected = MathBox::with_vec(vec![glyph_box(1, 0), glyph_box(2, 500)], 0);
+        assert!(diff(&expected, &expected).is_empty());
+
+        let actual = MathBox::with_vec(vec![glyph_box(1, 0), glyph_box(3, 502)], 0);
+        let differences = diff(&expected, &actual);
+        assert_eq!(
+            differences,
+            vec![
+                Difference {
+                    path: vec![],
+                    kind: DifferenceKind::Extents {
+                        expected: Extents::new(0, 1000, 700, 0),
+                        actual: Extents::new(0, 1002, 700, 0),
+                    },
+                },
+                Difference {
+                    path: vec![],
+                    kind: DifferenceKind::AdvanceWidth {
+                        expected: 1000,
+                        actual: 1002,
+                    },
+                },
+                Difference {
+                    path: vec![1],
+                    kind: DifferenceKind::Origin {
+                        expected: Vector { x: 500, y: 0 },
+                        actual: Vector { x: 502, y: 0 },
+                    },
+                },
+                Difference {
+                    path: vec![1],
+                    kind: DifferenceKind::Glyph {
+                        index: 0,
+                        expected: 2,
+                        actual: 3,
+                    },
+                },
+            ]
+        );
+        assert_eq!(differences[3].to_string(), "/1: glyph 0: 2 != 3");
+
+        let differences = diff_with_tolerance(&expected, &actual, 2);
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].path, vec![1]);
+
+        let line = MathBox::with_line(Vector::default(), Vector { x: 1000, y: 0 }, 40, 0);
+        let differences = diff(&expected, &line);
+        assert_eq!(
+            differences.last().unwrap().kind,
+            DifferenceKind::Content {
+                expected: "boxes",
+                actual: "line",
+            }
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use std::default::Default;
 use std::fmt;
 use std::ops::{Mul, Div};
+use std::any::Any;
 use std::sync::Arc;
 
+#[cfg(feature = "layout")]
 use crate::typesetting::math_box::Vector;
-use crate::typesetting::MathLayout;
 
 /// An identifier of a glyph inside a font.
 pub type GlyphCode = u32;
@@ -15,16 +16,77 @@ pub struct MathExpression {
     /// An arbitrary number provided by the user that will be passed through the layout process to
     /// the generated math boxes.
     user_data: u64,
+    /// The markup element this expression was created from (if any).
+    element: Option<Arc<ElementInfo>>,
+    /// Arbitrary data provided by the user that will be attached to the generated math box.
+    payload: Option<Payload>,
+    /// A background and border that are drawn behind the expression.
+    decoration: Option<Arc<Decoration>>,
+    /// How a formula may be broken into lines at this expression.
+    linebreak: Option<LinebreakAttributes>,
 }
 
+/// Structured data the user can attach to a `MathExpression` (e.g. a semantic identifier, a color
+/// or a hyperlink). It is passed on to the math box generated for the expression.
+pub type Payload = Arc<dyn Any + Send + Sync>;
+
 impl MathExpression {
     pub fn new(expr: MathItem, user_data: u64) -> MathExpression {
         MathExpression {
             item: Box::new(expr),
             user_data,
+            element: None,
+            payload: None,
+            decoration: None,
+            linebreak: None,
         }
     }
 
+    /// Returns the expression with the given payload attached.
+    pub fn with_payload(mut self, payload: Payload) -> MathExpression {
+        self.payload = Some(payload);
+        self
+    }
+
+    /// Returns the payload attached to this expression.
+    pub fn payload(&self) -> Option<&Payload> {
+        self.payload.as_ref()
+    }
+
+    /// Returns the expression with information about the markup element it originates from.
+    pub fn with_element(mut self, element: ElementInfo) -> MathExpression {
+        self.element = Some(Arc::new(element));
+        self
+    }
+
+    /// Returns information about the markup element this expression was created from.
+    pub fn element(&self) -> Option<&ElementInfo> {
+        self.element.as_ref().map(|element| &**element)
+    }
+
+    /// Returns the expression with a background and border drawn behind it, e.g. to highlight it.
+    pub fn with_decoration(mut self, decoration: Decoration) -> MathExpression {
+        self.decoration = Some(Arc::new(decoration));
+        self
+    }
+
+    /// Returns the decoration drawn behind this expression.
+    pub fn decoration(&self) -> Option<&Decoration> {
+        self.decoration.as_deref()
+    }
+
+    /// Returns the expression with attributes that control line breaks at it. They only have an
+    /// effect on operators and spaces.
+    pub fn with_linebreak(mut self, linebreak: LinebreakAttributes) -> MathExpression {
+        self.linebreak = Some(linebreak);
+        self
+    }
+
+    /// Returns the attributes that control line breaks at this expression.
+    pub fn linebreak(&self) -> Option<&LinebreakAttributes> {
+        self.linebreak.as_ref()
+    }
+
     // pub fn set_user_data(&mut self, user_data: u64) {
     //     self.user_data = user_data;
     // }
@@ -34,6 +96,175 @@ impl MathExpression {
     }
 }
 
+/// Describes the markup element (e.g. a MathML element) a `MathExpression` was created from.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ElementInfo {
+    /// The name of the element like `mi` or `mfrac`.
+    pub name: String,
+    /// The attributes of the element as (name, value) pairs in document order.
+    pub attributes: Vec<(String, String)>,
+}
+
+impl ElementInfo {
+    pub fn new<S: Into<String>>(name: S) -> ElementInfo {
+        ElementInfo {
+            name: name.into(),
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Returns the value of the attribute with the given name.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|&&(ref attr_name, _)| attr_name == name)
+            .map(|&(_, ref value)| value.as_str())
+    }
+}
+
+/// A hyperlink that can be attached as payload to an expression (like the MathML `href`
+/// attribute).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hyperlink {
+    /// The URI the link points to.
+    pub href: String,
+}
+
+/// A reference to an external image (like the `src` of a MathML `mglyph`). It is used as the
+/// payload of a `ForeignObject`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageReference {
+    /// The URI of the image.
+    pub src: String,
+    /// A textual description of the image.
+    pub alt: Option<String>,
+}
+
+/// A color with 8 bits per channel and straight (not premultiplied) alpha.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    /// Returns an opaque color.
+    pub fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b, a: 255 }
+    }
+
+    pub fn rgba(r: u8, g: u8, b: u8, a: u8) -> Color {
+        Color { r, g, b, a }
+    }
+}
+
+/// A border around a decorated expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Border {
+    pub color: Color,
+    pub thickness: Length,
+}
+
+/// A line that is drawn across a decorated expression (like the strikes of MathML `menclose`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strike {
+    Horizontal,
+    Vertical,
+    /// From the bottom left to the top right corner (like `\cancel` in TeX).
+    UpDiagonal,
+    /// From the top left to the bottom right corner (like `\bcancel` in TeX).
+    DownDiagonal,
+    /// An up diagonal strike whose arrow head sticks out at the top right corner (like
+    /// `\cancelto` in TeX). The arrow head doesn't contribute to the advance width.
+    UpDiagonalArrow,
+}
+
+/// A background and border that are drawn behind an expression (like the `mathbackground`
+/// attribute of MathML or a frame around a highlighted subexpression) and strikes that are
+/// drawn across it.
+///
+/// The decoration encloses the ink and the advance width of the expression plus `padding` on
+/// every side. Both the padding and the border make the expression wider.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Decoration {
+    pub background: Option<Color>,
+    pub border: Option<Border>,
+    /// The space between the expression and the border.
+    pub padding: Length,
+    pub strikes: Vec<Strike>,
+}
+
+/// Whether a formula may be broken into lines at an operator or a space (like the `linebreak`
+/// attribute of MathML).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Linebreak {
+    /// The line breaker decides.
+    Auto,
+    /// The formula is always broken.
+    Newline,
+    /// The formula is never broken.
+    NoBreak,
+    /// A break is preferred over other break points.
+    GoodBreak,
+    /// A break is only used if there are no better break points.
+    BadBreak,
+}
+
+impl Default for Linebreak {
+    fn default() -> Linebreak {
+        Linebreak::Auto
+    }
+}
+
+/// Where a line break at an operator is placed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LinebreakStyle {
+    /// The operator starts the next line.
+    Before,
+    /// The operator ends the current line.
+    After,
+    /// The operator appears both at the end of the current and the start of the next line.
+    Duplicate,
+}
+
+impl Default for LinebreakStyle {
+    fn default() -> LinebreakStyle {
+        LinebreakStyle::Before
+    }
+}
+
+/// How the line after a line break is aligned (like the `indentalign` attribute of MathML).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IndentAlign {
+    /// The alignment chosen by the line breaker.
+    Auto,
+    Left,
+    Center,
+    Right,
+}
+
+impl Default for IndentAlign {
+    fn default() -> IndentAlign {
+        IndentAlign::Auto
+    }
+}
+
+/// Controls line breaks at an operator or a space (like the MathML attributes `linebreak`,
+/// `linebreakstyle`, `indentalign` and `indentshift`).
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct LinebreakAttributes {
+    pub linebreak: Linebreak,
+    /// Where an operator is placed at a break. Spaces are dropped at a break.
+    pub style: LinebreakStyle,
+    /// The alignment of the line that starts after a break at this point.
+    pub indent_align: IndentAlign,
+    /// Moves the line that starts after a break at this point to the right. `None` uses the
+    /// indentation of the line breaker.
+    pub indent_shift: Option<Length>,
+}
+
 /// A `MathItem` is the abstract representation of mathematical notation that manages the layout
 /// of its subexpressions.
 #[derive(Debug, Clone)]
@@ -59,8 +290,14 @@ pub enum MathItem {
     Operator(Operator),
     /// A list of math expressions to be laid out sequentially.
     List(Vec<MathExpression>),
-    /// Any math expression of another type.
-    Other(Arc<dyn MathLayout + Send + Sync>),
+    /// Non-math content like an image or a diagram that is embedded in the formula.
+    Foreign(ForeignObject),
+    /// Words with embedded formulas (like "for all x such that") that are laid out like text.
+    TextRun(TextRun),
+    /// Any math expression of another type. The typesetting engine lays out the payload if it
+    /// holds a `Box<dyn MathLayout + Send + Sync>` (see `MathItem::other`) and treats it as empty
+    /// otherwise.
+    Other(Arc<dyn Any + Send + Sync>),
 }
 
 impl Default for MathItem {
@@ -88,10 +325,8 @@ pub enum Field {
     /// Represents some text that should be laid out using complex text layout features of
     /// OpenType.
     Unicode(String),
-    /// Represents a specific glyph in the current font.
-    /// 
-    /// *Beware*: This is not yet implemented!
-    // TODO
+    /// Represents a specific glyph in the current font. The glyph is scaled according to the
+    /// current layout style.
     Glyph(Glyph),
 }
 impl Default for Field {
@@ -137,6 +372,17 @@ impl MathSpace {
     }
 }
 
+/// Reserves space for content that is not typeset by this crate. The payload is handed to the
+/// renderer in `MathBoxContent::Foreign`.
+#[derive(Debug, Clone)]
+pub struct ForeignObject {
+    /// The size of the object. `ascent` is the height above and `descent` the depth below the
+    /// baseline.
+    pub size: MathSpace,
+    /// Application defined data describing the object (e.g. image bytes or a callback id).
+    pub payload: Payload,
+}
+
 /// An expression that consists of a base (called nucleus) and attachments at each corner (e.g.
 /// subscripts and superscripts).
 #[derive(Default, Debug, Clone)]
@@ -175,6 +421,45 @@ pub struct OverUnder {
     ///
     /// The main use of this is to display limits on large operators.
     pub is_limits: bool,
+    /// Overrides where the attachments are placed regardless of `is_limits` and the math style.
+    pub limits_placement: LimitsPlacement,
+    /// Whether stretchy accents keep their narrow form or stretch to the width of the nucleus.
+    pub accent_width: AccentWidth,
+}
+
+/// Where the attachments of an `OverUnder` are placed (like TeX's `\displaylimits`, `\limits` and
+/// `\nolimits`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LimitsPlacement {
+    /// Above and below the nucleus unless `is_limits` is set and the math style is inline.
+    Auto,
+    /// Always above and below the nucleus.
+    Limits,
+    /// Always as scripts to the right of the nucleus.
+    Scripts,
+}
+
+impl Default for LimitsPlacement {
+    fn default() -> LimitsPlacement {
+        LimitsPlacement::Auto
+    }
+}
+
+/// How stretchy accents of an `OverUnder` are sized (like TeX's `\hat` and `\widehat`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AccentWidth {
+    /// Narrow over a nucleus that consists of a single glyph and wide otherwise.
+    Auto,
+    /// Always use the unstretched accent glyph.
+    Narrow,
+    /// Always stretch the accent to the width of the nucleus.
+    Wide,
+}
+
+impl Default for AccentWidth {
+    fn default() -> AccentWidth {
+        AccentWidth::Auto
+    }
 }
 
 /// A structure describing a generalized fraction.
@@ -193,6 +478,192 @@ pub struct GeneralizedFraction {
     pub thickness: Option<MathExpression>,
 }
 
+impl GeneralizedFraction {
+    /// Returns a fraction whose rule has the given thickness. A thickness of zero gives a stack
+    /// without a rule (e.g. for binomial coefficients).
+    ///
+    /// The thickness is stored as a vertical space with the user data `thickness_user_data`. Like
+    /// every other node it needs an id of its own, so it must differ from the user data of the
+    /// numerator, the denominator and the expression that holds the fraction.
+    pub fn with_thickness(
+        numerator: MathExpression,
+        denominator: MathExpression,
+        thickness: Length,
+        thickness_user_data: u64,
+    ) -> Self {
+        let thickness = MathSpace {
+            ascent: thickness,
+            ..Default::default()
+        };
+        GeneralizedFraction {
+            numerator: Some(numerator),
+            denominator: Some(denominator),
+            thickness: Some(MathExpression::new(
+                MathItem::Space(thickness),
+                thickness_user_data,
+            )),
+        }
+    }
+
+    /// Returns the thickness of the rule if it was specified as a space (see `with_thickness`).
+    pub fn rule_thickness(&self) -> Option<Length> {
+        match self.thickness.as_ref().map(|thickness| &*thickness.item) {
+            Some(&MathItem::Space(space)) if space.descent.is_null() => Some(space.ascent),
+            _ => None,
+        }
+    }
+}
+
+impl MathExpression {
+    /// Returns the fraction between the `open` and `close` delimiters (e.g. parentheses) which
+    /// stretch to the height of the fraction. Empty delimiters are left out.
+    ///
+    /// `next_user_data` is called once for every node that is created and should return a new id
+    /// each time (e.g. `|| context.allocate_node_id().user_data()`). The ids go to the returned
+    /// list, the opening delimiter, the fraction and the closing delimiter in this order.
+    pub fn delimited_fraction(
+        open: &str,
+        fraction: GeneralizedFraction,
+        close: &str,
+        mut next_user_data: impl FnMut() -> u64,
+    ) -> MathExpression {
+        let delimiter = |text: &str, form, user_data| {
+            let operator = Operator {
+                field: Field::Unicode(text.to_string()),
+                form,
+                is_fence: true,
+                stretch_constraints: Some(StretchConstraints {
+                    symmetric: true,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            MathExpression::new(MathItem::Operator(operator), user_data)
+        };
+        let list_user_data = next_user_data();
+        let mut list = Vec::new();
+        if !open.is_empty() {
+            list.push(delimiter(open, Form::Prefix, next_user_data()));
+        }
+        list.push(MathExpression::new(
+            MathItem::GeneralizedFraction(fraction),
+            next_user_data(),
+        ));
+        if !close.is_empty() {
+            list.push(delimiter(close, Form::Postfix, next_user_data()));
+        }
+        MathExpression::new(MathItem::List(list), list_user_data)
+    }
+
+    /// Returns the binomial coefficient of `top` and `bottom`, i.e. `top` stacked above `bottom`
+    /// between parentheses.
+    ///
+    /// The first id returned by `next_user_data` goes to the zero thickness of the stack, the
+    /// others are handed out like in `delimited_fraction`.
+    pub fn binomial(
+        top: MathExpression,
+        bottom: MathExpression,
+        mut next_user_data: impl FnMut() -> u64,
+    ) -> MathExpression {
+        let stack =
+            GeneralizedFraction::with_thickness(top, bottom, Length::default(), next_user_data());
+        MathExpression::delimited_fraction("(", stack, ")", next_user_data)
+    }
+
+    /// Returns the arrow of a chemical reaction with the reaction conditions above and below it.
+    ///
+    /// The arrow stretches to the wider of the two labels and stays centered on the math axis.
+    /// Like a relation it is surrounded by thick spaces. The arrow and the returned expression get
+    /// the given user data.
+    pub fn reaction_arrow(
+        arrow: ReactionArrow,
+        above: Option<MathExpression>,
+        below: Option<MathExpression>,
+        user_data: u64,
+    ) -> MathExpression {
+        // 5mu like the space around relations
+        let space = Length::em(5.0 / 18.0);
+        let operator = Operator {
+            field: Field::Unicode(arrow.symbol().to_string()),
+            form: Form::Infix,
+            stretch_constraints: Some(StretchConstraints::default()),
+            leading_space: space,
+            trailing_space: space,
+            ..Default::default()
+        };
+        let over_under = OverUnder {
+            nucleus: Some(MathExpression::new(MathItem::Operator(operator), user_data)),
+            over: above,
+            under: below,
+            ..Default::default()
+        };
+        MathExpression::new(MathItem::OverUnder(over_under), user_data)
+    }
+
+    /// Returns the state of aggregation of a substance (like `aq` or `s`) in parentheses.
+    ///
+    /// The parentheses are ordinary characters instead of fences, so they neither stretch to the
+    /// formula before them nor get any space around them, and the state is set upright.
+    pub fn chemical_state(state: &str, user_data: u64) -> MathExpression {
+        let text = format!("({})", state);
+        MathExpression::new(MathItem::Field(Field::Unicode(text)), user_data)
+    }
+}
+
+/// The arrows of chemical equations (like the arrows of the `mhchem` package).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReactionArrow {
+    /// A reaction from left to right (`->`).
+    Forward,
+    /// A reaction from right to left (`<-`).
+    Backward,
+    /// A resonance between two structures (`<->`).
+    Resonance,
+    /// A chemical equilibrium (`<=>`).
+    Equilibrium,
+}
+
+impl ReactionArrow {
+    /// Returns the character of the arrow.
+    pub fn symbol(self) -> char {
+        match self {
+            ReactionArrow::Forward => '\u{2192}',
+            ReactionArrow::Backward => '\u{2190}',
+            ReactionArrow::Resonance => '\u{2194}',
+            ReactionArrow::Equilibrium => '\u{21cc}',
+        }
+    }
+}
+
+/// A piece of a `TextRun`.
+#[derive(Debug, Clone)]
+pub enum TextRunItem {
+    /// Text that is split into words at whitespace.
+    Text(String),
+    /// A formula inside of the text.
+    Math(MathExpression),
+}
+
+/// Text with embedded formulas.
+///
+/// Every word is shaped on its own and words are separated by the width of a space in the font.
+/// Like in HTML any sequence of whitespace (including whitespace at the start or the end of a
+/// `TextRunItem::Text` next to a formula) counts as a single space.
+#[derive(Debug, Default, Clone)]
+pub struct TextRun {
+    pub items: Vec<TextRunItem>,
+}
+
+impl TextRun {
+    /// Returns the formulas inside of the text.
+    pub fn formulas(&self) -> impl Iterator<Item = &MathExpression> {
+        self.items.iter().filter_map(|item| match *item {
+            TextRunItem::Math(ref expr) => Some(expr),
+            TextRunItem::Text(_) => None,
+        })
+    }
+}
+
 /// An expression consisting of a radical symbol encapsulating the radicand and an optional degree
 /// expression that is displayed above the beginning of the surd.
 #[derive(Debug, Default, Clone)]
@@ -203,17 +674,41 @@ pub struct Root {
     pub degree: Option<MathExpression>,
 }
 
+/// The position of an operator relative to its operands.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Ord, PartialOrd)]
+pub enum Form {
+    Prefix,
+    Infix,
+    Postfix,
+}
+
+impl Default for Form {
+    fn default() -> Form {
+        Form::Infix
+    }
+}
+
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct StretchConstraints {
     pub min_size: Option<Length>,
     pub max_size: Option<Length>,
     pub symmetric: bool,
+    /// The difference between the parts above and below the math axis, as a fraction of the
+    /// stretch height, up to which a symmetric operator only grows to the stretch height instead
+    /// of twice the larger part. Overrides `LayoutOptions::asymmetry_tolerance` if set.
+    pub asymmetry_tolerance: Option<f32>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct Operator {
     pub stretch_constraints: Option<StretchConstraints>,
     pub is_large_op: bool,
+    /// Fences (like parentheses) only stretch if the enclosed content is taller than they are.
+    /// Prefix and postfix fences of a list are matched and grow to the content between them.
+    pub is_fence: bool,
+    pub form: Form,
+    /// Separators (like commas) keep their spacing in inline style.
+    pub is_separator: bool,
     pub leading_space: Length,
     pub trailing_space: Length,
     pub field: Field,
@@ -227,6 +722,9 @@ pub enum LengthUnit {
     Em,
     /// The minimum height to display a display operator.
     DisplayOperatorMinHeight,
+    /// A multiple of the default (unstretched) size of an operator. This is only meaningful for
+    /// the stretch constraints of operators.
+    OperatorDefaultSize,
 }
 
 /// Lengths are specified with a numeric value an a unit.
@@ -274,7 +772,7 @@ impl Default for Length {
 /// ```
 #[derive(Default, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub struct PercentValue {
-    percent: u8,
+    percent: u16,
 }
 
 impl PercentValue {
@@ -283,10 +781,25 @@ impl PercentValue {
         debug_assert!(value <= 100, "Not a valid percent value");
         // for release builds still make sure that percentage is valid
         let value = if value > 100 { 100u8 } else { value };
+        PercentValue {
+            percent: value.into(),
+        }
+    }
+
+    /// Create a new `PercentValue` that may also enlarge, i.e. exceed 100 percent.
+    ///
+    /// # Examples
+    /// ```
+    /// # use math_render::PercentValue;
+    /// let scale = PercentValue::magnification(150);
+    /// assert_eq!(300, 200 * scale);
+    /// ```
+    pub fn magnification(value: u16) -> PercentValue {
         PercentValue { percent: value }
     }
 
-    /// Returns the percentage as an unsigned integer.
+    /// Returns the percentage as an unsigned integer. Magnifications above 255 percent saturate
+    /// at 255, use `magnification_percentage` for those.
     ///
     /// # Examples
     /// ```
@@ -295,6 +808,20 @@ impl PercentValue {
     /// assert_eq!( 64, percent.as_percentage() );
     /// ```
     pub fn as_percentage(self) -> u8 {
+        self.percent.min(u16::from(u8::MAX)) as u8
+    }
+
+    /// Returns the percentage as an unsigned integer, including magnifications above 255
+    /// percent.
+    ///
+    /// # Examples
+    /// ```
+    /// # use math_render::PercentValue;
+    /// let percent = PercentValue::magnification(400);
+    /// assert_eq!( 400, percent.magnification_percentage() );
+    /// assert_eq!( 255, percent.as_percentage() );
+    /// ```
+    pub fn magnification_percentage(self) -> u16 {
         self.percent
     }
 
@@ -362,6 +889,49 @@ impl Div<PercentValue> for u32 {
     }
 }
 
+/// A scale with separate factors for the horizontal and the vertical direction.
+///
+/// Glyphs are usually scaled uniformly (e.g. in scripts) but delimiters that are stretched
+/// geometrically only grow vertically.
+///
+/// # Examples
+/// ```
+/// # use math_render::{PercentScale2D, PercentValue};
+/// let scale = PercentScale2D::new(PercentValue::new(50), PercentValue::magnification(200));
+/// assert!(!scale.is_uniform());
+/// assert_eq!(PercentScale2D::from(PercentValue::new(70)).vertical, PercentValue::new(70));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PercentScale2D {
+    pub horizontal: PercentValue,
+    pub vertical: PercentValue,
+}
+
+impl PercentScale2D {
+    pub fn new(horizontal: PercentValue, vertical: PercentValue) -> PercentScale2D {
+        PercentScale2D {
+            horizontal,
+            vertical,
+        }
+    }
+
+    /// Returns the scale that scales both directions by the same factor.
+    pub fn uniform(scale: PercentValue) -> PercentScale2D {
+        PercentScale2D::new(scale, scale)
+    }
+
+    /// Returns true if both directions are scaled by the same factor.
+    pub fn is_uniform(self) -> bool {
+        self.horizontal == self.vertical
+    }
+}
+
+impl From<PercentValue> for PercentScale2D {
+    fn from(scale: PercentValue) -> PercentScale2D {
+        PercentScale2D::uniform(scale)
+    }
+}
+
 /// A font-dependent representation of a (possibly scaled) glyph.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Glyph {
@@ -381,9 +951,77 @@ pub enum MathStyle {
     Inline,
 }
 
+/// The direction in which the characters of a text are written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TextDirection {
+    /// Left to right like Latin text.
+    Ltr,
+    /// Right to left like Hebrew or Arabic text.
+    Rtl,
+}
+
+impl Default for TextDirection {
+    fn default() -> TextDirection {
+        TextDirection::Ltr
+    }
+}
+
+/// Additional OpenType features that are applied when text is shaped, e.g. stylistic alternates
+/// (`salt`, `ss01` to `ss20`) or character variants (`cv01` to `cv99`).
+///
+/// The set has a fixed capacity, so that `LayoutStyle` stays `Copy`. Use a style callback (see
+/// `layout_with_style_context`) to request features for single tokens only.
+#[cfg(feature = "layout")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct FontFeatures {
+    features: [([u8; 4], u32); FontFeatures::CAPACITY],
+    len: usize,
+}
+
+#[cfg(feature = "layout")]
+impl FontFeatures {
+    /// The maximum number of features in a set.
+    pub const CAPACITY: usize = 8;
+
+    /// Returns an empty set.
+    pub fn new() -> FontFeatures {
+        Default::default()
+    }
+
+    /// Returns the set with the feature `tag` set to `value`. A value of 0 disables the feature,
+    /// 1 enables it and larger values select one of several alternates. A feature that is
+    /// already in the set gets the new value.
+    ///
+    /// # Panics
+    /// Panics if the set already contains `CAPACITY` other features.
+    pub fn with(mut self, tag: &[u8; 4], value: u32) -> FontFeatures {
+        let features = &mut self.features[..self.len];
+        match features.iter_mut().find(|feature| feature.0 == *tag) {
+            Some(feature) => feature.1 = value,
+            None => {
+                assert!(self.len < FontFeatures::CAPACITY, "too many font features");
+                self.features[self.len] = (*tag, value);
+                self.len += 1;
+            }
+        }
+        self
+    }
+
+    /// Returns the tags and values of the features in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8; 4], u32)> {
+        let features = &self.features[..self.len];
+        features.iter().map(|(tag, value)| (tag, *value))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
 /// Determines the general style how a math expression should be laid out.
 ///
 /// This affects lots of parameters when laying out an equation.
+#[cfg(feature = "layout")]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct LayoutStyle {
     /// This affects how much vertical space the equation will use.
@@ -399,8 +1037,17 @@ pub struct LayoutStyle {
     pub stretch_constraints: Option<Vector<i32>>,
     /// Specifies whether a diacritic should be typeset as an accent.
     pub as_accent: bool,
+    /// The direction of text. Right-to-left text is shaped as such, so its glyphs are placed
+    /// from right to left.
+    pub direction: TextDirection,
+    /// The size of glyphs relative to the size that the `script_level` implies (like the MathML
+    /// `mathsize` attribute).
+    pub math_size: PercentValue,
+    /// OpenType features that are applied in addition to those the layout selects itself.
+    pub font_features: FontFeatures,
 }
 
+#[cfg(feature = "layout")]
 impl LayoutStyle {
     /// Returns a new `LayoutStyle` with default settings
     pub fn new() -> LayoutStyle {
@@ -458,8 +1105,7 @@ impl LayoutStyle {
     pub fn superscript_style(self) -> LayoutStyle {
         LayoutStyle {
             math_style: MathStyle::Inline,
-            script_level: self.script_level + 1,
-            ..self
+            ..self.with_increased_script_level()
         }
     }
 
@@ -469,6 +1115,7 @@ impl LayoutStyle {
     }
 }
 
+#[cfg(feature = "layout")]
 impl Default for LayoutStyle {
     fn default() -> LayoutStyle {
         LayoutStyle {
@@ -478,6 +1125,9 @@ impl Default for LayoutStyle {
             flat_accent: false,
             stretch_constraints: None,
             as_accent: false,
+            direction: TextDirection::Ltr,
+            math_size: PercentValue::new(100),
+            font_features: FontFeatures::new(),
         }
     }
 }
@@ -497,7 +1147,7 @@ pub enum CornerPosition {
 }
 
 
-pub use self::CornerPosition::{TopLeft, TopRight, BottomLeft, BottomRight};
+use self::CornerPosition::{TopLeft, TopRight, BottomLeft, BottomRight};
 impl CornerPosition {
     /// Returns true if the position is left of the base
     pub fn is_left(self) -> bool {
@@ -556,4 +1206,15 @@ mod tests {
         let val = PercentValue::new(101);
         assert_eq!(val.as_percentage(), 101);
     }
+    #[cfg(feature = "layout")]
+    #[test]
+    fn font_features_test() {
+        let features = FontFeatures::new()
+            .with(b"ss01", 1)
+            .with(b"cv02", 3)
+            .with(b"ss01", 0);
+        let features: Vec<_> = features.iter().collect();
+        assert_eq!(features, vec![(b"ss01", 0), (b"cv02", 3)]);
+        assert!(FontFeatures::new().is_empty());
+    }
 }
@@ -0,0 +1,563 @@
+//! Helpers for placing formulas in documents, either as display equations or inside lines of
+//! text.
+
+use super::layout;
+use super::layout::MathLayout;
+use super::math_box::{Extents, MathBox, MathBoxContent, MathBoxMetrics};
+use super::shaper::{MathConstant, MathShaper};
+use super::{layout_expression, LayoutOptions};
+use crate::types::*;
+
+/// The side of the column an equation tag is placed on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TagPosition {
+    Left,
+    Right,
+}
+
+impl Default for TagPosition {
+    fn default() -> TagPosition {
+        TagPosition::Right
+    }
+}
+
+/// Describes how a numbered equation is placed inside its column.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct NumberingOptions {
+    /// The width of the column in font units.
+    pub column_width: i32,
+    /// The side the tag is put on.
+    pub tag_position: TagPosition,
+    /// The minimum horizontal distance between the equation and its tag in font units.
+    pub min_gap: i32,
+}
+
+/// Lays out `expression` centered in a column of the given width together with the tag
+/// `number` (e.g. "(1)") flush to the right or left border of the column.
+///
+/// If the equation is too close to the tag it is moved away from the tag. If the equation and
+/// the tag don't fit on one line at all the tag is placed on a separate line below (or above)
+/// the equation.
+pub fn layout_numbered(
+    expression: &MathExpression,
+    shaper: &impl MathShaper,
+    number: &MathExpression,
+    options: NumberingOptions,
+) -> MathBox {
+    let mut equation = layout(expression, shaper);
+    let mut tag = layout(number, shaper);
+
+    let column_width = options.column_width;
+    let equation_width = equation.advance_width();
+    let tag_width = tag.advance_width();
+    let centered = (column_width - equation_width) / 2;
+    let fits_on_line = equation_width + options.min_gap + tag_width <= column_width;
+
+    let equation_x = match options.tag_position {
+        TagPosition::Right if fits_on_line => {
+            let max_x = column_width - tag_width - options.min_gap - equation_width;
+            centered.min(max_x)
+        }
+        TagPosition::Left if fits_on_line => centered.max(tag_width + options.min_gap),
+        _ => centered.max(0),
+    };
+    let tag_x = match options.tag_position {
+        TagPosition::Right => column_width - tag_width,
+        TagPosition::Left => 0,
+    };
+
+    equation.origin.x += equation_x;
+    tag.origin.x += tag_x;
+    if !fits_on_line {
+        let leading = shaper.math_constant(MathConstant::MathLeading);
+        match options.tag_position {
+            TagPosition::Right => {
+                tag.origin.y += equation.extents().descent + leading + tag.extents().ascent
+            }
+            TagPosition::Left => {
+                tag.origin.y -= equation.extents().ascent + leading + tag.extents().descent
+            }
+        }
+    }
+
+    let column = MathBox::empty(
+        Extents::new(0, column_width, 0, 0),
+        expression.get_user_data(),
+    );
+    MathBox::with_vec(vec![column, equation, tag], expression.get_user_data())
+}
+
+
+/// Determines the point at which the rows of an aligned block are aligned.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AlignmentMarker {
+    /// Align at the first top-level operator that consists of the given character (e.g. `=`).
+    FirstOperator(char),
+    /// Align at the start of the top-level item with the given index.
+    Item(usize),
+}
+
+impl AlignmentMarker {
+    // Returns the index of the top-level list item the expression is aligned at.
+    fn find_in(self, expression: &MathExpression) -> Option<usize> {
+        let list = match *expression.item {
+            MathItem::List(ref list) => list,
+            _ => return None,
+        };
+        match self {
+            AlignmentMarker::FirstOperator(character) => list.iter().position(|item| {
+                match *item.item {
+                    MathItem::Operator(Operator {
+                        field: Field::Unicode(ref text),
+                        ..
+                    }) => text.chars().eq(Some(character)),
+                    _ => false,
+                }
+            }),
+            AlignmentMarker::Item(index) if index < list.len() => Some(index),
+            AlignmentMarker::Item(_) => None,
+        }
+    }
+}
+
+// Lays out the expressions and moves them horizontally such that their alignment points share
+// the same x coordinate.
+fn align_rows(
+    expressions: &[MathExpression],
+    shaper: &impl MathShaper,
+    marker: AlignmentMarker,
+) -> Vec<MathBox> {
+    let mut rows = Vec::with_capacity(expressions.len());
+    for expression in expressions {
+        let row = layout(expression, shaper);
+        let alignment_x = match (marker.find_in(expression), row.content()) {
+            (Some(index), &MathBoxContent::Boxes(ref boxes)) if index < boxes.len() => {
+                row.origin.x + boxes[index].origin.x
+            }
+            _ => row.origin.x + row.advance_width(),
+        };
+        rows.push((row, alignment_x));
+    }
+
+    let max_alignment_x = rows.iter().map(|&(_, x)| x).max().unwrap_or_default();
+    rows.into_iter()
+        .map(|(mut row, alignment_x)| {
+            row.origin.x += max_alignment_x - alignment_x;
+            row
+        })
+        .collect()
+}
+
+/// Lays out the expressions in rows below each other such that the alignment points of all rows
+/// share the same x coordinate (like the rows of an `align` environment in LaTeX).
+///
+/// Rows without an alignment point are aligned at their end. `row_gap` is the vertical distance
+/// between the ink of two adjacent rows in font units. The box of the rows gets the given user
+/// data, the rows keep the user data of their expressions.
+pub fn layout_aligned(
+    expressions: &[MathExpression],
+    shaper: &impl MathShaper,
+    marker: AlignmentMarker,
+    row_gap: i32,
+    user_data: u64,
+) -> MathBox {
+    let mut bottom = None;
+    let rows = align_rows(expressions, shaper, marker)
+        .into_iter()
+        .map(|mut row| {
+            if let Some(bottom) = bottom {
+                let top = row.origin.y - row.extents().ascent;
+                row.origin.y += bottom + row_gap - top;
+            }
+            bottom = Some(row.origin.y + row.extents().descent);
+            row
+        })
+        .collect();
+
+    MathBox::with_vec(rows, user_data)
+}
+
+/// A line of a formula that is set in several lines (see `stack_lines`).
+#[derive(Debug)]
+pub struct Line {
+    /// The content of the line. Its origin is relative to the baseline of the line.
+    pub math_box: MathBox,
+    /// The y coordinate of the baseline of the line relative to the baseline of the first line in
+    /// font units.
+    pub baseline: i32,
+}
+
+impl Line {
+    /// Returns the y coordinate of the top of the content relative to the baseline of the first
+    /// line.
+    pub fn top(&self) -> i32 {
+        self.baseline + self.math_box.origin.y - self.math_box.extents().ascent
+    }
+
+    /// Returns the y coordinate of the bottom of the content relative to the baseline of the
+    /// first line.
+    pub fn bottom(&self) -> i32 {
+        self.baseline + self.math_box.origin.y + self.math_box.extents().descent
+    }
+}
+
+/// Returns the distance between the baselines of two adjacent lines in font units. The lines are
+/// as close as possible while the `MathLeading` of the font separates the maximum descent of the
+/// upper line from the maximum ascent of the lower line.
+pub fn baseline_distance(upper: &MathBox, lower: &MathBox, shaper: &impl MathShaper) -> i32 {
+    let leading = shaper.math_constant(MathConstant::MathLeading);
+    let descent = upper.origin.y + upper.extents().descent;
+    let ascent = lower.extents().ascent - lower.origin.y;
+    descent + leading + ascent
+}
+
+/// Places the boxes as lines below each other (see `baseline_distance`) and returns the lines
+/// with their baselines. Renderers that break a block of lines across pages can move the lines
+/// independently.
+pub fn stack_lines(lines: Vec<MathBox>, shaper: &impl MathShaper) -> Vec<Line> {
+    let mut stacked: Vec<Line> = Vec::with_capacity(lines.len());
+    for math_box in lines {
+        let baseline = match stacked.last() {
+            Some(upper) => upper.baseline + baseline_distance(&upper.math_box, &math_box, shaper),
+            None => 0,
+        };
+        stacked.push(Line { math_box, baseline });
+    }
+    stacked
+}
+
+/// Lays out the expressions as lines like `layout_aligned` but keeps the lines apart by the
+/// `MathLeading` of the font and returns them separately (see `stack_lines`).
+pub fn layout_aligned_lines(
+    expressions: &[MathExpression],
+    shaper: &impl MathShaper,
+    marker: AlignmentMarker,
+) -> Vec<Line> {
+    stack_lines(align_rows(expressions, shaper, marker), shaper)
+}
+
+/// Describes how `break_lines` breaks a formula into lines.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct LinebreakOptions {
+    /// The maximum width of a line in font units.
+    pub max_width: i32,
+    /// The alignment of continuation lines whose break point has no `indent_align` of its own.
+    /// `IndentAlign::Auto` aligns them to the left.
+    pub indent_align: IndentAlign,
+    /// The shift of continuation lines in font units whose break point has no `indent_shift` of
+    /// its own.
+    pub indent_shift: i32,
+}
+
+/// A position in a list at which the list may be broken into lines.
+struct BreakPoint {
+    index: usize,
+    attributes: LinebreakAttributes,
+    is_space: bool,
+    penalty: u32,
+}
+
+impl BreakPoint {
+    /// Returns the end of the line that ends at the break point and the start of the next line.
+    fn line_ends(&self) -> (usize, usize) {
+        if self.is_space {
+            return (self.index, self.index + 1);
+        }
+        match self.attributes.style {
+            LinebreakStyle::Before => (self.index, self.index),
+            LinebreakStyle::After => (self.index + 1, self.index + 1),
+            LinebreakStyle::Duplicate => (self.index + 1, self.index),
+        }
+    }
+}
+
+/// Returns the line break attributes of an operator or of the core of an embellished operator.
+fn core_linebreak(expr: &MathExpression) -> Option<&LinebreakAttributes> {
+    if let Some(linebreak) = expr.linebreak() {
+        return Some(linebreak);
+    }
+    match *expr.item {
+        MathItem::Atom(Atom {
+            nucleus: Some(ref nucleus),
+            ..
+        })
+        | MathItem::OverUnder(OverUnder {
+            nucleus: Some(ref nucleus),
+            ..
+        }) => core_linebreak(nucleus),
+        MathItem::List(ref list) => {
+            layout::embellished_operator_of_list(list).and_then(core_linebreak)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the positions at which the list may be broken. Operators inside of a pair of fences
+/// are not break points.
+fn break_points(list: &[MathExpression], options: LayoutOptions) -> Vec<BreakPoint> {
+    let mut fence_depth = 0usize;
+    let mut points = Vec::new();
+    for (index, expr) in list.iter().enumerate() {
+        if let MathItem::Space(_) = *expr.item {
+            // spaces only become break points if they ask for it
+            if let Some(&attributes) = expr.linebreak() {
+                if fence_depth == 0 && attributes.linebreak != Linebreak::Auto {
+                    points.push(BreakPoint {
+                        index,
+                        attributes,
+                        is_space: true,
+                        penalty: 2,
+                    });
+                }
+            }
+            continue;
+        }
+        let properties = match expr.operator_properties(options) {
+            Some(properties) => properties,
+            None => continue,
+        };
+        if properties.is_fence && properties.form == Form::Postfix {
+            fence_depth = fence_depth.saturating_sub(1);
+        }
+        let attributes = core_linebreak(expr).copied().unwrap_or_default();
+        let penalty = match attributes.linebreak {
+            Linebreak::GoodBreak | Linebreak::Newline => Some(0),
+            Linebreak::Auto if properties.is_separator => Some(1),
+            Linebreak::Auto => Some(2),
+            Linebreak::BadBreak => Some(3),
+            Linebreak::NoBreak => None,
+        };
+        if let (Some(penalty), 0) = (penalty, fence_depth) {
+            points.push(BreakPoint {
+                index,
+                attributes,
+                is_space: false,
+                penalty,
+            });
+        }
+        if properties.is_fence && properties.form == Form::Prefix {
+            fence_depth += 1;
+        }
+    }
+    points
+}
+
+/// Breaks `expression` into lines that are at most `options.max_width` wide and returns them
+/// stacked below each other (see `stack_lines`).
+///
+/// The expression is broken at operators and spaces of its outermost list, honoring their line
+/// break attributes: `Linebreak::Newline` always breaks, `Linebreak::NoBreak` never breaks and
+/// otherwise each line is filled as far as possible while good break points (and separators) are
+/// preferred over bad ones. The `indent_align` and `indent_shift` of a break point position the
+/// line that follows it. Lines that can't be broken any further may exceed the maximum width.
+pub fn break_lines(
+    expression: &MathExpression,
+    shaper: &impl MathShaper,
+    options: LinebreakOptions,
+) -> Vec<Line> {
+    let mut layout_options = LayoutOptions::new(shaper).user_data(expression.get_user_data());
+    let mut expr = expression;
+    // an `mrow` that only wraps another `mrow` can be broken like the inner one
+    let list = loop {
+        let list = match *expr.item {
+            MathItem::List(ref list) => list,
+            _ => return stack_lines(vec![layout_expression(expr, layout_options)], shaper),
+        };
+        layout_options = layout_options.for_expression(expr);
+        match list.len() {
+            1 => expr = &list[0],
+            _ => break list,
+        }
+    };
+    let points = break_points(list, layout_options);
+    // The list is laid out once to measure the lines. The origin of an item is the sum of the
+    // advance widths, spaces and italic corrections before it, so a line is as wide as the
+    // distance from the left edge of its first item to the right edge of its last item (only
+    // stretchy operators may be a bit smaller in the line).
+    let edges = match *list.layout(layout_options).content() {
+        MathBoxContent::Boxes(ref items) => items
+            .iter()
+            .map(|item| (item.origin.x, item.origin.x + item.advance_width()))
+            .collect::<Vec<_>>(),
+        _ => unreachable!("lists are laid out as boxes"),
+    };
+    let line_width = |start: usize, end: usize| match end.checked_sub(1) {
+        Some(last) if last >= start => edges[last].1 - edges[start].0,
+        _ => 0,
+    };
+
+    let mut boxes = Vec::new();
+    let mut start = 0;
+    let mut indent = (IndentAlign::Left, 0);
+    loop {
+        let (align, shift) = indent;
+        let available = options.max_width - shift.max(0);
+        let candidates = points.iter().filter(|point| {
+            let (end, next) = point.line_ends();
+            point.index > start && end > start && next < list.len()
+        });
+        let forced = candidates
+            .clone()
+            .find(|point| point.attributes.linebreak == Linebreak::Newline);
+        let limit = forced
+            .map(|point| point.line_ends().0)
+            .unwrap_or(list.len());
+        let chosen = if line_width(start, limit) <= available {
+            forced
+        } else {
+            let candidates: Vec<_> = candidates
+                .take_while(|point| point.line_ends().0 < limit)
+                .collect();
+            candidates
+                .iter()
+                .filter(|point| line_width(start, point.line_ends().0) <= available)
+                .min_by_key(|point| (point.penalty, std::cmp::Reverse(point.index)))
+                .or_else(|| candidates.first())
+                .copied()
+                .or(forced)
+        };
+        let (end, next) = chosen
+            .map(BreakPoint::line_ends)
+            .unwrap_or((list.len(), list.len()));
+
+        let mut math_box = list[start..end].layout(layout_options);
+        math_box.origin.x += match align {
+            IndentAlign::Center => (options.max_width - math_box.advance_width()) / 2,
+            IndentAlign::Right => options.max_width - math_box.advance_width(),
+            IndentAlign::Left | IndentAlign::Auto => 0,
+        } + shift;
+        boxes.push(math_box);
+
+        let point = match chosen {
+            Some(point) => point,
+            None => break,
+        };
+        let align = match point.attributes.indent_align {
+            IndentAlign::Auto => options.indent_align,
+            align => align,
+        };
+        let shift = point
+            .attributes
+            .indent_shift
+            .map(|shift| shift.to_font_units(shaper))
+            .unwrap_or(options.indent_shift);
+        indent = (align, shift);
+        start = next;
+    }
+    stack_lines(boxes, shaper)
+}
+
+/// Combines lines into a single math box whose baseline is the baseline of the first line. The
+/// combined box gets the given user data.
+pub fn join_lines(lines: Vec<Line>, user_data: u64) -> MathBox {
+    let boxes = lines
+        .into_iter()
+        .map(|line| {
+            let mut math_box = line.math_box;
+            math_box.origin.y += line.baseline;
+            math_box
+        })
+        .collect();
+    MathBox::with_vec(boxes, user_data)
+}
+
+/// Returns the vertical shift in font units (positive values move downwards) that centers the
+/// math box on the math axis of a surrounding text line whose baseline is at y = 0.
+///
+/// Add the result to `math_box.origin.y` to apply it.
+pub fn axis_centering_shift(math_box: &MathBox, shaper: &impl MathShaper) -> i32 {
+    let extents = math_box.extents();
+    let center = math_box.origin.y + (extents.descent - extents.ascent) / 2;
+    -shaper.math_constant(MathConstant::AxisHeight) - center
+}
+
+/// The space a formula needs above and below the baseline of a text line.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct LineSpacing {
+    /// The distance from the baseline to the top of the line in font units.
+    pub above: i32,
+    /// The distance from the baseline to the bottom of the line in font units.
+    pub below: i32,
+}
+
+/// Returns the recommended space above and below the baseline of a text line containing the
+/// math box. It includes the `MathLeading` of the font so that formulas in adjacent lines don't
+/// collide.
+pub fn line_spacing(math_box: &MathBox, shaper: &impl MathShaper) -> LineSpacing {
+    let extents = math_box.extents();
+    let leading = shaper.math_constant(MathConstant::MathLeading);
+    LineSpacing {
+        above: extents.ascent - math_box.origin.y + leading,
+        below: extents.descent + math_box.origin.y + leading,
+    }
+}
+
+/// The space a formula may use above and below its baseline, e.g. inside a table cell.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct VerticalBudget {
+    /// The maximum height above the baseline in font units.
+    pub ascent: i32,
+    /// The maximum depth below the baseline in font units.
+    pub descent: i32,
+}
+
+/// Lays out `expression` so that it fits into `budget` if possible and returns the box together
+/// with the style the expression was laid out with.
+///
+/// The expression is first laid out in display style. If it is too tall it is laid out in inline
+/// style and then with increasing script levels until it fits. When even scriptscript size is too
+/// tall that layout is returned anyway.
+pub fn layout_to_fit(
+    expression: &MathExpression,
+    shaper: &impl MathShaper,
+    budget: VerticalBudget,
+) -> (MathBox, LayoutStyle) {
+    let options = LayoutOptions::new(shaper).user_data(expression.get_user_data());
+    let mut style = LayoutStyle::default();
+    loop {
+        let math_box = layout_expression(expression, options.style(style));
+        let extents = math_box.extents();
+        let fits = extents.ascent <= budget.ascent && extents.descent <= budget.descent;
+        if fits || style.script_level >= options.max_script_level {
+            return (math_box, style);
+        }
+        style = match style.math_style {
+            MathStyle::Display => style.inline_style(),
+            MathStyle::Inline => style.with_increased_script_level(),
+        };
+    }
+}
+
+/// The style `layout_with_max_width` laid out a formula with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WidthFit {
+    /// The formula fits in display style.
+    Display,
+    /// The formula only fits in inline style, i.e. with smaller large operators and limits as
+    /// scripts.
+    Inline,
+    /// The formula is too wide even in inline style and has to be broken into lines. The box is
+    /// laid out in inline style.
+    TooWide,
+}
+
+/// Lays out `expression` in display style unless its advance width exceeds `max_width` (in font
+/// units). Formulas that are too wide are laid out in inline style instead. Returns the box
+/// together with the style that was used.
+pub fn layout_with_max_width(
+    expression: &MathExpression,
+    shaper: &impl MathShaper,
+    max_width: i32,
+) -> (MathBox, WidthFit) {
+    let options = LayoutOptions::new(shaper).user_data(expression.get_user_data());
+    let display = layout_expression(expression, options);
+    if display.advance_width() <= max_width {
+        return (display, WidthFit::Display);
+    }
+    let inline = layout_expression(expression, options.style(options.style.inline_style()));
+    if inline.advance_width() <= max_width {
+        (inline, WidthFit::Inline)
+    } else {
+        (inline, WidthFit::TooWide)
+    }
+}
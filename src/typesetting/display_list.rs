@@ -0,0 +1,211 @@
+//! A flat list of absolutely positioned glyphs, rules and line segments.
+//!
+//! Most renderers don't care about the box structure of a formula. `MathBox::flatten` walks the
+//! box tree once, accumulates the origins and converts the absolute positions to device units so
+//! that backends only have to draw what is in the `DisplayList`.
+
+use super::math_box::{DeviceScale, Drawable, MathBox, MathBoxContent, Vector};
+use crate::types::{Color, Payload, PercentScale2D};
+
+/// A glyph placed at an absolute position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionedGlyph {
+    pub glyph_code: u32,
+    /// The position of the glyph origin on the baseline in device units (y increases
+    /// downwards).
+    pub position: Vector<f32>,
+}
+
+/// Glyphs that are rendered at the same size and belong to the same box.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphRun {
+    pub glyphs: Vec<PositionedGlyph>,
+    /// The size of the glyphs relative to their normal size (smaller in scripts).
+    pub scale: PercentScale2D,
+    /// The font size in device units at which the glyphs have to be drawn. This already
+    /// includes `scale`.
+    pub font_size: f32,
+    /// The font size in the vertical direction. It differs from `font_size` only for delimiters
+    /// that were stretched geometrically, whose glyphs have to be scaled around their baseline.
+    pub vertical_font_size: f32,
+    pub user_data: u64,
+}
+
+/// A filled rectangle like a fraction bar or the overbar of a radical.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rule {
+    /// The top left corner in device units.
+    pub origin: Vector<f32>,
+    pub width: f32,
+    pub height: f32,
+    pub user_data: u64,
+}
+
+/// A straight line like the diagonal strike of a cancelled expression. It is stroked centered
+/// on the segment from `from` to `to` with butt caps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    /// The end points in device units.
+    pub from: Vector<f32>,
+    pub to: Vector<f32>,
+    pub thickness: f32,
+    pub user_data: u64,
+}
+
+/// A rectangle filled with a color like the background of a highlighted subexpression.
+///
+/// Rectangles lie behind the other content of the formula, so they have to be drawn first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle {
+    /// The top left corner in device units.
+    pub origin: Vector<f32>,
+    pub width: f32,
+    pub height: f32,
+    pub color: Color,
+    pub user_data: u64,
+}
+
+/// Non-math content (like an image) that has to be drawn into the given rectangle.
+#[derive(Debug, Clone)]
+pub struct ForeignItem {
+    /// The top left corner in device units.
+    pub origin: Vector<f32>,
+    pub width: f32,
+    pub height: f32,
+    pub payload: Payload,
+    pub user_data: u64,
+}
+
+/// Everything that has to be drawn to render a `MathBox`.
+#[derive(Debug, Clone, Default)]
+pub struct DisplayList {
+    pub glyph_runs: Vec<GlyphRun>,
+    pub rules: Vec<Rule>,
+    pub segments: Vec<Segment>,
+    pub rectangles: Vec<Rectangle>,
+    pub foreign_items: Vec<ForeignItem>,
+}
+
+impl MathBox {
+    /// Converts the box tree into a flat list of glyph runs, rules, line segments, rectangles and
+    /// foreign objects whose positions are relative to the origin of the parent of this box.
+    ///
+    /// Like everywhere else only absolute coordinates are converted to device units, so the
+    /// rounding of `scale` doesn't accumulate.
+    pub fn flatten(&self, scale: &DeviceScale) -> DisplayList {
+        let mut list = DisplayList::default();
+        flatten_into(&mut list, self, Vector::default(), scale);
+        list
+    }
+}
+
+fn flatten_into(
+    list: &mut DisplayList,
+    math_box: &MathBox,
+    parent_origin: Vector<i32>,
+    scale: &DeviceScale,
+) {
+    let origin = parent_origin + math_box.origin;
+    match *math_box.content() {
+        MathBoxContent::Boxes(ref boxes) => {
+            for child in boxes {
+                flatten_into(list, child, origin, scale);
+            }
+        }
+        MathBoxContent::Drawable(Drawable::Glyphs {
+            ref glyphs,
+            scale: glyph_scale,
+        }) => {
+            let mut advance = 0;
+            let glyphs = glyphs
+                .iter()
+                .map(|glyph| {
+                    let offset = Vector {
+                        x: advance + glyph.offset.x,
+                        y: glyph.offset.y,
+                    };
+                    advance += glyph.advance_width;
+                    PositionedGlyph {
+                        glyph_code: glyph.glyph_code,
+                        position: scale.point_to_device(origin + offset * glyph_scale),
+                    }
+                })
+                .collect();
+            list.glyph_runs.push(GlyphRun {
+                glyphs,
+                scale: glyph_scale,
+                font_size: scale.pixels_per_em * glyph_scale.horizontal.as_scale_mult(),
+                vertical_font_size: scale.pixels_per_em * glyph_scale.vertical.as_scale_mult(),
+                user_data: math_box.user_data(),
+            });
+        }
+        MathBoxContent::Drawable(Drawable::Line { vector, thickness }) => {
+            // the line starts at the origin of the box and is stroked centered
+            let from = scale.point_to_device(origin);
+            let to = scale.point_to_device(origin + vector);
+            let thickness = scale.thickness_to_device(thickness as i32);
+            let half_thickness = thickness / 2.0;
+            // horizontal and vertical lines become rules of their bounding box, everything else
+            // has to be stroked
+            let (expand_x, expand_y) = if from.y == to.y {
+                (0.0, half_thickness)
+            } else if from.x == to.x {
+                (half_thickness, 0.0)
+            } else {
+                list.segments.push(Segment {
+                    from,
+                    to,
+                    thickness,
+                    user_data: math_box.user_data(),
+                });
+                return;
+            };
+            let left = from.x.min(to.x) - expand_x;
+            let top = from.y.min(to.y) - expand_y;
+            list.rules.push(Rule {
+                origin: Vector { x: left, y: top },
+                width: from.x.max(to.x) + expand_x - left,
+                height: from.y.max(to.y) + expand_y - top,
+                user_data: math_box.user_data(),
+            });
+        }
+        MathBoxContent::Drawable(Drawable::Rectangle { ref extents, color }) => {
+            let top_left = scale.point_to_device(Vector {
+                x: origin.x + extents.left_side_bearing,
+                y: origin.y - extents.ascent,
+            });
+            let bottom_right = scale.point_to_device(Vector {
+                x: origin.x + extents.right_edge(),
+                y: origin.y + extents.descent,
+            });
+            list.rectangles.push(Rectangle {
+                origin: top_left,
+                width: bottom_right.x - top_left.x,
+                height: bottom_right.y - top_left.y,
+                color,
+                user_data: math_box.user_data(),
+            });
+        }
+        MathBoxContent::Foreign {
+            ref extents,
+            ref payload,
+        } => {
+            let top_left = scale.point_to_device(Vector {
+                x: origin.x + extents.left_side_bearing,
+                y: origin.y - extents.ascent,
+            });
+            let bottom_right = scale.point_to_device(Vector {
+                x: origin.x + extents.right_edge(),
+                y: origin.y + extents.descent,
+            });
+            list.foreign_items.push(ForeignItem {
+                origin: top_left,
+                width: bottom_right.x - top_left.x,
+                height: bottom_right.y - top_left.y,
+                payload: payload.clone(),
+                user_data: math_box.user_data(),
+            });
+        }
+        MathBoxContent::Empty(_) => {}
+    }
+}
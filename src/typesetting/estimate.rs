@@ -0,0 +1,276 @@
+//! Approximate sizes of expressions that are cheaper to compute than a layout.
+
+use std::cmp::max;
+
+use super::layout::{
+    places_attachments_as_scripts, LayoutOptions, MathLayout, OperatorProperties, SpacingModel,
+};
+use super::math_box::{Extents, MathBox, MathBoxMetrics};
+use super::shaper::{MathConstant, MathShaper};
+use super::spacing::tex_spacing;
+use crate::types::*;
+
+/// Returns the approximate size of the expression laid out with `style` in font units. The width
+/// is the advance width and the ascent and descent are measured from the baseline.
+///
+/// Fields are shaped like in a layout, but stretchy operators keep their natural size (so fences
+/// around tall content make the estimate too small) and scripts, limits, fractions and radicals
+/// are placed using only the shifts and gaps of the math constants, ignoring kerning. Editors and
+/// line breakers can use the estimate to make quick decisions before laying out the expression.
+pub fn estimate_extents(
+    expression: &MathExpression,
+    shaper: &impl MathShaper,
+    style: LayoutStyle,
+) -> Extents<i32> {
+    estimate(expression, LayoutOptions::new(shaper).style(style))
+}
+
+// Returns the size of a laid out box relative to the origin of its parent.
+fn box_extents(math_box: &MathBox) -> Extents<i32> {
+    let extents = math_box.extents();
+    Extents::new(
+        0,
+        math_box.advance_width(),
+        extents.ascent - math_box.origin.y,
+        extents.descent + math_box.origin.y,
+    )
+}
+
+fn estimate(expression: &MathExpression, options: LayoutOptions) -> Extents<i32> {
+    let options = options.for_expression(expression);
+    if options.is_too_deep() {
+        return Extents::default();
+    }
+    match *expression.item {
+        MathItem::List(ref list) => estimate_list(list, options),
+        MathItem::Atom(ref atom) => match atom.nucleus {
+            Some(ref nucleus) => {
+                let extents = estimate(nucleus, options);
+                let extents = attach_scripts(
+                    extents,
+                    atom.bottom_right.as_ref(),
+                    atom.top_right.as_ref(),
+                    options,
+                );
+                attach_scripts(
+                    extents,
+                    atom.bottom_left.as_ref(),
+                    atom.top_left.as_ref(),
+                    options,
+                )
+            }
+            None => Extents::default(),
+        },
+        MathItem::OverUnder(ref over_under) => estimate_over_under(over_under, options),
+        MathItem::GeneralizedFraction(ref fraction) => estimate_fraction(fraction, options),
+        MathItem::Root(ref root) => estimate_root(root, options),
+        // everything else is laid out, which is cheap as long as nothing stretches
+        ref item => box_extents(&item.layout(options.stretch_size(None))),
+    }
+}
+
+fn estimate_list(list: &[MathExpression], options: LayoutOptions) -> Extents<i32> {
+    let mut extents = Extents::default();
+    for item in list {
+        let item_extents = estimate(item, options);
+        extents.width += item_extents.width + operator_spacing(item, options);
+        extents.ascent = max(extents.ascent, item_extents.ascent);
+        extents.descent = max(extents.descent, item_extents.descent);
+    }
+    if options.spacing == SpacingModel::TeX {
+        extents.width += tex_spacing(list, options).into_iter().sum::<i32>();
+    }
+    extents
+}
+
+// Returns the space around an operator in a list with MathML spacing (see
+// `layout_list_element`).
+fn operator_spacing(item: &MathExpression, options: LayoutOptions) -> i32 {
+    let style = options.for_expression(item).style;
+    match item.operator_properties(options) {
+        Some(OperatorProperties {
+            leading_space,
+            trailing_space,
+            is_separator,
+            ..
+        }) if options.spacing == SpacingModel::MathML => {
+            let separator_spacing = is_separator && style.script_level == 0;
+            if style.math_style == MathStyle::Display || separator_spacing {
+                leading_space + trailing_space
+            } else {
+                0
+            }
+        }
+        _ => 0,
+    }
+}
+
+// Returns the size of a base with the given subscript and superscript on one side.
+fn attach_scripts(
+    mut extents: Extents<i32>,
+    subscript: Option<&MathExpression>,
+    superscript: Option<&MathExpression>,
+    options: LayoutOptions,
+) -> Extents<i32> {
+    let shaper = options.shaper;
+    let mut scripts_width = None;
+    if let Some(superscript) = superscript {
+        let superscript = estimate(
+            superscript,
+            options.style(options.style.superscript_style()),
+        );
+        let shift_up = shaper.math_constant(if options.style.is_cramped {
+            MathConstant::SuperscriptShiftUpCramped
+        } else {
+            MathConstant::SuperscriptShiftUp
+        });
+        extents.ascent = max(extents.ascent, shift_up + superscript.ascent);
+        extents.descent = max(extents.descent, superscript.descent - shift_up);
+        scripts_width = Some(superscript.width);
+    }
+    if let Some(subscript) = subscript {
+        let subscript = estimate(subscript, options.style(options.style.subscript_style()));
+        let shift_down = shaper.math_constant(MathConstant::SubscriptShiftDown);
+        extents.ascent = max(extents.ascent, subscript.ascent - shift_down);
+        extents.descent = max(extents.descent, shift_down + subscript.descent);
+        scripts_width = Some(max(scripts_width.unwrap_or_default(), subscript.width));
+    }
+    if let Some(scripts_width) = scripts_width {
+        extents.width += scripts_width + shaper.math_constant(MathConstant::SpaceAfterScript);
+    }
+    extents
+}
+
+fn estimate_over_under(over_under: &OverUnder, options: LayoutOptions) -> Extents<i32> {
+    let nucleus = match over_under.nucleus {
+        Some(ref nucleus) => nucleus,
+        None => return Extents::default(),
+    };
+    if places_attachments_as_scripts(over_under, nucleus, options) {
+        let extents = estimate(nucleus, options);
+        let (under, over) = (over_under.under.as_ref(), over_under.over.as_ref());
+        return attach_scripts(extents, under, over, options);
+    }
+
+    let shaper = options.shaper;
+    let mut extents = estimate(nucleus, options);
+    if let Some(ref over) = over_under.over {
+        let style = options.style.inline_style().no_flat_accent_style();
+        if over_under.over_is_accent {
+            // accents are drawn above the accent base height and move up with taller bases
+            let over = estimate(over, options.style(style));
+            let base_height = shaper.math_constant(MathConstant::AccentBaseHeight);
+            let shift_up = max(0, extents.ascent - base_height);
+            extents.ascent = max(extents.ascent, shift_up + over.ascent);
+            extents.width = max(extents.width, over.width);
+        } else {
+            let over = estimate(over, options.style(style.superscript_style()));
+            let gap = shaper.math_constant(MathConstant::UpperLimitGapMin);
+            extents.ascent += gap + over.height();
+            extents.width = max(extents.width, over.width);
+        }
+    }
+    if let Some(ref under) = over_under.under {
+        let style = options
+            .style
+            .inline_style()
+            .no_flat_accent_style()
+            .cramped_style();
+        let (style, gap) = if over_under.under_is_accent {
+            (style, 0)
+        } else {
+            let gap = shaper.math_constant(MathConstant::LowerLimitGapMin);
+            (style.subscript_style(), gap)
+        };
+        let under = estimate(under, options.style(style));
+        extents.descent += gap + under.height();
+        extents.width = max(extents.width, under.width);
+    }
+    extents
+}
+
+fn estimate_fraction(fraction: &GeneralizedFraction, options: LayoutOptions) -> Extents<i32> {
+    let (numerator, denominator) =
+        match (fraction.numerator.as_ref(), fraction.denominator.as_ref()) {
+            (Some(numerator), Some(denominator)) => (numerator, denominator),
+            _ => return Extents::default(),
+        };
+    let display = options.style.math_style == MathStyle::Display;
+    let mut numerator_style = options.style;
+    if display {
+        numerator_style.math_style = MathStyle::Inline;
+    } else {
+        numerator_style = numerator_style.with_increased_script_level();
+    }
+    let numerator = estimate(numerator, options.style(numerator_style));
+    let denominator = estimate(denominator, options.style(numerator_style.cramped_style()));
+
+    let is_stack = matches!(fraction.rule_thickness(), Some(thickness) if thickness.is_null());
+    let (shift_up, shift_down) = match (is_stack, display) {
+        (false, false) => (
+            MathConstant::FractionNumeratorShiftUp,
+            MathConstant::FractionDenominatorShiftDown,
+        ),
+        (false, true) => (
+            MathConstant::FractionNumeratorDisplayStyleShiftUp,
+            MathConstant::FractionDenominatorDisplayStyleShiftDown,
+        ),
+        (true, false) => (
+            MathConstant::StackTopShiftUp,
+            MathConstant::StackBottomShiftDown,
+        ),
+        (true, true) => (
+            MathConstant::StackTopDisplayStyleShiftUp,
+            MathConstant::StackBottomDisplayStyleShiftDown,
+        ),
+    };
+    Extents::new(
+        0,
+        max(numerator.width, denominator.width),
+        options.shaper.math_constant(shift_up) + numerator.ascent,
+        options.shaper.math_constant(shift_down) + denominator.descent,
+    )
+}
+
+fn estimate_root(root: &Root, options: LayoutOptions) -> Extents<i32> {
+    let radicand = match root.radicand {
+        Some(ref radicand) => radicand,
+        None => return Extents::default(),
+    };
+    let shaper = options.shaper;
+    let radicand = estimate(radicand, options.style(options.style.cramped_style()));
+    // the surd is stretched to the height of the radicand, so only its width is used
+    let surd_width = options
+        .radical_symbol
+        .glyph(shaper)
+        .map(|glyph| {
+            shaper
+                .shape_glyph(glyph, options.style, options.user_data)
+                .advance_width()
+        })
+        .unwrap_or_default();
+    let vertical_gap = shaper.math_constant(if options.style.math_style == MathStyle::Inline {
+        MathConstant::RadicalVerticalGap
+    } else {
+        MathConstant::RadicalDisplayStyleVerticalGap
+    });
+    let mut extents = Extents::new(
+        0,
+        surd_width + radicand.width,
+        radicand.ascent
+            + vertical_gap
+            + options.radical_rule_thickness_units()
+            + shaper.math_constant(MathConstant::RadicalExtraAscender),
+        radicand.descent,
+    );
+    if let Some(ref degree) = root.degree {
+        let mut degree_options = options;
+        degree_options.style.script_level = options.style.script_level.saturating_add(2);
+        degree_options.style.math_style = MathStyle::Inline;
+        let degree = estimate(degree, degree_options);
+        let kern_before = shaper.math_constant(MathConstant::RadicalKernBeforeDegree);
+        let kern_after = shaper.math_constant(MathConstant::RadicalKernAfterDegree);
+        extents.width += max(0, kern_before + degree.width + kern_after);
+    }
+    extents
+}
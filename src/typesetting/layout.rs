@@ -1,22 +1,218 @@
 #![allow(unused_variables, dead_code)]
 use crate::types::*;
+use std::any::Any;
+use std::cell::RefCell;
 use std::cmp::{max, min};
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
 
-use super::math_box::{Extents, MathBox, MathBoxMetrics, Vector};
+use super::math_box::{Drawable, Extents, MathBox, MathBoxContent, MathBoxMetrics, Vector};
 use super::multiscripts::*;
 use super::shaper::{MathConstant, MathShaper};
+use super::spacing::tex_spacing;
 use super::stretchy::*;
 
+/// Describes the expression whose style is requested from a style callback.
+#[derive(Copy, Clone, Debug)]
+pub struct StyleContext<'a> {
+    /// The user data of the expression.
+    pub user_data: u64,
+    /// The markup element the expression was created from (if known).
+    pub element: Option<&'a ElementInfo>,
+}
+
+fn keep_style(style: LayoutStyle, _: &StyleContext) -> LayoutStyle {
+    style
+}
+
+/// The parameters for laying out an expression with `layout_expression`.
+///
+/// Use `LayoutOptions::new` and the builder methods to lay out a subexpression in a custom
+/// container, e.g. a matrix cell whose delimiters should match the height of the whole row.
 #[derive(Copy, Clone)]
 pub struct LayoutOptions<'a> {
     pub shaper: &'a dyn MathShaper,
-    pub style_provider: &'a dyn Fn(LayoutStyle, u64) -> LayoutStyle,
+    pub style_provider: &'a dyn Fn(LayoutStyle, &StyleContext) -> LayoutStyle,
     pub style: LayoutStyle,
+    /// The size stretchy operators grow to. Lists determine the size of their stretchy operators
+    /// themselves unless the list is an embellished operator.
     pub stretch_size: Option<Extents<i32>>,
     pub user_data: u64,
+    /// Scripts nested deeper than this level are laid out like scripts of this level.
+    pub max_script_level: u8,
+    /// How the space between the items of lists is determined.
+    pub spacing: SpacingModel,
+    /// How asymmetric the stretch size of a symmetric operator may be (as a fraction of its
+    /// height) before the operator grows to twice the larger part above or below the math axis.
+    /// The default of 0 always stretches to the symmetric size.
+    pub asymmetry_tolerance: f32,
+    /// Where the limits of integrals are placed.
+    pub integral_limits: IntegralLimits,
+    /// Whether scripts are kerned with the `MathKernInfo` of the font.
+    pub math_kerning: MathKerning,
+    /// The symbol drawn in front of the radicand of roots.
+    pub radical_symbol: RadicalSymbol,
+    /// The thickness of the rule over the radicand of roots as a multiple of the
+    /// `RadicalRuleThickness` of the font.
+    pub radical_rule_thickness: f32,
+    /// Collects the problems that were worked around with placeholders (see `try_layout`).
+    pub errors: Option<&'a RefCell<Vec<LayoutError>>>,
+    /// The nesting depth of the expression that is laid out, counted by `for_expression`.
+    pub depth: usize,
+    /// Expressions nested deeper than this are laid out as empty boxes (see
+    /// `LayoutError::NestingTooDeep`), so that expressions built with the API or `edit` can't
+    /// overflow the stack.
+    pub max_depth: usize,
+}
+
+/// A part of a formula that couldn't be laid out as described.
+///
+/// `layout` substitutes placeholders (usually empty or unstretched boxes) for such parts while
+/// `try_layout` returns the first problem it encounters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    /// The font has no OpenType MATH table.
+    MissingMathTable,
+    /// The font has no glyph for a character the layout needs (e.g. the surd of a radical).
+    MissingGlyph(char),
+    /// A stretchy operator is given as a glyph index instead of text. Such operators are laid
+    /// out with their normal size.
+    UnstretchableGlyphOperator,
+    /// The expression is nested deeper than `LayoutOptions::max_depth` allows. The parts that
+    /// are too deep are left out.
+    NestingTooDeep(usize),
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            LayoutError::MissingMathTable => write!(f, "the font has no MATH table"),
+            LayoutError::MissingGlyph(c) => {
+                write!(f, "the font has no glyph for U+{:04X}", c as u32)
+            }
+            LayoutError::UnstretchableGlyphOperator => {
+                write!(f, "operators given as glyph indices can't be stretched")
+            }
+            LayoutError::NestingTooDeep(max_depth) => {
+                write!(
+                    f,
+                    "the expression is nested deeper than {} levels",
+                    max_depth
+                )
+            }
+        }
+    }
+}
+
+impl Error for LayoutError {}
+
+/// How the space between the items of a list is determined.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SpacingModel {
+    /// Operators are surrounded by their leading and trailing space like in MathML.
+    MathML,
+    /// The space between two items depends on their spacing classes like in TeX. The leading and
+    /// trailing space of operators is ignored.
+    TeX,
+}
+
+impl Default for SpacingModel {
+    fn default() -> SpacingModel {
+        SpacingModel::MathML
+    }
+}
+
+/// Where the limits of integrals (given as under- and overscripts that don't specify their
+/// placement) are placed in display style.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IntegralLimits {
+    /// As scripts to the right of the integral sign with the lower limit tucked in by the italic
+    /// correction like in TeX.
+    Scripts,
+    /// Above and below the integral sign like the limits of other large operators, which is
+    /// common in ISO style.
+    Limits,
+}
+
+impl Default for IntegralLimits {
+    fn default() -> IntegralLimits {
+        IntegralLimits::Scripts
+    }
+}
+
+/// How the kerning between a nucleus and its scripts from the `MathKernInfo` table of the font is
+/// applied.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MathKerning {
+    /// The kerning of the font is applied as is.
+    Enabled,
+    /// Scripts are placed without kerning, i.e. only the italic correction of the nucleus moves
+    /// them.
+    Disabled,
+    /// The kerning of a script is limited to the given amount in font units in either direction,
+    /// so broken kerning tables can't make scripts overlap their nucleus.
+    Clamped(i32),
+}
+
+impl Default for MathKerning {
+    fn default() -> MathKerning {
+        MathKerning::Enabled
+    }
+}
+
+/// The symbol drawn in front of the radicand of a root. It is stretched to the height of the
+/// radicand if the font has larger variants of it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RadicalSymbol {
+    /// The glyph the font maps the character to.
+    Character(char),
+    /// The glyph with the given index in the font, e.g. an alternate design of the surd that
+    /// can't be reached through the cmap.
+    Glyph(u32),
+}
+
+impl Default for RadicalSymbol {
+    fn default() -> RadicalSymbol {
+        RadicalSymbol::Character('\u{221A}') // Square Root
+    }
+}
+
+impl RadicalSymbol {
+    /// Returns the glyph of the symbol or `None` if the font doesn't cover the character.
+    pub(crate) fn glyph(self, shaper: &dyn MathShaper) -> Option<u32> {
+        match self {
+            RadicalSymbol::Character(c) => shaper.nominal_glyph(c),
+            RadicalSymbol::Glyph(glyph) => Some(glyph),
+        }
+    }
 }
 
 impl<'a> LayoutOptions<'a> {
+    /// Returns the options `layout` uses: display style, no stretch size and the style of
+    /// subexpressions left unchanged.
+    pub fn new(shaper: &'a dyn MathShaper) -> Self {
+        LayoutOptions {
+            shaper,
+            style_provider: &keep_style,
+            style: LayoutStyle::default(),
+            stretch_size: None,
+            user_data: 0,
+            max_script_level: 2,
+            spacing: SpacingModel::default(),
+            asymmetry_tolerance: 0.0,
+            integral_limits: IntegralLimits::default(),
+            math_kerning: MathKerning::default(),
+            radical_symbol: RadicalSymbol::default(),
+            radical_rule_thickness: 1.0,
+            errors: None,
+            depth: 0,
+            // a bit deeper than the parser allows by default (see `ParserOptions::max_depth`)
+            // since token elements may hold several fields
+            max_depth: 128,
+        }
+    }
+
     pub fn style(self, new_style: LayoutStyle) -> Self {
         LayoutOptions {
             style: new_style,
@@ -24,9 +220,142 @@ impl<'a> LayoutOptions<'a> {
         }
     }
 
+    pub fn style_provider(
+        self,
+        style_provider: &'a dyn Fn(LayoutStyle, &StyleContext) -> LayoutStyle,
+    ) -> Self {
+        LayoutOptions {
+            style_provider,
+            ..self
+        }
+    }
+
+    /// Makes stretchy operators grow to `stretch_size` instead of their natural size.
+    pub fn stretch_size(self, stretch_size: Option<Extents<i32>>) -> Self {
+        LayoutOptions {
+            stretch_size,
+            ..self
+        }
+    }
+
     pub fn user_data(self, user_data: u64) -> Self {
         LayoutOptions { user_data, ..self }
     }
+
+    pub fn max_script_level(self, max_script_level: u8) -> Self {
+        LayoutOptions {
+            max_script_level,
+            ..self
+        }
+    }
+
+    pub fn spacing(self, spacing: SpacingModel) -> Self {
+        LayoutOptions { spacing, ..self }
+    }
+
+    pub fn asymmetry_tolerance(self, asymmetry_tolerance: f32) -> Self {
+        LayoutOptions {
+            asymmetry_tolerance,
+            ..self
+        }
+    }
+
+    pub fn integral_limits(self, integral_limits: IntegralLimits) -> Self {
+        LayoutOptions {
+            integral_limits,
+            ..self
+        }
+    }
+
+    /// Sets whether scripts are kerned with the `MathKernInfo` of the font.
+    pub fn math_kerning(self, math_kerning: MathKerning) -> Self {
+        LayoutOptions {
+            math_kerning,
+            ..self
+        }
+    }
+
+    /// Sets the symbol drawn in front of the radicand of roots (√ by default).
+    pub fn radical_symbol(self, radical_symbol: RadicalSymbol) -> Self {
+        LayoutOptions {
+            radical_symbol,
+            ..self
+        }
+    }
+
+    /// Scales the rule over the radicand of roots relative to the `RadicalRuleThickness` of the
+    /// font. Negative factors are treated as 0 and NaN as the default of 1.
+    pub fn radical_rule_thickness(self, radical_rule_thickness: f32) -> Self {
+        LayoutOptions {
+            radical_rule_thickness: clamp_rule_thickness(radical_rule_thickness),
+            ..self
+        }
+    }
+
+    /// Returns the thickness of the rule over the radicand of roots in font units.
+    pub(crate) fn radical_rule_thickness_units(self) -> i32 {
+        let thickness = self
+            .shaper
+            .math_constant(MathConstant::RadicalRuleThickness);
+        // the field may have been set without the builder
+        let factor = clamp_rule_thickness(self.radical_rule_thickness);
+        max(0, (thickness as f32 * factor).round() as i32)
+    }
+
+    /// Sets how deep expressions may be nested.
+    pub fn max_depth(self, max_depth: usize) -> Self {
+        LayoutOptions { max_depth, ..self }
+    }
+
+    /// Returns whether the expression laid out with these options is nested too deep to be laid
+    /// out.
+    pub(crate) fn is_too_deep(self) -> bool {
+        self.depth > self.max_depth
+    }
+
+    /// Makes the layout push the problems it works around to `errors`.
+    pub fn errors(self, errors: &'a RefCell<Vec<LayoutError>>) -> Self {
+        LayoutOptions {
+            errors: Some(errors),
+            ..self
+        }
+    }
+
+    fn report(self, error: LayoutError) {
+        if let Some(errors) = self.errors {
+            errors.borrow_mut().push(error);
+        }
+    }
+
+    /// Returns the options that `expr` is laid out with when it is laid out with `self`, i.e.
+    /// with the attributes of its element and the style provider applied to the style.
+    pub fn for_expression(self, expr: &MathExpression) -> Self {
+        let mut old_style = self.style;
+        // the direction of an element (e.g. of an `mrow`) applies to all text inside of it
+        match expr.element().and_then(|element| element.attribute("dir")) {
+            Some("ltr") => old_style.direction = TextDirection::Ltr,
+            Some("rtl") => old_style.direction = TextDirection::Rtl,
+            _ => {}
+        }
+        let math_size = expr
+            .element()
+            .and_then(|element| element.attribute("mathsize"))
+            .and_then(|size| apply_math_size(size, old_style.math_size, self.shaper));
+        if let Some(math_size) = math_size {
+            old_style.math_size = math_size;
+        }
+        let context = StyleContext {
+            user_data: expr.get_user_data(),
+            element: expr.element(),
+        };
+        let mut new_style = (self.style_provider)(old_style, &context);
+        new_style.script_level = min(new_style.script_level, self.max_script_level);
+
+        LayoutOptions {
+            depth: self.depth + 1,
+            ..self.style(new_style).user_data(expr.get_user_data())
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Default)]
@@ -41,10 +370,13 @@ pub struct OperatorProperties {
     pub leading_space: i32,
     pub trailing_space: i32,
     pub is_large_op: bool,
+    pub is_fence: bool,
+    pub is_separator: bool,
+    pub form: Form,
 }
 
 impl Length {
-    fn to_font_units(self, shaper: &dyn MathShaper) -> i32 {
+    pub(crate) fn to_font_units(self, shaper: &dyn MathShaper) -> i32 {
         if self.is_null() {
             return 0;
         }
@@ -57,10 +389,52 @@ impl Length {
                 (shaper.math_constant(MathConstant::DisplayOperatorMinHeight) as f32 * self.value)
                     as i32
             }
+            // Without knowledge of the operator the best guess for its default size is one em.
+            LengthUnit::OperatorDefaultSize => Length::em(self.value).to_font_units(shaper),
         }
     }
 }
 
+/// Applies the value of a `mathsize` attribute to the current math size. Sizes in em, percent or
+/// without a unit are relative to the current size while sizes in points set the size of glyphs
+/// at script level 0.
+fn apply_math_size(
+    value: &str,
+    math_size: PercentValue,
+    shaper: &dyn MathShaper,
+) -> Option<PercentValue> {
+    let value = value.trim();
+    let current = f32::from(math_size.magnification_percentage());
+    let (number, percent_per_unit) = if let Some(percentage) = value.strip_suffix('%') {
+        (percentage, current / 100.0)
+    } else if let Some(points) = value.strip_suffix("pt") {
+        (points, 100.0 / shaper.ppem().0 as f32)
+    } else {
+        (value.strip_suffix("em").unwrap_or(value), current)
+    };
+    let percent = number.trim().parse::<f32>().ok()? * percent_per_unit;
+    let percent = percent.round().min(f32::from(u16::MAX));
+    // a size of zero is invalid like any other bad attribute value since lengths are divided by
+    // the size
+    if percent.is_finite() && percent >= 1.0 {
+        Some(PercentValue::magnification(percent as u16))
+    } else {
+        None
+    }
+}
+
+/// Makes a factor for the thickness of a rule non-negative.
+fn clamp_rule_thickness(factor: f32) -> f32 {
+    if factor.is_nan() {
+        1.0
+    } else {
+        factor.max(0.0)
+    }
+}
+
+/// The space on both sides of the label of an extensible arrow in em (5mu like in amsmath).
+const EXTENSIBLE_ARROW_PADDING: f32 = 5.0 / 18.0;
+
 fn clamp<T: Ord, U: Into<Option<T>>>(value: T, min: U, max: U) -> T {
     if let Some(min) = min.into() {
         if value < min {
@@ -98,7 +472,11 @@ impl MathLayout for Field {
     fn layout(&self, options: LayoutOptions) -> MathBox {
         match *self {
             Field::Empty => MathBox::default(),
-            Field::Glyph(ref glyph) => unimplemented!(),
+            Field::Glyph(ref glyph) => {
+                options
+                    .shaper
+                    .shape_glyph(glyph.glyph_code, options.style, options.user_data)
+            }
             Field::Unicode(ref content) => {
                 let shaper = options.shaper;
                 shaper.shape(&content, options.style, options.user_data)
@@ -110,21 +488,47 @@ impl MathLayout for Field {
 impl MathLayout for [MathExpression] {
     fn layout(&self, options: LayoutOptions) -> MathBox {
         let boxes = layout_strechy_list(self, options);
+        let spaces = match options.spacing {
+            SpacingModel::MathML => vec![0; self.len()],
+            SpacingModel::TeX => tex_spacing(self, options),
+        };
 
         let mut cursor = 0i32;
         let mut previout_italic_correction = 0;
-        let layouted = boxes.into_iter().map(move |mut math_box| {
-            // apply italic correction if current glyph is upright
-            if math_box.italic_correction() == 0 {
-                cursor += previout_italic_correction;
-            }
-            math_box.origin.x += cursor;
-            cursor += math_box.advance_width();
-            previout_italic_correction = math_box.italic_correction();
-            math_box
-        });
+        let layouted = boxes
+            .into_iter()
+            .zip(spaces)
+            .map(move |(mut math_box, space)| {
+                // apply italic correction if current glyph is upright
+                if math_box.italic_correction() == 0 {
+                    cursor += previout_italic_correction;
+                }
+                cursor += space;
+                math_box.origin.x += cursor;
+                cursor += math_box.advance_width();
+                previout_italic_correction = math_box.italic_correction();
+                math_box
+            });
         MathBox::with_vec(layouted.collect(), options.user_data)
     }
+
+    fn operator_properties(&self, options: LayoutOptions) -> Option<OperatorProperties> {
+        embellished_operator_of_list(self)
+            .and_then(|operator| operator.operator_properties(options))
+    }
+}
+
+/// Returns the operator of a list that consists of one embellished operator and spaces. Like in
+/// MathML such a list is an embellished operator itself.
+pub fn embellished_operator_of_list(list: &[MathExpression]) -> Option<&MathExpression> {
+    let mut operators = list.iter().filter(|item| match *item.item {
+        MathItem::Space(_) => false,
+        _ => true,
+    });
+    match (operators.next(), operators.next()) {
+        (Some(operator), None) => Some(operator),
+        _ => None,
+    }
 }
 
 impl MathLayout for Vec<MathExpression> {
@@ -158,17 +562,21 @@ fn layout_sub_superscript(
         Some(nucleus) => nucleus,
         None => return MathBox::empty(Extents::default(), options.user_data),
     };
+    // only the nucleus of an embellished operator stretches, never its scripts
     let subscript_options = LayoutOptions {
         style: options.style.subscript_style(),
+        stretch_size: None,
         ..options
     };
     let superscript_options = LayoutOptions {
         style: options.style.superscript_style(),
+        stretch_size: None,
         ..options
     };
     let subscript = subscript.map(|x| x.layout(subscript_options));
     let superscript = superscript.map(|x| x.layout(superscript_options));
     let nucleus_is_largeop = nucleus.is_large_op(options);
+    // the scripts are placed at the ends of the stretched nucleus
     let mut nucleus = nucleus.layout(options);
 
     let space_after_script = options.shaper.math_constant(MathConstant::SpaceAfterScript);
@@ -243,6 +651,43 @@ fn layout_sub_superscript(
     MathBox::with_vec(result, options.user_data)
 }
 
+/// Whether the attachments of an `OverUnder` are placed like the scripts of an `Atom`. This is the
+/// case for limits in inline style and (depending on the options) for the limits of integrals.
+pub(crate) fn places_attachments_as_scripts(
+    over_under: &OverUnder,
+    nucleus: &MathExpression,
+    options: LayoutOptions,
+) -> bool {
+    match over_under.limits_placement {
+        LimitsPlacement::Auto
+            if options.integral_limits == IntegralLimits::Scripts && is_integral(nucleus) =>
+        {
+            true
+        }
+        LimitsPlacement::Auto => {
+            over_under.is_limits && options.style.math_style == MathStyle::Inline
+        }
+        LimitsPlacement::Limits => false,
+        LimitsPlacement::Scripts => true,
+    }
+}
+
+/// Whether the expression is an integral sign or an embellished integral sign.
+fn is_integral(expr: &MathExpression) -> bool {
+    match *expr.item {
+        MathItem::Operator(Operator {
+            field: Field::Unicode(ref text),
+            ..
+        }) => text
+            .chars()
+            .any(|chr| matches!(chr, '\u{222B}'..='\u{2233}' | '\u{2A0B}'..='\u{2A1C}')),
+        MathItem::List(ref list) => embellished_operator_of_list(list)
+            .map(is_integral)
+            .unwrap_or_default(),
+        _ => false,
+    }
+}
+
 impl MathLayout for OverUnder {
     fn layout(&self, options: LayoutOptions) -> MathBox {
         let nucleus = match self.nucleus {
@@ -250,9 +695,7 @@ impl MathLayout for OverUnder {
             None => return MathBox::empty(Extents::default(), options.user_data),
         };
 
-        // Display `OverUnder` like an `Atom` if we want to render limits and the current style is
-        // inline style.
-        if self.is_limits && options.style.math_style == MathStyle::Inline {
+        if places_attachments_as_scripts(self, nucleus, options) {
             return layout_sub_superscript(
                 self.under.as_ref(),
                 self.over.as_ref(),
@@ -272,16 +715,27 @@ impl MathLayout for OverUnder {
         if !self.over_is_accent {
             over_options.style = over_options.style.superscript_style();
         }
+        // like subscripts, underscripts are always cramped
         let mut under_options = LayoutOptions {
-            style: options.style.inline_style().no_flat_accent_style(),
+            style: options
+                .style
+                .inline_style()
+                .no_flat_accent_style()
+                .cramped_style(),
             stretch_size: None,
             ..options
         };
         if !self.under_is_accent {
             under_options.style = under_options.style.subscript_style();
         }
+        // the base of an accent must not move the accent up more than necessary
+        let nucleus_options = if self.over.is_some() && self.over_is_accent {
+            options.style(options.style.cramped_style())
+        } else {
+            options
+        };
         let mut arguments = [
-            (Some(nucleus), options, false),
+            (Some(nucleus), nucleus_options, false),
             (self.over.as_ref(), over_options, self.over_is_accent),
             (self.under.as_ref(), under_options, self.under_is_accent),
         ];
@@ -309,6 +763,25 @@ impl MathLayout for OverUnder {
             .max()
             .unwrap_or_default();
 
+        // Stretchy bases with labels (like extensible arrows) extend beyond their labels.
+        let is_label = |attachment: &Option<MathBox>, is_accent: bool| {
+            attachment.is_some() && !is_accent
+        };
+        if nucleus_is_horizontally_stretchy
+            && (is_label(&boxes[1], self.over_is_accent) || is_label(&boxes[2], self.under_is_accent))
+        {
+            let padding = Length::em(EXTENSIBLE_ARROW_PADDING).to_font_units(options.shaper);
+            max_width += 2 * padding;
+        }
+
+        // Stretchy accents keep their narrow form over a single glyph (e.g. a hat over a letter)
+        // and are stretched to the width of wider bases.
+        let narrow_accents = match self.accent_width {
+            AccentWidth::Auto => boxes[0].as_ref().map_or(false, is_single_glyph),
+            AccentWidth::Narrow => true,
+            AccentWidth::Wide => false,
+        };
+
         // the OverUnder has to stretch to at least the current stretch size
         if let Some(Extents {
             width: stretch_width,
@@ -322,9 +795,11 @@ impl MathLayout for OverUnder {
         for (index, &mut (ref mut arg, ref mut options, as_accent)) in
             arguments.iter_mut().enumerate()
         {
-            let mut stretch_size = options.stretch_size.unwrap_or(Default::default());
-            stretch_size.width = max_width;
-            options.stretch_size = Some(stretch_size);
+            if !(as_accent && narrow_accents) {
+                let mut stretch_size = options.stretch_size.unwrap_or(Default::default());
+                stretch_size.width = max_width;
+                options.stretch_size = Some(stretch_size);
+            }
 
             options.style.as_accent = as_accent;
             if let Some(stretched_box) = arg.map(|arg| arg.layout(*options)) {
@@ -333,14 +808,22 @@ impl MathLayout for OverUnder {
         }
 
         let nucleus = boxes[0].take().unwrap_or_default();
+        // the nucleus is combined with the overscript first, so its italic correction is kept for
+        // positioning the limits of a large operator
+        let large_op_italic_correction = if nucleus_is_largeop {
+            Some(nucleus.italic_correction())
+        } else {
+            None
+        };
         let nucleus = if let Some(mut over) = boxes[1].take() {
             let (_, LayoutOptions { style, shaper, .. }, ..) = arguments[1];
 
-            // enable flat accents if needed
+            // Accents over bases that are taller than `FlattenedAccentBaseHeight` use flattened
+            // glyphs. The style is passed on to the shaper which applies the `flac` feature.
             let height = options
                 .shaper
                 .math_constant(MathConstant::FlattenedAccentBaseHeight);
-            if self.over_is_accent && nucleus.extents().ascent >= height {
+            if self.over_is_accent && nucleus.extents().ascent > height {
                 let (_, ref mut over_options, _) = arguments[1];
                 over_options.style.flat_accent = true;
                 over = self.over.as_ref().unwrap().layout(*over_options);
@@ -352,7 +835,7 @@ impl MathLayout for OverUnder {
                 options,
                 true,
                 self.over_is_accent,
-                nucleus_is_largeop,
+                large_op_italic_correction,
                 nucleus_is_horizontally_stretchy,
             )
         } else {
@@ -367,7 +850,7 @@ impl MathLayout for OverUnder {
                 options,
                 false,
                 self.under_is_accent,
-                nucleus_is_largeop,
+                large_op_italic_correction,
                 nucleus_is_horizontally_stretchy,
             )
         } else {
@@ -382,19 +865,27 @@ impl MathLayout for OverUnder {
     }
 }
 
+/// Whether the box draws exactly one glyph, e.g. a single letter.
+fn is_single_glyph(math_box: &MathBox) -> bool {
+    match *math_box.content() {
+        MathBoxContent::Drawable(Drawable::Glyphs { ref glyphs, .. }) => glyphs.len() == 1,
+        _ => false,
+    }
+}
+
 fn layout_over_or_under(
     mut attachment: MathBox,
     mut nucleus: MathBox,
     options: LayoutOptions,
     as_over: bool,
     as_accent: bool,
-    nucleus_is_large_op: bool,
+    large_op_italic_correction: Option<i32>,
     nucleus_is_horizontally_stretchy: bool,
 ) -> MathBox {
     let (shaper, style) = (options.shaper, options.style);
     let mut gap = 0;
     let mut shift = 0;
-    if nucleus_is_large_op {
+    if large_op_italic_correction.is_some() {
         if as_over {
             gap = shaper.math_constant(MathConstant::UpperLimitGapMin);
             shift = shaper.math_constant(MathConstant::UpperLimitBaselineRiseMin)
@@ -460,11 +951,11 @@ fn layout_over_or_under(
     }
 
     // LargeOp italic correction
-    if nucleus_is_large_op {
+    if let Some(italic_correction) = large_op_italic_correction {
         if as_over {
-            attachment.origin.x += nucleus.italic_correction() / 2;
+            attachment.origin.x += italic_correction / 2;
         } else {
-            attachment.origin.x -= nucleus.italic_correction() / 2;
+            attachment.origin.x -= italic_correction / 2;
         }
     }
 
@@ -507,10 +998,12 @@ impl MathLayout for GeneralizedFraction {
         if options.style.math_style == MathStyle::Display {
             numerator_options.style.math_style = MathStyle::Inline;
         } else {
-            numerator_options.style.script_level += 1;
+            numerator_options.style = numerator_options.style.with_increased_script_level();
         }
+        // the numerator may be the core of an embellished operator and stretch
         let denominator_options = LayoutOptions {
             style: numerator_options.style.cramped_style(),
+            stretch_size: None,
             ..options
         };
         let mut numerator = numerator.layout(numerator_options);
@@ -518,48 +1011,22 @@ impl MathLayout for GeneralizedFraction {
 
         let shaper = &options.shaper;
         let axis_height = shaper.math_constant(MathConstant::AxisHeight);
-        let default_thickness = shaper.math_constant(MathConstant::FractionRuleThickness);
-
-        let (numerator_shift_up, denominator_shift_dn) =
-            if options.style.math_style == MathStyle::Inline {
-                (
-                    shaper.math_constant(MathConstant::FractionNumeratorShiftUp),
-                    shaper.math_constant(MathConstant::FractionDenominatorShiftDown),
-                )
-            } else {
-                (
-                    shaper.math_constant(MathConstant::FractionNumeratorDisplayStyleShiftUp),
-                    shaper.math_constant(MathConstant::FractionDenominatorDisplayStyleShiftDown),
-                )
-            };
-
-        let (numerator_gap_min, denominator_gap_min) =
-            if options.style.math_style == MathStyle::Inline {
-                (
-                    shaper.math_constant(MathConstant::FractionNumeratorGapMin),
-                    shaper.math_constant(MathConstant::FractionDenominatorGapMin),
-                )
-            } else {
-                (
-                    shaper.math_constant(MathConstant::FractionNumDisplayStyleGapMin),
-                    shaper.math_constant(MathConstant::FractionDenomDisplayStyleGapMin),
-                )
-            };
-
-        let numerator_shift_up = max(
-            numerator_shift_up - axis_height,
-            numerator_gap_min + default_thickness / 2 + numerator.extents().descent,
-        );
-        let denominator_shift_dn = max(
-            denominator_shift_dn + axis_height,
-            denominator_gap_min + default_thickness / 2 + denominator.extents().ascent,
-        );
-
-        numerator.origin.y -= axis_height;
-        denominator.origin.y -= axis_height;
+        let thickness = match self.thickness {
+            Some(ref thickness) => {
+                let thickness_options = LayoutOptions {
+                    stretch_size: None,
+                    ..options
+                };
+                thickness.layout(thickness_options).extents().height()
+            }
+            None => shaper.math_constant(MathConstant::FractionRuleThickness),
+        };
 
-        numerator.origin.y -= numerator_shift_up;
-        denominator.origin.y += denominator_shift_dn;
+        if thickness <= 0 {
+            layout_stack(&mut numerator, &mut denominator, options);
+        } else {
+            layout_fraction_parts(&mut numerator, &mut denominator, thickness, options);
+        }
 
         // centering
         let center_difference = (numerator.origin.x + numerator.extents().center())
@@ -570,6 +1037,10 @@ impl MathLayout for GeneralizedFraction {
             denominator.origin.x += center_difference;
         }
 
+        if thickness <= 0 {
+            return MathBox::with_vec(vec![numerator, denominator], options.user_data);
+        }
+
         // the fraction rule
         let origin = Vector {
             x: min(
@@ -585,8 +1056,7 @@ impl MathLayout for GeneralizedFraction {
             ),
             ..origin
         };
-        let fraction_rule =
-            MathBox::with_line(origin, target, default_thickness as u32, options.user_data);
+        let fraction_rule = MathBox::with_line(origin, target, thickness as u32, options.user_data);
 
         MathBox::with_vec(
             vec![numerator, fraction_rule, denominator],
@@ -601,6 +1071,86 @@ impl MathLayout for GeneralizedFraction {
     }
 }
 
+/// Places the numerator and the denominator of a fraction with a rule of the given thickness
+/// relative to the baseline.
+fn layout_fraction_parts(
+    numerator: &mut MathBox,
+    denominator: &mut MathBox,
+    thickness: i32,
+    options: LayoutOptions,
+) {
+    let shaper = options.shaper;
+    let axis_height = shaper.math_constant(MathConstant::AxisHeight);
+    let (numerator_shift_up, denominator_shift_dn) =
+        if options.style.math_style == MathStyle::Inline {
+            (
+                shaper.math_constant(MathConstant::FractionNumeratorShiftUp),
+                shaper.math_constant(MathConstant::FractionDenominatorShiftDown),
+            )
+        } else {
+            (
+                shaper.math_constant(MathConstant::FractionNumeratorDisplayStyleShiftUp),
+                shaper.math_constant(MathConstant::FractionDenominatorDisplayStyleShiftDown),
+            )
+        };
+
+    let (numerator_gap_min, denominator_gap_min) = if options.style.math_style == MathStyle::Inline
+    {
+        (
+            shaper.math_constant(MathConstant::FractionNumeratorGapMin),
+            shaper.math_constant(MathConstant::FractionDenominatorGapMin),
+        )
+    } else {
+        (
+            shaper.math_constant(MathConstant::FractionNumDisplayStyleGapMin),
+            shaper.math_constant(MathConstant::FractionDenomDisplayStyleGapMin),
+        )
+    };
+
+    let numerator_shift_up = max(
+        numerator_shift_up - axis_height,
+        numerator_gap_min + thickness / 2 + numerator.extents().descent,
+    );
+    let denominator_shift_dn = max(
+        denominator_shift_dn + axis_height,
+        denominator_gap_min + thickness / 2 + denominator.extents().ascent,
+    );
+
+    numerator.origin.y -= axis_height;
+    denominator.origin.y -= axis_height;
+
+    numerator.origin.y -= numerator_shift_up;
+    denominator.origin.y += denominator_shift_dn;
+}
+
+/// Places the top and the bottom of a fraction without a rule (e.g. of a binomial coefficient)
+/// relative to the baseline.
+fn layout_stack(top: &mut MathBox, bottom: &mut MathBox, options: LayoutOptions) {
+    let shaper = options.shaper;
+    let (mut shift_up, mut shift_down, gap_min) = if options.style.math_style == MathStyle::Inline {
+        (
+            shaper.math_constant(MathConstant::StackTopShiftUp),
+            shaper.math_constant(MathConstant::StackBottomShiftDown),
+            shaper.math_constant(MathConstant::StackGapMin),
+        )
+    } else {
+        (
+            shaper.math_constant(MathConstant::StackTopDisplayStyleShiftUp),
+            shaper.math_constant(MathConstant::StackBottomDisplayStyleShiftDown),
+            shaper.math_constant(MathConstant::StackDisplayStyleGapMin),
+        )
+    };
+    // move both parts apart evenly if they are too close
+    let gap = (shift_up - top.extents().descent) + (shift_down - bottom.extents().ascent);
+    if gap < gap_min {
+        let delta = gap_min - gap;
+        shift_up += delta / 2;
+        shift_down += delta - delta / 2;
+    }
+    top.origin.y -= shift_up;
+    bottom.origin.y += shift_down;
+}
+
 impl MathLayout for Root {
     fn layout(&self, options: LayoutOptions) -> MathBox {
         let radicand = match &self.radicand {
@@ -609,7 +1159,7 @@ impl MathLayout for Root {
         };
 
         let shaper = options.shaper;
-        let line_thickness = shaper.math_constant(MathConstant::RadicalRuleThickness);
+        let line_thickness = options.radical_rule_thickness_units();
         let vertical_gap = if options.style.math_style == MathStyle::Inline {
             shaper.math_constant(MathConstant::RadicalVerticalGap)
         } else {
@@ -618,7 +1168,7 @@ impl MathLayout for Root {
         let extra_ascender = shaper.math_constant(MathConstant::RadicalExtraAscender);
 
         // calculate the needed surd height based on the height of the radicand
-        let mut radicand = radicand.layout(options);
+        let mut radicand = radicand.layout(options.style(options.style.cramped_style()));
         let needed_surd_height = radicand.extents().height() + vertical_gap + line_thickness;
 
         // draw a stretched version of the surd
@@ -629,20 +1179,25 @@ impl MathLayout for Root {
         //     }),
         //     ..options.style
         // };
-        let surd = options.shaper.shape("√", options.style, options.user_data);
-        let mut surd = surd
-            .first_glyph()
-            .and_then(|(glyph, _scale)| {
-                if options.shaper.is_stretchable(glyph.glyph_code, false) {
-                    Some(options.shaper.stretch_glyph(
-                        glyph.glyph_code,
+        let surd_glyph = options.radical_symbol.glyph(shaper);
+        if let (None, RadicalSymbol::Character(c)) = (surd_glyph, options.radical_symbol) {
+            options.report(LayoutError::MissingGlyph(c));
+        }
+        let mut surd = surd_glyph
+            .map(|glyph| {
+                if options.shaper.is_stretchable(glyph, false) {
+                    options.shaper.stretch_glyph(
+                        glyph,
                         false,
                         needed_surd_height.abs() as u32,
                         options.style,
                         options.user_data,
-                    ))
+                    )
                 } else {
-                    None
+                    // symbols without variants keep their size
+                    options
+                        .shaper
+                        .shape_glyph(glyph, options.style, options.user_data)
                 }
             })
             .unwrap_or_default();
@@ -674,8 +1229,11 @@ impl MathLayout for Root {
 
         // typeset the self degree
         if let &Some(ref degree) = &self.degree {
+            // fonts may contain values outside of 0..=100 which aren't valid percentages
             let degree_bottom_raise_percent = PercentValue::new(
-                shaper.math_constant(MathConstant::RadicalDegreeBottomRaisePercent) as u8,
+                shaper
+                    .math_constant(MathConstant::RadicalDegreeBottomRaisePercent)
+                    .clamp(0, 100) as u8,
             );
             let kern_before = shaper.math_constant(MathConstant::RadicalKernBeforeDegree);
             let kern_after = shaper.math_constant(MathConstant::RadicalKernAfterDegree);
@@ -684,13 +1242,20 @@ impl MathLayout for Root {
                 surd.origin.y + surd.extents().descent - surd_height * degree_bottom_raise_percent;
 
             let mut degree_options = options;
-            degree_options.style.script_level += 2;
+            degree_options.style.script_level = options.style.script_level.saturating_add(2);
             degree_options.style.math_style = MathStyle::Inline;
             let mut degree = degree.layout(degree_options);
-            degree.origin.y += degree_bottom;
+            // the bottom of the degree's ink (not its baseline) is raised so that descenders
+            // don't run into the surd
+            degree.origin.y += degree_bottom - degree.extents().descent;
             degree.origin.x += kern_before;
 
-            let surd_kern = kern_before + degree.advance_width() + kern_after;
+            // the kern after the degree is usually negative so that the degree extends over the
+            // surd. Ink of the degree that sticks out beyond its advance width (e.g. of italic
+            // letters) must not collide with the surd though. The surd never moves in front of
+            // the start of the formula.
+            let degree_width = max(degree.advance_width(), degree.extents().right_edge());
+            let surd_kern = max(0, kern_before + degree_width + kern_after);
             surd.origin.x += surd_kern;
             radicand.origin.x += surd_kern;
             radical_rule.origin.x += surd_kern;
@@ -716,19 +1281,14 @@ impl Operator {
     ) -> MathBox {
         match self.field {
             Field::Unicode(ref string) => {
-                let shape_result = options.shaper.shape(
-                    string,
-                    options.style.no_flat_accent_style(),
-                    options.user_data,
-                );
-                let first_glyph = match shape_result.first_glyph() {
-                    Some((glyph, _scale)) => glyph,
+                let glyph = match stretchable_glyph(string, options) {
+                    Some(glyph) => glyph,
                     None => return MathBox::empty(Extents::default(), options.user_data),
                 };
 
-                if needed_width > 0 && options.shaper.is_stretchable(first_glyph.glyph_code, true) {
+                if needed_width > 0 && options.shaper.is_stretchable(glyph, true) {
                     return options.shaper.stretch_glyph(
-                        first_glyph.glyph_code,
+                        glyph,
                         true,
                         needed_width,
                         options.style,
@@ -736,43 +1296,194 @@ impl Operator {
                     );
                 }
 
-                if needed_height > 0 && options.shaper.is_stretchable(first_glyph.glyph_code, false)
-                {
+                if needed_height > 0 && options.shaper.is_stretchable(glyph, false) {
                     let mut math_box = options.shaper.stretch_glyph(
-                        first_glyph.glyph_code,
+                        glyph,
                         false,
                         needed_height,
                         options.style,
                         options.user_data,
                     );
-                    let stretch_constraints =
-                        self.stretch_constraints.unwrap_or(StretchConstraints {
-                            symmetric: true,
-                            ..Default::default()
-                        });
-                    if stretch_constraints.symmetric {
-                        let axis_height = options.shaper.math_constant(MathConstant::AxisHeight);
-                        let shift_up = (math_box.extents().descent - math_box.extents().ascent) / 2
-                            + axis_height;
-                        math_box.origin.y -= shift_up;
-                    } else {
-                        let stretch_size = options.stretch_size.unwrap_or_default();
-                        let excess_ascent = math_box.extents().ascent - stretch_size.ascent;
-                        let excess_descent = math_box.extents().descent - stretch_size.descent;
-                        math_box.origin.y += (excess_ascent - excess_descent) / 2;
-                    }
-
+                    self.align_stretched_vertically(&mut math_box, options);
                     return math_box;
                 }
 
                 // fallback
-                options
+                if is_single_char(string) {
+                    let math_box =
+                        options
+                            .shaper
+                            .shape_glyph(glyph, options.style, options.user_data);
+                    if self.is_fence && needed_width == 0 {
+                        return self.stretch_geometrically(math_box, needed_height, options);
+                    }
+                    math_box
+                } else {
+                    options
+                        .shaper
+                        .shape(string, options.style, options.user_data)
+                }
+            }
+            Field::Glyph(_) => {
+                options.report(LayoutError::UnstretchableGlyphOperator);
+                self.field.layout(options)
+            }
+            Field::Empty => MathBox::default(),
+        }
+    }
+
+    /// Scales the glyph of a delimiter vertically to the needed height. This is the fallback for
+    /// fonts that have neither variants nor an assembly for the delimiter.
+    fn stretch_geometrically(
+        &self,
+        math_box: MathBox,
+        needed_height: u32,
+        options: LayoutOptions,
+    ) -> MathBox {
+        let height = math_box.extents().height();
+        if height <= 0 || needed_height as i32 <= height {
+            return math_box;
+        }
+        let (glyphs, scale) = match *math_box.content() {
+            MathBoxContent::Drawable(Drawable::Glyphs { ref glyphs, scale }) => {
+                (glyphs.clone(), scale)
+            }
+            _ => return math_box,
+        };
+        // round up so that the delimiter covers the whole height
+        let (needed_height, height) = (i64::from(needed_height), i64::from(height));
+        let current = i64::from(scale.vertical.magnification_percentage());
+        let percent = (needed_height * current + height - 1) / height;
+        let vertical = PercentValue::magnification(min(percent, i64::from(u16::MAX)) as u16);
+        let scale = PercentScale2D::new(scale.horizontal, vertical);
+        let mut stretched = MathBox::with_scaled_glyphs(glyphs, scale, options.user_data);
+        self.align_stretched_vertically(&mut stretched, options);
+        stretched
+    }
+
+    /// Positions a vertically stretched operator. Symmetric operators are centered on the math
+    /// axis while all other operators are centered on the vertical range they were stretched to.
+    fn align_stretched_vertically(&self, math_box: &mut MathBox, options: LayoutOptions) {
+        let symmetric = self
+            .stretch_constraints
+            .map(|constraints| constraints.symmetric)
+            .unwrap_or_default();
+        if symmetric {
+            center_on_axis(math_box, options);
+        } else {
+            let stretch_size = options.stretch_size.unwrap_or_default();
+            let excess_ascent = math_box.extents().ascent - stretch_size.ascent;
+            let excess_descent = math_box.extents().descent - stretch_size.descent;
+            math_box.origin.y += (excess_ascent - excess_descent) / 2;
+        }
+    }
+
+    /// Converts a size constraint of the operator to font units. Sizes that are relative to the
+    /// default size of the operator are resolved by measuring the unstretched operator.
+    fn resolve_stretch_size(&self, size: Length, horizontal: bool, options: LayoutOptions) -> i32 {
+        if size.unit != LengthUnit::OperatorDefaultSize {
+            return size.to_font_units(options.shaper);
+        }
+        let extents = self.field.layout(options).extents();
+        let default_size = if horizontal {
+            extents.width
+        } else {
+            extents.height()
+        };
+        (default_size as f32 * size.value) as i32
+    }
+
+    /// Lays out a large operator (like ∑ or ∫) that is not stretched by its surroundings.
+    ///
+    /// In display style a bigger variant of at least `DisplayOperatorMinHeight` is chosen. The
+    /// result is always centered on the math axis, regardless of whether a variant, an assembly or
+    /// the base glyph was chosen.
+    fn layout_large_op(&self, options: LayoutOptions) -> MathBox {
+        let mut math_box = match self.field {
+            Field::Unicode(ref string) if options.style.math_style == MathStyle::Display => {
+                let display_min_height = (options
                     .shaper
-                    .shape(string, options.style, options.user_data)
+                    .math_constant(MathConstant::DisplayOperatorMinHeight)
+                    as f32
+                    * 1.42) as u32;
+                let shape_result = options.shaper.shape(
+                    string,
+                    options.style.no_flat_accent_style(),
+                    options.user_data,
+                );
+                match shape_result.first_glyph() {
+                    Some((glyph, _, _))
+                        if options.shaper.is_stretchable(glyph.glyph_code, false) =>
+                    {
+                        options.shaper.stretch_glyph(
+                            glyph.glyph_code,
+                            false,
+                            display_min_height,
+                            options.style,
+                            options.user_data,
+                        )
+                    }
+                    _ => shape_result,
+                }
             }
-            _ => unimplemented!(),
+            _ => self.field.layout(options),
+        };
+        center_on_axis(&mut math_box, options);
+        math_box
+    }
+}
+
+fn is_single_char(string: &str) -> bool {
+    let mut chars = string.chars();
+    chars.next().is_some() && chars.next().is_none()
+}
+
+/// Returns the glyph of an operator that is used as the base for stretching.
+///
+/// Operators usually consist of a single character whose glyph is looked up in the cmap directly
+/// so that the operator doesn't have to be shaped before (and again after) stretching it.
+fn stretchable_glyph(string: &str, options: LayoutOptions) -> Option<u32> {
+    if is_single_char(string) {
+        let c = string.chars().next()?;
+        if let Some(glyph) = options.shaper.nominal_glyph(c) {
+            return Some(glyph);
         }
     }
+    let shape_result = options.shaper.shape(
+        string,
+        options.style.no_flat_accent_style(),
+        options.user_data,
+    );
+    shape_result
+        .first_glyph()
+        .map(|(glyph, _origin, _scale)| glyph.glyph_code)
+}
+
+/// Shifts the box vertically so that its ink is centered on the math axis.
+fn center_on_axis(math_box: &mut MathBox, options: LayoutOptions) {
+    let axis_height = options.shaper.math_constant(MathConstant::AxisHeight);
+    let extents = math_box.extents();
+    let shift_up = (extents.descent - extents.ascent) / 2 + axis_height;
+    math_box.origin.y -= shift_up;
+}
+
+/// Returns the height a symmetric operator needs to cover `stretch_size` when it is centered on
+/// the math axis. Slightly asymmetric sizes within `tolerance` keep their height, so that the
+/// operator doesn't overshoot on the shorter side.
+fn symmetric_stretch_height(
+    stretch_size: Extents<i32>,
+    tolerance: f32,
+    options: LayoutOptions,
+) -> i32 {
+    let axis_height = options.shaper.math_constant(MathConstant::AxisHeight);
+    let above_axis = stretch_size.ascent - axis_height;
+    let below_axis = axis_height + stretch_size.descent;
+    let height = stretch_size.ascent + stretch_size.descent;
+    if (above_axis - below_axis).abs() as f32 <= tolerance * height as f32 {
+        height
+    } else {
+        max(above_axis, below_axis) * 2
+    }
 }
 
 impl MathLayout for Operator {
@@ -781,31 +1492,44 @@ impl MathLayout for Operator {
             (Some(stretch_size), Some(stretch_constraints)) => {
                 let min_size = stretch_constraints
                     .min_size
-                    .map(|size| size.to_font_units(options.shaper));
+                    .map(|size| self.resolve_stretch_size(size, false, options));
                 let max_size = stretch_constraints
                     .max_size
-                    .map(|size| size.to_font_units(options.shaper));
+                    .map(|size| self.resolve_stretch_size(size, false, options));
                 let mut needed_height = if stretch_constraints.symmetric {
-                    let axis_height = options.shaper.math_constant(MathConstant::AxisHeight);
-                    max(
-                        stretch_size.ascent - axis_height,
-                        axis_height + stretch_size.descent,
-                    ) * 2
+                    let tolerance = stretch_constraints
+                        .asymmetry_tolerance
+                        .unwrap_or(options.asymmetry_tolerance);
+                    symmetric_stretch_height(stretch_size, tolerance, options)
                 } else {
                     stretch_size.ascent + stretch_size.descent
                 };
                 needed_height = clamp(needed_height, min_size, max_size);
                 let needed_height = max(0, needed_height) as u32;
-                self.layout_stretchy(needed_height, stretch_size.width as u32, options)
+
+                let mut needed_width = stretch_size.width;
+                if needed_width > 0 {
+                    let min_size = stretch_constraints
+                        .min_size
+                        .map(|size| self.resolve_stretch_size(size, true, options));
+                    let max_size = stretch_constraints
+                        .max_size
+                        .map(|size| self.resolve_stretch_size(size, true, options));
+                    needed_width = clamp(needed_width, min_size, max_size);
+                }
+                let needed_width = max(0, needed_width) as u32;
+                if self.is_fence && needed_width == 0 {
+                    // fences keep their natural size and position around content that fits
+                    let unstretched = self.field.layout(options);
+                    if needed_height <= unstretched.extents().height() as u32 {
+                        return unstretched;
+                    }
+                }
+                self.layout_stretchy(needed_height, needed_width, options)
             }
             _ => {
-                if self.is_large_op && options.style.math_style == MathStyle::Display {
-                    let display_min_height = (options
-                        .shaper
-                        .math_constant(MathConstant::DisplayOperatorMinHeight)
-                        as f32
-                        * 1.42) as i32;
-                    self.layout_stretchy(display_min_height as u32, 0, options)
+                if self.is_large_op {
+                    self.layout_large_op(options)
                 } else {
                     self.field.layout(options)
                 }
@@ -822,6 +1546,9 @@ impl MathLayout for Operator {
             leading_space: self.leading_space.to_font_units(options.shaper),
             trailing_space: self.trailing_space.to_font_units(options.shaper),
             is_large_op: self.is_large_op,
+            is_fence: self.is_fence,
+            is_separator: self.is_separator,
+            form: self.form,
         })
     }
 }
@@ -838,6 +1565,61 @@ impl MathLayout for MathSpace {
     }
 }
 
+impl MathLayout for ForeignObject {
+    fn layout(&self, options: LayoutOptions) -> MathBox {
+        let extents = self.size.layout(options).extents();
+        MathBox::foreign(extents, self.payload.clone(), options.user_data)
+    }
+}
+
+impl MathLayout for TextRun {
+    fn layout(&self, options: LayoutOptions) -> MathBox {
+        let shaper = options.shaper;
+        let formula_options = LayoutOptions {
+            stretch_size: None,
+            ..options
+        };
+        // the words and formulas of the run where `None` stands for whitespace
+        let mut pieces = Vec::new();
+        for item in &self.items {
+            match *item {
+                TextRunItem::Text(ref text) => {
+                    for (index, word) in text.split(char::is_whitespace).enumerate() {
+                        if index > 0 {
+                            pieces.push(None);
+                        }
+                        if !word.is_empty() {
+                            pieces.push(Some(shaper.shape(word, options.style, options.user_data)));
+                        }
+                    }
+                }
+                TextRunItem::Math(ref expr) => pieces.push(Some(expr.layout(formula_options))),
+            }
+        }
+
+        let space_width = shaper.space_width(options.style);
+        let mut boxes = Vec::with_capacity(pieces.len());
+        let mut cursor = 0;
+        let mut pending_space = false;
+        for piece in pieces {
+            match piece {
+                // whitespace at the start and the end of the run is ignored
+                None => pending_space = !boxes.is_empty(),
+                Some(mut math_box) => {
+                    if pending_space {
+                        cursor += space_width;
+                        pending_space = false;
+                    }
+                    math_box.origin.x += cursor;
+                    cursor += math_box.advance_width();
+                    boxes.push(math_box);
+                }
+            }
+        }
+        MathBox::with_vec(boxes, options.user_data)
+    }
+}
+
 impl MathLayout for Option<MathExpression> {
     fn layout(&self, options: LayoutOptions) -> MathBox {
         match *self {
@@ -852,6 +1634,20 @@ impl MathLayout for Option<MathExpression> {
     }
 }
 
+impl MathItem {
+    /// Wraps an expression type that lays itself out into a `MathItem::Other`.
+    pub fn other<T: MathLayout + Send + Sync + 'static>(layout: T) -> MathItem {
+        let layout: Box<dyn MathLayout + Send + Sync> = Box::new(layout);
+        MathItem::Other(Arc::new(layout))
+    }
+}
+
+fn downcast_other(other: &Arc<dyn Any + Send + Sync>) -> Option<&(dyn MathLayout + Send + Sync)> {
+    other
+        .downcast_ref::<Box<dyn MathLayout + Send + Sync>>()
+        .map(|layout| &**layout)
+}
+
 impl MathLayout for MathItem {
     fn layout(&self, options: LayoutOptions) -> MathBox {
         match *self {
@@ -863,7 +1659,12 @@ impl MathLayout for MathItem {
             MathItem::Root(ref root) => root.layout(options),
             MathItem::Operator(ref operator) => operator.layout(options),
             MathItem::List(ref list) => list.layout(options),
-            MathItem::Other(ref other) => other.layout(options),
+            MathItem::Foreign(ref foreign) => foreign.layout(options),
+            MathItem::TextRun(ref run) => run.layout(options),
+            MathItem::Other(ref other) => match downcast_other(other) {
+                Some(other) => other.layout(options),
+                None => MathBox::empty(Extents::default(), options.user_data),
+            },
         }
     }
 
@@ -877,25 +1678,181 @@ impl MathLayout for MathItem {
             MathItem::List(ref list) => (&list[..]).operator_properties(options),
             MathItem::Root(ref root) => root.operator_properties(options),
             MathItem::Operator(ref operator) => operator.operator_properties(options),
-            MathItem::Other(ref other) => other.operator_properties(options),
+            MathItem::Foreign(ref foreign) => foreign.operator_properties(options),
+            MathItem::TextRun(ref run) => run.operator_properties(options),
+            MathItem::Other(ref other) => {
+                downcast_other(other).and_then(|other| other.operator_properties(options))
+            }
         }
     }
 }
 
+/// Lays out the expression with the given options.
 pub fn layout_expression(expr: &MathExpression, options: LayoutOptions) -> MathBox {
     expr.layout(options)
 }
 
+/// The length of the arrow head of an `Strike::UpDiagonalArrow` in multiples of the strike
+/// thickness.
+const STRIKE_ARROW_HEAD_LENGTH: i32 = 5;
+
+/// Returns a line between two points. Lines always point to the right so that their width isn't
+/// negative.
+fn line_between(a: Vector<i32>, b: Vector<i32>, thickness: i32, user_data: u64) -> MathBox {
+    let (from, to) = if a.x <= b.x { (a, b) } else { (b, a) };
+    MathBox::with_line(from, to, thickness as u32, user_data)
+}
+
+/// Returns the lines of a strike across the rectangle from `top_left` to `bottom_right`.
+fn layout_strike(
+    strike: Strike,
+    top_left: Vector<i32>,
+    bottom_right: Vector<i32>,
+    thickness: i32,
+    user_data: u64,
+) -> Vec<MathBox> {
+    let (left, top, right, bottom) = (top_left.x, top_left.y, bottom_right.x, bottom_right.y);
+    let (center_x, center_y) = ((left + right) / 2, (top + bottom) / 2);
+    let point = |x, y| Vector { x, y };
+    let line = |a, b| line_between(a, b, thickness, user_data);
+    match strike {
+        Strike::Horizontal => vec![line(point(left, center_y), point(right, center_y))],
+        Strike::Vertical => vec![line(point(center_x, top), point(center_x, bottom))],
+        Strike::UpDiagonal => vec![line(point(left, bottom), point(right, top))],
+        Strike::DownDiagonal => vec![line(point(left, top), point(right, bottom))],
+        Strike::UpDiagonalArrow => {
+            let (dx, dy) = ((right - left) as f32, (top - bottom) as f32);
+            let length = (dx * dx + dy * dy).sqrt().max(1.0);
+            let (dx, dy) = (dx / length, dy / length);
+            // the arrow head sticks out beyond the corner so that it doesn't hide the content
+            let head_length = (STRIKE_ARROW_HEAD_LENGTH * thickness) as f32;
+            let offset = |x: f32, y: f32| Vector {
+                x: (x * head_length).round() as i32,
+                y: (y * head_length).round() as i32,
+            };
+            let tip = point(right, top) + offset(dx, dy);
+            // the sides of the arrow head are rotated by 30 degrees against the shaft
+            let (sin, cos) = (0.5f32, 0.75f32.sqrt());
+            let side = |sign: f32| {
+                let (x, y) = (dx * cos - sign * dy * sin, sign * dx * sin + dy * cos);
+                tip - offset(x, y)
+            };
+            vec![
+                line(point(left, bottom), tip),
+                line(side(1.0), tip),
+                line(side(-1.0), tip),
+            ]
+        }
+    }
+}
+
+/// Places the background and the border of a decoration behind the box of the decorated
+/// expression and its strikes in front of it. The box is moved to the right by the padding and
+/// the border thickness.
+fn layout_decoration(
+    mut math_box: MathBox,
+    decoration: &Decoration,
+    options: LayoutOptions,
+) -> MathBox {
+    let border_thickness = decoration
+        .border
+        .map(|border| max(0, border.thickness.to_font_units(options.shaper)))
+        .unwrap_or_default();
+    let shaper = options.shaper;
+    let strike_thickness = match decoration.strikes.len() {
+        0 => 0,
+        _ => max(0, shaper.math_constant(MathConstant::FractionRuleThickness)),
+    };
+    // the strikes end half their thickness inside of the border, so their ink is covered by the
+    // extents of the decoration
+    let strike_inset = border_thickness + strike_thickness / 2;
+    let inset = max(0, decoration.padding.to_font_units(options.shaper)) + strike_inset;
+
+    let content = math_box.rect(Vector::default());
+    math_box.origin.x += inset - content.origin.x;
+    let extents = Extents {
+        left_side_bearing: 0,
+        width: content.width + 2 * inset,
+        ascent: -content.origin.y + inset,
+        descent: content.origin.y + content.height + inset,
+    };
+
+    let mut boxes = Vec::with_capacity(6);
+    // the first box covers the whole decoration even without a background so that the padding
+    // contributes to the advance width
+    boxes.push(match decoration.background {
+        Some(color) => MathBox::with_rectangle(extents, color, options.user_data),
+        None => MathBox::empty(extents, options.user_data),
+    });
+    if let Some(border) = decoration.border {
+        let (width, height) = (extents.width, extents.height());
+        let (top, bottom) = (-extents.ascent + border_thickness, extents.descent);
+        let right = width - border_thickness;
+        // every side is a rectangle whose origin is at its bottom left corner
+        let sides = [
+            (0, top, width, border_thickness),
+            (0, bottom, width, border_thickness),
+            (0, bottom, border_thickness, height),
+            (right, bottom, border_thickness, height),
+        ];
+        for &(x, y, width, height) in sides.iter() {
+            let side_extents = Extents::new(0, width, height, 0);
+            let mut side = MathBox::with_rectangle(side_extents, border.color, options.user_data);
+            side.origin = Vector { x, y };
+            boxes.push(side);
+        }
+    }
+    let top_accent_attachment = math_box.origin.x + math_box.top_accent_attachment();
+    boxes.push(math_box);
+    let top_left = Vector {
+        x: strike_inset,
+        y: -extents.ascent + strike_inset,
+    };
+    let bottom_right = Vector {
+        x: extents.width - strike_inset,
+        y: extents.descent - strike_inset,
+    };
+    for &strike in &decoration.strikes {
+        let lines = layout_strike(
+            strike,
+            top_left,
+            bottom_right,
+            strike_thickness,
+            options.user_data,
+        );
+        boxes.extend(lines);
+    }
+
+    let mut math_box = MathBox::with_vec(boxes, options.user_data);
+    // the decoration covers the ink of the content, so no italic correction is necessary
+    math_box.metrics.advance_width = extents.width;
+    math_box.metrics.italic_correction = 0;
+    math_box.metrics.top_accent_attachment = top_accent_attachment;
+    math_box
+}
+
 impl MathLayout for MathExpression {
     fn layout(&self, options: LayoutOptions) -> MathBox {
-        let old_style = options.style;
-        let new_style = (options.style_provider)(old_style, options.user_data);
-
-        self.item
-            .layout(options.style(new_style).user_data(self.get_user_data()))
+        let options = options.for_expression(self);
+        if options.is_too_deep() {
+            options.report(LayoutError::NestingTooDeep(options.max_depth));
+            return MathBox::empty(Extents::default(), options.user_data);
+        }
+        let mut math_box = self.item.layout(options);
+        if let Some(decoration) = self.decoration() {
+            math_box = layout_decoration(math_box, decoration, options);
+        }
+        if self.payload().is_some() {
+            math_box.set_payload(self.payload().cloned());
+        }
+        math_box
     }
 
     fn operator_properties(&self, options: LayoutOptions) -> Option<OperatorProperties> {
+        let options = options.for_expression(self);
+        if options.is_too_deep() {
+            return None;
+        }
         self.item.operator_properties(options)
     }
 }
@@ -1,4 +1,4 @@
-use crate::types::PercentValue;
+use crate::types::{Color, Payload, PercentScale2D, PercentValue};
 use std::cmp::{max, min};
 use std::default::Default;
 use std::ops::{Add, Div, Mul, Sub};
@@ -60,6 +60,15 @@ impl Mul<PercentValue> for Vector<i32> {
         }
     }
 }
+impl Mul<PercentScale2D> for Vector<i32> {
+    type Output = Vector<i32>;
+    fn mul(self, _rhs: PercentScale2D) -> Vector<i32> {
+        Vector {
+            x: self.x * _rhs.horizontal,
+            y: self.y * _rhs.vertical,
+        }
+    }
+}
 
 /// Basic Extents of ink inside boxes
 // TODO: Image for documentation
@@ -129,6 +138,48 @@ impl Mul<PercentValue> for Extents<i32> {
         }
     }
 }
+impl Mul<PercentScale2D> for Extents<i32> {
+    type Output = Extents<i32>;
+    fn mul(self, _rhs: PercentScale2D) -> Extents<i32> {
+        Extents {
+            left_side_bearing: self.left_side_bearing * _rhs.horizontal,
+            width: self.width * _rhs.horizontal,
+            ascent: self.ascent * _rhs.vertical,
+            descent: self.descent * _rhs.vertical,
+        }
+    }
+}
+
+/// An axis-aligned rectangle.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Rect<T> {
+    /// The top left corner.
+    pub origin: Vector<T>,
+    pub width: T,
+    pub height: T,
+}
+impl Rect<i32> {
+    pub fn right(&self) -> i32 {
+        self.origin.x + self.width
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.origin.y + self.height
+    }
+
+    /// Returns the smallest rectangle containing both rectangles.
+    pub fn union(self, other: Rect<i32>) -> Rect<i32> {
+        let origin = Vector {
+            x: min(self.origin.x, other.origin.x),
+            y: min(self.origin.y, other.origin.y),
+        };
+        Rect {
+            origin,
+            width: max(self.right(), other.right()) - origin.x,
+            height: max(self.bottom(), other.bottom()) - origin.y,
+        }
+    }
+}
 
 #[derive(Default, Copy, Clone, PartialEq, Eq)]
 pub struct Moved<T> {
@@ -215,8 +266,111 @@ impl Mul<PercentValue> for Bounds {
     }
 }
 
+/// How device coordinates are rounded by a `DeviceScale`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Rounding {
+    /// Coordinates are not rounded at all.
+    Exact,
+    /// Coordinates are rounded to multiples of 1/64 device units (26.6 fixed point).
+    FixedPoint,
+    /// Coordinates are rounded to whole device units (pixels).
+    Pixel,
+}
+
+impl Default for Rounding {
+    fn default() -> Rounding {
+        Rounding::Exact
+    }
+}
+
+/// Converts font units into (possibly fractional) device units such as pixels.
+///
+/// Layout happens in integer font units which is exact. Rounding policy: only *absolute*
+/// coordinates (the sum of all origins from the root box down to a box) should be converted and
+/// rounded. Converting relative offsets and adding them up in device space accumulates rounding
+/// errors. Rule thicknesses are rounded too but a rule that is visible in font units never
+/// becomes thinner than the smallest representable device length.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DeviceScale {
+    /// The number of font units per em (see `MathShaper::em_size`).
+    pub units_per_em: i32,
+    /// The number of device units per em, i.e. the font size in device units.
+    pub pixels_per_em: f32,
+    /// The rounding that is applied to all converted lengths.
+    pub rounding: Rounding,
+}
+
+impl DeviceScale {
+    /// Creates a scale without rounding.
+    pub fn new(units_per_em: i32, pixels_per_em: f32) -> Self {
+        DeviceScale {
+            units_per_em,
+            pixels_per_em,
+            rounding: Rounding::Exact,
+        }
+    }
+
+    pub fn with_rounding(self, rounding: Rounding) -> Self {
+        DeviceScale { rounding, ..self }
+    }
+
+    /// The number of device units per font unit.
+    pub fn factor(&self) -> f32 {
+        self.pixels_per_em / self.units_per_em as f32
+    }
+
+    /// Rounds a device length according to the rounding policy.
+    pub fn round(&self, value: f32) -> f32 {
+        match self.rounding {
+            Rounding::Exact => value,
+            Rounding::FixedPoint => (value * 64.0).round() / 64.0,
+            Rounding::Pixel => value.round(),
+        }
+    }
+
+    /// Converts an absolute coordinate or a length from font units to device units.
+    pub fn to_device(&self, value: i32) -> f32 {
+        self.round(value as f32 * self.factor())
+    }
+
+    /// Converts an absolute position from font units to device units.
+    pub fn point_to_device(&self, point: Vector<i32>) -> Vector<f32> {
+        Vector {
+            x: self.to_device(point.x),
+            y: self.to_device(point.y),
+        }
+    }
+
+    /// Converts the thickness of a rule (e.g. a fraction bar) to device units.
+    pub fn thickness_to_device(&self, thickness: i32) -> f32 {
+        if thickness <= 0 {
+            return 0.0;
+        }
+        let exact = thickness as f32 * self.factor();
+        let minimum = match self.rounding {
+            Rounding::Exact => exact,
+            Rounding::FixedPoint => 1.0 / 64.0,
+            Rounding::Pixel => 1.0,
+        };
+        self.round(exact).max(minimum)
+    }
+
+    /// Converts a value in font units to 26.6 fixed point device units (as used by FreeType).
+    pub fn to_26_6(&self, value: i32) -> i32 {
+        (value as f32 * self.factor() * 64.0).round() as i32
+    }
+}
+
+pub(crate) mod sealed {
+    /// Keeps `MathBoxMetrics` from being implemented outside of this crate, so that methods can be
+    /// added to it without breaking other crates.
+    pub trait Sealed {}
+}
+
 /// A box used in mathematical typesetting must have these metric values.
-pub trait MathBoxMetrics {
+///
+/// The trait is sealed: only the boxes and glyphs of this crate implement it.
+pub trait MathBoxMetrics: sealed::Sealed {
     /// distance from the left edge of a box to the left edge of the following box
     fn advance_width(&self) -> i32;
     /// the size of a box
@@ -246,6 +400,8 @@ impl Metrics {
     }
 }
 
+impl sealed::Sealed for Metrics {}
+
 impl MathBoxMetrics for Metrics {
     fn advance_width(&self) -> i32 {
         self.advance_width
@@ -269,22 +425,32 @@ pub enum Drawable {
         glyphs: Vec<MathGlyph>,
         /// The size at which these glyphs should be rendered relative to their normal size.
         ///
-        /// This is used to render subscripts and superscripts in a smaller size.
-        scale: PercentValue,
+        /// This is used to render subscripts and superscripts in a smaller size. Delimiters that
+        /// were stretched geometrically because the font has no larger variants of them are
+        /// scaled more in the vertical direction (around the baseline).
+        scale: PercentScale2D,
     },
     Line {
         vector: Vector<i32>,
         thickness: u32,
     },
+    /// A rectangle filled with a solid color like the background of a decorated expression.
+    Rectangle {
+        extents: Extents<i32>,
+        color: Color,
+    },
 }
 
+impl sealed::Sealed for Drawable {}
+
 impl MathBoxMetrics for Drawable {
     fn advance_width(&self) -> i32 {
         match self {
             Drawable::Glyphs { glyphs, scale } => {
-                glyphs.iter().map(|g| g.advance_width).sum::<i32>() * *scale
+                glyphs.iter().map(|g| g.advance_width).sum::<i32>() * scale.horizontal
             }
             Drawable::Line { ref vector, .. } => vector.x,
+            Drawable::Rectangle { ref extents, .. } => extents.right_edge(),
         }
     }
     fn extents(&self) -> Extents<i32> {
@@ -295,18 +461,18 @@ impl MathBoxMetrics for Drawable {
                     .map(|item| -item.offset.y + item.extents().ascent)
                     .max()
                     .unwrap_or_default()
-                    * scale;
+                    * scale.vertical;
                 let max_descent = glyphs
                     .iter()
                     .map(|item| item.offset.y + item.extents().descent)
                     .max()
                     .unwrap_or_default()
-                    * scale;
+                    * scale.vertical;
                 let left_side_bearing = glyphs
                     .first()
                     .map(|x| x.extents().left_side_bearing)
                     .unwrap_or(0)
-                    * scale;
+                    * scale.horizontal;
 
                 let right_side_bearing = glyphs
                     .last()
@@ -316,7 +482,7 @@ impl MathBoxMetrics for Drawable {
                             - item.extents().left_side_bearing
                     })
                     .unwrap_or(0)
-                    * scale;
+                    * scale.horizontal;
 
                 let width = self.advance_width() - right_side_bearing - left_side_bearing;
                 Extents {
@@ -332,6 +498,7 @@ impl MathBoxMetrics for Drawable {
                 ascent: max(0, -vector.y),
                 descent: max(0, vector.y),
             },
+            Drawable::Rectangle { extents, .. } => extents,
         }
     }
 
@@ -339,16 +506,16 @@ impl MathBoxMetrics for Drawable {
         match self {
             Drawable::Glyphs { glyphs, scale } => glyphs
                 .last()
-                .map(|g| g.italic_correction * *scale)
+                .map(|g| g.italic_correction * scale.horizontal)
                 .unwrap_or_default(),
-            Drawable::Line { .. } => 0,
+            Drawable::Line { .. } | Drawable::Rectangle { .. } => 0,
         }
     }
 
     fn top_accent_attachment(&self) -> i32 {
         let value = match self {
             Drawable::Glyphs { glyphs, scale } if glyphs.len() == 1 => {
-                glyphs[0].top_accent_attachment() * *scale
+                glyphs[0].top_accent_attachment() * scale.horizontal
             }
             _ => 0,
         };
@@ -365,6 +532,12 @@ pub enum MathBoxContent {
     /// Represents a box without any content
     Empty(Extents<i32>),
     Drawable(Drawable),
+    /// Non-math content (like an image) that occupies the given extents. The payload is the one
+    /// of the `ForeignObject` this box was generated from.
+    Foreign {
+        extents: Extents<i32>,
+        payload: Payload,
+    },
     /// A vector of boxes that are logically inside the parent box.
     ///
     /// If this `Vec` is empty then thix box is considered empty.
@@ -377,6 +550,42 @@ pub struct MathBox {
     pub(crate) metrics: Metrics,
     pub content: MathBoxContent,
     user_data: u64,
+    payload: Option<Payload>,
+    pub(crate) assembly: Option<Box<GlyphAssembly>>,
+}
+
+/// Describes how a stretched glyph was built from the parts of a glyph assembly in the MATH
+/// table.
+///
+/// All lengths are in font units and already scaled like the glyphs of the box.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlyphAssembly {
+    /// `true` if the parts are placed side by side, `false` if they are stacked from top to
+    /// bottom.
+    pub horizontal: bool,
+    /// The parts in the order in which they are placed. A repeated extender appears once for
+    /// every repetition.
+    pub parts: Vec<AssemblyPart>,
+    /// The length by which adjacent parts overlap.
+    pub connector_overlap: i32,
+}
+
+/// A single part of a `GlyphAssembly`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssemblyPart {
+    pub glyph_code: u32,
+    pub is_extender: bool,
+    /// The advance of the part in the direction of the assembly.
+    pub full_advance: i32,
+}
+
+impl GlyphAssembly {
+    /// The distance from the start of the first part to the end of the last part.
+    pub fn length(&self) -> i32 {
+        let advances: i32 = self.parts.iter().map(|part| part.full_advance).sum();
+        let overlaps = self.connector_overlap * (self.parts.len() as i32 - 1).max(0);
+        advances - overlaps
+    }
 }
 
 impl Default for MathBoxContent {
@@ -385,10 +594,13 @@ impl Default for MathBoxContent {
     }
 }
 
+impl sealed::Sealed for MathBoxContent {}
+
 impl MathBoxMetrics for MathBoxContent {
     fn advance_width(&self) -> i32 {
         match *self {
             MathBoxContent::Empty(ref extents) => extents.width,
+            MathBoxContent::Foreign { ref extents, .. } => extents.width,
             MathBoxContent::Drawable(ref drawable) => drawable.advance_width(),
             MathBoxContent::Boxes(ref boxes) => boxes
                 .iter()
@@ -401,6 +613,7 @@ impl MathBoxMetrics for MathBoxContent {
     fn extents(&self) -> Extents<i32> {
         match *self {
             MathBoxContent::Empty(ref extents) => *extents,
+            MathBoxContent::Foreign { ref extents, .. } => *extents,
             MathBoxContent::Drawable(ref drawable) => drawable.extents(),
             MathBoxContent::Boxes(ref boxes) => {
                 let slice = boxes.as_slice();
@@ -438,7 +651,7 @@ impl MathBoxMetrics for MathBoxContent {
 
     fn italic_correction(&self) -> i32 {
         match *self {
-            MathBoxContent::Empty(_) => 0,
+            MathBoxContent::Empty(_) | MathBoxContent::Foreign { .. } => 0,
             MathBoxContent::Drawable(ref drawable) => drawable.italic_correction(),
             MathBoxContent::Boxes(ref boxes) => boxes
                 .as_slice()
@@ -469,6 +682,29 @@ impl MathBox {
         self.user_data
     }
 
+    /// Returns the payload of the expression this box was generated from.
+    ///
+    /// Only the outermost box of an expression carries its payload. Renderers that want to apply
+    /// a payload to the whole expression should pass it on to the contents of the box.
+    pub fn payload(&self) -> Option<&Payload> {
+        self.payload.as_ref()
+    }
+
+    /// Returns the payload if it is of type `T`.
+    pub fn payload_as<T: 'static>(&self) -> Option<&T> {
+        self.payload.as_ref().and_then(|payload| payload.downcast_ref())
+    }
+
+    pub fn set_payload(&mut self, payload: Option<Payload>) {
+        self.payload = payload;
+    }
+
+    /// Returns how the box was assembled if it is a glyph stretched with a glyph assembly. The
+    /// parts correspond to the child boxes in the same order.
+    pub fn glyph_assembly(&self) -> Option<&GlyphAssembly> {
+        self.assembly.as_deref()
+    }
+
     fn with_content(content: MathBoxContent, user_data: u64) -> Self {
         let metrics = Metrics::from_metrics(&content);
         MathBox {
@@ -476,6 +712,8 @@ impl MathBox {
             metrics,
             origin: Vector::default(),
             user_data,
+            payload: None,
+            assembly: None,
         }
     }
 
@@ -483,6 +721,10 @@ impl MathBox {
         MathBox::with_content(MathBoxContent::Empty(extents), user_data)
     }
 
+    pub fn foreign(extents: Extents<i32>, payload: Payload, user_data: u64) -> Self {
+        MathBox::with_content(MathBoxContent::Foreign { extents, payload }, user_data)
+    }
+
     pub fn with_line(from: Vector<i32>, to: Vector<i32>, thickness: u32, user_data: u64) -> Self {
         let mut math_box = MathBox::with_content(
             MathBoxContent::Drawable(Drawable::Line {
@@ -495,7 +737,24 @@ impl MathBox {
         math_box
     }
 
+    /// Creates a box filled with the given color whose ink has the given extents.
+    pub fn with_rectangle(extents: Extents<i32>, color: Color, user_data: u64) -> Self {
+        MathBox::with_content(
+            MathBoxContent::Drawable(Drawable::Rectangle { extents, color }),
+            user_data,
+        )
+    }
+
     pub fn with_glyphs(glyphs: Vec<MathGlyph>, scale: PercentValue, user_data: u64) -> Self {
+        MathBox::with_scaled_glyphs(glyphs, scale.into(), user_data)
+    }
+
+    /// Like `with_glyphs` but allows to scale the glyphs differently in both directions.
+    pub fn with_scaled_glyphs(
+        glyphs: Vec<MathGlyph>,
+        scale: PercentScale2D,
+        user_data: u64,
+    ) -> Self {
         MathBox::with_content(
             MathBoxContent::Drawable(Drawable::Glyphs { glyphs, scale }),
             user_data,
@@ -517,28 +776,155 @@ impl MathBox {
         &self.content
     }
 
-    /// recursive search for a glyph at the leftmost position
-    pub fn first_glyph(&self) -> Option<(MathGlyph, PercentValue)> {
-        match self.content() {
-            MathBoxContent::Drawable(Drawable::Glyphs { glyphs, scale }) => {
-                glyphs.first().map(|&g| (g, *scale))
+    /// Makes every rule inside the box (e.g. fraction bars and radical overbars) at least
+    /// `min_thickness` device units thick when rendered with the given scale so that rules don't
+    /// vanish at small sizes. The metrics of the boxes are not affected.
+    ///
+    /// Returns the number of rules that were made thicker.
+    pub fn enforce_min_rule_thickness(&mut self, scale: &DeviceScale, min_thickness: f32) -> usize {
+        let min_thickness = (min_thickness / scale.factor()).ceil() as u32;
+        self.enforce_min_rule_thickness_units(min_thickness)
+    }
+
+    fn enforce_min_rule_thickness_units(&mut self, min_thickness: u32) -> usize {
+        match self.content {
+            MathBoxContent::Drawable(Drawable::Line {
+                ref mut thickness, ..
+            }) if *thickness < min_thickness => {
+                *thickness = min_thickness;
+                1
             }
-            MathBoxContent::Boxes(boxes) => boxes.first().and_then(|node| node.first_glyph()),
-            _ => None,
+            MathBoxContent::Boxes(ref mut boxes) => boxes
+                .iter_mut()
+                .map(|math_box| math_box.enforce_min_rule_thickness_units(min_thickness))
+                .sum(),
+            _ => 0,
         }
     }
 
-    pub fn last_glyph(&self) -> Option<(MathGlyph, PercentValue)> {
+    /// Returns the rectangle covered by the box given the absolute origin of its parent. It
+    /// contains both the ink and the advance width of the box.
+    pub fn rect(&self, parent_origin: Vector<i32>) -> Rect<i32> {
+        let origin = parent_origin + self.origin;
+        let extents = self.extents();
+        let left = min(0, extents.left_side_bearing);
+        let right = max(self.advance_width(), extents.right_edge());
+        Rect {
+            origin: Vector {
+                x: origin.x + left,
+                y: origin.y - extents.ascent,
+            },
+            width: right - left,
+            height: extents.height(),
+        }
+    }
+
+    /// Calls `visitor` with every box inside this box (including itself) and its absolute origin
+    /// until `visitor` returns `false`, in which case the children of that box are skipped.
+    pub(crate) fn visit<F>(&self, parent_origin: Vector<i32>, visitor: &mut F)
+    where
+        F: FnMut(&MathBox, Vector<i32>) -> bool,
+    {
+        if !visitor(self, parent_origin) {
+            return;
+        }
+        if let MathBoxContent::Boxes(ref boxes) = self.content {
+            let origin = parent_origin + self.origin;
+            for child in boxes {
+                child.visit(origin, visitor);
+            }
+        }
+    }
+
+    /// Returns the rectangle covering all outermost boxes whose user data matches `predicate`,
+    /// e.g. to highlight a subexpression. The coordinates are in font units relative to the
+    /// origin of the parent of this box.
+    pub fn bounds_of(&self, predicate: impl Fn(u64) -> bool) -> Option<Rect<i32>> {
+        let mut bounds: Option<Rect<i32>> = None;
+        self.visit(Vector::default(), &mut |math_box, parent_origin| {
+            if !predicate(math_box.user_data()) {
+                return true;
+            }
+            let rect = math_box.rect(parent_origin);
+            bounds = Some(bounds.map_or(rect, |bounds| bounds.union(rect)));
+            false
+        });
+        bounds
+    }
+
+    /// Returns the first glyph of the box (skipping children without glyphs like spaces) together
+    /// with its origin relative to the origin of the parent of this box and the scale it is
+    /// rendered with.
+    pub fn first_glyph(&self) -> Option<(MathGlyph, Vector<i32>, PercentScale2D)> {
+        self.edge_glyph(Vector::default(), false)
+    }
+
+    /// Like `first_glyph` but returns the last glyph of the box.
+    pub fn last_glyph(&self) -> Option<(MathGlyph, Vector<i32>, PercentScale2D)> {
+        self.edge_glyph(Vector::default(), true)
+    }
+
+    fn edge_glyph(
+        &self,
+        parent_origin: Vector<i32>,
+        last: bool,
+    ) -> Option<(MathGlyph, Vector<i32>, PercentScale2D)> {
+        let origin = parent_origin + self.origin;
         match self.content() {
             MathBoxContent::Drawable(Drawable::Glyphs { glyphs, scale }) => {
-                glyphs.last().map(|g| (*g, *scale))
+                let index = match last {
+                    true => glyphs.len().checked_sub(1)?,
+                    false => 0,
+                };
+                let glyph = *glyphs.get(index)?;
+                let advance = glyphs[..index].iter().map(|g| g.advance_width).sum();
+                let offset = glyph_offset(&glyph, advance, *scale);
+                Some((glyph, origin + offset, *scale))
             }
-            MathBoxContent::Boxes(ref boxes) => boxes.last().and_then(|node| node.last_glyph()),
+            MathBoxContent::Boxes(boxes) if last => boxes
+                .iter()
+                .rev()
+                .find_map(|child| child.edge_glyph(origin, last)),
+            MathBoxContent::Boxes(boxes) => boxes
+                .iter()
+                .find_map(|child| child.edge_glyph(origin, last)),
             _ => None,
         }
     }
+
+    /// Returns all glyphs of the box in drawing order together with their origins relative to
+    /// the origin of the parent of this box and the scale they are rendered with.
+    pub fn glyphs(&self) -> impl Iterator<Item = (MathGlyph, Vector<i32>, PercentScale2D)> {
+        let mut result = Vec::new();
+        self.visit(Vector::default(), &mut |math_box, parent_origin| {
+            if let MathBoxContent::Drawable(Drawable::Glyphs { glyphs, scale }) = math_box.content()
+            {
+                let origin = parent_origin + math_box.origin;
+                let mut advance = 0;
+                for glyph in glyphs {
+                    let offset = glyph_offset(glyph, advance, *scale);
+                    result.push((*glyph, origin + offset, *scale));
+                    advance += glyph.advance_width;
+                }
+            }
+            true
+        });
+        result.into_iter()
+    }
 }
 
+/// Returns the offset of a glyph from the origin of its box. `advance` is the sum of the advance
+/// widths of the glyphs before it.
+fn glyph_offset(glyph: &MathGlyph, advance: i32, scale: PercentScale2D) -> Vector<i32> {
+    let offset = Vector {
+        x: advance + glyph.offset.x,
+        y: glyph.offset.y,
+    };
+    offset * scale
+}
+
+impl sealed::Sealed for MathBox {}
+
 impl MathBoxMetrics for MathBox {
     fn advance_width(&self) -> i32 {
         self.metrics.advance_width()
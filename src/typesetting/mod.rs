@@ -1,46 +1,81 @@
+pub mod display;
+pub mod display_list;
+mod estimate;
 mod layout;
 pub mod math_box;
 mod multiscripts;
+pub mod navigation;
+pub mod outline;
+mod selection;
 pub mod shaper;
+mod spacing;
 mod stretchy;
-pub mod unicode_math;
 
-pub use self::layout::{layout_expression, LayoutOptions, MathLayout};
+pub use self::estimate::estimate_extents;
+pub use self::layout::{
+    layout_expression, IntegralLimits, LayoutError, LayoutOptions, MathKerning, MathLayout,
+    RadicalSymbol, SpacingModel, StyleContext,
+};
+pub use self::spacing::SpacingClass;
 use self::math_box::MathBox;
 use self::shaper::MathShaper;
 use crate::types::*;
+use std::cell::RefCell;
 
 // Calculates the dimensions of the components and their relative positioning. However no space
 // is distributed.
 pub fn layout<'a>(expression: &'a MathExpression, shaper: &'a impl MathShaper) -> MathBox {
-    layout_with_style(expression, shaper, |old, _| old)
+    layout_with_style_context(expression, shaper, |old, _| old)
 }
 
+/// Like `layout` but fails if a part of the expression can't be laid out as described instead of
+/// substituting a placeholder for it. The error is the first problem in layout order.
+///
+/// Shapers for fonts without a MATH table (which approximate the math constants) fail with
+/// `LayoutError::MissingMathTable` right away.
+pub fn try_layout(
+    expression: &MathExpression,
+    shaper: &impl MathShaper,
+) -> Result<MathBox, LayoutError> {
+    if shaper.get_math_table().is_empty() {
+        return Err(LayoutError::MissingMathTable);
+    }
+    let errors = RefCell::new(Vec::new());
+    let options = LayoutOptions::new(shaper)
+        .user_data(expression.get_user_data())
+        .errors(&errors);
+    let math_box = layout::layout_expression(expression, options);
+    match errors.into_inner().into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(math_box),
+    }
+}
+
+/// Lays out the expression while letting `style` alter the style of every subexpression based on
+/// its user data.
 pub fn layout_with_style<'a>(
     expression: &'a MathExpression,
     shaper: &'a impl MathShaper,
     style: impl Fn(LayoutStyle, u64) -> LayoutStyle,
 ) -> MathBox {
-    let user_data = expression.get_user_data();
-
-    let default_style = LayoutStyle {
-        math_style: MathStyle::Display,
-        script_level: 0,
-        is_cramped: false,
-        flat_accent: false,
-        stretch_constraints: None,
-        as_accent: false,
-    };
-
-    let new_style = style(default_style, user_data);
+    layout_with_style_context(expression, shaper, |old, context| {
+        style(old, context.user_data)
+    })
+}
 
-    let options = LayoutOptions {
-        shaper: shaper,
-        style_provider: &style,
-        style: new_style,
-        stretch_size: None,
-        user_data: expression.get_user_data(),
-    };
+/// Lays out the expression while letting `style` alter the style of every subexpression.
+///
+/// In contrast to `layout_with_style` the callback also receives the markup element the
+/// subexpression was created from, so that e.g. only `mtext` elements can be scaled.
+pub fn layout_with_style_context<'a>(
+    expression: &'a MathExpression,
+    shaper: &'a impl MathShaper,
+    style: impl Fn(LayoutStyle, &StyleContext) -> LayoutStyle,
+) -> MathBox {
+    // The style callback is applied to the expression itself during layout.
+    let options = LayoutOptions::new(shaper)
+        .style_provider(&style)
+        .user_data(expression.get_user_data());
 
     layout::layout_expression(expression, options)
 }
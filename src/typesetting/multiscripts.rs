@@ -1,9 +1,20 @@
 use std::cmp::max;
 
-use super::layout::LayoutOptions;
+use super::layout::{LayoutOptions, MathKerning};
 use super::math_box::{MathBox, MathBoxMetrics};
 use super::shaper::{MathConstant, Position};
-use crate::types::CornerPosition;
+use crate::types::{CornerPosition, PercentScale2D};
+
+// The nucleus may have been shifted vertically (like a stretched delimiter or a large operator
+// that is centered on the math axis), so its extents are measured from the baseline of the script
+// layout instead of its own baseline.
+fn ascent_above_baseline(nucleus: &MathBox) -> i32 {
+    nucleus.extents().ascent - nucleus.origin.y
+}
+
+fn descent_below_baseline(nucleus: &MathBox) -> i32 {
+    nucleus.extents().descent + nucleus.origin.y
+}
 
 pub fn get_superscript_shift_up(
     superscript: &MathBox,
@@ -21,8 +32,8 @@ pub fn get_superscript_shift_up(
     let min_shift_up =
         superscript.extents().descent + shaper.math_constant(MathConstant::SuperscriptBottomMin);
 
-    let min_shift_from_baseline_drop =
-        nucleus.extents().ascent - shaper.math_constant(MathConstant::SuperscriptBaselineDropMax);
+    let min_shift_from_baseline_drop = ascent_above_baseline(nucleus)
+        - shaper.math_constant(MathConstant::SuperscriptBaselineDropMax);
 
     max(
         min_shift_from_baseline_drop,
@@ -36,8 +47,8 @@ pub fn get_subscript_shift_dn(
     options: LayoutOptions,
 ) -> Position {
     let shaper = options.shaper;
-    let min_shift_dn_from_baseline_drop =
-        nucleus.extents().descent + shaper.math_constant(MathConstant::SubscriptBaselineDropMin);
+    let min_shift_dn_from_baseline_drop = descent_below_baseline(nucleus)
+        + shaper.math_constant(MathConstant::SubscriptBaselineDropMin);
 
     let std_shift_dn = shaper.math_constant(MathConstant::SubscriptShiftDown);
     let min_shift_dn =
@@ -80,7 +91,13 @@ pub fn get_subsup_shifts(
     (sub_shift, super_shift)
 }
 
-// TODO: needs tests
+/// Returns the horizontal kerning between the nucleus and an attachment at the given corner from
+/// the `MathKernInfo` of the font, limited as `options.math_kerning` demands.
+///
+/// `attachment_shift` is the distance the attachment will be moved away from the baseline (up for
+/// top and down for bottom attachments). The kerning is looked up at heights relative to the
+/// baselines of the glyphs that face each other, so glyphs that are shifted or scaled inside of
+/// the nucleus or the attachment (like a nested script) are kerned correctly.
 pub fn get_attachment_kern(
     nucleus: &MathBox,
     attachment: &MathBox,
@@ -90,42 +107,58 @@ pub fn get_attachment_kern(
 ) -> Position {
     let shaper = options.shaper;
     let mut kerning = 0;
+    if options.math_kerning == MathKerning::Disabled {
+        return kerning;
+    }
 
-    let nucleus_glyph = if attachment_position.is_left() {
-        nucleus.last_glyph()
+    let (nucleus_glyph, attachment_glyph) = if attachment_position.is_left() {
+        (nucleus.first_glyph(), attachment.last_glyph())
     } else {
-        nucleus.first_glyph()
+        (nucleus.last_glyph(), attachment.first_glyph())
     };
-
-    if let Some((nucleus_glyph, scale)) = nucleus_glyph {
-        let attachment_glyph = if attachment_position.is_left() {
-            attachment.last_glyph()
+    // the correction heights are divided by the scale, so glyphs scaled down to nothing aren't
+    // kerned
+    let is_visible = |glyph: &(_, _, PercentScale2D)| glyph.2.vertical.as_percentage() > 0;
+    let nucleus_glyph = nucleus_glyph.filter(is_visible);
+    let attachment_glyph = attachment_glyph.filter(is_visible);
+
+    if let (Some(nucleus_glyph), Some(attachment_glyph)) = (nucleus_glyph, attachment_glyph) {
+        let (nucleus_glyph, nucleus_origin, scale) = nucleus_glyph;
+        let (attachment_glyph, attachment_origin, attachment_scale) = attachment_glyph;
+        let shift = if attachment_position.is_top() {
+            -attachment_shift
         } else {
-            attachment.first_glyph()
+            attachment_shift
         };
-        if let Some((attachment_glyph, attachment_scale)) = attachment_glyph {
-            let (bch, ach) = if attachment_position.is_top() {
-                let base_correction_height =
-                    attachment_shift - attachment.extents().descent * attachment_scale;
-                let attachment_correction_height =
-                    nucleus.extents().ascent * scale - attachment_shift;
-                (base_correction_height, attachment_correction_height)
-            } else {
-                let base_correction_height =
-                    -attachment_shift + attachment.extents().ascent * attachment_scale;
-                let attachment_correction_height =
-                    attachment_shift - nucleus.extents().descent * scale;
-                (base_correction_height, attachment_correction_height)
-            };
-            kerning += shaper.math_kerning(&nucleus_glyph, attachment_position, bch) * scale;
-            kerning += shaper.math_kerning(
-                &attachment_glyph,
-                attachment_position.diagonal_mirror(),
-                ach,
-            ) * attachment_scale;
-        }
-    };
-    kerning
+        let attachment_baseline = attachment_origin.y + shift;
+        // the edges of the boxes that face each other
+        let (attachment_edge, nucleus_edge) = if attachment_position.is_top() {
+            (
+                attachment.origin.y + shift + attachment.extents().descent,
+                nucleus.origin.y - nucleus.extents().ascent,
+            )
+        } else {
+            (
+                attachment.origin.y + shift - attachment.extents().ascent,
+                nucleus.origin.y + nucleus.extents().descent,
+            )
+        };
+        // the heights are measured upwards in the units of the unscaled glyphs
+        let base_correction_height = (nucleus_origin.y - attachment_edge) / scale.vertical;
+        let attachment_correction_height =
+            (attachment_baseline - nucleus_edge) / attachment_scale.vertical;
+        kerning += shaper.math_kerning(&nucleus_glyph, attachment_position, base_correction_height)
+            * scale.horizontal;
+        kerning += shaper.math_kerning(
+            &attachment_glyph,
+            attachment_position.diagonal_mirror(),
+            attachment_correction_height,
+        ) * attachment_scale.horizontal;
+    }
+    match options.math_kerning {
+        MathKerning::Clamped(limit) => kerning.max(-limit.abs()).min(limit.abs()),
+        _ => kerning,
+    }
 }
 
 pub fn position_attachment(
@@ -137,7 +170,6 @@ pub fn position_attachment(
     options: LayoutOptions,
 ) {
     let shift = attachment_vert_shift;
-
     let kern = get_attachment_kern(nucleus, attachment, attachment_position, shift, options);
 
     let italic_correction = match (nucleus_is_largeop, attachment_position.is_top()) {
@@ -154,7 +186,6 @@ pub fn position_attachment(
         attachment.origin.x += kern;
     }
 
-    attachment.origin.y += nucleus.origin.y;
     if attachment_position.is_top() {
         attachment.origin.y -= shift;
     } else {
@@ -0,0 +1,202 @@
+//! Caret movement for equation editors.
+//!
+//! The caret can be placed before and after every glyph of a token and before and after foreign
+//! objects. A position is identified by the user data of the token and the index of the glyph
+//! it precedes, so editors can map it back to their own document model.
+
+use std::cmp::Reverse;
+
+use super::math_box::{Drawable, MathBox, MathBoxContent, MathBoxMetrics, Vector};
+
+/// A place where the caret can be put.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CaretPosition {
+    /// The user data of the token the caret is in.
+    pub user_data: u64,
+    /// The index of the glyph after the caret. It equals the number of glyphs of the token if
+    /// the caret is at its end.
+    pub index: usize,
+}
+
+/// The line to draw for a caret in font units.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Caret {
+    pub x: i32,
+    /// The top of the caret.
+    pub y: i32,
+    pub height: i32,
+}
+
+impl Caret {
+    fn bottom(&self) -> i32 {
+        self.y + self.height
+    }
+
+    fn is_on_line_of(&self, other: &Caret) -> bool {
+        self.y < other.bottom() && other.y < self.bottom()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Movement {
+    Left,
+    Right,
+    /// Moves to the horizontally closest position above the caret, e.g. from the denominator of
+    /// a fraction into the numerator.
+    Up,
+    /// Moves to the horizontally closest position below the caret.
+    Down,
+    Start,
+    End,
+}
+
+/// A caret position together with its geometry.
+#[derive(Debug, Clone)]
+struct Stop {
+    position: CaretPosition,
+    caret: Caret,
+    /// The indices of the boxes containing the position in their parent boxes.
+    path: Vec<usize>,
+}
+
+/// The caret positions of a laid out formula.
+#[derive(Debug, Clone, Default)]
+pub struct Navigator {
+    /// All positions in the order of the box tree.
+    stops: Vec<Stop>,
+}
+
+fn collect_stops(
+    stops: &mut Vec<Stop>,
+    math_box: &MathBox,
+    parent_origin: Vector<i32>,
+    path: &mut Vec<usize>,
+) {
+    let origin = parent_origin + math_box.origin;
+    let extents = math_box.extents();
+    let mut push = |index, x| {
+        stops.push(Stop {
+            position: CaretPosition {
+                user_data: math_box.user_data(),
+                index,
+            },
+            caret: Caret {
+                x,
+                y: origin.y - extents.ascent,
+                height: extents.height(),
+            },
+            path: path.clone(),
+        })
+    };
+    match *math_box.content() {
+        MathBoxContent::Boxes(ref boxes) => {
+            for (index, child) in boxes.iter().enumerate() {
+                path.push(index);
+                collect_stops(stops, child, origin, path);
+                path.pop();
+            }
+        }
+        MathBoxContent::Drawable(Drawable::Glyphs { ref glyphs, scale }) => {
+            let mut advance = 0;
+            for (index, glyph) in glyphs.iter().enumerate() {
+                push(index, origin.x + advance * scale.horizontal);
+                advance += glyph.advance_width;
+            }
+            push(glyphs.len(), origin.x + advance * scale.horizontal);
+        }
+        MathBoxContent::Foreign { .. } => {
+            push(0, origin.x);
+            push(1, origin.x + math_box.advance_width());
+        }
+        _ => {}
+    }
+}
+
+impl Navigator {
+    /// Collects the caret positions of `math_box`. The carets are relative to the origin of the
+    /// parent of `math_box`.
+    pub fn new(math_box: &MathBox) -> Navigator {
+        let mut stops = Vec::new();
+        collect_stops(&mut stops, math_box, Vector::default(), &mut Vec::new());
+        Navigator { stops }
+    }
+
+    /// Returns all caret positions.
+    pub fn positions(&self) -> impl Iterator<Item = CaretPosition> + '_ {
+        self.stops.iter().map(|stop| stop.position)
+    }
+
+    /// Returns the caret to draw for `position` or `None` if there is no such position.
+    pub fn caret(&self, position: CaretPosition) -> Option<Caret> {
+        self.find(position).map(|index| self.stops[index].caret)
+    }
+
+    /// Returns the position after applying `movement` to `position`. The position doesn't change
+    /// if there is nowhere to move to or if it is unknown.
+    pub fn move_caret(&self, position: CaretPosition, movement: Movement) -> CaretPosition {
+        let current = match self.find(position) {
+            Some(current) => current,
+            None => return position,
+        };
+        let stop = &self.stops[current];
+        let caret = stop.caret;
+        // the end of a token and the start of the next token are usually at the same place
+        let moves_caret =
+            |other: &&Stop| other.caret.x != caret.x || !other.caret.is_on_line_of(&caret);
+        let target = match movement {
+            Movement::Left => self.stops[..current].iter().rev().find(moves_caret),
+            Movement::Right => self.stops[current + 1..].iter().find(moves_caret),
+            Movement::Up => self.nearest(stop, |other| other.bottom() <= caret.y),
+            Movement::Down => self.nearest(stop, |other| other.y >= caret.bottom()),
+            Movement::Start => self.stops.first(),
+            Movement::End => self.stops.last(),
+        };
+        target.map_or(position, |stop| stop.position)
+    }
+
+    /// Returns the position whose caret is closest to the point `(x, y)`, e.g. to place the
+    /// caret with the mouse.
+    pub fn position_at(&self, x: i32, y: i32) -> Option<CaretPosition> {
+        let point = Caret { x, y, height: 0 };
+        self.stops
+            .iter()
+            .min_by_key(|stop| (stop.caret.x - x).abs() + vertical_gap(&point, &stop.caret))
+            .map(|stop| stop.position)
+    }
+
+    fn find(&self, position: CaretPosition) -> Option<usize> {
+        self.stops.iter().position(|stop| stop.position == position)
+    }
+
+    /// Returns the stop matching `predicate` that is closest to `from` in the box tree (so that
+    /// the caret moves between the numerator and denominator of the same fraction), then
+    /// horizontally and then vertically.
+    fn nearest(&self, from: &Stop, predicate: impl Fn(&Caret) -> bool) -> Option<&Stop> {
+        self.stops
+            .iter()
+            .filter(|other| predicate(&other.caret))
+            .min_by_key(|other| {
+                let common_ancestors = from
+                    .path
+                    .iter()
+                    .zip(&other.path)
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                (
+                    Reverse(common_ancestors),
+                    (other.caret.x - from.caret.x).abs(),
+                    vertical_gap(&from.caret, &other.caret),
+                )
+            })
+    }
+}
+
+fn vertical_gap(from: &Caret, to: &Caret) -> i32 {
+    if to.bottom() < from.y {
+        from.y - to.bottom()
+    } else if to.y > from.bottom() {
+        to.y - from.bottom()
+    } else {
+        0
+    }
+}
@@ -0,0 +1,89 @@
+//! Access to the outlines of the glyphs in a math box.
+//!
+//! Renderers can use this to draw glyphs without loading the font a second time with a
+//! different font library.
+
+use std::convert::TryFrom;
+
+use ttf_parser::{self, GlyphId, OutlineBuilder};
+
+use super::shaper::HarfbuzzShaper;
+
+/// A segment of a glyph outline.
+///
+/// Coordinates are in font units with the y axis pointing upwards (as is common in fonts). Note
+/// that this is the opposite of the y axis of math boxes.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PathSegment {
+    /// Starts a new contour at the given point.
+    MoveTo { x: f32, y: f32 },
+    /// A straight line to the given point.
+    LineTo { x: f32, y: f32 },
+    /// A quadratic Bézier curve with the control point `(x1, y1)`.
+    QuadTo { x1: f32, y1: f32, x: f32, y: f32 },
+    /// A cubic Bézier curve with the control points `(x1, y1)` and `(x2, y2)`.
+    CubicTo {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        x: f32,
+        y: f32,
+    },
+    /// Closes the current contour.
+    Close,
+}
+
+/// Types that can provide the outlines of the glyphs they shape.
+pub trait GlyphOutlines {
+    /// Returns the outline of the glyph with the given glyph code or `None` if the glyph has no
+    /// outline (e.g. a space).
+    fn glyph_outline(&self, glyph_code: u32) -> Option<Vec<PathSegment>>;
+}
+
+impl<'a> GlyphOutlines for HarfbuzzShaper<'a> {
+    fn glyph_outline(&self, glyph_code: u32) -> Option<Vec<PathSegment>> {
+        let glyph_id = GlyphId(u16::try_from(glyph_code).ok()?);
+        let face = self.font.face();
+        let data = face.face_data();
+        let mut face = ttf_parser::Face::from_slice(&data, face.index()).ok()?;
+        for &(ref tag, value) in self.variations() {
+            face.set_variation(ttf_parser::Tag::from_bytes(tag), value);
+        }
+
+        let mut builder = OutlineCollector(Vec::new());
+        face.outline_glyph(glyph_id, &mut builder)?;
+        Some(builder.0)
+    }
+}
+
+struct OutlineCollector(Vec<PathSegment>);
+
+impl OutlineBuilder for OutlineCollector {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.0.push(PathSegment::MoveTo { x, y });
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.0.push(PathSegment::LineTo { x, y });
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.0.push(PathSegment::QuadTo { x1, y1, x, y });
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.0.push(PathSegment::CubicTo {
+            x1,
+            y1,
+            x2,
+            y2,
+            x,
+            y,
+        });
+    }
+
+    fn close(&mut self) {
+        self.0.push(PathSegment::Close);
+    }
+}
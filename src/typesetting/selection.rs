@@ -0,0 +1,87 @@
+//! Rectangles for highlighting selected parts of a formula in editors.
+
+use std::cmp::max;
+use std::ops::Range;
+
+use super::math_box::{Drawable, MathBox, MathBoxContent, MathBoxMetrics, Rect, Vector};
+
+/// Merges rectangles of the same line (rectangles that overlap vertically) that touch or overlap
+/// horizontally.
+fn merge_rects(mut rects: Vec<Rect<i32>>) -> Vec<Rect<i32>> {
+    rects.sort_by_key(|rect| (rect.origin.x, rect.origin.y));
+    let mut merged: Vec<Rect<i32>> = Vec::with_capacity(rects.len());
+    'rects: for rect in rects {
+        for existing in merged.iter_mut() {
+            let same_line = rect.origin.y < existing.bottom() && existing.origin.y < rect.bottom();
+            if same_line && rect.origin.x <= existing.right() {
+                *existing = existing.union(rect);
+                continue 'rects;
+            }
+        }
+        merged.push(rect);
+    }
+    merged
+}
+
+impl MathBox {
+    /// Returns the rectangles to highlight when the subexpressions whose user data matches
+    /// `predicate` are selected. Adjacent rectangles on the same line are merged.
+    ///
+    /// The coordinates are in font units relative to the origin of the parent of this box.
+    pub fn selection_rects(&self, predicate: impl Fn(u64) -> bool) -> Vec<Rect<i32>> {
+        let mut rects = Vec::new();
+        self.visit(Vector::default(), &mut |math_box, parent_origin| {
+            if predicate(math_box.user_data()) {
+                // the italic correction is part of the space taken by the box in a list
+                let mut rect = math_box.rect(parent_origin);
+                let right = parent_origin.x
+                    + math_box.origin.x
+                    + math_box.advance_width()
+                    + math_box.italic_correction();
+                rect.width = max(rect.right(), right) - rect.origin.x;
+                rects.push(rect);
+                false
+            } else {
+                true
+            }
+        });
+        merge_rects(rects)
+    }
+
+    /// Returns the rectangles to highlight when the text in `range` (UTF-8 byte offsets) of the
+    /// token with the given user data is selected.
+    ///
+    /// A glyph is selected if its cluster lies in the range. The rectangles span the advance of
+    /// the glyphs and the height of the whole token.
+    pub fn text_selection_rects(&self, user_data: u64, range: Range<u32>) -> Vec<Rect<i32>> {
+        let mut rects = Vec::new();
+        self.visit(Vector::default(), &mut |math_box, parent_origin| {
+            let (glyphs, scale) = match *math_box.content() {
+                MathBoxContent::Drawable(Drawable::Glyphs { ref glyphs, scale })
+                    if math_box.user_data() == user_data =>
+                {
+                    (glyphs, scale.horizontal)
+                }
+                _ => return true,
+            };
+            let origin = parent_origin + math_box.origin;
+            let extents = math_box.extents();
+            let mut advance = 0;
+            for glyph in glyphs {
+                if range.start <= glyph.cluster && glyph.cluster < range.end {
+                    rects.push(Rect {
+                        origin: Vector {
+                            x: origin.x + advance * scale,
+                            y: origin.y - extents.ascent,
+                        },
+                        width: glyph.advance_width * scale,
+                        height: extents.height(),
+                    });
+                }
+                advance += glyph.advance_width;
+            }
+            false
+        });
+        merge_rects(rects)
+    }
+}
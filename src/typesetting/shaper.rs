@@ -2,19 +2,26 @@ extern crate harfbuzz_rs;
 
 use self::harfbuzz_rs::hb;
 use std;
-use std::cell::RefCell;
-use std::cmp::min;
+use std::cmp::{max, min};
+use std::fmt;
 
 pub use self::harfbuzz_rs::Position;
 use self::harfbuzz_rs::{
-    shape, Blob, Feature, Font, GlyphBuffer, GlyphInfo, GlyphPosition, HarfbuzzObject, Shared, Tag,
-    UnicodeBuffer,
+    shape, Blob, Direction, Face, Feature, Font, GlyphBuffer, GlyphInfo, GlyphPosition,
+    HarfbuzzObject, Shared, Tag, UnicodeBuffer, Variation,
 };
 use self::harfbuzz_rs::{FontFuncs, Glyph};
-use super::math_box::{Drawable, Extents, MathBox, MathBoxContent, MathBoxMetrics, Vector};
-use crate::types::{CornerPosition, LayoutStyle, PercentValue};
+use super::layout::LayoutError;
+use super::math_box::{
+    sealed::Sealed, AssemblyPart, Drawable, Extents, GlyphAssembly, MathBox, MathBoxContent,
+    MathBoxMetrics, Vector,
+};
+use crate::types::{CornerPosition, FontFeatures, LayoutStyle, PercentValue, TextDirection};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(C)]
 pub enum MathConstant {
     ScriptPercentScaleDown = 0,
@@ -75,6 +82,220 @@ pub enum MathConstant {
     RadicalDegreeBottomRaisePercent,
 }
 
+macro_rules! math_constants {
+    ($($(#[$doc:meta])* $field:ident: $constant:ident,)*) => {
+        /// A snapshot of all math constants of a font.
+        ///
+        /// All values are in font units except for the ones ending in `percent`.
+        #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        pub struct MathConstants {
+            $($(#[$doc])* pub $field: i32,)*
+        }
+
+        impl MathConstants {
+            /// Reads all math constants from the font of the shaper.
+            pub fn from_shaper(shaper: &impl MathShaper) -> MathConstants {
+                MathConstants {
+                    $($field: shaper.math_constant(MathConstant::$constant),)*
+                }
+            }
+
+            /// Returns the value of the given constant.
+            pub fn get(&self, constant: MathConstant) -> i32 {
+                match constant {
+                    $(MathConstant::$constant => self.$field,)*
+                }
+            }
+        }
+    };
+}
+
+math_constants! {
+    /// Scale-down factor in percent for the first level of scripts.
+    script_percent_scale_down: ScriptPercentScaleDown,
+    /// Scale-down factor in percent for the second level of scripts.
+    script_script_percent_scale_down: ScriptScriptPercentScaleDown,
+    /// Minimum height of n-ary operators that are used as delimiters.
+    delimited_sub_formula_min_height: DelimitedSubFormulaMinHeight,
+    /// Minimum height of n-ary operators in display style.
+    display_operator_min_height: DisplayOperatorMinHeight,
+    /// White space between lines of math formulas.
+    math_leading: MathLeading,
+    /// Height of the math axis above the baseline.
+    axis_height: AxisHeight,
+    /// Maximum height of a base that does not require raising its accents.
+    accent_base_height: AccentBaseHeight,
+    /// Minimum height of a base that requires flattened accents.
+    flattened_accent_base_height: FlattenedAccentBaseHeight,
+    /// Standard shift down applied to subscripts.
+    subscript_shift_down: SubscriptShiftDown,
+    /// Maximum height of the top of a subscript above the baseline.
+    subscript_top_max: SubscriptTopMax,
+    /// Minimum drop of the subscript baseline below the bottom of the base.
+    subscript_baseline_drop_min: SubscriptBaselineDropMin,
+    /// Standard shift up applied to superscripts.
+    superscript_shift_up: SuperscriptShiftUp,
+    /// Standard shift up applied to superscripts in cramped style.
+    superscript_shift_up_cramped: SuperscriptShiftUpCramped,
+    /// Minimum height of the bottom of a superscript above the baseline.
+    superscript_bottom_min: SuperscriptBottomMin,
+    /// Maximum drop of the superscript baseline below the top of the base.
+    superscript_baseline_drop_max: SuperscriptBaselineDropMax,
+    /// Minimum gap between a subscript and a superscript.
+    sub_superscript_gap_min: SubSuperscriptGapMin,
+    /// Maximum height of the bottom of a superscript that has a subscript.
+    superscript_bottom_max_with_subscript: SuperscriptBottomMaxWithSubscript,
+    /// Extra space after subscripts and superscripts.
+    space_after_script: SpaceAfterScript,
+    /// Minimum gap between an upper limit and the base.
+    upper_limit_gap_min: UpperLimitGapMin,
+    /// Minimum rise of the upper limit baseline above the top of the base.
+    upper_limit_baseline_rise_min: UpperLimitBaselineRiseMin,
+    /// Minimum gap between a lower limit and the base.
+    lower_limit_gap_min: LowerLimitGapMin,
+    /// Minimum drop of the lower limit baseline below the bottom of the base.
+    lower_limit_baseline_drop_min: LowerLimitBaselineDropMin,
+    /// Standard shift up of the top element of a stack.
+    stack_top_shift_up: StackTopShiftUp,
+    /// Standard shift up of the top element of a stack in display style.
+    stack_top_display_style_shift_up: StackTopDisplayStyleShiftUp,
+    /// Standard shift down of the bottom element of a stack.
+    stack_bottom_shift_down: StackBottomShiftDown,
+    /// Standard shift down of the bottom element of a stack in display style.
+    stack_bottom_display_style_shift_down: StackBottomDisplayStyleShiftDown,
+    /// Minimum gap between the elements of a stack.
+    stack_gap_min: StackGapMin,
+    /// Minimum gap between the elements of a stack in display style.
+    stack_display_style_gap_min: StackDisplayStyleGapMin,
+    /// Standard shift up of the element above a stretched glyph.
+    stretch_stack_top_shift_up: StretchStackTopShiftUp,
+    /// Standard shift down of the element below a stretched glyph.
+    stretch_stack_bottom_shift_down: StretchStackBottomShiftDown,
+    /// Minimum gap between a stretched glyph and the element above it.
+    stretch_stack_gap_above_min: StretchStackGapAboveMin,
+    /// Minimum gap between a stretched glyph and the element below it.
+    stretch_stack_gap_below_min: StretchStackGapBelowMin,
+    /// Standard shift up of the numerator.
+    fraction_numerator_shift_up: FractionNumeratorShiftUp,
+    /// Standard shift up of the numerator in display style.
+    fraction_numerator_display_style_shift_up: FractionNumeratorDisplayStyleShiftUp,
+    /// Standard shift down of the denominator.
+    fraction_denominator_shift_down: FractionDenominatorShiftDown,
+    /// Standard shift down of the denominator in display style.
+    fraction_denominator_display_style_shift_down: FractionDenominatorDisplayStyleShiftDown,
+    /// Minimum gap between the numerator and the fraction bar.
+    fraction_numerator_gap_min: FractionNumeratorGapMin,
+    /// Minimum gap between the numerator and the fraction bar in display style.
+    fraction_num_display_style_gap_min: FractionNumDisplayStyleGapMin,
+    /// Thickness of the fraction bar.
+    fraction_rule_thickness: FractionRuleThickness,
+    /// Minimum gap between the denominator and the fraction bar.
+    fraction_denominator_gap_min: FractionDenominatorGapMin,
+    /// Minimum gap between the denominator and the fraction bar in display style.
+    fraction_denom_display_style_gap_min: FractionDenomDisplayStyleGapMin,
+    /// Horizontal gap between numerator and denominator of skewed fractions.
+    skewed_fraction_horizontal_gap: SkewedFractionHorizontalGap,
+    /// Vertical gap between numerator and denominator of skewed fractions.
+    skewed_fraction_vertical_gap: SkewedFractionVerticalGap,
+    /// Gap between the overbar and the base.
+    overbar_vertical_gap: OverbarVerticalGap,
+    /// Thickness of the overbar.
+    overbar_rule_thickness: OverbarRuleThickness,
+    /// Extra white space above the overbar.
+    overbar_extra_ascender: OverbarExtraAscender,
+    /// Gap between the underbar and the base.
+    underbar_vertical_gap: UnderbarVerticalGap,
+    /// Thickness of the underbar.
+    underbar_rule_thickness: UnderbarRuleThickness,
+    /// Extra white space below the underbar.
+    underbar_extra_descender: UnderbarExtraDescender,
+    /// Gap between the radicand and the rule of a radical.
+    radical_vertical_gap: RadicalVerticalGap,
+    /// Gap between the radicand and the rule of a radical in display style.
+    radical_display_style_vertical_gap: RadicalDisplayStyleVerticalGap,
+    /// Thickness of the radical rule.
+    radical_rule_thickness: RadicalRuleThickness,
+    /// Extra white space above the radical rule.
+    radical_extra_ascender: RadicalExtraAscender,
+    /// Extra horizontal space before the degree of a radical.
+    radical_kern_before_degree: RadicalKernBeforeDegree,
+    /// Extra horizontal space after the degree of a radical.
+    radical_kern_after_degree: RadicalKernAfterDegree,
+    /// Height of the bottom of the radical degree in percent of the radical height.
+    radical_degree_bottom_raise_percent: RadicalDegreeBottomRaisePercent,
+}
+
+impl MathConstants {
+    /// Approximates the math constants of a font without a MATH table from its em size, x-height
+    /// and rule (e.g. underline) thickness in font units.
+    ///
+    /// The values are those of Latin Modern Math relative to these metrics, i.e. gaps are
+    /// multiples of the rule thickness and most shifts are fractions of the em size like in TeX.
+    pub fn from_font_metrics(em_size: i32, x_height: i32, rule_thickness: i32) -> MathConstants {
+        let em = |per_mille: i32| em_size * per_mille / 1000;
+        let rule = |count: i32| rule_thickness * count;
+        MathConstants {
+            script_percent_scale_down: 70,
+            script_script_percent_scale_down: 50,
+            delimited_sub_formula_min_height: em(1300),
+            display_operator_min_height: em(1300),
+            math_leading: em(154),
+            axis_height: x_height * 58 / 100,
+            accent_base_height: x_height,
+            flattened_accent_base_height: em(664),
+            subscript_shift_down: em(247),
+            subscript_top_max: x_height * 4 / 5,
+            subscript_baseline_drop_min: em(200),
+            superscript_shift_up: em(363),
+            superscript_shift_up_cramped: em(289),
+            superscript_bottom_min: x_height / 4,
+            superscript_baseline_drop_max: em(250),
+            sub_superscript_gap_min: rule(4),
+            superscript_bottom_max_with_subscript: x_height * 4 / 5,
+            space_after_script: em(56),
+            upper_limit_gap_min: em(200),
+            upper_limit_baseline_rise_min: em(111),
+            lower_limit_gap_min: em(167),
+            lower_limit_baseline_drop_min: em(600),
+            stack_top_shift_up: em(444),
+            stack_top_display_style_shift_up: em(677),
+            stack_bottom_shift_down: em(345),
+            stack_bottom_display_style_shift_down: em(686),
+            stack_gap_min: rule(3),
+            stack_display_style_gap_min: rule(7),
+            stretch_stack_top_shift_up: em(111),
+            stretch_stack_bottom_shift_down: em(600),
+            stretch_stack_gap_above_min: em(200),
+            stretch_stack_gap_below_min: em(167),
+            fraction_numerator_shift_up: em(394),
+            fraction_numerator_display_style_shift_up: em(677),
+            fraction_denominator_shift_down: em(345),
+            fraction_denominator_display_style_shift_down: em(686),
+            fraction_numerator_gap_min: rule(1),
+            fraction_num_display_style_gap_min: rule(3),
+            fraction_rule_thickness: rule(1),
+            fraction_denominator_gap_min: rule(1),
+            fraction_denom_display_style_gap_min: rule(3),
+            skewed_fraction_horizontal_gap: em(350),
+            skewed_fraction_vertical_gap: em(96),
+            overbar_vertical_gap: rule(3),
+            overbar_rule_thickness: rule(1),
+            overbar_extra_ascender: rule(1),
+            underbar_vertical_gap: rule(3),
+            underbar_rule_thickness: rule(1),
+            underbar_extra_descender: rule(1),
+            radical_vertical_gap: rule(1) + rule(1) / 4,
+            radical_display_style_vertical_gap: rule(1) + x_height / 4,
+            radical_rule_thickness: rule(1),
+            radical_extra_ascender: rule(1),
+            radical_kern_before_degree: em_size * 5 / 18,
+            radical_kern_after_degree: -em_size * 10 / 18,
+            radical_degree_bottom_raise_percent: 60,
+        }
+    }
+}
+
 /// A structure that describes an individual glyph in a font.
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct MathGlyph {
@@ -94,6 +315,8 @@ pub struct MathGlyph {
     pub top_accent_attachment: i32,
 }
 
+impl Sealed for MathGlyph {}
+
 impl MathBoxMetrics for MathGlyph {
     fn advance_width(&self) -> i32 {
         self.advance_width
@@ -118,6 +341,28 @@ pub trait MathShaper {
 
     fn shape(&self, string: &str, style: LayoutStyle, user_data: u64) -> MathBox;
 
+    /// Returns the advance width of a word space (U+0020) in the given style.
+    fn space_width(&self, style: LayoutStyle) -> Position {
+        self.shape(" ", style, 0).advance_width()
+    }
+
+    /// Lays out the glyph with the given index in the font without going through the cmap.
+    ///
+    /// Layout uses this for glyph fields, radical symbols and stretchy operators that keep their
+    /// nominal glyph, so the box must draw the glyph.
+    fn shape_glyph(&self, glyph: u32, style: LayoutStyle, user_data: u64) -> MathBox;
+
+    /// Returns the glyph the font maps `c` to without shaping or `None` if the font doesn't
+    /// cover `c`.
+    ///
+    /// The default implementation shapes `c` on its own and returns the resulting glyph.
+    fn nominal_glyph(&self, c: char) -> Option<u32> {
+        self.shape(c.encode_utf8(&mut [0; 4]), LayoutStyle::default(), 0)
+            .first_glyph()
+            .map(|(glyph, _, _)| glyph.glyph_code)
+            .filter(|&glyph| glyph != 0)
+    }
+
     /// Returns a pointer to an OpenType-Math table.
     fn get_math_table(&self) -> &[u8];
 
@@ -155,6 +400,8 @@ pub struct HarfbuzzGlyph<'a> {
     shaper: &'a HarfbuzzShaper<'a>,
 }
 
+impl<'a> Sealed for HarfbuzzGlyph<'a> {}
+
 impl<'a> MathBoxMetrics for HarfbuzzGlyph<'a> {
     fn advance_width(&self) -> i32 {
         self.advance.x
@@ -233,12 +480,22 @@ impl<'a> From<HarfbuzzGlyph<'a>> for MathGlyph {
 }
 
 /// The basic font structure used
+///
+/// The lifetime `'a` is the lifetime of the font data. A shaper created with
+/// `HarfbuzzShaper::from_bytes` owns its data (see `OwnedShaper`).
+///
+/// Every shaping call uses its own buffer, so the shaper is `Send` and `Sync` and can be shared
+/// between threads (e.g. behind an `Arc`).
 #[derive(Debug)]
 pub struct HarfbuzzShaper<'a> {
     pub font: Shared<Font<'a>>,
     pub no_cmap_font: Shared<Font<'a>>,
-    buffer: RefCell<Option<UnicodeBuffer>>,
-    math_table: Shared<Blob<'a>>,
+    math_table: Option<Shared<Blob<'a>>>,
+    // the synthesized constants of a font without a MATH table
+    fallback_constants: Option<MathConstants>,
+    variant_selector: Box<dyn VariantSelector>,
+    // the coordinates of the variation axes the instance of a variable font is selected with
+    variations: Vec<([u8; 4], f32)>,
 }
 
 pub struct IdentityFuncs;
@@ -249,24 +506,131 @@ impl FontFuncs for IdentityFuncs {
     }
 }
 
+/// A shaper that owns its font data and can therefore be kept in long-lived structures.
+pub type OwnedShaper = HarfbuzzShaper<'static>;
+
+impl HarfbuzzShaper<'static> {
+    /// Creates a shaper for the face with the given index that takes ownership of the font data.
+    ///
+    /// # Panics
+    /// Panics if the face has no MATH table.
+    pub fn from_bytes(data: Vec<u8>, face_index: u32) -> OwnedShaper {
+        let face = Face::new(data, face_index);
+        HarfbuzzShaper::new(Font::new(face).into())
+    }
+}
+
 impl<'a> HarfbuzzShaper<'a> {
+    /// Creates a shaper for the font.
+    ///
+    /// # Panics
+    /// Panics if the font has no MATH table (see `try_new` and `new_with_fallback`).
     pub fn new(font: Shared<Font>) -> HarfbuzzShaper {
-        let buffer = Some(UnicodeBuffer::new()).into();
-        let mut no_cmap_font = Font::create_sub_font(font.clone());
-        no_cmap_font.set_font_funcs(IdentityFuncs);
+        HarfbuzzShaper::try_new(font).expect("MATH table must be present")
+    }
+
+    /// Creates a shaper for the font or fails with `LayoutError::MissingMathTable`.
+    pub fn try_new(font: Shared<Font>) -> Result<HarfbuzzShaper, LayoutError> {
         let math_table = font
             .face()
             .table_with_tag(b"MATH")
-            .expect("MATH table must be present");
+            .ok_or(LayoutError::MissingMathTable)?;
+        Ok(HarfbuzzShaper::with_math_table(
+            font,
+            Some(math_table),
+            None,
+        ))
+    }
+
+    /// Creates a shaper for the font like `new` but also accepts ordinary text fonts.
+    ///
+    /// The math constants of a font without a MATH table are approximated from its x-height and
+    /// underline thickness (see `MathConstants::from_font_metrics`). Such a font has no glyph
+    /// variants, so delimiters and accents aren't stretched and only simple formulas look right.
+    /// `try_layout` fails with `LayoutError::MissingMathTable` for these shapers.
+    pub fn new_with_fallback(font: Shared<Font>) -> HarfbuzzShaper {
+        if let Some(math_table) = font.face().table_with_tag(b"MATH") {
+            return HarfbuzzShaper::with_math_table(font, Some(math_table), None);
+        }
+        let face = font.face();
+        let em_size = face.upem() as i32;
+        let data = face.face_data();
+        let metrics = ttf_parser::Face::from_slice(&data, face.index())
+            .ok()
+            .map(|face| {
+                let x_height = face.x_height().map(i32::from);
+                let rule_thickness = face
+                    .underline_metrics()
+                    .map(|metrics| i32::from(metrics.thickness))
+                    .filter(|&thickness| thickness > 0);
+                (x_height, rule_thickness)
+            });
+        let (x_height, rule_thickness) = metrics.unwrap_or((None, None));
+        let constants = MathConstants::from_font_metrics(
+            em_size,
+            x_height.unwrap_or(em_size * 43 / 100),
+            rule_thickness.unwrap_or(em_size / 25),
+        );
+        HarfbuzzShaper::with_math_table(font, None, Some(constants))
+    }
+
+    fn with_math_table(
+        font: Shared<Font<'a>>,
+        math_table: Option<Shared<Blob<'a>>>,
+        fallback_constants: Option<MathConstants>,
+    ) -> HarfbuzzShaper<'a> {
+        let mut no_cmap_font = Font::create_sub_font(font.clone());
+        no_cmap_font.set_font_funcs(IdentityFuncs);
         HarfbuzzShaper {
             font,
             no_cmap_font: no_cmap_font.into(),
-            buffer,
             math_table,
+            fallback_constants,
+            variant_selector: Box::new(DefaultVariantSelector),
+            variations: Vec::new(),
         }
     }
 
-    // Return the font's scale factor for a given script level.
+    /// Returns true if the font has a MATH table, i.e. the shaper doesn't use approximated math
+    /// constants (see `new_with_fallback`).
+    pub fn has_math_table(&self) -> bool {
+        self.math_table.is_some()
+    }
+
+    /// Replaces the policy that decides which variant or assembly of a glyph is used when it is
+    /// stretched (e.g. for delimiters and wide accents).
+    pub fn with_variant_selector(mut self, selector: impl VariantSelector + 'static) -> Self {
+        self.variant_selector = Box::new(selector);
+        self
+    }
+
+    /// Selects an instance of a variable font by setting the coordinates of its variation axes,
+    /// e.g. `(*b"opsz", 8.0)` for text set at 8 points or `(*b"wght", 700.0)` for bold glyphs.
+    /// Axes that aren't given have their default value and axes the font doesn't have are
+    /// ignored.
+    ///
+    /// The math constants, glyph metrics, variants and outlines are read from the selected
+    /// instance.
+    pub fn with_variations(self, variations: &[([u8; 4], f32)]) -> Self {
+        let hb_variations: Vec<_> = variations
+            .iter()
+            .map(|&(ref tag, value)| Variation::new(tag, value))
+            .collect();
+        let mut font = Font::create_sub_font(self.font.clone());
+        font.set_variations(&hb_variations);
+        HarfbuzzShaper {
+            variant_selector: self.variant_selector,
+            variations: variations.to_vec(),
+            ..HarfbuzzShaper::with_math_table(font.into(), self.math_table, self.fallback_constants)
+        }
+    }
+
+    /// Returns the coordinates of the variation axes set with `with_variations`.
+    pub fn variations(&self) -> &[([u8; 4], f32)] {
+        &self.variations
+    }
+
+    // Return the font's scale factor for a given script level and math size.
     fn scale_factor(&self, style: LayoutStyle) -> PercentValue {
         let percent = if style.script_level >= 1 {
             if style.script_level >= 2 {
@@ -277,15 +641,21 @@ impl<'a> HarfbuzzShaper<'a> {
         } else {
             100
         };
-        PercentValue::new(percent as u8)
+        // scripts of tiny sizes are rounded up to 1% so that dividing by the factor is possible
+        let percent = max(1, percent * style.math_size);
+        PercentValue::magnification(percent as u16)
     }
 
     fn shape_with_style(&self, string: &str, style: LayoutStyle, user_data: u64) -> MathBox {
-        let mut buffer = self.buffer.borrow_mut().take().unwrap();
-
-        buffer = buffer.add_str(string);
-        *self.buffer.borrow_mut() = Some(buffer);
-        self.do_shape(&self.font, style, user_data)
+        let buffer = UnicodeBuffer::new().add_str(string);
+        let buffer = match style.direction {
+            TextDirection::Ltr => buffer.set_script(Tag::from(b"Math")),
+            // shaping right-to-left text (e.g. joining Arabic letters) depends on its script
+            TextDirection::Rtl => buffer
+                .set_direction(Direction::Rtl)
+                .guess_segment_properties(),
+        };
+        self.do_shape(&self.font, buffer, style, user_data)
     }
 
     fn glyph_from_index(
@@ -294,41 +664,45 @@ impl<'a> HarfbuzzShaper<'a> {
         style: LayoutStyle,
         user_data: u64,
     ) -> Vec<MathGlyph> {
-        let buffer = self.buffer.borrow_mut().take().unwrap();
-        let buffer = buffer.add(glyph_index, 0);
-        *self.buffer.borrow_mut() = Some(buffer);
-        let math_box = self.do_shape(&self.no_cmap_font, style, user_data);
+        let buffer = UnicodeBuffer::new()
+            .add(glyph_index, 0)
+            .set_script(Tag::from(b"Math"));
+        let math_box = self.do_shape(&self.no_cmap_font, buffer, style, user_data);
         match math_box.content {
             MathBoxContent::Drawable(Drawable::Glyphs { glyphs, .. }) => glyphs,
             _ => unreachable!(),
         }
     }
 
-    fn do_shape(&self, font: &Font, style: LayoutStyle, user_data: u64) -> MathBox {
-        let mut features = Vec::with_capacity(2);
+    fn do_shape(
+        &self,
+        font: &Font,
+        buffer: UnicodeBuffer,
+        style: LayoutStyle,
+        user_data: u64,
+    ) -> MathBox {
+        let mut features = Vec::with_capacity(2 + FontFeatures::CAPACITY);
         if style.script_level >= 1 {
             let math_variants_tag = Tag::new('s', 's', 't', 'y');
-            let variant_num = style.script_level as u32;
+            // fonts only contain alternates for script and scriptscript size
+            let variant_num = min(style.script_level, 2) as u32;
 
             features.push(Feature::new(math_variants_tag, variant_num, ..));
         }
         if style.flat_accent {
             features.push(Feature::new(Tag::from(b"flac"), 1, ..));
         }
-
-        let buffer = self
-            .buffer
-            .borrow_mut()
-            .take()
-            .expect("Buffer not available");
-        let glyph_buffer = shape(font, buffer.set_script(Tag::from(b"Math")), &features);
-        let math_box = {
-            let shaped_glyphs = self.layout_boxes(&glyph_buffer, style);
-            MathBox::with_glyphs(shaped_glyphs.collect(), self.scale_factor(style), user_data)
-        };
-        *self.buffer.borrow_mut() = Some(glyph_buffer.clear());
-
-        math_box
+        // features that are given later take precedence
+        features.extend(
+            style
+                .font_features
+                .iter()
+                .map(|(tag, value)| Feature::new(Tag::from(tag), value, ..)),
+        );
+
+        let glyph_buffer = shape(font, buffer, &features);
+        let shaped_glyphs = self.layout_boxes(&glyph_buffer, style);
+        MathBox::with_glyphs(shaped_glyphs.collect(), self.scale_factor(style), user_data)
     }
 
     fn layout_boxes<'b>(
@@ -355,17 +729,31 @@ fn point_with_offset(offset: i32, horizontal: bool) -> Vector<i32> {
 
 impl<'a> MathShaper for HarfbuzzShaper<'a> {
     fn math_constant(&self, c: MathConstant) -> i32 {
+        if let Some(ref constants) = self.fallback_constants {
+            return constants.get(c);
+        }
         unsafe { hb::hb_ot_math_get_constant(self.font.as_raw(), c as _) }
     }
 
     fn get_math_table(&self) -> &[u8] {
-        &self.math_table
+        self.math_table
+            .as_ref()
+            .map_or(&[], |math_table| &math_table[..])
     }
 
     fn shape(&self, string: &str, style: LayoutStyle, user_data: u64) -> MathBox {
         self.shape_with_style(string, style, user_data)
     }
 
+    fn shape_glyph(&self, glyph: u32, style: LayoutStyle, user_data: u64) -> MathBox {
+        let glyphs = self.glyph_from_index(glyph, style, user_data);
+        MathBox::with_glyphs(glyphs, self.scale_factor(style), user_data)
+    }
+
+    fn nominal_glyph(&self, c: char) -> Option<u32> {
+        self.font.get_nominal_glyph(c)
+    }
+
     fn is_stretchable(&self, glyph: u32, horizontal: bool) -> bool {
         let direction = if horizontal {
             hb::HB_DIRECTION_LTR
@@ -409,9 +797,21 @@ impl<'a> MathShaper for HarfbuzzShaper<'a> {
         // rescale target size for the current layout
         let target_size = target_size / self.scale_factor(style);
 
-        let glyphs = try_base_glyph(self, glyph, horizontal, target_size, style, user_data)
-            .or_else(|| try_variant(self, glyph, horizontal, target_size, style, user_data))
-            .or_else(|| try_assembly(self, glyph, horizontal, target_size, style, user_data))
+        let stretch = |glyph| stretch_glyph(self, glyph, horizontal, target_size, style, user_data);
+        // Accents above tall bases are stretched starting from their flattened form (see the
+        // `flac` feature). If the font has no variants for the flattened glyph the variants of
+        // the original glyph are used.
+        let flattened_glyph = if style.flat_accent {
+            self.glyph_from_index(glyph, style, user_data)
+                .first()
+                .map(|flattened| flattened.glyph_code)
+                .filter(|&flattened| flattened != glyph)
+        } else {
+            None
+        };
+        let mut glyphs = flattened_glyph
+            .and_then(|flattened| stretch(flattened))
+            .or_else(|| stretch(glyph))
             .unwrap_or_else(|| {
                 MathBox::with_glyphs(
                     self.glyph_from_index(glyph, style, user_data),
@@ -419,16 +819,7 @@ impl<'a> MathShaper for HarfbuzzShaper<'a> {
                     user_data,
                 )
             });
-
-        // let result = {
-        //     let glyph_indices = glyphs.iter().map(|shaped_glyph| shaped_glyph.glyph);
-        //     let mut layout_style = LayoutStyle::new();
-        //     layout_style.flat_accent = true;
-        //     self.shape_glyph_indices(glyph_indices, LayoutStyle::new())
-        // };
-        // for (ref mut original_glyph, shaped_glyph) in glyphs.iter_mut().zip(result) {
-        //     original_glyph.glyph = shaped_glyph.glyph;
-        // }
+        set_stretched_top_accent_attachment(&mut glyphs);
         glyphs
     }
 
@@ -453,30 +844,157 @@ impl<'a> MathShaper for HarfbuzzShaper<'a> {
     }
 }
 
-fn try_base_glyph<'a>(
-    shaper: &HarfbuzzShaper,
+/// Fonts usually don't specify a top accent attachment for glyph variants and never for glyph
+/// assemblies. In this case half the advance width would be used which is visibly off for wide
+/// accents like a stretched hat. Use the horizontal center of the ink instead.
+fn set_stretched_top_accent_attachment(math_box: &mut MathBox) {
+    let has_attachment = match math_box.content {
+        MathBoxContent::Drawable(Drawable::Glyphs { ref glyphs, .. }) if glyphs.len() == 1 => {
+            // HarfBuzz reports half the advance width if there is no attachment in the font
+            glyphs[0].top_accent_attachment != glyphs[0].advance_width / 2
+        }
+        _ => false,
+    };
+    if !has_attachment {
+        math_box.metrics.top_accent_attachment = math_box.extents().center();
+    }
+}
+
+/// A variant of a glyph from the MATH table.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GlyphVariant {
+    pub glyph: u32,
+    /// The size of the variant in the direction of stretching.
+    pub advance: i32,
+}
+
+/// The sizes in which a glyph that is to be stretched is available.
+///
+/// All lengths are in font units of the unscaled font.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StretchCandidates {
+    /// The size the glyph should be stretched to.
+    pub target_size: i32,
+    pub horizontal: bool,
+    /// Accents should not become larger than the base they are placed over.
+    pub as_accent: bool,
+    /// The size of the unstretched glyph.
+    pub base_size: i32,
+    /// The variants of the glyph as listed in the font (usually ordered by size).
+    pub variants: Vec<GlyphVariant>,
+    /// Whether the font contains a glyph assembly that can be stretched to any size.
+    pub has_assembly: bool,
+}
+
+/// The way a glyph is stretched.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StretchChoice {
+    /// Use the glyph itself.
+    Base,
+    /// Use the variant at the given index of `StretchCandidates::variants`.
+    Variant(usize),
+    /// Build the glyph from the parts of its glyph assembly.
+    Assembly,
+}
+
+/// Decides how a glyph is stretched to a target size.
+///
+/// Applications can install their own selector with `HarfbuzzShaper::with_variant_selector`, e.g.
+/// to prefer glyph assemblies for large delimiters or to accept variants that are slightly too
+/// small.
+pub trait VariantSelector: fmt::Debug + Send + Sync {
+    /// Returns how to stretch the glyph. If `None` is returned (or the font has no assembly) the
+    /// glyph is used unstretched.
+    fn select(&self, candidates: &StretchCandidates) -> Option<StretchChoice>;
+}
+
+/// Chooses the base glyph if it is large enough, otherwise the smallest variant that covers the
+/// target size and otherwise the glyph assembly. Accents use the largest variant that is not
+/// larger than the target size instead.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct DefaultVariantSelector;
+
+impl VariantSelector for DefaultVariantSelector {
+    fn select(&self, candidates: &StretchCandidates) -> Option<StretchChoice> {
+        if candidates.base_size >= candidates.target_size {
+            return Some(StretchChoice::Base);
+        }
+        let variants = candidates.variants.iter().enumerate();
+        let variant = if candidates.as_accent {
+            // the largest variant that is smaller than the target size
+            variants
+                .filter(|&(_, variant)| variant.advance <= candidates.target_size)
+                .max_by_key(|&(_, variant)| variant.advance)
+        } else {
+            // the smallest variant that is larger than the target size
+            variants
+                .filter(|&(_, variant)| variant.advance >= candidates.target_size)
+                .min_by_key(|&(_, variant)| variant.advance)
+        };
+        match variant {
+            Some((index, _)) => Some(StretchChoice::Variant(index)),
+            None if candidates.has_assembly => Some(StretchChoice::Assembly),
+            None => None,
+        }
+    }
+}
+
+fn stretch_glyph<'a>(
+    shaper: &'a HarfbuzzShaper<'a>,
     glyph: u32,
     horizontal: bool,
     target_size: u32,
     style: LayoutStyle,
     user_data: u64,
 ) -> Option<MathBox> {
-    let glyph = shaper.glyph_from_index(glyph, style, user_data)[0];
-
-    let advance = if horizontal {
-        glyph.extents.width
+    let direction = if horizontal {
+        hb::HB_DIRECTION_LTR
     } else {
-        -glyph.extents.height()
+        hb::HB_DIRECTION_TTB
     };
-
-    if advance >= target_size as i32 {
-        Some(MathBox::with_glyphs(
-            vec![glyph],
-            shaper.scale_factor(style),
-            user_data,
-        ))
+    let base_glyph = shaper.glyph_from_index(glyph, style, user_data)[0];
+    let base_size = if horizontal {
+        base_glyph.extents.width
     } else {
-        None
+        -base_glyph.extents.height()
+    };
+    let variants = VariantIterator {
+        shaper,
+        glyph,
+        direction,
+        index: 0,
+    };
+    let assembly = AssemblyIterator {
+        shaper,
+        glyph,
+        direction,
+        index: 0,
+    };
+    let candidates = StretchCandidates {
+        target_size: target_size as i32,
+        horizontal,
+        as_accent: style.as_accent,
+        base_size,
+        variants: variants
+            .map(|variant| GlyphVariant {
+                glyph: variant.glyph,
+                advance: variant.advance,
+            })
+            .collect(),
+        has_assembly: assembly.len() > 0,
+    };
+
+    let scale = shaper.scale_factor(style);
+    match shaper.variant_selector.select(&candidates)? {
+        StretchChoice::Base => Some(MathBox::with_glyphs(vec![base_glyph], scale, user_data)),
+        StretchChoice::Variant(index) => {
+            let variant = candidates.variants.get(index)?;
+            let glyphs = shaper.glyph_from_index(variant.glyph, style, user_data);
+            Some(MathBox::with_glyphs(glyphs, scale, user_data))
+        }
+        StretchChoice::Assembly => {
+            try_assembly(shaper, glyph, horizontal, target_size, style, user_data)
+        }
     }
 }
 
@@ -530,50 +1048,6 @@ impl<'a> Iterator for VariantIterator<'a> {
 
 impl<'a> ExactSizeIterator for VariantIterator<'a> {}
 
-fn try_variant<'a>(
-    shaper: &'a HarfbuzzShaper<'a>,
-    glyph: u32,
-    horizontal: bool,
-    target_size: u32,
-    style: LayoutStyle,
-    user_data: u64,
-) -> Option<MathBox> {
-    let direction = if horizontal {
-        hb::HB_DIRECTION_LTR
-    } else {
-        hb::HB_DIRECTION_TTB
-    };
-
-    let iter = VariantIterator {
-        shaper: shaper,
-        glyph: glyph,
-        direction: direction,
-        index: 0,
-    };
-
-    let variant = if style.as_accent {
-        // return the largest variant that is smaller than the target size
-        iter.filter(|&variant| variant.advance <= target_size as i32)
-            .max_by_key(|&variant| variant.advance)
-    } else {
-        // return the smallest variant that is larger than the target size
-        iter.filter(|&variant| variant.advance >= target_size as i32)
-            .min_by_key(|&variant| variant.advance)
-    };
-
-    let variant = match variant {
-        Some(variant) => variant,
-        None => return None,
-    };
-
-    let glyphs = shaper.glyph_from_index(variant.glyph, style, user_data);
-    Some(MathBox::with_glyphs(
-        glyphs,
-        shaper.scale_factor(style),
-        user_data,
-    ))
-}
-
 struct AssemblyIterator<'a> {
     shaper: &'a HarfbuzzShaper<'a>,
     glyph: u32,
@@ -626,6 +1100,25 @@ impl<'a> Iterator for AssemblyIterator<'a> {
 
 impl<'a> ExactSizeIterator for AssemblyIterator<'a> {}
 
+impl<'a> AssemblyIterator<'a> {
+    /// The italic correction of the assembled glyph.
+    fn italic_correction(&self) -> i32 {
+        let mut italics_correction: i32 = 0;
+        unsafe {
+            hb::hb_ot_math_get_glyph_assembly(
+                self.shaper.font.as_raw(),
+                self.glyph,
+                self.direction,
+                0,
+                &mut 0,
+                std::ptr::null_mut(),
+                &mut italics_correction,
+            )
+        };
+        italics_correction
+    }
+}
+
 fn try_assembly<'a>(
     shaper: &'a HarfbuzzShaper<'a>,
     glyph: u32,
@@ -712,9 +1205,10 @@ fn try_assembly<'a>(
         0
     };
 
+    let italic_correction = assembly_iter.italic_correction();
     assembly_iter.index = 0;
-    let result = assembly_iter
-        // Repeat the extenders `repeat_count_ext` times .
+    // Repeat the extenders `repeat_count_ext` times.
+    let parts: Vec<_> = assembly_iter
         .flat_map(move |part| {
             let repeat_count = if part.flags == hb::HB_OT_MATH_GLYPH_PART_FLAG_EXTENDER {
                 repeat_count_ext
@@ -723,30 +1217,67 @@ fn try_assembly<'a>(
             } as usize;
             std::iter::repeat(part).take(repeat_count)
         })
-        // Offset the each glyph from the previous glyph by the advance of the part minus the
-        // connector overlap.
-        .scan(/* initial offset */ 0, move |current_offset, part| {
-            let delta_offset = part.full_advance - connector_overlap;
-            let origin = point_with_offset(*current_offset, horizontal);
+        .collect();
+
+    // Offset the each glyph from the previous glyph by the advance of the part minus the
+    // connector overlap. The offsets are scaled like the glyphs themselves.
+    let scale = shaper.scale_factor(style);
+    let mut current_offset = 0;
+    let boxes = parts
+        .iter()
+        .map(|part| {
             let glyphs = shaper.glyph_from_index(part.glyph, style, user_data);
+            let mut math_box = MathBox::with_glyphs(glyphs, scale, user_data);
+            math_box.origin = point_with_offset(current_offset * scale, horizontal);
 
-            let mut math_box = MathBox::with_glyphs(glyphs, shaper.scale_factor(style), user_data);
-            math_box.origin = origin;
-
+            let delta_offset = part.full_advance - connector_overlap;
             if horizontal {
-                *current_offset += delta_offset;
+                current_offset += delta_offset;
             } else {
-                *current_offset -= delta_offset;
+                current_offset -= delta_offset;
             }
-            Some(math_box)
-        });
-
-    Some(MathBox::with_vec(result.collect(), user_data))
+            math_box
+        })
+        .collect();
+
+    let mut math_box = MathBox::with_vec(boxes, user_data);
+    // The italic correction of the last part doesn't apply to the whole assembly.
+    math_box.metrics.italic_correction = italic_correction * scale;
+    math_box.assembly = Some(Box::new(GlyphAssembly {
+        horizontal,
+        parts: parts
+            .iter()
+            .map(|part| AssemblyPart {
+                glyph_code: part.glyph,
+                is_extender: part.flags == hb::HB_OT_MATH_GLYPH_PART_FLAG_EXTENDER,
+                full_advance: part.full_advance * scale,
+            })
+            .collect(),
+        connector_overlap: connector_overlap * scale,
+    }));
+    Some(math_box)
 }
 
 #[cfg(test)]
 mod test {
+    use super::*;
 
     #[test]
     fn test_assembly() {}
+
+    #[test]
+    fn test_constants_from_font_metrics() {
+        // the metrics of Latin Modern give (nearly) its own constants
+        let constants = MathConstants::from_font_metrics(1000, 431, 40);
+        assert_eq!(constants.axis_height, 249);
+        assert_eq!(constants.fraction_rule_thickness, 40);
+        assert_eq!(constants.fraction_num_display_style_gap_min, 120);
+        assert_eq!(constants.radical_display_style_vertical_gap, 147);
+        assert_eq!(constants.script_percent_scale_down, 70);
+
+        // lengths scale with the em size
+        let constants = MathConstants::from_font_metrics(2048, 2 * 431, 2 * 40);
+        assert_eq!(constants.superscript_shift_up, 743);
+        assert_eq!(constants.stack_display_style_gap_min, 560);
+    }
 }
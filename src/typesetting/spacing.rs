@@ -0,0 +1,164 @@
+//! TeX's spacing between the items of a list.
+//!
+//! Every item gets a spacing class (TeX's atom types) and the space between two adjacent items
+//! is looked up from the classes of both items like in the table of The TeXbook, chapter 18.
+
+use super::layout::{LayoutOptions, MathLayout, OperatorProperties};
+use crate::types::{Form, MathExpression, MathItem};
+
+/// The spacing class of an item in a list.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SpacingClass {
+    /// Ordinary symbols like letters and digits.
+    Ordinary,
+    /// Large operators like sums and integrals.
+    LargeOperator,
+    /// Binary operators like `+`.
+    Binary,
+    /// Relations like `=`.
+    Relation,
+    /// Opening fences.
+    Opening,
+    /// Closing fences.
+    Closing,
+    /// Punctuation like commas.
+    Punctuation,
+    /// Fractions.
+    Inner,
+}
+
+use self::SpacingClass::*;
+
+/// The space between two classes in mu (1/18 em). Negative values are only inserted in
+/// non-script styles. Combinations that can't occur (e.g. a binary operator after a relation)
+/// are 0.
+const SPACING_TABLE: [[i32; 8]; 8] = [
+    // Ord Op  Bin Rel Open Close Punct Inner
+    [0, 3, -4, -5, 0, 0, 0, -3],     // Ordinary
+    [3, 3, 0, -5, 0, 0, 0, -3],      // LargeOperator
+    [-4, -4, 0, 0, -4, 0, 0, -4],    // Binary
+    [-5, -5, 0, 0, -5, 0, 0, -5],    // Relation
+    [0, 0, 0, 0, 0, 0, 0, 0],        // Opening
+    [0, 3, -4, -5, 0, 0, 0, -3],     // Closing
+    [-3, -3, 0, -3, -3, -3, -3, -3], // Punctuation
+    [-3, 3, -4, -5, -3, 0, -3, -3],  // Inner
+];
+
+fn table_index(class: SpacingClass) -> usize {
+    match class {
+        Ordinary => 0,
+        LargeOperator => 1,
+        Binary => 2,
+        Relation => 3,
+        Opening => 4,
+        Closing => 5,
+        Punctuation => 6,
+        Inner => 7,
+    }
+}
+
+/// Returns the spacing class of a list item or `None` for spaces, which don't take part in the
+/// spacing.
+pub fn spacing_class(item: &MathExpression, options: LayoutOptions) -> Option<SpacingClass> {
+    let class = match item.operator_properties(options) {
+        Some(OperatorProperties {
+            is_large_op: true, ..
+        }) => LargeOperator,
+        Some(OperatorProperties {
+            is_separator: true, ..
+        }) => Punctuation,
+        Some(OperatorProperties {
+            is_fence: true,
+            form,
+            ..
+        }) => match form {
+            Form::Prefix => Opening,
+            Form::Postfix => Closing,
+            Form::Infix => Ordinary,
+        },
+        // The operator dictionary gives relations a thick space and binary operators a medium
+        // space.
+        Some(OperatorProperties {
+            form: Form::Infix,
+            leading_space,
+            ..
+        }) => {
+            let em = options.shaper.em_size();
+            if leading_space >= em * 5 / 18 {
+                Relation
+            } else if leading_space > 0 {
+                Binary
+            } else {
+                Ordinary
+            }
+        }
+        Some(_) => Ordinary,
+        None => match *item.item() {
+            MathItem::Space(_) => return None,
+            MathItem::GeneralizedFraction(_) => Inner,
+            _ => Ordinary,
+        },
+    };
+    Some(class)
+}
+
+/// Returns the space in font units that goes in front of every item of the list.
+pub fn tex_spacing(list: &[MathExpression], options: LayoutOptions) -> Vec<i32> {
+    let mut classes = list
+        .iter()
+        .map(|item| spacing_class(item, options))
+        .collect::<Vec<_>>();
+
+    // A binary operator without an operand on either side is an ordinary symbol (like the minus
+    // in `-x`).
+    let mut previous: Option<usize> = None;
+    for index in 0..classes.len() {
+        let class = match classes[index] {
+            Some(class) => class,
+            None => continue,
+        };
+        let previous_class = previous.and_then(|previous| classes[previous]);
+        match (previous_class, class) {
+            (None, Binary)
+            | (Some(Binary), Binary)
+            | (Some(LargeOperator), Binary)
+            | (Some(Relation), Binary)
+            | (Some(Opening), Binary)
+            | (Some(Punctuation), Binary) => classes[index] = Some(Ordinary),
+            (Some(Binary), Relation) | (Some(Binary), Closing) | (Some(Binary), Punctuation) => {
+                if let Some(previous) = previous {
+                    classes[previous] = Some(Ordinary);
+                }
+            }
+            _ => {}
+        }
+        previous = Some(index);
+    }
+    if let Some(last) = previous {
+        if classes[last] == Some(Binary) {
+            classes[last] = Some(Ordinary);
+        }
+    }
+
+    let em = options.shaper.em_size();
+    let is_script = options.style.script_level > 0;
+    let mut previous = None;
+    classes
+        .into_iter()
+        .map(|class| {
+            let class = match class {
+                Some(class) => class,
+                None => return 0,
+            };
+            let mu = match previous {
+                Some(previous) => SPACING_TABLE[table_index(previous)][table_index(class)],
+                None => 0,
+            };
+            previous = Some(class);
+            match mu {
+                mu if mu < 0 && is_script => 0,
+                mu => mu.abs() * em / 18,
+            }
+        })
+        .collect()
+}
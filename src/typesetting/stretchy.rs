@@ -1,25 +1,77 @@
 use super::*;
 
-use super::layout::{MathLayout, OperatorProperties};
+use super::layout::{embellished_operator_of_list, MathLayout, OperatorProperties, SpacingModel};
 use crate::math_box::{Extents, MathBoxMetrics};
 use crate::types::MathExpression;
 
-fn indices_of_stretchy_elements(list: &[MathExpression], options: LayoutOptions) -> Vec<usize> {
-    list.iter()
-        .enumerate()
-        .filter(|&(_, ref expr)| expr.can_stretch(options))
-        .map(|(index, _)| index)
-        .collect()
+fn is_stretchy(properties: &Option<OperatorProperties>) -> bool {
+    properties
+        .map(|properties| properties.stretch_properties.is_some())
+        .unwrap_or_default()
+}
+
+/// Pairs every stretchy postfix fence with the closest preceding unmatched prefix fence of the
+/// list (like TeX's `\left` and `\right`). Inner pairs come before the pairs enclosing them.
+fn matching_fences(properties: &[Option<OperatorProperties>]) -> Vec<(usize, usize)> {
+    let mut open_fences = Vec::new();
+    let mut pairs = Vec::new();
+    for (index, item_properties) in properties.iter().enumerate() {
+        match *item_properties {
+            Some(OperatorProperties {
+                is_fence: true,
+                stretch_properties: Some(_),
+                form,
+                ..
+            }) => match form {
+                Form::Prefix => open_fences.push(index),
+                Form::Postfix => {
+                    if let Some(open_index) = open_fences.pop() {
+                        pairs.push((open_index, index));
+                    }
+                }
+                Form::Infix => {}
+            },
+            _ => {}
+        }
+    }
+    pairs
+}
+
+/// The vertical extents that stretchy operators have to cover to enclose the given boxes.
+///
+/// Items may have been shifted vertically (e.g. large operators centered on the math axis) so
+/// their origin is taken into account.
+fn stretch_size_of<'a>(boxes: impl Iterator<Item = &'a MathBox> + Clone) -> Extents<i32> {
+    Extents {
+        left_side_bearing: 0,
+        width: 0,
+        ascent: boxes
+            .clone()
+            .map(|math_box| math_box.extents().ascent - math_box.origin.y)
+            .max()
+            .unwrap_or_default(),
+        descent: boxes
+            .map(|math_box| math_box.extents().descent + math_box.origin.y)
+            .max()
+            .unwrap_or_default(),
+    }
 }
 
 pub fn layout_list_element(item: &MathExpression, options: LayoutOptions) -> MathBox {
     if let Some(OperatorProperties {
         leading_space,
         trailing_space,
+        is_separator,
         ..
     }) = item.operator_properties(options)
     {
-        if options.style.math_style == MathStyle::Display {
+        // the spacing depends on the style of the operator itself, which the style provider may
+        // have changed
+        let style = options.for_expression(item).style;
+        // like punctuation in TeX separators are spaced in all but script styles
+        let separator_spacing = is_separator && style.script_level == 0;
+        let is_spaced = style.math_style == MathStyle::Display || separator_spacing;
+        if is_spaced && options.spacing == SpacingModel::MathML {
             let left_space =
                 MathBox::empty(Extents::new(0, leading_space, 0, 0), item.get_user_data());
             let mut elem = item.layout(options);
@@ -34,55 +86,60 @@ pub fn layout_list_element(item: &MathExpression, options: LayoutOptions) -> Mat
     item.layout(options)
 }
 
+/// Lays out the items of a list and stretches its stretchy operators.
+///
+/// Matching fences stretch to the items enclosed by them (including nested fences) while all other
+/// stretchy operators stretch to the non-stretchy items of the entire list.
 pub fn layout_strechy_list(list: &[MathExpression], options: LayoutOptions) -> Vec<MathBox> {
-    let stretchy_indices = indices_of_stretchy_elements(list, options);
+    let properties = list
+        .iter()
+        .map(|item| item.operator_properties(options))
+        .collect::<Vec<_>>();
 
-    if stretchy_indices.is_empty() {
+    if !properties.iter().any(is_stretchy) {
         return list
             .iter()
             .map(move |item| layout_list_element(item, options))
             .collect();
     }
 
-    let mut items = Vec::with_capacity(list.len());
-
-    let mut max_intrinsic_size = 0;
-    for ref item in list {
-        if let Some(OperatorProperties {
-            stretch_properties: Some(stretch_props),
-            ..
-        }) = item.operator_properties(options)
-        {
-            max_intrinsic_size = ::std::cmp::max(max_intrinsic_size, stretch_props.intrinsic_size);
-        } else {
-            let math_box = layout_list_element(*item, options);
-            items.push(math_box);
-        }
-    }
-
-    let max_ascent = items.iter().map(|math_box| math_box.extents().ascent).max();
-    let max_descent = items
+    let mut boxes = list
         .iter()
-        .map(|math_box| math_box.extents().descent)
-        .max();
+        .zip(&properties)
+        .map(|(item, item_properties)| {
+            if is_stretchy(item_properties) {
+                None
+            } else {
+                Some(layout_list_element(item, options))
+            }
+        })
+        .collect::<Vec<_>>();
 
-    let options = LayoutOptions {
-        stretch_size: Some(Extents {
-            left_side_bearing: 0,
-            width: 0,
-            ascent: max_ascent.unwrap_or_default(),
-            descent: max_descent.unwrap_or_default(),
-        }),
-        ..options
+    // a list that is an embellished operator stretches to the size it was given from outside
+    let list_size = match (options.stretch_size, embellished_operator_of_list(list)) {
+        (Some(stretch_size), Some(_)) => stretch_size,
+        _ => stretch_size_of(boxes.iter().flatten()),
     };
 
-    for &stretchy_index in stretchy_indices.iter() {
-        let stretchy_item = &list[stretchy_index];
-        let math_box = layout_list_element(stretchy_item, options);
-        items.insert(stretchy_index, math_box);
+    for (open_index, close_index) in matching_fences(&properties) {
+        let fence_options = LayoutOptions {
+            stretch_size: Some(stretch_size_of(
+                boxes[open_index + 1..close_index].iter().flatten(),
+            )),
+            ..options
+        };
+        boxes[open_index] = Some(layout_list_element(&list[open_index], fence_options));
+        boxes[close_index] = Some(layout_list_element(&list[close_index], fence_options));
     }
 
-    items
+    let options = LayoutOptions {
+        stretch_size: Some(list_size),
+        ..options
+    };
+    list.iter()
+        .zip(boxes)
+        .map(|(item, math_box)| math_box.unwrap_or_else(|| layout_list_element(item, options)))
+        .collect()
 }
 
 // TODO: Tests
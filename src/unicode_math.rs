@@ -1,7 +1,7 @@
 use std::char;
 
 /// Mathematical font families available from the unicode character range.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 #[repr(u32)]
 #[derive(Debug)]
 pub enum Family {
@@ -21,6 +21,56 @@ pub enum Family {
     Monospace = 13,
 }
 
+impl Family {
+    /// Returns true if characters of this family are drawn with a bold weight.
+    pub fn is_bold(self) -> bool {
+        match self {
+            Family::Bold
+            | Family::BoldItalics
+            | Family::BoldScript
+            | Family::BoldFraktur
+            | Family::SansSerifBold
+            | Family::SansSerifBoldItalics => true,
+            _ => false,
+        }
+    }
+
+    /// Returns true if characters of this family are drawn slanted.
+    pub fn is_italic(self) -> bool {
+        match self {
+            Family::Italics
+            | Family::BoldItalics
+            | Family::SansSerifItalics
+            | Family::SansSerifBoldItalics => true,
+            _ => false,
+        }
+    }
+}
+
+/// The result of converting a character to a mathematical family.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Conversion {
+    /// The family has a dedicated code point for the character.
+    Mapped(char),
+    /// Unicode has no styled code point for the character in the requested family. The character
+    /// is left unchanged and the style has to be synthesized by the renderer (e.g. by emboldening
+    /// or slanting the glyph) if it should be visible.
+    Synthesized {
+        character: char,
+        bold: bool,
+        italic: bool,
+    },
+}
+
+impl Conversion {
+    /// Returns the character that should be shaped.
+    pub fn character(self) -> char {
+        match self {
+            Conversion::Mapped(character) | Conversion::Synthesized { character, .. } => character,
+        }
+    }
+}
+
 #[cfg_attr(rustfmt, rustfmt_skip)]
 const ASCII_CHARACTERS: &'static [u32] = &[
     // small latin
@@ -53,7 +103,7 @@ const ASCII_WITH_GREEK_CHARACTERS: &'static [u32] = &[
 
     // capital greek (notice theta symbol 0x3f4)
     0x391, 0x392, 0x393, 0x394, 0x395, 0x396, 0x397, 0x398, 0x399,
-    0x39a, 0x39b, 0x39c, 0x39d, 0x39e, 0x39f, 0x3a0, 0x3f4, 0x3a2,
+    0x39a, 0x39b, 0x39c, 0x39d, 0x39e, 0x39f, 0x3a0, 0x3a1, 0x3f4,
     0x3a3, 0x3a4, 0x3a5, 0x3a6, 0x3a7, 0x3a8, 0x3a9, 0x2207, //nabla
 ];
 
@@ -77,7 +127,7 @@ const ASCII_WITH_GREEK_AND_DOTLESS_CHARACTERS: &'static [u32] = &[
 
     // capital greek (notice theta symbol 0x3f4)
     0x391, 0x392, 0x393, 0x394, 0x395, 0x396, 0x397, 0x398, 0x399,
-    0x39a, 0x39b, 0x39c, 0x39d, 0x39e, 0x39f, 0x3a0, 0x3f4, 0x3a2,
+    0x39a, 0x39b, 0x39c, 0x39d, 0x39e, 0x39f, 0x3a0, 0x3a1, 0x3f4,
     0x3a3, 0x3a4, 0x3a5, 0x3a6, 0x3a7, 0x3a8, 0x3a9, 0x2207, //nabla
 
     // dotless i and j
@@ -114,16 +164,19 @@ const ASCII_WITH_NUMERALS_AND_GREEK_CHARACTERS: &'static [u32] = &[
     0x3ba, 0x3bb, 0x3bc, 0x3bd, 0x3be, 0x3bf, 0x3c0, 0x3c1, 0x3c2,
     0x3c3, 0x3c4, 0x3c5, 0x3c6, 0x3c7, 0x3c8, 0x3c9, 0x2202, // partial diff
     0x3f5, /* epsilon symbol */ 0x3d1, /* theta symbol */
-    0x3f0, /* kappa symbol */   0x278, /* phi symbol */
+    0x3f0, /* kappa symbol */   0x3d5, /* phi symbol */
     0x3f1, /* rho symbol */     0x3d6, /* pi symbol */
 
     // capital greek (notice theta symbol 0x3f4)
     0x391, 0x392, 0x393, 0x394, 0x395, 0x396, 0x397, 0x398, 0x399,
-    0x39a, 0x39b, 0x39c, 0x39d, 0x39e, 0x39f, 0x3a0, 0x3f4, 0x3a2,
+    0x39a, 0x39b, 0x39c, 0x39d, 0x39e, 0x39f, 0x3a0, 0x3a1, 0x3f4,
     0x3a3, 0x3a4, 0x3a5, 0x3a6, 0x3a7, 0x3a8, 0x3a9, 0x2207, //nabla
 
     // numerals
     0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39,
+
+    // capital and small digamma (only available in bold)
+    0x3dc, 0x3dd,
 ];
 
 /*
@@ -198,7 +251,10 @@ const BOLD: &'static [u32] = &[
 
     // numerals
     0x1d7ce, 0x1d7cf, 0x1d7d0, 0x1d7d1, 0x1d7d2, 0x1d7d3, 0x1d7d4,
-    0x1d7d5, 0x1d7d6, 0x1d7d7
+    0x1d7d5, 0x1d7d6, 0x1d7d7,
+
+    // digamma
+    0x1d7ca, 0x1d7cb,
 ];
 
 #[cfg_attr(rustfmt, rustfmt_skip)]
@@ -269,7 +325,7 @@ const FRAKTUR: &'static [u32] = &[
 
     // capital latin
     0x1d504, 0x1d505, 0x212d,  0x1d507, 0x1d508, 0x1d509, 0x1d50a,
-    0x210e,  0x2111,  0x1d50d, 0x1d50e, 0x1d50f, 0x1d510, 0x1d511,
+    0x210c,  0x2111,  0x1d50d, 0x1d50e, 0x1d50f, 0x1d510, 0x1d511,
     0x1d512, 0x1d513, 0x1d514, 0x211c,  0x1d516, 0x1d517, 0x1d518,
     0x1d519, 0x1d51a, 0x1d51b, 0x1d51c, 0x2128,
 ];
@@ -285,7 +341,7 @@ const DOUBLE_STRUCK: &'static [u32] = &[
     // capital latin
     0x1d538, 0x1d539, 0x2102,  0x1d53b, 0x1d53c, 0x1d53d, 0x1d53e,
     0x210d,  0x1d540, 0x1d541, 0x1d542, 0x1d543, 0x1d544, 0x2115,
-    0x1d546, 0x1d547, 0x211a,  0x211d,  0x1d54a, 0x1d54b, 0x1d54c,
+    0x1d546, 0x2119,  0x211a,  0x211d,  0x1d54a, 0x1d54b, 0x1d54c,
     0x1d54d, 0x1d54e, 0x1d54f, 0x1d550, 0x2124,
 
     // numerals
@@ -449,18 +505,45 @@ static CHARACTER_TABLES: &'static [&'static [u32]] = &[ASCII_WITH_GREEK_AND_DOTL
                                                        ASCII_WITH_GREEK_CHARACTERS,
                                                        ASCII_WITH_NUMERALS_CHARACTERS];
 
-pub fn convert_character_to_family(c: char, family: Family) -> char {
+/// Converts `c` to the code point of the given family in the Mathematical Alphanumeric Symbols
+/// block (or the Letterlike Symbols block for the holes in it).
+///
+/// Returns `None` if Unicode has no styled variant of the character.
+pub fn try_convert_character_to_family(c: char, family: Family) -> Option<char> {
     if let Family::Normal = family {
-        return c;
+        return Some(c);
     }
     let family_index = family as usize - 1;
     let table_to_search = CHARACTER_TABLES[family_index];
-    for (index, code) in table_to_search.iter().enumerate() {
-        if c as u32 == *code {
-            return unsafe { char::from_u32_unchecked(FAMILY_TABLES[family_index][index]) };
-        }
+    table_to_search
+        .iter()
+        .position(|&code| c as u32 == code)
+        .and_then(|index| FAMILY_TABLES[family_index].get(index))
+        .and_then(|&code| char::from_u32(code))
+}
+
+/// Converts `c` to the given family and reports whether the style has to be synthesized.
+pub fn convert_character(c: char, family: Family) -> Conversion {
+    match try_convert_character_to_family(c, family) {
+        Some(character) => Conversion::Mapped(character),
+        None => Conversion::Synthesized {
+            character: c,
+            bold: family.is_bold(),
+            italic: family.is_italic(),
+        },
     }
-    c
+}
+
+/// Converts `c` to the given family. Characters without a styled variant are returned unchanged.
+pub fn convert_character_to_family(c: char, family: Family) -> char {
+    convert_character(c, family).character()
+}
+
+/// Converts every character of `text` to the given family.
+pub fn convert_str_to_family(text: &str, family: Family) -> String {
+    text.chars()
+        .map(|chr| convert_character_to_family(chr, family))
+        .collect()
 }
 
 #[cfg(test)]
@@ -478,4 +561,36 @@ mod tests {
         assert_eq!(mathematical_dotless_i,
                    convert_character_to_family(latin_dotless_i, Family::Italics));
     }
+
+    #[test]
+    fn table_lengths_test() {
+        for (index, family_table) in FAMILY_TABLES.iter().enumerate() {
+            let character_table = CHARACTER_TABLES[index];
+            assert!(family_table.len() <= character_table.len());
+            assert!(family_table.iter().all(|&code| char::from_u32(code).is_some()));
+        }
+    }
+
+    #[test]
+    fn exceptional_characters_test() {
+        assert_eq!(Some('\u{210e}'), try_convert_character_to_family('h', Family::Italics));
+        assert_eq!(Some('\u{210c}'), try_convert_character_to_family('H', Family::Fraktur));
+        assert_eq!(Some('\u{2119}'), try_convert_character_to_family('P', Family::DoubleStruck));
+        assert_eq!(Some('\u{1d6b8}'), try_convert_character_to_family('\u{3a1}', Family::Bold));
+        assert_eq!(Some('\u{1d6b9}'), try_convert_character_to_family('\u{3f4}', Family::Bold));
+        assert_eq!(Some('\u{1d6df}'), try_convert_character_to_family('\u{3d5}', Family::Bold));
+        assert_eq!(Some('\u{1d7cb}'), try_convert_character_to_family('\u{3dd}', Family::Bold));
+        assert_eq!(Some('\u{1d7d8}'), try_convert_character_to_family('0', Family::DoubleStruck));
+        assert_eq!(None, try_convert_character_to_family('\u{3dd}', Family::SansSerifBold));
+    }
+
+    #[test]
+    fn synthesized_conversion_test() {
+        assert_eq!(Conversion::Synthesized { character: '1', bold: false, italic: true },
+                   convert_character('1', Family::Italics));
+        assert_eq!(Conversion::Synthesized { character: '+', bold: true, italic: false },
+                   convert_character('+', Family::Bold));
+        assert_eq!('+', convert_character_to_family('+', Family::Bold));
+        assert_eq!("\u{1d400}\u{1d7cf}", convert_str_to_family("A1", Family::Bold));
+    }
 }
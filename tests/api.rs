@@ -0,0 +1,64 @@
+//! Uses the public interface the way other crates do, so that changes that break them fail to
+//! compile here.
+#![cfg(all(feature = "mathml_parser", feature = "layout"))]
+
+extern crate math_render;
+
+use math_render::mathmlparser::{self, ParseContext, ParserOptions};
+use math_render::prelude::*;
+use math_render::testing;
+
+mod util;
+use crate::util::TEST_FONT;
+
+const FORMULA: &str = "<mrow><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo>\
+                       <mfrac><mn>1</mn><msqrt><mi>y</mi></msqrt></mfrac></mrow>";
+
+#[test]
+fn prelude_test() {
+    TEST_FONT.with(|font| {
+        let expr: MathExpression = parse(FORMULA.as_bytes()).unwrap();
+        let math_box: MathBox = layout(&expr, font);
+        let checked = try_layout(&expr, font).unwrap();
+        assert!(testing::diff(&math_box, &checked).is_empty());
+        let extents: Extents<i32> = math_box.extents();
+        assert!(math_box.advance_width() > 0 && extents.ascent > 0);
+
+        let options = LayoutOptions::new(font);
+        assert_eq!(options.style, LayoutStyle::default());
+        let list = math_box.flatten(&DeviceScale::new(font.em_size(), 16.0));
+        assert!(!list.glyph_runs.is_empty());
+    })
+}
+
+#[test]
+fn parse_context_test() {
+    let (expr, context): (MathExpression, ParseContext) =
+        mathmlparser::parse_with_context(FORMULA.as_bytes(), ParserOptions::default()).unwrap();
+    assert_eq!(
+        context.options().max_depth,
+        ParserOptions::default().max_depth
+    );
+
+    // only the `mo` is an operator
+    let operators = expr
+        .children()
+        .into_iter()
+        .filter_map(|child| context.info_for_expr(child))
+        .filter(|info| info.is_operator())
+        .count();
+    assert_eq!(operators, 1);
+}
+
+#[test]
+fn mathml_round_trip_test() {
+    TEST_FONT.with(|font| {
+        let expr = parse(FORMULA.as_bytes()).unwrap();
+        let written = mathmlparser::to_mathml(&expr);
+        let reparsed = parse(written.as_bytes()).unwrap();
+        assert_eq!(mathmlparser::to_mathml(&reparsed), written);
+
+        let differences = testing::diff(&layout(&expr, font), &layout(&reparsed, font));
+        assert!(differences.is_empty(), "{:?}", differences);
+    })
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "layout")]
+
 extern crate math_render;
 extern crate freetype;
 
@@ -0,0 +1,128 @@
+//! Lays out random small expressions and checks the invariants of the resulting box trees.
+//!
+//! Run with
+//!
+//! ```text
+//! cargo test --features invariants --test invariants
+//! ```
+#![cfg(feature = "invariants")]
+
+extern crate math_render;
+extern crate proptest;
+
+use math_render::invariants;
+use math_render::{
+    Atom, Field, Form, GeneralizedFraction, MathExpression, MathItem, Operator, OverUnder, Root,
+    StretchConstraints,
+};
+use proptest::prelude::*;
+
+mod util;
+use crate::util::TEST_FONT;
+
+/// The shape of an expression. It is converted to a `MathExpression` with distinct user data for
+/// every node so that the boxes of lists can be found.
+#[derive(Debug, Clone)]
+enum Node {
+    Token(&'static str),
+    Operator(&'static str, Form, bool),
+    List(Vec<Node>),
+    Scripts(Box<Node>, Option<Box<Node>>, Option<Box<Node>>),
+    Accent(Box<Node>, &'static str),
+    Fraction(Box<Node>, Box<Node>),
+    Root(Box<Node>, Option<Box<Node>>),
+}
+
+fn leaf() -> impl Strategy<Value = Node> {
+    prop_oneof![
+        prop::sample::select(vec!["x", "y", "f", "1", "42", "ab", "\u{3b1}"]).prop_map(Node::Token),
+        prop::sample::select(vec!["+", "=", "\u{2212}"])
+            .prop_map(|text| Node::Operator(text, Form::Infix, false)),
+        Just(Node::Operator("(", Form::Prefix, true)),
+        Just(Node::Operator(")", Form::Postfix, true)),
+    ]
+}
+
+fn node() -> impl Strategy<Value = Node> {
+    leaf().prop_recursive(4, 24, 4, |inner| {
+        let boxed = || inner.clone().prop_map(Box::new);
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 1..5).prop_map(Node::List),
+            (boxed(), prop::option::of(boxed()), prop::option::of(boxed()))
+                .prop_map(|(nucleus, sub, sup)| Node::Scripts(nucleus, sub, sup)),
+            (boxed(), prop::sample::select(vec!["^", "\u{2192}", "\u{af}"]))
+                .prop_map(|(nucleus, accent)| Node::Accent(nucleus, accent)),
+            (boxed(), boxed()).prop_map(|(num, denom)| Node::Fraction(num, denom)),
+            (boxed(), prop::option::of(boxed()))
+                .prop_map(|(radicand, degree)| Node::Root(radicand, degree)),
+        ]
+    })
+}
+
+fn to_expression(node: &Node, next_user_data: &mut u64) -> MathExpression {
+    let user_data = *next_user_data;
+    *next_user_data += 1;
+    let mut convert = |node: &Node| Some(to_expression(node, next_user_data));
+    let item = match *node {
+        Node::Token(text) => MathItem::Field(Field::Unicode(text.to_string())),
+        Node::Operator(text, form, is_fence) => MathItem::Operator(Operator {
+            field: Field::Unicode(text.to_string()),
+            form,
+            is_fence,
+            stretch_constraints: if is_fence {
+                Some(StretchConstraints::default())
+            } else {
+                None
+            },
+            ..Default::default()
+        }),
+        Node::List(ref nodes) => MathItem::List(
+            nodes
+                .iter()
+                .map(|node| to_expression(node, next_user_data))
+                .collect(),
+        ),
+        Node::Scripts(ref nucleus, ref sub, ref sup) => MathItem::Atom(Atom {
+            nucleus: convert(nucleus),
+            bottom_right: sub.as_ref().and_then(|sub| convert(sub)),
+            top_right: sup.as_ref().and_then(|sup| convert(sup)),
+            ..Default::default()
+        }),
+        Node::Accent(ref nucleus, accent) => MathItem::OverUnder(OverUnder {
+            nucleus: convert(nucleus),
+            over: convert(&Node::Operator(accent, Form::Postfix, false)),
+            over_is_accent: true,
+            ..Default::default()
+        }),
+        Node::Fraction(ref numerator, ref denominator) => {
+            MathItem::GeneralizedFraction(GeneralizedFraction {
+                numerator: convert(numerator),
+                denominator: convert(denominator),
+                thickness: None,
+            })
+        }
+        Node::Root(ref radicand, ref degree) => MathItem::Root(Root {
+            radicand: convert(radicand),
+            degree: degree.as_ref().and_then(|degree| convert(degree)),
+        }),
+    };
+    MathExpression::new(item, user_data)
+}
+
+proptest! {
+    #[test]
+    fn layout_invariants_test(node in node()) {
+        let expr = to_expression(&node, &mut 0);
+        TEST_FONT.with(|font| {
+            let math_box = math_render::layout(&expr, font);
+            let violations = invariants::check_layout(&expr, &math_box, 1);
+            let report = violations
+                .iter()
+                .map(|violation| violation.to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            prop_assert!(violations.is_empty(), "{}\n{}", report, math_box.dump());
+            Ok(())
+        })?;
+    }
+}
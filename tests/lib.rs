@@ -1,10 +1,11 @@
-#![cfg(feature = "mathml_parser")]
+#![cfg(all(feature = "mathml_parser", feature = "layout"))]
 
 extern crate math_render;
 extern crate freetype;
 
 use math_render::mathmlparser;
-use math_render::math_box::{MathBox, MathBoxContent, MathBoxMetrics};
+use math_render::math_box::{Drawable, MathBox, MathBoxContent, MathBoxMetrics};
+use math_render::{Field, MathExpression, MathItem, Operator};
 
 mod util;
 use crate::util::TEST_FONT;
@@ -31,6 +32,18 @@ fn assume_boxes(content: &MathBoxContent) -> &[MathBox] {
     }
 }
 
+fn field(text: &str, user_data: u64) -> MathExpression {
+    MathExpression::new(MathItem::Field(Field::Unicode(text.to_string())), user_data)
+}
+
+fn operator(text: &str, user_data: u64) -> MathExpression {
+    let operator = Operator {
+        field: Field::Unicode(text.to_string()),
+        ..Default::default()
+    };
+    MathExpression::new(MathItem::Operator(operator), user_data)
+}
+
 #[test]
 fn no_scale_division_test() {
     TEST_FONT.with(|font| {
@@ -81,3 +94,2465 @@ fn fraction_centering_test() {
         assert!((left_space - right_space).abs() <= 2);
     })
 }
+
+#[test]
+fn element_style_context_test() {
+    TEST_FONT.with(|font| {
+        let xml = "<mrow><mi>x</mi><mtext>abc</mtext></mrow>";
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let plain = math_render::layout(&expr, font);
+        let scaled = math_render::layout_with_style_context(&expr, font, |style, context| {
+            match context.element {
+                Some(element) if element.name == "mtext" => math_render::LayoutStyle {
+                    script_level: style.script_level + 1,
+                    ..style
+                },
+                _ => style,
+            }
+        });
+
+        let plain = assume_boxes(plain.content());
+        let scaled = assume_boxes(scaled.content());
+        assert_eq!(plain[0].advance_width(), scaled[0].advance_width());
+        assert!(scaled[1].advance_width() < plain[1].advance_width());
+    })
+}
+
+#[test]
+fn operator_style_override_test() {
+    use math_render::shaper::MathShaper;
+    use math_render::{Form, LayoutStyle, Length, MathStyle};
+
+    let plus = Operator {
+        field: Field::Unicode("+".to_string()),
+        form: Form::Infix,
+        leading_space: Length::em(0.25),
+        trailing_space: Length::em(0.25),
+        ..Default::default()
+    };
+    let expr = MathExpression::new(
+        MathItem::List(vec![
+            field("a", 1),
+            MathExpression::new(MathItem::Operator(plus), 2),
+            field("b", 3),
+        ]),
+        0,
+    );
+
+    TEST_FONT.with(|font| {
+        let spaced = math_render::layout(&expr, font);
+        // an operator laid out in inline style isn't surrounded by space even if its list is
+        // laid out in display style
+        let unspaced = math_render::layout_with_style(&expr, font, |style, user_data| {
+            if user_data == 2 {
+                LayoutStyle {
+                    math_style: MathStyle::Inline,
+                    ..style
+                }
+            } else {
+                style
+            }
+        });
+        let space = font.em_size() / 2;
+        assert_eq!(spaced.advance_width(), unspaced.advance_width() + space);
+    })
+}
+
+#[test]
+fn tex_spacing_test() {
+    use math_render::shaper::MathShaper;
+    use math_render::{Form, LayoutOptions, LayoutStyle, Length, SpacingModel};
+
+    let spaced_operator = |text: &str, mu: f32| {
+        let operator = Operator {
+            field: Field::Unicode(text.to_string()),
+            form: Form::Infix,
+            leading_space: Length::em(mu / 18.0),
+            trailing_space: Length::em(mu / 18.0),
+            ..Default::default()
+        };
+        MathExpression::new(MathItem::Operator(operator), 0)
+    };
+    let expr = MathExpression::new(
+        MathItem::List(vec![
+            spaced_operator("\u{2212}", 4.0),
+            field("1", 0),
+            spaced_operator("+", 4.0),
+            field("2", 0),
+            spaced_operator("=", 5.0),
+            field("3", 0),
+        ]),
+        0,
+    );
+
+    TEST_FONT.with(|font| {
+        let layout = |style: LayoutStyle| {
+            let options = LayoutOptions::new(font)
+                .style(style)
+                .spacing(SpacingModel::TeX);
+            math_render::layout_expression(&expr, options)
+        };
+        let gaps = |result: &MathBox| {
+            let boxes = assume_boxes(result.content());
+            boxes
+                .windows(2)
+                .map(|pair| pair[1].origin.x - pair[0].origin.x - pair[0].advance_width())
+                .collect::<Vec<_>>()
+        };
+
+        // the leading minus is unary, the plus is binary and the equals sign is a relation
+        let (medium, thick) = (4 * font.em_size() / 18, 5 * font.em_size() / 18);
+        let result = layout(LayoutStyle::default());
+        assert_eq!(gaps(&result), vec![0, medium, medium, thick, thick]);
+        // scripts have no space around binary operators and relations
+        let result = layout(LayoutStyle::default().with_increased_script_level());
+        assert_eq!(gaps(&result), vec![0; 5]);
+    })
+}
+
+#[test]
+fn payload_test() {
+    use std::sync::Arc;
+
+    TEST_FONT.with(|font| {
+        let link = field("x", 1).with_payload(Arc::new("https://example.com"));
+        let plain = field("y", 2);
+        let expr = MathExpression::new(MathItem::List(vec![link, plain]), 0);
+
+        let result = math_render::layout(&expr, font);
+        let boxes = assume_boxes(result.content());
+        assert_eq!(boxes[0].user_data(), 1);
+        assert_eq!(
+            boxes[0].payload_as::<&str>(),
+            Some(&"https://example.com")
+        );
+        assert!(boxes[0].payload_as::<String>().is_none());
+        assert!(boxes[1].payload().is_none());
+    })
+}
+
+#[test]
+fn mglyph_index_test() {
+    TEST_FONT.with(|font| {
+        let shaped = math_render::layout(&mathmlparser::parse(&b"<mi>x</mi>"[..]).unwrap(), font);
+        let glyph_code = match *shaped.content() {
+            MathBoxContent::Drawable(Drawable::Glyphs { ref glyphs, .. }) => glyphs[0].glyph_code,
+            ref other => panic!("Expected glyphs. Found {:?}.", other),
+        };
+
+        let xml = format!("<mi><mglyph index=\"{}\"/></mi>", glyph_code);
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let result = math_render::layout(&expr, font);
+        match *result.content() {
+            MathBoxContent::Drawable(Drawable::Glyphs { ref glyphs, .. }) => {
+                assert_eq!(glyphs[0].glyph_code, glyph_code)
+            }
+            ref other => panic!("Expected glyphs. Found {:?}.", other),
+        }
+        assert_eq!(result.advance_width(), shaped.advance_width());
+    })
+}
+
+#[test]
+fn nominal_glyph_test() {
+    use math_render::shaper::MathShaper;
+    use math_render::LayoutStyle;
+
+    TEST_FONT.with(|font| {
+        for &c in ['(', '\u{221a}', '\u{2211}'].iter() {
+            let shaped = font.shape(&c.to_string(), LayoutStyle::default(), 0);
+            let (glyph, _, _) = shaped.first_glyph().unwrap();
+            assert_eq!(font.nominal_glyph(c), Some(glyph.glyph_code));
+        }
+        assert_eq!(font.nominal_glyph('\u{e000}'), None);
+    })
+}
+
+#[test]
+fn foreign_object_test() {
+    use math_render::shaper::MathShaper;
+    use math_render::{ForeignObject, Length, MathSpace};
+    use std::sync::Arc;
+
+    TEST_FONT.with(|font| {
+        let foreign = ForeignObject {
+            size: MathSpace {
+                width: Length::em(2.0),
+                ascent: Length::em(1.0),
+                descent: Length::em(0.5),
+            },
+            payload: Arc::new(42u32),
+        };
+        let expr = MathExpression::new(MathItem::Foreign(foreign), 0);
+        let result = math_render::layout(&expr, font);
+
+        let em = font.em_size();
+        let extents = result.extents();
+        assert_eq!(extents.width, 2 * em);
+        assert_eq!(extents.ascent, em);
+        assert_eq!(extents.descent, em / 2);
+        match *result.content() {
+            MathBoxContent::Foreign { ref payload, .. } => {
+                assert_eq!(payload.downcast_ref::<u32>(), Some(&42))
+            }
+            ref other => panic!("Expected foreign content. Found {:?}.", other),
+        }
+    })
+}
+
+#[test]
+fn numbered_equation_test() {
+    use math_render::display::{layout_numbered, NumberingOptions, TagPosition};
+    use math_render::shaper::MathShaper;
+
+    TEST_FONT.with(|font| {
+        let expr = mathmlparser::parse(&b"<mi>x</mi><mo>=</mo><mn>1</mn>"[..]).unwrap();
+        let number = mathmlparser::parse(&b"<mtext>(1)</mtext>"[..]).unwrap();
+        let equation_width = math_render::layout(&expr, font).advance_width();
+        let tag_width = math_render::layout(&number, font).advance_width();
+        let em = font.em_size();
+
+        let options = NumberingOptions {
+            column_width: 20 * em,
+            tag_position: TagPosition::Right,
+            min_gap: em,
+        };
+        let result = layout_numbered(&expr, font, &number, options);
+        let boxes = assume_boxes(result.content());
+        assert_eq!(result.advance_width(), 20 * em);
+        assert_eq!(boxes[1].origin.x, (20 * em - equation_width) / 2);
+        assert_eq!(boxes[2].origin.x + tag_width, 20 * em);
+        assert_eq!(boxes[2].origin.y, 0);
+
+        // the equation has to give way to the tag
+        let column_width = equation_width + 2 * tag_width + em;
+        let options = NumberingOptions {
+            column_width,
+            tag_position: TagPosition::Left,
+            min_gap: em,
+        };
+        let result = layout_numbered(&expr, font, &number, options);
+        let boxes = assume_boxes(result.content());
+        assert_eq!(boxes[1].origin.x, tag_width + em);
+        assert_eq!(boxes[2].origin.x, 0);
+
+        // the tag doesn't fit next to the equation
+        let options = NumberingOptions {
+            column_width: equation_width,
+            tag_position: TagPosition::Right,
+            min_gap: em,
+        };
+        let result = layout_numbered(&expr, font, &number, options);
+        let boxes = assume_boxes(result.content());
+        assert_eq!(boxes[1].origin.x, 0);
+        assert!(boxes[2].origin.y > boxes[1].extents().descent);
+    })
+}
+
+#[test]
+fn layout_to_fit_test() {
+    use math_render::display::{layout_to_fit, VerticalBudget};
+    use math_render::{LayoutOptions, MathStyle};
+
+    TEST_FONT.with(|font| {
+        let xml = "<munderover><mo>&#x2211;</mo><mi>i</mi><mi>n</mi></munderover>\
+                   <mfrac><mn>1</mn><msup><mi>i</mi><mn>2</mn></msup></mfrac>";
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let display = math_render::layout(&expr, font);
+        let budget = |math_box: &MathBox| VerticalBudget {
+            ascent: math_box.extents().ascent,
+            descent: math_box.extents().descent,
+        };
+
+        let (result, style) = layout_to_fit(&expr, font, budget(&display));
+        assert_eq!(style.math_style, MathStyle::Display);
+        assert_eq!(result.dump(), display.dump());
+
+        // a formula that is slightly too tall switches to inline style
+        let mut smaller = budget(&display);
+        smaller.ascent -= 1;
+        let (result, style) = layout_to_fit(&expr, font, smaller);
+        assert_eq!(style.math_style, MathStyle::Inline);
+        assert_eq!(style.script_level, 0);
+        assert!(result.extents().ascent <= smaller.ascent);
+
+        // without any space the smallest layout is used
+        let (result, style) = layout_to_fit(&expr, font, VerticalBudget::default());
+        assert_eq!(style.math_style, MathStyle::Inline);
+        assert_eq!(style.script_level, 2);
+        let options = LayoutOptions::new(font).style(style);
+        let smallest = math_render::layout_expression(&expr, options);
+        assert_eq!(result.dump(), smallest.dump());
+    })
+}
+
+#[test]
+fn layout_with_max_width_test() {
+    use math_render::display::{layout_with_max_width, WidthFit};
+
+    TEST_FONT.with(|font| {
+        let xml = "<mfrac><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow><mi>c</mi></mfrac>";
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let display_width = math_render::layout(&expr, font).advance_width();
+
+        let (result, fit) = layout_with_max_width(&expr, font, display_width);
+        assert_eq!(fit, WidthFit::Display);
+        assert_eq!(result.advance_width(), display_width);
+
+        // the numerator loses its operator spacing in inline style
+        let (result, fit) = layout_with_max_width(&expr, font, display_width - 1);
+        assert_eq!(fit, WidthFit::Inline);
+        assert!(result.advance_width() < display_width);
+
+        let (result, fit) = layout_with_max_width(&expr, font, 0);
+        assert_eq!(fit, WidthFit::TooWide);
+        assert!(result.advance_width() < display_width);
+    })
+}
+
+#[test]
+fn aligned_equations_test() {
+    use math_render::display::{layout_aligned, AlignmentMarker};
+
+    TEST_FONT.with(|font| {
+        let rows = vec![
+            vec![field("x", 0), operator("=", 0), field("1", 0)],
+            vec![
+                field("a", 0),
+                operator("+", 0),
+                field("b", 0),
+                operator("=", 0),
+                field("2", 0),
+            ],
+            vec![operator("=", 0), field("3", 0)],
+        ];
+        let expressions = rows
+            .into_iter()
+            .map(|row| MathExpression::new(MathItem::List(row), 0))
+            .collect::<Vec<_>>();
+        let marker = AlignmentMarker::FirstOperator('=');
+        let result = layout_aligned(&expressions, font, marker, 100, 1);
+        assert_eq!(result.user_data(), 1);
+        let rows = assume_boxes(result.content());
+        let operator_indices = [1, 3, 0];
+
+        let alignment_x = |index: usize| {
+            let row = &rows[index];
+            row.origin.x + assume_boxes(row.content())[operator_indices[index]].origin.x
+        };
+        assert_eq!(alignment_x(0), alignment_x(1));
+        assert_eq!(alignment_x(1), alignment_x(2));
+        assert_eq!(rows.iter().map(|row| row.origin.x).min(), Some(0));
+
+        for pair in rows.windows(2) {
+            let bottom = pair[0].origin.y + pair[0].extents().descent;
+            let top = pair[1].origin.y - pair[1].extents().ascent;
+            assert_eq!(top - bottom, 100);
+        }
+    })
+}
+
+#[test]
+fn stacked_lines_test() {
+    use math_render::display::{join_lines, layout_aligned_lines, AlignmentMarker};
+    use math_render::shaper::{MathConstant, MathShaper};
+
+    TEST_FONT.with(|font| {
+        let expressions = [
+            "<mrow><mi>x</mi><mo>=</mo><mfrac><mn>1</mn><mn>2</mn></mfrac></mrow>",
+            "<mrow><mo>=</mo><msup><mi>y</mi><mn>2</mn></msup></mrow>",
+            "<mrow><mo>=</mo><mi>z</mi></mrow>",
+        ]
+        .iter()
+        .map(|xml| mathmlparser::parse(xml.as_bytes()).unwrap())
+        .collect::<Vec<_>>();
+        let lines = layout_aligned_lines(&expressions, font, AlignmentMarker::FirstOperator('='));
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].baseline, 0);
+
+        // the ink of adjacent lines is separated by the leading
+        let leading = font.math_constant(MathConstant::MathLeading);
+        for pair in lines.windows(2) {
+            assert_eq!(pair[1].top() - pair[0].bottom(), leading);
+        }
+        // the fraction makes the first line deeper than the others
+        let distance = |index: usize| lines[index + 1].baseline - lines[index].baseline;
+        assert!(distance(0) > distance(1));
+
+        let tops = lines.iter().map(|line| line.top()).collect::<Vec<_>>();
+        let joined = join_lines(lines, 7);
+        assert_eq!(joined.user_data(), 7);
+        let boxes = assume_boxes(joined.content());
+        for (math_box, top) in boxes.iter().zip(tops) {
+            assert_eq!(math_box.origin.y - math_box.extents().ascent, top);
+        }
+    })
+}
+
+#[test]
+fn break_lines_test() {
+    use math_render::display::{break_lines, LinebreakOptions};
+    use math_render::IndentAlign;
+
+    TEST_FONT.with(|font| {
+        let parse = |xml: &str| mathmlparser::parse(xml.as_bytes()).unwrap();
+        let line_lengths = |lines: &[math_render::display::Line]| {
+            lines
+                .iter()
+                .map(|line| assume_boxes(line.math_box.content()).len())
+                .collect::<Vec<_>>()
+        };
+
+        let expr = parse("<mrow><mi>a</mi><mo>+</mo><mi>b</mi><mo>+</mo><mi>c</mi></mrow>");
+        let width = math_render::layout(&expr, font).advance_width();
+        let options = LinebreakOptions {
+            max_width: width,
+            indent_shift: 500,
+            ..Default::default()
+        };
+        assert_eq!(break_lines(&expr, font, options).len(), 1);
+
+        // the line is filled as far as possible and the operator starts the next line
+        let options = LinebreakOptions {
+            max_width: width - 1,
+            ..options
+        };
+        let lines = break_lines(&expr, font, options);
+        assert_eq!(line_lengths(&lines), vec![3, 2]);
+        assert_eq!(lines[1].math_box.origin.x, 500);
+        assert!(lines[1].baseline > 0);
+
+        let expr = parse(
+            "<mrow><mi>a</mi><mo>+</mo><mi>b</mi><mo linebreak=\"nobreak\">+</mo><mi>c</mi>\
+             </mrow>",
+        );
+        assert_eq!(line_lengths(&break_lines(&expr, font, options)), vec![1, 4]);
+
+        // a fence that must not be broken still encloses its contents
+        let expr = parse(
+            "<mrow><mo linebreak=\"nobreak\">(</mo><mi>a</mi><mo>+</mo><mi>b</mi><mo>)</mo>\
+             </mrow>",
+        );
+        let fenced_options = LinebreakOptions {
+            max_width: math_render::layout(&expr, font).advance_width() / 2,
+            ..options
+        };
+        assert_eq!(
+            line_lengths(&break_lines(&expr, font, fenced_options)),
+            vec![4, 1]
+        );
+
+        // separators end the line
+        let expr = parse("<mrow><mi>a</mi><mo>,</mo><mi>b</mi><mo>,</mo><mi>c</mi></mrow>");
+        let options = LinebreakOptions {
+            max_width: math_render::layout(&expr, font).advance_width() - 1,
+            ..options
+        };
+        assert_eq!(line_lengths(&break_lines(&expr, font, options)), vec![4, 1]);
+
+        let expr = parse(
+            "<mrow><mi>x</mi><mo linebreak=\"newline\" indentalign=\"right\" \
+             indentshift=\"0\">=</mo><mi>y</mi></mrow>",
+        );
+        let options = LinebreakOptions {
+            max_width: 10000,
+            indent_align: IndentAlign::Center,
+            indent_shift: 0,
+        };
+        let lines = break_lines(&expr, font, options);
+        assert_eq!(line_lengths(&lines), vec![1, 2]);
+        let last = &lines[1].math_box;
+        assert_eq!(last.origin.x + last.advance_width(), 10000);
+    })
+}
+
+#[test]
+fn estimate_extents_test() {
+    use math_render::shaper::MathShaper;
+    use math_render::{estimate_extents, LayoutStyle};
+
+    TEST_FONT.with(|font| {
+        let formulas = [
+            "<mi>x</mi>",
+            "<mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow>",
+            "<msubsup><mi>x</mi><mi>i</mi><mn>2</mn></msubsup>",
+            "<mfrac><mn>1</mn><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow></mfrac>",
+            "<msqrt><mi>y</mi></msqrt>",
+            "<munderover><mo>&sum;</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow>\
+             <mi>n</mi></munderover>",
+        ];
+        for &style in &[LayoutStyle::new(), LayoutStyle::new().inline_style()] {
+            for xml in formulas.iter() {
+                let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+                let estimate = estimate_extents(&expr, font, style);
+                let options = math_render::LayoutOptions::new(font).style(style);
+                let result = math_render::layout_expression(&expr, options);
+                let extents = result.extents();
+
+                // the estimate is off by less than a quarter of an em
+                let tolerance = font.em_size() / 4;
+                let width = result.advance_width();
+                assert!((estimate.width - width).abs() < tolerance, "width of {}", xml);
+                let ascent = extents.ascent - result.origin.y;
+                assert!((estimate.ascent - ascent).abs() < tolerance, "ascent of {}", xml);
+                let descent = extents.descent + result.origin.y;
+                assert!((estimate.descent - descent).abs() < tolerance, "descent of {}", xml);
+            }
+        }
+
+        // fences aren't stretched
+        let xml = "<mrow><mo>(</mo><mfrac><mi>a</mi><mi>b</mi></mfrac><mo>)</mo></mrow>";
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let estimate = estimate_extents(&expr, font, LayoutStyle::new());
+        assert!(estimate.width < math_render::layout(&expr, font).advance_width());
+
+        // single fields are shaped like in the layout
+        let expr = mathmlparser::parse(&b"<mi>abc</mi>"[..]).unwrap();
+        let result = math_render::layout(&expr, font);
+        let estimate = estimate_extents(&expr, font, LayoutStyle::new());
+        assert_eq!(estimate.width, result.advance_width());
+        assert_eq!(estimate.ascent, result.extents().ascent);
+    })
+}
+
+#[test]
+fn embedding_in_text_test() {
+    use math_render::display::{axis_centering_shift, line_spacing};
+    use math_render::shaper::{MathConstant, MathShaper};
+
+    TEST_FONT.with(|font| {
+        let xml = "<mfrac><mi>a</mi><mrow><mi>b</mi><mo>+</mo><mi>c</mi></mrow></mfrac>";
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let mut result = math_render::layout(&expr, font);
+        result.origin.y += axis_centering_shift(&result, font);
+
+        let extents = result.extents();
+        let center = result.origin.y + (extents.descent - extents.ascent) / 2;
+        let axis_height = font.math_constant(MathConstant::AxisHeight);
+        assert!((center + axis_height).abs() <= 1);
+        assert!(axis_centering_shift(&result, font).abs() <= 1);
+
+        let leading = font.math_constant(MathConstant::MathLeading);
+        let spacing = line_spacing(&result, font);
+        assert_eq!(spacing.above + spacing.below, extents.height() + 2 * leading);
+        assert_eq!(spacing.above, extents.ascent - result.origin.y + leading);
+    })
+}
+
+#[test]
+fn math_constants_snapshot_test() {
+    use math_render::shaper::{MathConstant, MathConstants, MathShaper};
+
+    TEST_FONT.with(|font| {
+        let constants = MathConstants::from_shaper(font);
+        assert_eq!(
+            constants.axis_height,
+            font.math_constant(MathConstant::AxisHeight)
+        );
+        assert_eq!(
+            constants.get(MathConstant::RadicalDegreeBottomRaisePercent),
+            font.math_constant(MathConstant::RadicalDegreeBottomRaisePercent)
+        );
+        assert!(constants.script_percent_scale_down > 0);
+    })
+}
+
+#[test]
+fn has_math_table_test() {
+    assert!(math_render::font::has_math_table(util::get_bytes(), 0));
+    assert!(!math_render::font::has_math_table(b"not a font", 0));
+}
+
+#[test]
+fn load_math_font_test() {
+    use math_render::font::{load_math_font, FontError};
+    use math_render::shaper::{MathConstant, MathShaper};
+
+    let shaper = load_math_font(util::get_bytes()).unwrap();
+    TEST_FONT.with(|font| {
+        assert_eq!(
+            shaper.math_constant(MathConstant::AxisHeight),
+            font.math_constant(MathConstant::AxisHeight)
+        )
+    });
+
+    match load_math_font(&b"not a font"[..]) {
+        Err(FontError::NoMathTable) => {}
+        other => panic!("Expected missing MATH table. Found {:?}.", other.err()),
+    }
+    match load_math_font(std::path::Path::new("does/not/exist.otf")) {
+        Err(FontError::Io(_)) => {}
+        other => panic!("Expected IO error. Found {:?}.", other.err()),
+    }
+}
+
+#[test]
+fn owned_shaper_test() {
+    use math_render::shaper::{HarfbuzzShaper, MathShaper, OwnedShaper};
+
+    // an owned shaper can be stored without borrowing the font data
+    struct Document {
+        shaper: OwnedShaper,
+    }
+
+    let document = Document {
+        shaper: HarfbuzzShaper::from_bytes(util::get_bytes().to_vec(), 0),
+    };
+    let expr = mathmlparser::parse(&b"<mi>x</mi>"[..]).unwrap();
+    let result = math_render::layout(&expr, &document.shaper);
+    assert!(result.advance_width() > 0);
+    assert_eq!(document.shaper.em_size(), TEST_FONT.with(|font| font.em_size()));
+}
+
+#[test]
+fn shared_shaper_test() {
+    use math_render::shaper::{HarfbuzzShaper, OwnedShaper};
+    use std::sync::Arc;
+    use std::thread;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<OwnedShaper>();
+
+    let shaper = Arc::new(HarfbuzzShaper::from_bytes(util::get_bytes().to_vec(), 0));
+    let expr = Arc::new(mathmlparser::parse(&b"<mfrac><mi>a</mi><mi>b</mi></mfrac>"[..]).unwrap());
+    let expected = math_render::layout(&*expr, &*shaper).extents();
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let shaper = shaper.clone();
+            let expr = expr.clone();
+            thread::spawn(move || math_render::layout(&*expr, &*shaper).extents())
+        })
+        .collect();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), expected);
+    }
+}
+
+#[test]
+fn glyph_outline_test() {
+    use math_render::outline::{GlyphOutlines, PathSegment};
+
+    let expr = mathmlparser::parse(&b"<mi>x</mi>"[..]).unwrap();
+    TEST_FONT.with(|font| {
+        let result = math_render::layout(&expr, font);
+        let (glyph, _, _) = result.first_glyph().unwrap();
+        let outline = font.glyph_outline(glyph.glyph_code).unwrap();
+        match outline.first() {
+            Some(PathSegment::MoveTo { .. }) => {}
+            other => panic!("outline starts with {:?}", other),
+        }
+        assert_eq!(outline.last(), Some(&PathSegment::Close));
+    });
+}
+
+#[test]
+fn variations_test() {
+    use math_render::outline::GlyphOutlines;
+    use math_render::shaper::HarfbuzzShaper;
+
+    // Latin Modern has no variation axes, so the default instance is used
+    let shaper = HarfbuzzShaper::from_bytes(util::get_bytes().to_vec(), 0);
+    let varied = HarfbuzzShaper::from_bytes(util::get_bytes().to_vec(), 0)
+        .with_variations(&[(*b"opsz", 8.0), (*b"wght", 700.0)]);
+    assert_eq!(varied.variations(), &[(*b"opsz", 8.0), (*b"wght", 700.0)]);
+    assert!(shaper.variations().is_empty());
+
+    let expr = mathmlparser::parse(&b"<mfrac><mi>x</mi><mn>2</mn></mfrac>"[..]).unwrap();
+    let result = math_render::layout(&expr, &shaper);
+    let differences = math_render::testing::diff(&result, &math_render::layout(&expr, &varied));
+    assert!(differences.is_empty(), "{:?}", differences);
+    let (glyph, _, _) = result.first_glyph().unwrap();
+    assert_eq!(
+        varied.glyph_outline(glyph.glyph_code),
+        shaper.glyph_outline(glyph.glyph_code)
+    );
+}
+
+#[test]
+fn device_scale_test() {
+    use math_render::math_box::{DeviceScale, Rounding, Vector};
+
+    // 1000 units per em at 10px: one font unit is 1/100 px
+    let scale = DeviceScale::new(1000, 10.0);
+    assert_eq!(scale.to_device(1234), 12.34);
+    assert_eq!(scale.with_rounding(Rounding::Pixel).to_device(1234), 12.0);
+    assert_eq!(scale.with_rounding(Rounding::FixedPoint).to_device(1234), 12.34375);
+    assert_eq!(scale.to_26_6(1234), 790);
+    assert_eq!(
+        scale
+            .with_rounding(Rounding::Pixel)
+            .point_to_device(Vector { x: 150, y: -149 }),
+        Vector { x: 2.0, y: -1.0 }
+    );
+
+    // thin rules must not disappear
+    assert_eq!(scale.with_rounding(Rounding::Pixel).thickness_to_device(40), 1.0);
+    assert_eq!(scale.with_rounding(Rounding::FixedPoint).thickness_to_device(1), 1.0 / 64.0);
+    assert_eq!(scale.with_rounding(Rounding::Pixel).thickness_to_device(0), 0.0);
+}
+
+#[test]
+fn min_rule_thickness_test() {
+    use math_render::math_box::{DeviceScale, Vector};
+
+    fn rule_thickness(math_box: &MathBox) -> Option<u32> {
+        match *math_box.content() {
+            MathBoxContent::Drawable(Drawable::Line { thickness, .. }) => Some(thickness),
+            MathBoxContent::Boxes(ref boxes) => boxes.iter().filter_map(rule_thickness).next(),
+            _ => None,
+        }
+    }
+
+    let origin = Vector::default();
+    let bar = MathBox::with_line(origin, Vector { x: 500, y: 0 }, 40, 0);
+    let thick_bar = MathBox::with_line(origin, Vector { x: 500, y: 0 }, 300, 0);
+    let mut math_box = MathBox::with_vec(vec![MathBox::with_vec(vec![bar], 0), thick_bar], 0);
+    let extents = math_box.extents();
+
+    // at 5px per em a 40 unit bar is only 0.2px thick
+    let scale = DeviceScale::new(1000, 5.0);
+    assert_eq!(math_box.enforce_min_rule_thickness(&scale, 1.0), 1);
+    assert_eq!(rule_thickness(&math_box), Some(200));
+    assert_eq!(math_box.extents(), extents);
+    assert_eq!(math_box.enforce_min_rule_thickness(&scale, 1.0), 0);
+}
+
+fn assert_centered_on_axis(
+    xml: &str,
+    font: &math_render::shaper::HarfbuzzShaper,
+    math_style: math_render::MathStyle,
+) {
+    use math_render::shaper::{MathConstant, MathShaper};
+
+    let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+    let result = math_render::layout_with_style(&expr, font, |style, _| math_render::LayoutStyle {
+        math_style,
+        ..style
+    });
+    let extents = result.extents();
+    // the vertical center of the ink relative to the baseline (y increases downwards)
+    let center = result.origin.y + (extents.descent - extents.ascent) / 2;
+    let axis_height = font.math_constant(MathConstant::AxisHeight);
+    println!("{}: center = {:?}, axis = {:?}", xml, center, axis_height);
+    // allow rounding errors
+    assert!((center + axis_height).abs() <= 1);
+}
+
+#[test]
+fn largeop_axis_centering_test() {
+    use math_render::MathStyle::{Display, Inline};
+
+    TEST_FONT.with(|font| {
+        for &math_style in &[Display, Inline] {
+            assert_centered_on_axis("<mo>\u{2211}</mo>", font, math_style);
+            assert_centered_on_axis("<mo>\u{222B}</mo>", font, math_style);
+            assert_centered_on_axis("<mo largeop=\"true\">\u{220F}</mo>", font, math_style);
+        }
+    })
+}
+
+// Returns the stretched operator and the content it was stretched to from the layout of
+// `<mrow><mo>…</mo>content</mrow>` in display style.
+fn operator_and_content(result: &MathBox) -> (&MathBox, &MathBox) {
+    let boxes = assume_boxes(result.content());
+    // operators in display style are surrounded by their leading and trailing space
+    let operator = &assume_boxes(boxes[0].content())[1];
+    (operator, &boxes[1])
+}
+
+fn vertical_center(math_box: &MathBox) -> i32 {
+    math_box.origin.y + (math_box.extents().descent - math_box.extents().ascent) / 2
+}
+
+#[test]
+fn symmetric_stretching_test() {
+    use math_render::shaper::{MathConstant, MathShaper};
+
+    TEST_FONT.with(|font| {
+        let xml = "<mrow><mo>[</mo><mover><mi>x</mi><mover><mi>y</mi><mi>z</mi></mover></mover>\
+                   </mrow>";
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let result = math_render::layout(&expr, font);
+        let (bracket, content) = operator_and_content(&result);
+        assert!(bracket.extents().height() >= content.extents().height());
+
+        // symmetric operators are centered on the math axis
+        let axis_height = font.math_constant(MathConstant::AxisHeight);
+        assert!((vertical_center(bracket) + axis_height).abs() <= 1);
+    })
+}
+
+#[test]
+fn non_symmetric_stretching_test() {
+    TEST_FONT.with(|font| {
+        for xml in &[
+            "<mrow><mo stretchy=\"true\">/</mo>\
+             <mover><mi>x</mi><mover><mi>y</mi><mi>z</mi></mover></mover></mrow>",
+            "<mrow><mo symmetric=\"false\">[</mo>\
+             <mover><mi>x</mi><mover><mi>y</mi><mi>z</mi></mover></mover></mrow>",
+        ] {
+            let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+            let result = math_render::layout(&expr, font);
+            let (operator, content) = operator_and_content(&result);
+
+            // non-symmetric operators are centered on the content they stretch to
+            let difference = vertical_center(operator) - vertical_center(content);
+            println!("{}: difference = {:?}", xml, difference);
+            assert!(difference.abs() <= 1);
+        }
+    })
+}
+
+#[test]
+fn asymmetry_tolerance_test() {
+    use math_render::shaper::MathShaper;
+    use math_render::{layout_expression, LayoutOptions};
+
+    TEST_FONT.with(|font| {
+        // the content is much taller above the math axis than below it
+        let xml = "<mrow><mo>[</mo><mover><mi>x</mi><mover><mi>y</mi><mi>z</mi></mover></mover>\
+                   </mrow>";
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let height = |options: LayoutOptions| {
+            let result = layout_expression(&expr, options);
+            let (bracket, content) = operator_and_content(&result);
+            (bracket.extents().height(), content.extents().height())
+        };
+
+        let (symmetric, content) = height(LayoutOptions::new(font));
+        let (tolerant, _) = height(LayoutOptions::new(font).asymmetry_tolerance(1.0));
+        assert!(tolerant < symmetric);
+        // the bracket only grows to the height of the content
+        assert!(tolerant >= content);
+        assert!(tolerant - content < font.em_size() / 4);
+
+        let (strict, _) = height(LayoutOptions::new(font).asymmetry_tolerance(0.05));
+        assert_eq!(strict, symmetric);
+    })
+}
+
+#[test]
+fn stretched_delimiter_scripts_test() {
+    use math_render::shaper::{MathConstant, MathShaper};
+
+    TEST_FONT.with(|font| {
+        let content = "<mfrac><mfrac><mfrac><mi>a</mi><mi>b</mi></mfrac><mi>c</mi></mfrac>\
+                       <mfrac><mi>a</mi><mfrac><mi>a</mi><mi>b</mi></mfrac></mfrac></mfrac>";
+        let xml = format!(
+            "<mrow><mo>[</mo>{}<msubsup><mo>]</mo><mn>1</mn><mn>2</mn></msubsup></mrow>",
+            content
+        );
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let result = math_render::layout(&expr, font);
+        let boxes = assume_boxes(result.content());
+        let content = &boxes[1];
+        // the closing bracket is surrounded by the spacing of its embellished operator
+        let scripts = assume_boxes(assume_boxes(boxes[2].content())[1].content());
+        let (bracket, subscript, superscript) = (&scripts[0], &scripts[1], &scripts[2]);
+
+        // the bracket stretches like a bracket without scripts
+        assert!(bracket.extents().height() >= content.extents().height());
+        let opening = &assume_boxes(boxes[0].content())[1];
+        assert_eq!(bracket.extents().height(), opening.extents().height());
+        assert_eq!(bracket.origin.y, opening.origin.y);
+
+        // and the scripts are attached to its stretched ends
+        let top = bracket.origin.y - bracket.extents().ascent;
+        let bottom = bracket.origin.y + bracket.extents().descent;
+        let drop_max = font.math_constant(MathConstant::SuperscriptBaselineDropMax);
+        let drop_min = font.math_constant(MathConstant::SubscriptBaselineDropMin);
+        assert!(superscript.origin.y <= top + drop_max);
+        assert!(superscript.origin.y > top);
+        // below the bracket the baseline drop dominates the default subscript shift
+        assert_eq!(subscript.origin.y, bottom + drop_min);
+        assert!(subscript.origin.x >= bracket.origin.x + bracket.advance_width() - 50);
+    })
+}
+
+#[test]
+fn integral_limits_test() {
+    use math_render::{layout_expression, IntegralLimits, LayoutOptions};
+
+    TEST_FONT.with(|font| {
+        let xml = "<munderover><mo>&#x222B;</mo><mi>a</mi><mi>b</mi></munderover>";
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+
+        // like in TeX the limits are scripts and the lower limit is tucked under the integral
+        let result = layout_expression(&expr, LayoutOptions::new(font));
+        let boxes = assume_boxes(result.content());
+        let (integral, lower, upper) = (&boxes[0], &boxes[1], &boxes[2]);
+        let italic_correction = integral.italic_correction();
+        assert!(italic_correction > 0);
+        assert!(upper.origin.y < integral.origin.y && lower.origin.y > integral.origin.y);
+        assert_eq!(upper.origin.x, integral.advance_width());
+        assert_eq!(upper.origin.x - lower.origin.x, italic_correction);
+
+        // in ISO style the limits are above and below the integral and follow its slant
+        let options = LayoutOptions::new(font).integral_limits(IntegralLimits::Limits);
+        let result = layout_expression(&expr, options);
+        let boxes = assume_boxes(result.content());
+        let over_boxes = assume_boxes(boxes[0].content());
+        let (integral, upper, lower) = (&over_boxes[0], &over_boxes[1], &boxes[1]);
+        assert!(upper.origin.y + upper.extents().descent < integral.origin.y);
+        assert!(lower.origin.y - lower.extents().ascent > integral.origin.y);
+        let center = |math_box: &MathBox| math_box.origin.x + math_box.extents().center();
+        let offset = center(upper) - center(lower);
+        assert!((offset - italic_correction).abs() <= 1, "{}", offset);
+    })
+}
+
+#[test]
+fn wide_accent_attachment_test() {
+    TEST_FONT.with(|font| {
+        for accent in &["&#x0302;", "&#x0303;", "&#x23DE;"] {
+            let xml = format!(
+                "<mover accent=\"true\"><mrow><mi>x</mi><mi>y</mi><mi>z</mi><mi>w</mi></mrow>\
+                 <mo>{}</mo></mover>",
+                accent
+            );
+            let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+            let result = math_render::layout(&expr, font);
+            let boxes = assume_boxes(result.content());
+            let (base, accent) = (&boxes[0], &boxes[1]);
+
+            // the ink of the accent is centered above the attachment point of the base
+            let accent_center = accent.origin.x + accent.extents().center();
+            let base_attachment = base.origin.x + base.top_accent_attachment();
+            println!("{}: {:?} vs {:?}", xml, accent_center, base_attachment);
+            assert!((accent_center - base_attachment).abs() <= 2);
+        }
+    })
+}
+
+#[test]
+fn accent_width_test() {
+    use math_render::{AccentWidth, Form, OverUnder, StretchConstraints};
+
+    fn hat(nucleus: &str, accent_width: AccentWidth) -> MathExpression {
+        let hat = Operator {
+            field: Field::Unicode("\u{302}".to_string()),
+            form: Form::Postfix,
+            stretch_constraints: Some(StretchConstraints::default()),
+            ..Default::default()
+        };
+        let over_under = OverUnder {
+            nucleus: Some(field(nucleus, 0)),
+            over: Some(MathExpression::new(MathItem::Operator(hat), 0)),
+            over_is_accent: true,
+            accent_width,
+            ..Default::default()
+        };
+        MathExpression::new(MathItem::OverUnder(over_under), 0)
+    }
+
+    TEST_FONT.with(|font| {
+        let accent_width = |expr: &MathExpression| {
+            let result = math_render::layout(expr, font);
+            assume_boxes(result.content())[1].extents().width
+        };
+        let narrow = accent_width(&hat("x", AccentWidth::Narrow));
+        // the narrow form doesn't depend on the nucleus
+        assert_eq!(accent_width(&hat("xyzw", AccentWidth::Narrow)), narrow);
+        assert!(accent_width(&hat("xyzw", AccentWidth::Wide)) > narrow);
+        // by default only accents over single glyphs are narrow
+        assert_eq!(accent_width(&hat("x", AccentWidth::Auto)), narrow);
+        assert_eq!(
+            accent_width(&hat("xyzw", AccentWidth::Auto)),
+            accent_width(&hat("xyzw", AccentWidth::Wide))
+        );
+    })
+}
+
+#[test]
+fn glyph_assembly_test() {
+    use math_render::shaper::MathShaper;
+    use math_render::LayoutStyle;
+
+    TEST_FONT.with(|font| {
+        let expr = mathmlparser::parse(&b"<mo>(</mo>"[..]).unwrap();
+        let (glyph, _, _) = math_render::layout(&expr, font).first_glyph().unwrap();
+
+        let result = font.stretch_glyph(glyph.glyph_code, false, 10000, LayoutStyle::new(), 0);
+        let assembly = result.glyph_assembly().expect("no glyph assembly");
+        assert!(!assembly.horizontal);
+        assert!(assembly.parts.iter().any(|part| part.is_extender));
+        assert!(assembly.length() >= 10000);
+
+        // every part corresponds to a child box, placed with the reported overlap
+        let boxes = assume_boxes(result.content());
+        assert_eq!(boxes.len(), assembly.parts.len());
+        for (part, math_box) in assembly.parts.iter().zip(boxes) {
+            assert_eq!(math_box.first_glyph().unwrap().0.glyph_code, part.glyph_code);
+        }
+        for (index, pair) in boxes.windows(2).enumerate() {
+            let distance = pair[0].origin.y - pair[1].origin.y;
+            assert_eq!(
+                distance,
+                assembly.parts[index].full_advance - assembly.connector_overlap
+            );
+        }
+
+        // unstretched glyphs have no assembly
+        let small = font.stretch_glyph(glyph.glyph_code, false, 0, LayoutStyle::new(), 0);
+        assert!(small.glyph_assembly().is_none());
+    })
+}
+
+#[test]
+fn variant_selector_test() {
+    use math_render::shaper::{
+        HarfbuzzShaper, MathShaper, StretchCandidates, StretchChoice, VariantSelector,
+    };
+    use math_render::LayoutStyle;
+
+    /// Uses an assembly for every glyph that doesn't fit unstretched.
+    #[derive(Debug)]
+    struct PreferAssemblies;
+
+    impl VariantSelector for PreferAssemblies {
+        fn select(&self, candidates: &StretchCandidates) -> Option<StretchChoice> {
+            if candidates.base_size >= candidates.target_size {
+                Some(StretchChoice::Base)
+            } else if candidates.has_assembly {
+                Some(StretchChoice::Assembly)
+            } else {
+                None
+            }
+        }
+    }
+
+    let shaper = HarfbuzzShaper::from_bytes(util::get_bytes().to_vec(), 0);
+    let glyph = shaper.nominal_glyph('(').unwrap();
+    let target_size = 2 * shaper.em_size() as u32;
+
+    // a variant is large enough by default
+    let result = shaper.stretch_glyph(glyph, false, target_size, LayoutStyle::new(), 0);
+    assert!(result.glyph_assembly().is_none());
+    assert!(result.extents().height() >= target_size as i32);
+
+    let shaper = shaper.with_variant_selector(PreferAssemblies);
+    let result = shaper.stretch_glyph(glyph, false, target_size, LayoutStyle::new(), 0);
+    assert!(result.glyph_assembly().expect("no glyph assembly").length() >= target_size as i32);
+}
+
+#[test]
+fn fence_and_separator_test() {
+    use math_render::shaper::MathShaper;
+    use math_render::{LayoutStyle, Length, MathStyle, StretchConstraints};
+
+    fn parenthesis(text: &str, is_fence: bool) -> MathExpression {
+        let operator = Operator {
+            field: Field::Unicode(text.to_string()),
+            stretch_constraints: Some(StretchConstraints::default()),
+            is_fence,
+            ..Default::default()
+        };
+        MathExpression::new(MathItem::Operator(operator), 0)
+    }
+    fn separator(text: &str) -> MathExpression {
+        let operator = Operator {
+            field: Field::Unicode(text.to_string()),
+            trailing_space: Length::em(3.0 / 18.0),
+            is_separator: true,
+            ..Default::default()
+        };
+        MathExpression::new(MathItem::Operator(operator), 0)
+    }
+    let inline = |style: LayoutStyle, _| LayoutStyle {
+        math_style: MathStyle::Inline,
+        ..style
+    };
+
+    TEST_FONT.with(|font| {
+        // fences around small content are neither stretched nor moved
+        let list = |is_fence| {
+            MathExpression::new(
+                MathItem::List(vec![
+                    parenthesis("(", is_fence),
+                    field("x", 0),
+                    parenthesis(")", is_fence),
+                ]),
+                0,
+            )
+        };
+        let unstretched = math_render::layout(&parenthesis("(", true), font);
+        let result = math_render::layout_with_style(&list(true), font, inline);
+        let fence = &assume_boxes(result.content())[0];
+        assert_eq!(fence.origin.y, 0);
+        assert_eq!(fence.extents(), unstretched.extents());
+        let result = math_render::layout_with_style(&list(false), font, inline);
+        assert_ne!(assume_boxes(result.content())[0].origin.y, 0);
+
+        // separators are followed by their trailing space in inline style
+        let expr = MathExpression::new(
+            MathItem::List(vec![field("a", 0), separator(","), field("b", 0)]),
+            0,
+        );
+        let comma = math_render::layout(&field(",", 0), font);
+        let result = math_render::layout_with_style(&expr, font, inline);
+        let boxes = assume_boxes(result.content());
+        assert_eq!(
+            boxes[1].advance_width(),
+            comma.advance_width() + font.em_size() as i32 * 3 / 18
+        );
+    })
+}
+
+#[test]
+fn delimiter_matching_test() {
+    use math_render::{Form, LayoutStyle, Length, MathSpace, MathStyle, StretchConstraints};
+
+    fn expression(item: MathItem) -> MathExpression {
+        MathExpression::new(item, 0)
+    }
+    fn stretchy(text: &str, is_fence: bool, form: Form) -> MathExpression {
+        expression(MathItem::Operator(Operator {
+            field: Field::Unicode(text.to_string()),
+            stretch_constraints: Some(StretchConstraints {
+                symmetric: true,
+                ..Default::default()
+            }),
+            is_fence,
+            form,
+            ..Default::default()
+        }))
+    }
+    fn open() -> MathExpression {
+        stretchy("(", true, Form::Prefix)
+    }
+    fn close() -> MathExpression {
+        stretchy(")", true, Form::Postfix)
+    }
+    fn space(ascent: f32, descent: f32) -> MathExpression {
+        expression(MathItem::Space(MathSpace {
+            ascent: Length::em(ascent),
+            descent: Length::em(descent),
+            ..Default::default()
+        }))
+    }
+    fn heights(list: Vec<MathExpression>) -> Vec<i32> {
+        let inline = |style: LayoutStyle, _| LayoutStyle {
+            math_style: MathStyle::Inline,
+            ..style
+        };
+        TEST_FONT.with(|font| {
+            let expr = expression(MathItem::List(list));
+            let result = math_render::layout_with_style(&expr, font, inline);
+            assume_boxes(result.content())
+                .iter()
+                .map(|math_box| math_box.extents().height())
+                .collect()
+        })
+    }
+
+    let natural_height = heights(vec![open()])[0];
+
+    // fences only grow to the content between them, not to other items of the list
+    let result = heights(vec![
+        open(),
+        space(0.3, 0.0),
+        close(),
+        stretchy("|", false, Form::Infix),
+        space(3.0, 3.0),
+    ]);
+    assert_eq!(result[0], natural_height);
+    assert_eq!(result[2], natural_height);
+    assert!(result[3] > 3 * natural_height);
+
+    // nested fences grow from the inside out
+    let result = heights(vec![
+        open(),
+        open(),
+        space(2.0, 2.0),
+        close(),
+        space(0.3, 0.0),
+        close(),
+    ]);
+    assert!(result[1] > natural_height);
+    assert_eq!(result[1], result[3]);
+    assert!(result[0] >= result[1]);
+    assert_eq!(result[0], result[5]);
+}
+
+#[test]
+fn embellished_operator_stretching_test() {
+    use math_render::{Atom, LayoutStyle, Length, MathSpace, MathStyle, StretchConstraints};
+
+    fn expression(item: MathItem) -> MathExpression {
+        MathExpression::new(item, 0)
+    }
+    fn bar() -> MathExpression {
+        expression(MathItem::Operator(Operator {
+            field: Field::Unicode("|".to_string()),
+            stretch_constraints: Some(StretchConstraints::default()),
+            ..Default::default()
+        }))
+    }
+    fn tall_space() -> MathExpression {
+        expression(MathItem::Space(MathSpace {
+            ascent: Length::em(3.0),
+            descent: Length::em(3.0),
+            ..Default::default()
+        }))
+    }
+
+    // in display style operators would be surrounded by their spacing
+    let inline = |style: LayoutStyle, _| LayoutStyle {
+        math_style: MathStyle::Inline,
+        ..style
+    };
+
+    TEST_FONT.with(|font| {
+        let natural_height = math_render::layout(&bar(), font).extents().height();
+
+        // a subscripted operator stretches while its (stretchy) subscript doesn't
+        let atom = expression(MathItem::Atom(Atom {
+            nucleus: Some(bar()),
+            bottom_right: Some(bar()),
+            ..Default::default()
+        }));
+        let expr = expression(MathItem::List(vec![atom, tall_space()]));
+        let result = math_render::layout_with_style(&expr, font, inline);
+        let atom = &assume_boxes(result.content())[0];
+        let atom_boxes = assume_boxes(atom.content());
+        assert!(atom_boxes[0].extents().height() > 3 * natural_height);
+        assert!(atom_boxes[1].extents().height() <= natural_height);
+
+        // so does an operator wrapped in a list of its own
+        let wrapped = expression(MathItem::List(vec![bar()]));
+        let expr = expression(MathItem::List(vec![wrapped, tall_space()]));
+        let result = math_render::layout_with_style(&expr, font, inline);
+        let wrapped = &assume_boxes(result.content())[0];
+        assert!(wrapped.extents().height() > 3 * natural_height);
+    })
+}
+
+#[test]
+fn extensible_arrow_test() {
+    use math_render::shaper::MathShaper;
+    use math_render::{OverUnder, StretchConstraints};
+
+    fn expression(item: MathItem) -> MathExpression {
+        MathExpression::new(item, 0)
+    }
+    fn arrow() -> MathExpression {
+        expression(MathItem::Operator(Operator {
+            field: Field::Unicode("\u{2192}".to_string()),
+            stretch_constraints: Some(StretchConstraints::default()),
+            ..Default::default()
+        }))
+    }
+
+    TEST_FONT.with(|font| {
+        let expr = expression(MathItem::OverUnder(OverUnder {
+            nucleus: Some(arrow()),
+            over: Some(field("long label", 0)),
+            ..Default::default()
+        }));
+        let result = math_render::layout(&expr, font);
+        let boxes = assume_boxes(result.content());
+        let (arrow, label_box) = (&boxes[0], &boxes[1]);
+
+        // the arrow extends at least 5mu beyond its label on each side
+        let padding = font.em_size() as i32 * 5 / 18;
+        assert!(arrow.extents().width >= label_box.extents().width + 2 * padding);
+        let center = |math_box: &MathBox| math_box.origin.x + math_box.extents().center();
+        assert!((center(arrow) - center(label_box)).abs() <= 1);
+    })
+}
+
+#[test]
+fn synthetic_stretch_test() {
+    use math_render::math_box::{DeviceScale, Extents};
+    use math_render::shaper::MathShaper;
+    use math_render::{layout_expression, Form, LayoutOptions, StretchConstraints};
+
+    // Latin Modern has no variants of the corner
+    let corner = MathExpression::new(
+        MathItem::Operator(Operator {
+            field: Field::Unicode("\u{231c}".to_string()),
+            stretch_constraints: Some(StretchConstraints::default()),
+            is_fence: true,
+            form: Form::Prefix,
+            ..Default::default()
+        }),
+        0,
+    );
+    TEST_FONT.with(|font| {
+        let glyph = font.nominal_glyph('\u{231c}').unwrap();
+        assert!(!font.is_stretchable(glyph, false));
+
+        let natural = math_render::layout(&corner, font);
+        let row = Extents::new(0, 0, 1500, 800);
+        let options = LayoutOptions::new(font).stretch_size(Some(row));
+        let stretched = layout_expression(&corner, options);
+        assert!(stretched.extents().height() >= row.height());
+        assert_eq!(stretched.advance_width(), natural.advance_width());
+        // the stretched glyph covers the row
+        assert!(stretched.origin.y - stretched.extents().ascent <= -row.ascent);
+        assert!(stretched.origin.y + stretched.extents().descent >= row.descent);
+
+        let scale = match *stretched.content() {
+            MathBoxContent::Drawable(Drawable::Glyphs { scale, .. }) => scale,
+            ref other => panic!("Expected glyphs. Found {:?}.", other),
+        };
+        assert_eq!(scale.horizontal.magnification_percentage(), 100);
+        assert!(scale.vertical.magnification_percentage() > 100);
+        let list = stretched.flatten(&DeviceScale::new(font.em_size(), 20.0));
+        let run = &list.glyph_runs[0];
+        assert_eq!(run.font_size, 20.0);
+        assert_eq!(
+            run.vertical_font_size,
+            20.0 * scale.vertical.as_scale_mult()
+        );
+    })
+}
+
+#[test]
+fn anisotropic_scale_test() {
+    use math_render::shaper::MathShaper;
+    use math_render::{LayoutStyle, PercentScale2D, PercentValue};
+
+    TEST_FONT.with(|font| {
+        let glyph = font.nominal_glyph('x').unwrap();
+        let glyphs = match font.shape_glyph(glyph, LayoutStyle::default(), 0).content {
+            MathBoxContent::Drawable(Drawable::Glyphs { glyphs, .. }) => glyphs,
+            ref other => panic!("Expected glyphs. Found {:?}.", other),
+        };
+        let half = PercentValue::new(50);
+        let uniform = MathBox::with_glyphs(glyphs.clone(), half, 0);
+        let scale = PercentScale2D::new(half, PercentValue::magnification(200));
+        let stretched = MathBox::with_scaled_glyphs(glyphs, scale, 0);
+
+        // widths follow the horizontal and heights the vertical scale
+        assert_eq!(stretched.advance_width(), uniform.advance_width());
+        assert_eq!(stretched.extents().width, uniform.extents().width);
+        assert!((stretched.extents().ascent - 4 * uniform.extents().ascent).abs() <= 4);
+        assert_eq!(stretched.italic_correction(), uniform.italic_correction());
+    })
+}
+
+#[test]
+fn try_layout_test() {
+    use math_render::shaper::MathShaper;
+    use math_render::{Glyph, LayoutError, OverUnder, PercentValue, StretchConstraints};
+
+    fn expression(item: MathItem) -> MathExpression {
+        MathExpression::new(item, 0)
+    }
+
+    TEST_FONT.with(|font| {
+        let arrow = font.nominal_glyph('\u{2192}').unwrap();
+        let label = field("long label", 0);
+        let expr = expression(MathItem::OverUnder(OverUnder {
+            nucleus: Some(expression(MathItem::Operator(Operator {
+                field: Field::Glyph(Glyph {
+                    glyph_code: arrow,
+                    scale: PercentValue::new(100),
+                }),
+                stretch_constraints: Some(StretchConstraints::default()),
+                ..Default::default()
+            }))),
+            over: Some(label.clone()),
+            ..Default::default()
+        }));
+        assert_eq!(
+            math_render::try_layout(&expr, font).unwrap_err(),
+            LayoutError::UnstretchableGlyphOperator
+        );
+        // the infallible layout keeps the operator at its normal size
+        let result = math_render::layout(&expr, font);
+        assert_eq!(assume_boxes(result.content()).len(), 2);
+
+        assert!(math_render::try_layout(&label, font).is_ok());
+    })
+}
+
+#[test]
+fn nesting_depth_test() {
+    use math_render::{LayoutError, LayoutOptions};
+
+    let nested = |depth: usize| {
+        (1..depth).fold(field("x", 0), |expr, _| {
+            MathExpression::new(MathItem::List(vec![expr]), 0)
+        })
+    };
+    TEST_FONT.with(|font| {
+        let max_depth = LayoutOptions::new(font).max_depth;
+        assert!(math_render::try_layout(&nested(max_depth), font).is_ok());
+        assert_eq!(
+            math_render::try_layout(&nested(1_000), font).unwrap_err(),
+            LayoutError::NestingTooDeep(max_depth)
+        );
+        // the infallible layout leaves out the innermost expressions
+        let result = math_render::layout(&nested(1_000), font);
+        assert_eq!(result.extents().width, 0);
+    })
+}
+
+#[test]
+fn limits_placement_test() {
+    use math_render::{Atom, LayoutStyle, LimitsPlacement, MathStyle, OverUnder};
+
+    fn over_under(limits_placement: LimitsPlacement) -> MathExpression {
+        let over_under = OverUnder {
+            nucleus: Some(field("X", 0)),
+            over: Some(field("a", 0)),
+            under: Some(field("b", 0)),
+            is_limits: true,
+            limits_placement,
+            ..Default::default()
+        };
+        MathExpression::new(MathItem::OverUnder(over_under), 0)
+    }
+    let scripts = MathExpression::new(
+        MathItem::Atom(Atom {
+            nucleus: Some(field("X", 0)),
+            top_right: Some(field("a", 0)),
+            bottom_right: Some(field("b", 0)),
+            ..Default::default()
+        }),
+        0,
+    );
+
+    TEST_FONT.with(|font| {
+        for &math_style in &[MathStyle::Display, MathStyle::Inline] {
+            let layout = |expr: &MathExpression| {
+                math_render::layout_with_style(expr, font, |style: LayoutStyle, _| LayoutStyle {
+                    math_style,
+                    ..style
+                })
+                .extents()
+            };
+            let as_scripts = layout(&scripts);
+
+            // forced scripts look like an msubsup in every style
+            assert_eq!(layout(&over_under(LimitsPlacement::Scripts)), as_scripts);
+            // forced limits are never moved into script positions
+            assert_ne!(layout(&over_under(LimitsPlacement::Limits)), as_scripts);
+            // otherwise limits only move in inline style
+            let auto = layout(&over_under(LimitsPlacement::Auto));
+            assert_eq!(auto == as_scripts, math_style == MathStyle::Inline);
+        }
+    })
+}
+
+#[test]
+fn deep_nesting_test() {
+    let max_depth = mathmlparser::ParserOptions::default().max_depth;
+    let depth = max_depth - 1;
+    let xml = "<msup><mi>x</mi>".repeat(depth) + "<mi>x</mi>" + &"</msup>".repeat(depth);
+
+    TEST_FONT.with(|font| {
+        // the deepest documents the parser accepts are laid out without overflowing the stack
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        math_render::layout(&expr, font);
+    })
+}
+
+#[test]
+fn script_level_test() {
+    use math_render::{Atom, LayoutStyle};
+
+    fn glyphs_of(math_box: &MathBox, user_data: u64) -> Option<(u32, u16)> {
+        match *math_box.content() {
+            MathBoxContent::Drawable(Drawable::Glyphs { ref glyphs, scale })
+                if math_box.user_data() == user_data =>
+            {
+                let percent = scale.horizontal.magnification_percentage();
+                Some((glyphs[0].glyph_code, percent))
+            }
+            MathBoxContent::Boxes(ref boxes) => {
+                boxes.iter().filter_map(|child| glyphs_of(child, user_data)).next()
+            }
+            _ => None,
+        }
+    }
+
+    // x^(x^(x^x)) where the innermost x has user data 3
+    let expr = (0..3).rev().fold(field("x", 3), |superscript, user_data| {
+        let atom = Atom {
+            nucleus: Some(field("x", user_data)),
+            top_right: Some(superscript),
+            ..Default::default()
+        };
+        MathExpression::new(MathItem::Atom(atom), 10 + user_data)
+    });
+
+    TEST_FONT.with(|font| {
+        let result = math_render::layout(&expr, font);
+        let levels = (0..4)
+            .map(|user_data| glyphs_of(&result, user_data).unwrap())
+            .collect::<Vec<_>>();
+        assert!(levels[0].1 > levels[1].1);
+        assert!(levels[1].1 > levels[2].1);
+        // there is no smaller size than scriptscript size
+        assert_eq!(levels[2], levels[3]);
+
+        // explicit script levels don't overflow when scripts are nested inside
+        let huge_script_level = |style: LayoutStyle, _| LayoutStyle {
+            script_level: 255,
+            ..style
+        };
+        let result = math_render::layout_with_style(&expr, font, huge_script_level);
+        assert_eq!(glyphs_of(&result, 0), Some(levels[2]));
+    })
+}
+
+#[test]
+fn math_size_test() {
+    use math_render::shaper::MathConstant;
+    use math_render::shaper::MathShaper;
+
+    fn scale_of(math_box: &MathBox) -> u16 {
+        match *math_box.content() {
+            MathBoxContent::Drawable(Drawable::Glyphs { scale, .. }) => {
+                scale.horizontal.magnification_percentage()
+            }
+            _ => panic!("expected glyphs"),
+        }
+    }
+
+    TEST_FONT.with(|font| {
+        let layout = |xml: &str| {
+            let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+            math_render::layout(&expr, font)
+        };
+        let normal = layout("<mi>x</mi>");
+        let big = layout("<mi mathsize=\"2em\">x</mi>");
+        assert_eq!(scale_of(&big), 200);
+        assert_eq!(big.extents().ascent, 2 * normal.extents().ascent);
+        assert_eq!(scale_of(&layout("<mi mathsize=\"50%\">x</mi>")), 50);
+        // the em size of the test font is 1000 points
+        assert_eq!(scale_of(&layout("<mi mathsize=\"1500pt\">x</mi>")), 150);
+        // invalid sizes are ignored
+        assert_eq!(scale_of(&layout("<mi mathsize=\"huge\">x</mi>")), 100);
+
+        // the math size applies on top of the scale of the script level
+        let script = layout("<msup><mi>x</mi><mi mathsize=\"200%\">y</mi></msup>");
+        let script_percent = font.math_constant(MathConstant::ScriptPercentScaleDown) as u16;
+        let superscript = &assume_boxes(script.content())[1];
+        assert_eq!(scale_of(superscript), 2 * script_percent);
+    })
+}
+
+#[test]
+fn zero_math_size_test() {
+    TEST_FONT.with(|font| {
+        let layout = |xml: &str| {
+            let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+            math_render::layout(&expr, font)
+        };
+        // a size of zero is ignored instead of dividing by zero while stretching or kerning
+        let xml = "<msup><mo mathsize=\"0%\" stretchy=\"true\">(</mo><mi>x</mi></msup>";
+        let zero = layout(xml);
+        let normal = layout("<msup><mo stretchy=\"true\">(</mo><mi>x</mi></msup>");
+        assert_eq!(zero.extents(), normal.extents());
+        let plain_width = layout("<mo>(</mo>").advance_width();
+        for size in &["0", "0em", "0.001", "-1em", "0.2pt"] {
+            let xml = format!("<mo mathsize=\"{}\" stretchy=\"true\">(</mo>", size);
+            assert_eq!(layout(&xml).advance_width(), plain_width);
+        }
+
+        // sizes that only round to zero in a script are still drawn at 1%
+        let tiny = layout("<msup><mi>x</mi><mo mathsize=\"1%\" stretchy=\"true\">(</mo></msup>");
+        assert!(tiny.advance_width() > 0);
+    })
+}
+
+#[test]
+fn cramped_style_test() {
+    // returns how far the superscript of the (only) msup in `xml` is raised
+    fn superscript_shift(xml: &str, path: &[usize]) -> i32 {
+        TEST_FONT.with(|font| {
+            let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+            let result = math_render::layout(&expr, font);
+            let scripts = path
+                .iter()
+                .fold(&result, |math_box, &index| &assume_boxes(math_box.content())[index]);
+            -assume_boxes(scripts.content())[1].origin.y
+        })
+    }
+
+    let square = "<msup><mi>x</mi><mn>2</mn></msup>";
+    let uncramped = superscript_shift(square, &[]);
+    let under_root = superscript_shift(&format!("<msqrt>{}</msqrt>", square), &[2]);
+    let denominator = superscript_shift(&format!("<mfrac><mn>1</mn>{}</mfrac>", square), &[2]);
+    let accent = format!("<mover accent=\"true\">{}<mo>^</mo></mover>", square);
+    let under_accent = superscript_shift(&accent, &[0]);
+    assert!(under_root < uncramped);
+    assert_eq!(under_root, denominator);
+    assert_eq!(under_root, under_accent);
+
+    // the numerator is only cramped if the fraction is
+    let numerator = superscript_shift(&format!("<mfrac>{}<mn>1</mn></mfrac>", square), &[0]);
+    let nested = format!("<msqrt><mfrac>{}<mn>1</mn></mfrac></msqrt>", square);
+    assert!(numerator > superscript_shift(&nested, &[2, 0]));
+}
+
+#[test]
+fn stack_test() {
+    use math_render::shaper::{MathConstant, MathShaper};
+
+    TEST_FONT.with(|font| {
+        let xml = "<mfrac linethickness=\"0\"><mi>n</mi><mi>k</mi></mfrac>";
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let result = math_render::layout(&expr, font);
+        // there is no rule between the two parts
+        let parts = assume_boxes(result.content());
+        assert_eq!(parts.len(), 2);
+        let (top, bottom) = (&parts[0], &parts[1]);
+        let gap =
+            (bottom.origin.y - bottom.extents().ascent) - (top.origin.y + top.extents().descent);
+        assert!(gap >= font.math_constant(MathConstant::StackGapMin));
+
+        let mut ids = 2..;
+        let binomial = MathExpression::binomial(field("n", 1), field("k", 1), || ids.next().unwrap());
+        let result = math_render::layout(&binomial, font);
+        let items = assume_boxes(result.content());
+        assert_eq!(items.len(), 3);
+        // the parentheses stretch to the height of the stack
+        let parenthesis = mathmlparser::parse("<mo>(</mo>".as_bytes()).unwrap();
+        let parenthesis = math_render::layout(&parenthesis, font);
+        assert!(items[0].extents().height() > parenthesis.extents().height());
+        assert!(items[0].extents().height() >= items[1].extents().height());
+    })
+}
+
+#[test]
+fn delimited_fraction_test() {
+    use math_render::GeneralizedFraction;
+
+    let fraction = || GeneralizedFraction {
+        numerator: Some(field("a", 1)),
+        denominator: Some(field("b", 1)),
+        thickness: None,
+    };
+    TEST_FONT.with(|font| {
+        let mut ids = 2..;
+        let expr = MathExpression::delimited_fraction("[", fraction(), "]", || ids.next().unwrap());
+        let result = math_render::layout(&expr, font);
+        let items = assume_boxes(result.content());
+        assert_eq!(items.len(), 3);
+        for bracket in [&items[0], &items[2]].iter() {
+            assert!(bracket.extents().ascent >= items[1].extents().ascent);
+            assert!(bracket.extents().descent >= items[1].extents().descent);
+        }
+        // the list, the brackets and the fraction can be told apart
+        assert_eq!(result.user_data(), 2);
+        let user_data = items.iter().map(MathBox::user_data).collect::<Vec<_>>();
+        assert_eq!(user_data, [3, 4, 5]);
+
+        let mut ids = 2..;
+        let expr = MathExpression::delimited_fraction("", fraction(), "|", || ids.next().unwrap());
+        let result = math_render::layout(&expr, font);
+        assert_eq!(assume_boxes(result.content()).len(), 2);
+    })
+}
+
+#[test]
+fn external_stretch_size_test() {
+    use math_render::math_box::Extents;
+    use math_render::{layout_expression, testing, LayoutOptions};
+
+    TEST_FONT.with(|font| {
+        let expr = mathmlparser::parse("<mo>(</mo>".as_bytes()).unwrap();
+        let natural = math_render::layout(&expr, font);
+        let options = LayoutOptions::new(font).user_data(expr.get_user_data());
+        assert!(testing::diff(&natural, &layout_expression(&expr, options)).is_empty());
+
+        // a matrix cell would pass the size of its row here
+        let row = Extents::new(0, 0, 3 * natural.extents().ascent, 3 * natural.extents().descent);
+        let stretched = layout_expression(&expr, options.stretch_size(Some(row)));
+        assert!(stretched.extents().height() > 2 * natural.extents().height());
+    })
+}
+
+#[test]
+fn root_degree_test() {
+    use math_render::shaper::{MathConstant, MathShaper};
+
+    TEST_FONT.with(|font| {
+        let kern_before = font.math_constant(MathConstant::RadicalKernBeforeDegree);
+        let kern_after = font.math_constant(MathConstant::RadicalKernAfterDegree);
+        let raise_percent = font.math_constant(MathConstant::RadicalDegreeBottomRaisePercent);
+        let layout_root = |degree: &str| {
+            let xml = format!("<mroot><mi>x</mi>{}</mroot>", degree);
+            let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+            math_render::layout(&expr, font)
+        };
+
+        let mut radicand_positions = Vec::new();
+        for degree in ["<mrow></mrow>", "<mn>2</mn>", "<mn>100</mn>"].iter() {
+            let result = layout_root(degree);
+            let boxes = assume_boxes(result.content());
+            let (degree, surd, radicand) = (&boxes[0], &boxes[1], &boxes[3]);
+            assert_eq!(degree.origin.x, kern_before);
+            // the surd only overlaps the degree by the kern after the degree but never starts
+            // before the root
+            let degree_end = degree.origin.x + degree.advance_width();
+            assert!(surd.origin.x >= 0);
+            assert!(surd.origin.x >= degree_end + kern_after);
+            // the bottom of the degree (including the overshoot of round digits) is raised
+            let surd_bottom = surd.origin.y + surd.extents().descent;
+            let raise = surd.extents().height() * raise_percent / 100;
+            assert_eq!(
+                degree.origin.y + degree.extents().descent,
+                surd_bottom - raise
+            );
+            radicand_positions.push(radicand.origin.x);
+        }
+        // wide degrees push the surd and the radicand to the right
+        assert!(radicand_positions[2] > radicand_positions[1]);
+    })
+}
+
+#[test]
+fn text_direction_test() {
+    // returns the glyphs of the first run of glyphs in the layout of `xml`
+    fn glyph_codes(xml: &str) -> Vec<u32> {
+        fn find_glyphs(math_box: &MathBox) -> Option<Vec<u32>> {
+            match *math_box.content() {
+                MathBoxContent::Drawable(Drawable::Glyphs { ref glyphs, .. }) => {
+                    Some(glyphs.iter().map(|glyph| glyph.glyph_code).collect())
+                }
+                MathBoxContent::Boxes(ref boxes) => boxes.iter().filter_map(find_glyphs).next(),
+                _ => None,
+            }
+        }
+        TEST_FONT.with(|font| {
+            let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+            find_glyphs(&math_render::layout(&expr, font)).unwrap()
+        })
+    }
+
+    let ltr = glyph_codes("<mtext>abc</mtext>");
+    let rtl = glyph_codes("<mtext dir=\"rtl\">abc</mtext>");
+    assert_eq!(rtl, ltr.iter().rev().cloned().collect::<Vec<_>>());
+    // the direction of a row applies to the tokens inside of it
+    let row = "<mrow dir=\"rtl\"><mtext>abc</mtext></mrow>";
+    assert_eq!(glyph_codes(row), rtl);
+    let row = "<mrow dir=\"rtl\"><mtext>abc</mtext><mi>x</mi></mrow>";
+    assert_eq!(glyph_codes(row), rtl);
+    let nested = "<mrow dir=\"rtl\"><mtext dir=\"ltr\">abc</mtext></mrow>";
+    assert_eq!(glyph_codes(nested), ltr);
+}
+
+#[test]
+fn font_features_test() {
+    use math_render::shaper::MathShaper;
+    use math_render::{FontFeatures, LayoutStyle};
+
+    TEST_FONT.with(|font| {
+        let glyph = |text: &str, style: LayoutStyle| {
+            let math_box = font.shape(text, style, 0);
+            math_box.first_glyph().unwrap().0.glyph_code
+        };
+        let dotless = FontFeatures::new().with(b"dtls", 1);
+        let dotless_style = LayoutStyle {
+            font_features: dotless,
+            ..LayoutStyle::new()
+        };
+        assert_ne!(glyph("i", dotless_style), glyph("i", LayoutStyle::new()));
+        // the features of the style override those the layout selects (here script alternates)
+        let script = LayoutStyle::new().with_increased_script_level();
+        let no_alternates = LayoutStyle {
+            font_features: FontFeatures::new().with(b"ssty", 0),
+            ..script
+        };
+        let prime = glyph("\u{2032}", LayoutStyle::new());
+        assert_ne!(glyph("\u{2032}", script), prime);
+        assert_eq!(glyph("\u{2032}", no_alternates), prime);
+
+        // a style callback requests features for single tokens
+        let xml = "<mrow><mi>i</mi><mtext>i</mtext></mrow>";
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let result = math_render::layout_with_style_context(&expr, font, |style, context| {
+            match context.element {
+                Some(element) if element.name == "mtext" => LayoutStyle {
+                    font_features: dotless,
+                    ..style
+                },
+                _ => style,
+            }
+        });
+        let boxes = assume_boxes(result.content());
+        let italic_i = "\u{1D456}";
+        let first_glyph = |math_box: &MathBox| math_box.first_glyph().unwrap().0.glyph_code;
+        assert_eq!(first_glyph(&boxes[0]), glyph(italic_i, LayoutStyle::new()));
+        assert_eq!(first_glyph(&boxes[1]), glyph(italic_i, dotless_style));
+        assert_ne!(first_glyph(&boxes[0]), first_glyph(&boxes[1]));
+    })
+}
+
+#[test]
+fn display_list_test() {
+    use math_render::math_box::DeviceScale;
+    use math_render::shaper::MathShaper;
+
+    TEST_FONT.with(|font| {
+        let xml = "<mfrac><mrow><mi>a</mi><mi>b</mi></mrow>\
+                   <msup><mi>c</mi><mi>d</mi></msup></mfrac>";
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let math_box = math_render::layout(&expr, font);
+        let scale = DeviceScale::new(font.em_size(), 20.0);
+        let list = math_box.flatten(&scale);
+
+        assert_eq!(list.rules.len(), 1);
+        assert_eq!(list.glyph_runs.len(), 4);
+        let rule = list.rules[0];
+        assert!(rule.height > 0.0 && rule.width > 0.0);
+        let (numerator, denominator) = (&list.glyph_runs[0], &list.glyph_runs[2]);
+        assert!(numerator.glyphs[0].position.y < rule.origin.y);
+        assert!(denominator.glyphs[0].position.y > rule.origin.y + rule.height);
+
+        // the superscript is drawn smaller and further right than its base
+        let superscript = &list.glyph_runs[3];
+        assert!(superscript.font_size < denominator.font_size);
+        assert!(superscript.glyphs[0].position.x > denominator.glyphs[0].position.x);
+        assert_eq!(denominator.font_size, 20.0);
+    })
+}
+
+#[test]
+fn bounds_of_test() {
+    use math_render::GeneralizedFraction;
+
+    TEST_FONT.with(|font| {
+        let fraction = GeneralizedFraction {
+            numerator: Some(field("b", 3)),
+            denominator: Some(field("c", 4)),
+            ..Default::default()
+        };
+        let expr = MathExpression::new(
+            MathItem::List(vec![
+                field("a", 1),
+                MathExpression::new(MathItem::GeneralizedFraction(fraction), 2),
+            ]),
+            0,
+        );
+        let math_box = math_render::layout(&expr, font);
+
+        let a = math_box.bounds_of(|user_data| user_data == 1).unwrap();
+        let numerator = math_box.bounds_of(|user_data| user_data == 3).unwrap();
+        let denominator = math_box.bounds_of(|user_data| user_data == 4).unwrap();
+        let fraction = math_box.bounds_of(|user_data| user_data == 2).unwrap();
+        assert!(a.right() <= fraction.origin.x);
+        assert!(numerator.bottom() < denominator.origin.y);
+        assert_eq!(fraction.union(numerator), fraction);
+        assert_eq!(fraction.union(denominator), fraction);
+        assert_eq!(
+            math_box.bounds_of(|user_data| user_data == 1 || user_data == 4),
+            Some(a.union(denominator))
+        );
+        assert_eq!(math_box.bounds_of(|user_data| user_data == 5), None);
+    })
+}
+
+#[test]
+fn selection_rects_test() {
+    use math_render::GeneralizedFraction;
+
+    TEST_FONT.with(|font| {
+        let fraction = GeneralizedFraction {
+            numerator: Some(field("b", 4)),
+            denominator: Some(field("c", 5)),
+            ..Default::default()
+        };
+        let expr = MathExpression::new(
+            MathItem::List(vec![
+                field("a", 1),
+                field("xyz", 2),
+                MathExpression::new(MathItem::GeneralizedFraction(fraction), 3),
+            ]),
+            0,
+        );
+        let math_box = math_render::layout(&expr, font);
+
+        // adjacent boxes on the same line are merged
+        let rects = math_box.selection_rects(|user_data| user_data == 1 || user_data == 2);
+        assert_eq!(rects.len(), 1);
+        let a =math_box.bounds_of(|user_data| user_data == 1).unwrap();
+        let xyz = math_box.bounds_of(|user_data| user_data == 2).unwrap();
+        assert_eq!(rects[0].origin, a.origin);
+        assert_eq!(rects[0].right(), xyz.right());
+
+        // numerator and denominator are on different lines
+        let rects = math_box.selection_rects(|user_data| user_data == 4 || user_data == 5);
+        assert_eq!(rects.len(), 2);
+
+        // only the glyph of "y" is selected
+        let rects = math_box.text_selection_rects(2, 1..2);
+        assert_eq!(rects.len(), 1);
+        assert!(rects[0].origin.x > xyz.origin.x);
+        assert!(rects[0].right() < xyz.right());
+        assert_eq!(math_box.text_selection_rects(2, 0..3)[0].width, xyz.width);
+
+        assert!(math_box.selection_rects(|user_data| user_data == 6).is_empty());
+    })
+}
+
+#[test]
+fn navigation_test() {
+    use math_render::navigation::{CaretPosition, Movement, Navigator};
+    use math_render::GeneralizedFraction;
+
+    TEST_FONT.with(|font| {
+        let fraction = GeneralizedFraction {
+            numerator: Some(field("b", 3)),
+            denominator: Some(field("c", 4)),
+            ..Default::default()
+        };
+        let expr = MathExpression::new(
+            MathItem::List(vec![
+                field("1", 1),
+                field("2", 5),
+                MathExpression::new(MathItem::GeneralizedFraction(fraction), 2),
+            ]),
+            0,
+        );
+        let navigator = Navigator::new(&math_render::layout(&expr, font));
+        let position = |user_data, index| CaretPosition { user_data, index };
+
+        let start = navigator.move_caret(position(5, 1), Movement::Start);
+        assert_eq!(start, position(1, 0));
+        assert_eq!(navigator.move_caret(start, Movement::Left), start);
+        assert_eq!(navigator.move_caret(start, Movement::Right), position(1, 1));
+        // the end of "1" is at the same place as the start of "2"
+        assert_eq!(navigator.move_caret(position(1, 1), Movement::Right), position(5, 1));
+        assert_eq!(navigator.move_caret(position(5, 1), Movement::Right), position(3, 0));
+
+        assert_eq!(navigator.move_caret(position(3, 1), Movement::Down), position(4, 1));
+        assert_eq!(navigator.move_caret(position(4, 0), Movement::Up), position(3, 0));
+        assert_eq!(navigator.move_caret(position(3, 0), Movement::Up), position(3, 0));
+        assert_eq!(navigator.move_caret(start, Movement::End), position(4, 1));
+
+        let caret = navigator.caret(position(3, 1)).unwrap();
+        assert_eq!(navigator.position_at(caret.x + 1, caret.y + 1), Some(position(3, 1)));
+        assert!(caret.height > 0);
+        assert_eq!(navigator.caret(position(6, 0)), None);
+    })
+}
+
+#[test]
+fn decoration_test() {
+    use math_render::math_box::DeviceScale;
+    use math_render::{Border, Color, Decoration, Length};
+
+    let red = Color::rgb(255, 0, 0);
+    let decoration = Decoration {
+        background: Some(Color::rgba(255, 255, 0, 128)),
+        border: Some(Border {
+            color: red,
+            thickness: Length::em(0.05),
+        }),
+        padding: Length::em(0.1),
+        ..Default::default()
+    };
+    TEST_FONT.with(|font| {
+        let plain = math_render::layout(&field("x", 1), font);
+        let decorated = math_render::layout(&field("x", 1).with_decoration(decoration), font);
+        // padding and border on both sides
+        let inset = 150;
+        assert_eq!(decorated.advance_width(), plain.advance_width() + 2 * inset);
+        assert_eq!(decorated.extents().ascent, plain.extents().ascent + inset);
+        assert_eq!(decorated.extents().descent, plain.extents().descent + inset);
+
+        // the background and the four sides of the border come before the content
+        let items = assume_boxes(decorated.content());
+        assert_eq!(items.len(), 6);
+        assert_eq!(items[5].origin.x, inset);
+        for side in &items[1..5] {
+            match *side.content() {
+                MathBoxContent::Drawable(Drawable::Rectangle { color, .. }) => {
+                    assert_eq!(color, red)
+                }
+                _ => panic!("border is not a rectangle"),
+            }
+        }
+
+        let list = decorated.flatten(&DeviceScale::new(1000, 1000.0));
+        assert_eq!(list.rectangles.len(), 5);
+        assert_eq!(list.rectangles[0].width, decorated.advance_width() as f32);
+        assert_eq!(list.glyph_runs[0].glyphs[0].position.x, inset as f32);
+
+        let padded = Decoration {
+            padding: Length::em(0.1),
+            ..Default::default()
+        };
+        let padded = math_render::layout(&field("x", 1).with_decoration(padded), font);
+        assert_eq!(padded.advance_width(), plain.advance_width() + 200);
+    })
+}
+
+#[test]
+fn cancel_test() {
+    use math_render::{Decoration, Strike};
+
+    let cancel = |strikes: Vec<Strike>| Decoration {
+        strikes,
+        ..Default::default()
+    };
+    TEST_FONT.with(|font| {
+        let plain = math_render::layout(&field("x", 1), font);
+        let strike = cancel(vec![Strike::UpDiagonal]);
+        let cancelled = math_render::layout(&field("x", 1).with_decoration(strike), font);
+        // the strike is drawn in front of the content and stays inside the extents
+        let items = assume_boxes(cancelled.content());
+        assert_eq!(items.len(), 3);
+        let strike = &items[2];
+        match *strike.content() {
+            MathBoxContent::Drawable(Drawable::Line { vector, .. }) => {
+                assert!(vector.x > 0 && vector.y < 0)
+            }
+            _ => panic!("strike is not a line"),
+        }
+        assert!(strike.origin.y <= cancelled.extents().descent);
+        assert!(strike.origin.y - strike.extents().ascent >= -cancelled.extents().ascent);
+        assert!(cancelled.extents().height() > plain.extents().height());
+
+        // the arrow head sticks out at the top right without changing the advance width
+        let arrow = cancel(vec![Strike::UpDiagonalArrow]);
+        let with_arrow = math_render::layout(&field("x", 1).with_decoration(arrow), font);
+        assert_eq!(assume_boxes(with_arrow.content()).len(), 5);
+        assert_eq!(with_arrow.advance_width(), cancelled.advance_width());
+        assert!(with_arrow.extents().right_edge() > cancelled.extents().right_edge());
+        assert!(with_arrow.extents().ascent > cancelled.extents().ascent);
+    })
+}
+
+#[test]
+fn cancel_display_list_test() {
+    use math_render::math_box::{DeviceScale, Vector};
+    use math_render::shaper::MathShaper;
+    use math_render::{Decoration, Strike};
+
+    TEST_FONT.with(|font| {
+        let decoration = Decoration {
+            strikes: vec![
+                Strike::Horizontal,
+                Strike::UpDiagonal,
+                Strike::DownDiagonal,
+                Strike::UpDiagonalArrow,
+            ],
+            ..Default::default()
+        };
+        let cancelled = math_render::layout(&field("x", 1).with_decoration(decoration), font);
+        let scale = DeviceScale::new(font.em_size(), 20.0);
+        let list = cancelled.flatten(&scale);
+
+        // only the horizontal strike is a rule, the diagonals are stroked instead of filling
+        // their bounding boxes
+        assert_eq!(list.rules.len(), 1);
+        assert_eq!(list.segments.len(), 5);
+        let nucleus = assume_boxes(cancelled.content())[1].rect(cancelled.origin);
+        let top_left = scale.point_to_device(nucleus.origin);
+        let bottom_right = scale.point_to_device(Vector {
+            x: nucleus.right(),
+            y: nucleus.bottom(),
+        });
+        for rule in &list.rules {
+            assert!(
+                rule.origin.x > top_left.x
+                    || rule.origin.y > top_left.y
+                    || rule.origin.x + rule.width < bottom_right.x
+                    || rule.origin.y + rule.height < bottom_right.y
+            );
+        }
+        for segment in &list.segments {
+            assert!(segment.from.x != segment.to.x && segment.from.y != segment.to.y);
+            assert!(segment.thickness > 0.0);
+        }
+    })
+}
+
+#[test]
+fn reaction_arrow_test() {
+    use math_render::shaper::{MathConstant, MathShaper};
+    use math_render::ReactionArrow;
+
+    TEST_FONT.with(|font| {
+        let axis_height = font.math_constant(MathConstant::AxisHeight);
+        let arrows = [
+            ReactionArrow::Forward,
+            ReactionArrow::Backward,
+            ReactionArrow::Resonance,
+            ReactionArrow::Equilibrium,
+        ];
+        for &kind in arrows.iter() {
+            let above = Some(field("heat", 1));
+            let below = Some(field("catalyst", 1));
+            let expr = MathExpression::reaction_arrow(kind, above, below, 0);
+            let result = math_render::layout(&expr, font);
+            let boxes = assume_boxes(result.content());
+            // the arrow is the nucleus of the label above which contains the one below
+            let (inner, above) = (assume_boxes(boxes[0].content()), &boxes[1]);
+            let (arrow, below) = (&inner[0], &inner[1]);
+            let width = |math_box: &MathBox| math_box.extents().width;
+            assert!(width(arrow) > width(above).max(width(below)), "{:?}", kind);
+            let center = -arrow.origin.y + (arrow.extents().ascent - arrow.extents().descent) / 2;
+            assert!((center - axis_height).abs() <= 20, "{:?}", kind);
+        }
+
+        let list = MathExpression::new(
+            MathItem::List(vec![field("NaCl", 1), MathExpression::chemical_state("aq", 2)]),
+            0,
+        );
+        let result = math_render::layout(&list, font);
+        let boxes = assume_boxes(result.content());
+        // the state follows the formula without any space
+        let end_of_formula = boxes[0].advance_width() + boxes[0].italic_correction();
+        assert_eq!(boxes[1].origin.x, end_of_formula);
+    })
+}
+
+#[test]
+fn text_run_test() {
+    use math_render::shaper::MathShaper;
+    use math_render::{LayoutStyle, TextRun, TextRunItem};
+
+    let text = |text: &str| TextRunItem::Text(text.to_string());
+    let run = |items| MathExpression::new(MathItem::TextRun(TextRun { items }), 0);
+    let x = field("\u{1d465}", 1);
+    TEST_FONT.with(|font| {
+        let space_width = font.space_width(LayoutStyle::default());
+        assert!(space_width > 0);
+
+        let expr = run(vec![
+            text(" for  all "),
+            TextRunItem::Math(x),
+            text(" such that "),
+        ]);
+        let result = math_render::layout(&expr, font);
+        let boxes = assume_boxes(result.content());
+        assert_eq!(boxes.len(), 5);
+        // whitespace at the ends of the run is dropped and everything else is a single space
+        assert_eq!(boxes[0].origin.x, 0);
+        let end = |math_box: &MathBox| math_box.origin.x + math_box.advance_width();
+        for pair in boxes.windows(2) {
+            assert_eq!(pair[1].origin.x, end(&pair[0]) + space_width);
+        }
+        assert_eq!(boxes[2].user_data(), 1);
+        assert_eq!(result.advance_width(), end(&boxes[4]));
+    })
+}
+
+/// Delegates to the test font but gives every glyph the same math kerning towards its scripts.
+struct KernShaper<'a> {
+    font: &'a math_render::shaper::HarfbuzzShaper<'a>,
+    kern: i32,
+    /// The corners and correction heights the kerning was requested for.
+    requests: std::cell::RefCell<Vec<(math_render::CornerPosition, i32)>>,
+    /// Overrides the scale of shaped strings.
+    glyph_scale: Option<math_render::PercentValue>,
+}
+
+impl<'a> math_render::shaper::MathShaper for KernShaper<'a> {
+    fn math_constant(&self, c: math_render::shaper::MathConstant) -> i32 {
+        self.font.math_constant(c)
+    }
+    fn shape(&self, string: &str, style: math_render::LayoutStyle, user_data: u64) -> MathBox {
+        let math_box = self.font.shape(string, style, user_data);
+        match (self.glyph_scale, math_box.content()) {
+            (Some(scale), MathBoxContent::Drawable(Drawable::Glyphs { glyphs, .. })) => {
+                MathBox::with_glyphs(glyphs.clone(), scale, user_data)
+            }
+            _ => math_box,
+        }
+    }
+    fn shape_glyph(&self, glyph: u32, style: math_render::LayoutStyle, user_data: u64) -> MathBox {
+        self.font.shape_glyph(glyph, style, user_data)
+    }
+    fn nominal_glyph(&self, c: char) -> Option<u32> {
+        self.font.nominal_glyph(c)
+    }
+    fn get_math_table(&self) -> &[u8] {
+        self.font.get_math_table()
+    }
+    fn em_size(&self) -> i32 {
+        self.font.em_size()
+    }
+    fn is_stretchable(&self, glyph: u32, horizontal: bool) -> bool {
+        self.font.is_stretchable(glyph, horizontal)
+    }
+    fn stretch_glyph(
+        &self,
+        glyph: u32,
+        horizontal: bool,
+        target_size: u32,
+        style: math_render::LayoutStyle,
+        user_data: u64,
+    ) -> MathBox {
+        self.font
+            .stretch_glyph(glyph, horizontal, target_size, style, user_data)
+    }
+    fn math_kerning(
+        &self,
+        _glyph: &math_render::shaper::MathGlyph,
+        corner: math_render::CornerPosition,
+        correction_height: i32,
+    ) -> i32 {
+        self.requests.borrow_mut().push((corner, correction_height));
+        // only the nucleus is kerned, the corners of the scripts that face it have no kerning
+        match corner {
+            math_render::CornerPosition::TopRight | math_render::CornerPosition::BottomRight => {
+                self.kern
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// Delegates to the test font but only implements the required methods of `MathShaper`.
+struct MinimalShaper<'a>(&'a math_render::shaper::HarfbuzzShaper<'a>);
+
+impl<'a> math_render::shaper::MathShaper for MinimalShaper<'a> {
+    fn math_constant(&self, c: math_render::shaper::MathConstant) -> i32 {
+        self.0.math_constant(c)
+    }
+    fn shape(&self, string: &str, style: math_render::LayoutStyle, user_data: u64) -> MathBox {
+        self.0.shape(string, style, user_data)
+    }
+    fn shape_glyph(&self, glyph: u32, style: math_render::LayoutStyle, user_data: u64) -> MathBox {
+        self.0.shape_glyph(glyph, style, user_data)
+    }
+    fn get_math_table(&self) -> &[u8] {
+        self.0.get_math_table()
+    }
+    fn em_size(&self) -> i32 {
+        self.0.em_size()
+    }
+    fn is_stretchable(&self, glyph: u32, horizontal: bool) -> bool {
+        self.0.is_stretchable(glyph, horizontal)
+    }
+    fn stretch_glyph(
+        &self,
+        glyph: u32,
+        horizontal: bool,
+        target_size: u32,
+        style: math_render::LayoutStyle,
+        user_data: u64,
+    ) -> MathBox {
+        self.0
+            .stretch_glyph(glyph, horizontal, target_size, style, user_data)
+    }
+    fn math_kerning(
+        &self,
+        glyph: &math_render::shaper::MathGlyph,
+        corner: math_render::CornerPosition,
+        correction_height: i32,
+    ) -> i32 {
+        self.0.math_kerning(glyph, corner, correction_height)
+    }
+}
+
+#[test]
+fn minimal_shaper_test() {
+    use math_render::math_box::DeviceScale;
+    use math_render::shaper::MathShaper;
+
+    // fences that don't need to stretch and the surd are drawn through their nominal glyph
+    TEST_FONT.with(|font| {
+        let xml = "<mrow><mo>(</mo><msqrt><mi>x</mi></msqrt><mo>)</mo><mo>|</mo></mrow>";
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let scale = DeviceScale::new(font.em_size(), 20.0);
+        let expected = math_render::layout(&expr, font).flatten(&scale);
+        let list = math_render::layout(&expr, &MinimalShaper(font)).flatten(&scale);
+
+        let glyph_count: usize = list.glyph_runs.iter().map(|run| run.glyphs.len()).sum();
+        assert_eq!(glyph_count, 5);
+        assert_eq!(list.glyph_runs, expected.glyph_runs);
+        assert_eq!(list.rules, expected.rules);
+    })
+}
+
+#[test]
+fn math_kerning_test() {
+    use math_render::{layout_expression, LayoutOptions, MathKerning};
+
+    // Latin Modern Math has no kerning information, so the scripts of `math_kern.xml` are placed
+    // after the nucleus and its italic correction
+    TEST_FONT.with(|font| {
+        let bytes = include_bytes!("testfiles/math_kern.xml");
+        let expr = mathmlparser::parse(&bytes[..]).unwrap();
+        for &kerning in &[MathKerning::Enabled, MathKerning::Disabled] {
+            let result = layout_expression(&expr, LayoutOptions::new(font).math_kerning(kerning));
+            let rows = assume_boxes(result.content());
+            let script_x = |row: &MathBox| assume_boxes(row.content())[1].origin.x;
+            assert_eq!(script_x(&rows[0]), 632);
+            assert_eq!(script_x(&rows[1]), 583);
+        }
+    });
+
+    TEST_FONT.with(|font| {
+        let shaper = KernShaper {
+            font,
+            kern: -100,
+            requests: Default::default(),
+            glyph_scale: None,
+        };
+        let expr = mathmlparser::parse("<msup><mi>A</mi><mi>x</mi></msup>".as_bytes()).unwrap();
+        let script_x = |kerning: MathKerning| {
+            let options = LayoutOptions::new(&shaper).math_kerning(kerning);
+            let result = layout_expression(&expr, options);
+            assume_boxes(result.content())[1].origin.x
+        };
+        let unkerned = script_x(MathKerning::Disabled);
+        assert_eq!(unkerned, 750);
+        assert_eq!(script_x(MathKerning::Enabled), unkerned - 100);
+        assert_eq!(script_x(MathKerning::Clamped(30)), unkerned - 30);
+        assert_eq!(script_x(MathKerning::Clamped(200)), unkerned - 100);
+    });
+
+    // glyphs that are scaled down to nothing aren't kerned
+    TEST_FONT.with(|font| {
+        let shaper = KernShaper {
+            font,
+            kern: -100,
+            requests: Default::default(),
+            glyph_scale: Some(math_render::PercentValue::magnification(0)),
+        };
+        let expr = mathmlparser::parse("<msup><mi>A</mi><mi>x</mi></msup>".as_bytes()).unwrap();
+        math_render::layout(&expr, &shaper);
+        assert!(shaper.requests.borrow().is_empty());
+    })
+}
+
+#[test]
+fn math_kerning_height_test() {
+    use math_render::shaper::{MathConstant, MathShaper};
+    use math_render::CornerPosition;
+
+    TEST_FONT.with(|font| {
+        let shaper = KernShaper {
+            font,
+            kern: 0,
+            requests: Default::default(),
+            glyph_scale: None,
+        };
+        let xml = "<msup><mi>A</mi><msup><mi>x</mi><mn>2</mn></msup></msup>";
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let result = math_render::layout(&expr, &shaper);
+        let boxes = assume_boxes(result.content());
+        let (nucleus, superscript) = (&boxes[0], &boxes[1]);
+
+        // the outer superscript is kerned last; the heights are measured from the baselines of
+        // the facing glyphs in the units of the unscaled glyphs
+        let requests = shaper.requests.borrow();
+        let (nucleus_corner, nucleus_height) = requests[requests.len() - 2];
+        let (script_corner, script_height) = requests[requests.len() - 1];
+        assert!(matches!(nucleus_corner, CornerPosition::TopRight));
+        assert!(matches!(script_corner, CornerPosition::BottomLeft));
+        let bottom = superscript.origin.y + superscript.extents().descent;
+        assert_eq!(nucleus_height, -bottom);
+        let scale = font.math_constant(MathConstant::ScriptPercentScaleDown);
+        let (_, x_origin, _) = superscript.first_glyph().unwrap();
+        let top = nucleus.origin.y - nucleus.extents().ascent;
+        let expected = (x_origin.y - top) * 100 / scale;
+        assert!((script_height - expected).abs() <= 1, "{} {}", script_height, expected);
+    })
+}
+
+#[test]
+fn glyphs_test() {
+    use math_render::math_box::DeviceScale;
+    use math_render::shaper::{MathConstant, MathShaper};
+
+    TEST_FONT.with(|font| {
+        let xml = "<msup><mi>x</mi><msup><mi>y</mi><mn>2</mn></msup></msup>";
+        let expr = mathmlparser::parse(xml.as_bytes()).unwrap();
+        let mut math_box = math_render::layout(&expr, font);
+        math_box.origin.x += 1000;
+
+        let glyphs = math_box.glyphs().collect::<Vec<_>>();
+        assert_eq!(glyphs.len(), 3);
+        assert_eq!(math_box.first_glyph(), Some(glyphs[0]));
+        assert_eq!(math_box.last_glyph(), Some(glyphs[2]));
+
+        let scales = [
+            100,
+            font.math_constant(MathConstant::ScriptPercentScaleDown),
+            font.math_constant(MathConstant::ScriptScriptPercentScaleDown),
+        ];
+        for (&(_, _, scale), &percent) in glyphs.iter().zip(scales.iter()) {
+            assert_eq!(scale.horizontal.as_scale_mult(), percent as f32 / 100.0);
+            assert_eq!(scale.vertical, scale.horizontal);
+        }
+
+        // the glyphs are where the renderer draws them
+        let scale = DeviceScale::new(font.em_size(), font.em_size() as f32);
+        let list = math_box.flatten(&scale);
+        let drawn = list
+            .glyph_runs
+            .iter()
+            .flat_map(|run| run.glyphs.iter())
+            .collect::<Vec<_>>();
+        assert_eq!(drawn.len(), glyphs.len());
+        for (&(glyph, origin, _), drawn) in glyphs.iter().zip(drawn) {
+            assert_eq!(glyph.glyph_code, drawn.glyph_code);
+            assert_eq!(origin.x as f32, drawn.position.x);
+            assert_eq!(origin.y as f32, drawn.position.y);
+        }
+        assert!(glyphs[2].1.y < glyphs[1].1.y && glyphs[1].1.y < glyphs[0].1.y);
+        assert!(glyphs[0].1.x >= 1000);
+    })
+}
+
+#[test]
+fn radical_symbol_test() {
+    use math_render::math_box::Drawable;
+    use math_render::shaper::{MathConstant, MathShaper};
+    use math_render::{layout_expression, LayoutError, LayoutOptions, RadicalSymbol};
+    use std::cell::RefCell;
+
+    TEST_FONT.with(|font| {
+        let expr = mathmlparser::parse("<msqrt><mi>x</mi></msqrt>".as_bytes()).unwrap();
+        let default = math_render::layout(&expr, font);
+        let surd_glyph = |math_box: &MathBox| assume_boxes(math_box.content())[0].first_glyph();
+
+        let square_root = font.nominal_glyph('\u{221A}').unwrap();
+        let options = LayoutOptions::new(font).radical_symbol(RadicalSymbol::Glyph(square_root));
+        assert_eq!(layout_expression(&expr, options).dump(), default.dump());
+
+        // the bottom part of the radical symbol has no variants and keeps its size
+        let bottom = RadicalSymbol::Character('\u{23B7}');
+        let result = layout_expression(&expr, LayoutOptions::new(font).radical_symbol(bottom));
+        let (glyph, _, _) = surd_glyph(&result).unwrap();
+        assert_eq!(Some(glyph.glyph_code), font.nominal_glyph('\u{23B7}'));
+        assert_ne!(surd_glyph(&result), surd_glyph(&default));
+
+        // Latin Modern Math has no cube root
+        let errors = RefCell::new(Vec::new());
+        let cube_root = RadicalSymbol::Character('\u{221B}');
+        let options = LayoutOptions::new(font)
+            .radical_symbol(cube_root)
+            .errors(&errors);
+        layout_expression(&expr, options);
+        assert_eq!(
+            errors.into_inner(),
+            vec![LayoutError::MissingGlyph('\u{221B}')]
+        );
+
+        let rule_thickness =
+            |math_box: &MathBox| match *assume_boxes(math_box.content())[1].content() {
+                MathBoxContent::Drawable(Drawable::Line { thickness, .. }) => thickness as i32,
+                ref other => panic!("expected the radical rule, found {:?}", other),
+            };
+        let thickness = font.math_constant(MathConstant::RadicalRuleThickness);
+        assert_eq!(rule_thickness(&default), thickness);
+        let options = LayoutOptions::new(font).radical_rule_thickness(2.0);
+        let result = layout_expression(&expr, options);
+        assert_eq!(rule_thickness(&result), 2 * thickness);
+        // the surd grows with the rule so that the rule stays clear of the radicand
+        let radicand = |math_box: &MathBox| assume_boxes(math_box.content())[2].origin.y;
+        assert_eq!(radicand(&result), radicand(&default));
+        assert!(result.extents().ascent > default.extents().ascent);
+
+        // invalid factors don't make the rule negative
+        let options = LayoutOptions::new(font).radical_rule_thickness(-1.0);
+        assert_eq!(rule_thickness(&layout_expression(&expr, options)), 0);
+        let options = LayoutOptions::new(font).radical_rule_thickness(f32::NAN);
+        assert_eq!(rule_thickness(&layout_expression(&expr, options)), thickness);
+    })
+}
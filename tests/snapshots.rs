@@ -0,0 +1,94 @@
+//! Layout regression tests.
+//!
+//! Every file in `tests/testfiles` is laid out with the bundled Latin Modern Math font and the
+//! resulting box tree is compared against the snapshot with the same name in `tests/snapshots`.
+//!
+//! After an intended layout change regenerate the snapshots with
+//!
+//! ```text
+//! UPDATE_SNAPSHOTS=1 cargo test --features mathml_parser --test snapshots
+//! ```
+//!
+//! and review the differences before committing them.
+#![cfg(all(feature = "mathml_parser", feature = "layout"))]
+
+extern crate math_render;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use math_render::mathmlparser;
+
+mod util;
+use crate::util::TEST_FONT;
+
+fn snapshot_of(path: &Path) -> String {
+    let bytes = fs::read(path).expect("could not read test file");
+    match mathmlparser::parse(&bytes[..]) {
+        Ok(expr) => TEST_FONT.with(|font| math_render::layout(&expr, font).dump()),
+        Err(error) => format!("parse error: {:?}\n", error),
+    }
+}
+
+fn first_difference(expected: &str, actual: &str) -> String {
+    for (index, (expected, actual)) in expected.lines().zip(actual.lines()).enumerate() {
+        if expected != actual {
+            return format!(
+                "line {}:\n  expected: {}\n  actual:   {}",
+                index + 1,
+                expected.trim(),
+                actual.trim()
+            );
+        }
+    }
+    format!(
+        "expected {} lines, got {} lines",
+        expected.lines().count(),
+        actual.lines().count()
+    )
+}
+
+#[test]
+fn layout_snapshots() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let snapshot_dir = root.join("snapshots");
+    let update = env::var_os("UPDATE_SNAPSHOTS").is_some();
+
+    let mut test_files: Vec<_> = fs::read_dir(root.join("testfiles"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "xml"))
+        .collect();
+    test_files.sort();
+    assert!(!test_files.is_empty());
+
+    let mut failures = Vec::new();
+    for path in &test_files {
+        let name = path.file_name().unwrap().to_string_lossy();
+        let actual = snapshot_of(path);
+        let snapshot_path = snapshot_dir
+            .join(path.file_stem().unwrap())
+            .with_extension("txt");
+        if update {
+            fs::create_dir_all(&snapshot_dir).unwrap();
+            fs::write(&snapshot_path, &actual).unwrap();
+            continue;
+        }
+        match fs::read_to_string(&snapshot_path) {
+            Ok(ref expected) if *expected == actual => {}
+            Ok(expected) => failures.push(format!(
+                "{}: {}",
+                name,
+                first_difference(&expected, &actual)
+            )),
+            Err(_) => failures.push(format!("{}: snapshot missing", name)),
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "layout differs from the snapshots (run with UPDATE_SNAPSHOTS=1 to accept):\n{}",
+        failures.join("\n")
+    );
+}
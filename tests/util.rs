@@ -1,3 +1,5 @@
+#![cfg(feature = "layout")]
+
 extern crate freetype;
 extern crate harfbuzz_rs;
 extern crate math_render;